@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS custom_service_definitions (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    category TEXT NOT NULL,
+    dashboard_icons_path TEXT NOT NULL DEFAULT '',
+    simple_icons_path TEXT NOT NULL DEFAULT '',
+    vector_logo_zone_icons_path TEXT NOT NULL DEFAULT '',
+    logo_needs_white_background BOOLEAN NOT NULL DEFAULT FALSE,
+    discovery_pattern JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (network_id, name)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS scan_evidence (
+    id UUID PRIMARY KEY,
+    host_id UUID NOT NULL REFERENCES hosts(id) ON DELETE CASCADE,
+    interface_id UUID NOT NULL,
+    evidence_key TEXT NOT NULL,
+    evidence JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (host_id, interface_id, evidence_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_evidence_host_id ON scan_evidence(host_id);
@@ -0,0 +1,5 @@
+ALTER TABLE services ADD COLUMN IF NOT EXISTS version TEXT;
+ALTER TABLE services ADD COLUMN IF NOT EXISTS version_history JSONB NOT NULL DEFAULT '[]';
+
+ALTER TABLE custom_service_definitions
+    ADD COLUMN IF NOT EXISTS version_extractors JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS vulnerabilities (
+    id TEXT PRIMARY KEY,
+    products TEXT[] NOT NULL,
+    vulnerability JSONB NOT NULL,
+    imported_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_vulnerabilities_products ON vulnerabilities USING GIN (products);
+
+CREATE TABLE IF NOT EXISTS service_vulnerabilities (
+    service_id UUID NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    vulnerability_id TEXT NOT NULL REFERENCES vulnerabilities(id) ON DELETE CASCADE,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    matched_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (service_id, vulnerability_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_service_vulnerabilities_network_id ON service_vulnerabilities(network_id);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS mac_vendor_overrides (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    oui TEXT NOT NULL,
+    vendor TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (network_id, oui)
+);
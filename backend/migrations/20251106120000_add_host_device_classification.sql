@@ -0,0 +1,2 @@
+ALTER TABLE hosts ADD COLUMN IF NOT EXISTS device_fingerprint JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE hosts ADD COLUMN IF NOT EXISTS device_classification JSONB DEFAULT 'null';
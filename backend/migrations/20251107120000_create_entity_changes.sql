@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS entity_changes (
+    id UUID PRIMARY KEY,
+    entity_type TEXT NOT NULL,
+    entity_id UUID NOT NULL,
+    network_id UUID NOT NULL,
+    action TEXT NOT NULL,
+    actor JSONB NOT NULL,
+    discovery_session_id UUID,
+    changes JSONB NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_entity_changes_entity ON entity_changes(entity_type, entity_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_entity_changes_network ON entity_changes(network_id, changed_at);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS network_snapshots (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    trigger JSONB NOT NULL,
+    contents JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_network_snapshots_network ON network_snapshots(network_id, created_at);
@@ -0,0 +1,16 @@
+ALTER TABLE hosts ADD COLUMN IF NOT EXISTS identity JSONB NOT NULL DEFAULT '{}';
+
+CREATE TABLE IF NOT EXISTS host_merge_suggestions (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    host_id UUID NOT NULL REFERENCES hosts(id) ON DELETE CASCADE,
+    other_host_id UUID NOT NULL REFERENCES hosts(id) ON DELETE CASCADE,
+    score INTEGER NOT NULL,
+    evidence JSONB NOT NULL,
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (host_id, other_host_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_host_merge_suggestions_network ON host_merge_suggestions(network_id);
@@ -0,0 +1,11 @@
+ALTER TABLE services ADD COLUMN IF NOT EXISTS stale BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS observation_states (
+    host_id UUID NOT NULL REFERENCES hosts(id) ON DELETE CASCADE,
+    entity_key TEXT NOT NULL,
+    last_seen_session UUID,
+    last_missed_session UUID,
+    missed_scans INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (host_id, entity_key)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS ip_ranges (
+    id UUID PRIMARY KEY,
+    subnet_id UUID NOT NULL REFERENCES subnets(id) ON DELETE CASCADE,
+    range_type TEXT NOT NULL,
+    start_ip TEXT NOT NULL,
+    end_ip TEXT NOT NULL,
+    host_id UUID REFERENCES hosts(id) ON DELETE SET NULL,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ip_ranges_subnet ON ip_ranges(subnet_id);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS vlans (
+    id UUID PRIMARY KEY,
+    network_id UUID NOT NULL REFERENCES networks(id) ON DELETE CASCADE,
+    tag INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    purpose TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (network_id, tag)
+);
+
+ALTER TABLE subnets ADD COLUMN IF NOT EXISTS vlan_id UUID REFERENCES vlans(id) ON DELETE SET NULL;
@@ -0,0 +1,31 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Lower-cased name, hostname, IP and MAC addresses searched by the host list
+ALTER TABLE hosts ADD COLUMN IF NOT EXISTS search_text TEXT GENERATED ALWAYS AS (
+    lower(
+        name || ' ' || coalesce(hostname, '') || ' '
+        || coalesce(jsonb_path_query_array(interfaces, '$[*].ip_address')::text, '') || ' '
+        || coalesce(jsonb_path_query_array(interfaces, '$[*].mac_address')::text, '')
+    )
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_hosts_search ON hosts USING GIN (search_text gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_hosts_interfaces ON hosts USING GIN (interfaces jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_hosts_source ON hosts USING GIN (source jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_hosts_source_type ON hosts(network_id, (source->>'type'));
+CREATE INDEX IF NOT EXISTS idx_hosts_tags ON hosts USING GIN (tags jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_hosts_custom_fields ON hosts USING GIN (custom_fields jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_hosts_network_name ON hosts(network_id, name, id);
+CREATE INDEX IF NOT EXISTS idx_hosts_network_created ON hosts(network_id, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_hosts_network_updated ON hosts(network_id, updated_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_services_search ON services USING GIN (lower(name) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_services_definition ON services(network_id, service_definition);
+CREATE INDEX IF NOT EXISTS idx_services_host_definition ON services(host_id, service_definition);
+CREATE INDEX IF NOT EXISTS idx_services_source ON services USING GIN (source jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_services_source_type ON services(network_id, (source->>'type'));
+CREATE INDEX IF NOT EXISTS idx_services_tags ON services USING GIN (tags jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_services_custom_fields ON services USING GIN (custom_fields jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_services_network_name ON services(network_id, name, id);
+CREATE INDEX IF NOT EXISTS idx_services_network_created ON services(network_id, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_services_network_updated ON services(network_id, updated_at, id);
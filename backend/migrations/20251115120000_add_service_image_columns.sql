@@ -0,0 +1,41 @@
+-- Repository and tag of the image a docker service's container was created from, normalized
+-- by the server on write so services can be looked up by image
+ALTER TABLE services ADD COLUMN IF NOT EXISTS image_repository TEXT;
+ALTER TABLE services ADD COLUMN IF NOT EXISTS image_tag TEXT;
+
+-- Backfill existing services the same way: drop any digest, split off the tag, default the
+-- registry to docker.io and docker hub images without a namespace to library
+WITH refs AS (
+    SELECT id, split_part(virtualization->'details'->>'image', '@', 1) AS ref
+    FROM services
+    WHERE virtualization->>'type' = 'Docker'
+      AND virtualization->'details'->>'image' IS NOT NULL
+), split AS (
+    SELECT
+        id,
+        lower(regexp_replace(ref, ':[^:/]*$', '')) AS repo,
+        substring(ref FROM ':([^:/]*)$') AS tag
+    FROM refs
+), registry AS (
+    SELECT
+        id,
+        tag,
+        CASE
+            WHEN repo LIKE 'index.docker.io/%' THEN 'docker.io/' || substring(repo FROM 17)
+            WHEN position('/' IN repo) > 0
+                AND (split_part(repo, '/', 1) ~ '[.:]' OR split_part(repo, '/', 1) = 'localhost')
+                THEN repo
+            ELSE 'docker.io/' || repo
+        END AS repo
+    FROM split
+)
+UPDATE services s SET
+    image_repository = CASE
+        WHEN r.repo ~ '^docker\.io/[^/]+$' THEN 'docker.io/library/' || substring(r.repo FROM 11)
+        ELSE r.repo
+    END,
+    image_tag = r.tag
+FROM registry r
+WHERE s.id = r.id;
+
+CREATE INDEX IF NOT EXISTS idx_services_image ON services(network_id, image_repository);
@@ -0,0 +1,5 @@
+-- Container virtualization covers Docker, Podman and containerd, so services are tagged
+-- "Container" rather than "Docker"
+UPDATE services
+SET virtualization = jsonb_set(virtualization, '{type}', '"Container"')
+WHERE virtualization->>'type' = 'Docker';
@@ -60,6 +60,34 @@ struct Cli {
     /// Concurrent scans for discovery
     #[arg(long)]
     concurrent_scans: Option<usize>,
+
+    /// Path to kubeconfig for Kubernetes discovery. Defaults to in-cluster config, $KUBECONFIG, or ~/.kube/config
+    #[arg(long)]
+    kubeconfig: Option<String>,
+
+    /// Libvirt connection URI for libvirt discovery. Defaults to qemu:///system
+    #[arg(long)]
+    libvirt_uri: Option<String>,
+
+    /// Kubernetes namespace to limit containerd discovery to when Docker and Podman are unavailable. Defaults to all namespaces
+    #[arg(long)]
+    containerd_namespace: Option<String>,
+
+    /// Minimum evidence score for a service match to be accepted. Defaults to 0, accepting any match
+    #[arg(long)]
+    min_match_score: Option<u32>,
+
+    /// Keep a trace of how service definitions were matched for each host in the last discovery session
+    #[arg(long)]
+    record_match_traces: bool,
+
+    /// Don't query hosts over mDNS and SSDP to classify end-user devices
+    #[arg(long)]
+    no_device_fingerprinting: bool,
+
+    /// Passively listen for DHCP requests on UDP port 67 to classify end-user devices by the options they request. Needs permission to bind port 67
+    #[arg(long)]
+    dhcp_listener: bool,
 }
 
 impl From<Cli> for CliArgs {
@@ -74,6 +102,13 @@ impl From<Cli> for CliArgs {
             log_level: cli.log_level,
             heartbeat_interval: cli.heartbeat_interval,
             concurrent_scans: cli.concurrent_scans,
+            kubeconfig_path: cli.kubeconfig,
+            libvirt_uri: cli.libvirt_uri,
+            containerd_namespace: cli.containerd_namespace,
+            min_match_score: cli.min_match_score,
+            record_match_traces: cli.record_match_traces.then_some(true),
+            device_fingerprinting: cli.no_device_fingerprinting.then_some(false),
+            dhcp_listener: cli.dhcp_listener.then_some(true),
         }
     }
 }
@@ -113,6 +148,15 @@ async fn main() -> anyhow::Result<()> {
     let discovery_service = state.services.discovery_service.clone();
     let discovery_manager = state.services.discovery_manager.clone();
 
+    if config.dhcp_listener {
+        let dhcp_fingerprints = discovery_service.dhcp_fingerprints.clone();
+        tokio::spawn(async move {
+            if let Err(e) = dhcp_fingerprints.listen().await {
+                tracing::warn!("DHCP listener stopped: {}", e);
+            }
+        });
+    }
+
     // Create HTTP server with config values
     let api_router = create_router().with_state(state);
 
@@ -6,6 +6,7 @@ use netvisor::server::{
     shared::handlers::create_router,
     users::types::{User, UserBase},
 };
+use std::path::PathBuf;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -37,6 +38,22 @@ struct Cli {
     /// Override integrated daemon url
     #[arg(long)]
     integrated_daemon_url: Option<String>,
+
+    /// Override minimum evidence score for service matches
+    #[arg(long)]
+    min_match_score: Option<u32>,
+
+    /// Override vulnerability feed path
+    #[arg(long)]
+    vulnerability_feed_path: Option<PathBuf>,
+
+    /// Override reconciliation policy: keep, mark_stale or remove
+    #[arg(long)]
+    reconciliation_policy: Option<String>,
+
+    /// Override missed discovery sessions before the reconciliation policy applies
+    #[arg(long)]
+    reconciliation_missed_scans: Option<u32>,
 }
 
 impl From<Cli> for CliArgs {
@@ -47,6 +64,10 @@ impl From<Cli> for CliArgs {
             rust_log: cli.rust_log,
             database_url: cli.database_url,
             integrated_daemon_url: cli.integrated_daemon_url,
+            min_match_score: cli.min_match_score,
+            vulnerability_feed_path: cli.vulnerability_feed_path,
+            reconciliation_policy: cli.reconciliation_policy,
+            reconciliation_missed_scans: cli.reconciliation_missed_scans,
         }
     }
 }
@@ -77,6 +98,20 @@ async fn main() -> anyhow::Result<()> {
     let state = AppState::new(config, DiscoverySessionManager::new()).await?;
     let user_service = state.services.user_service.clone();
 
+    // Import the vulnerability feed, if configured
+    if let Some(feed_path) = state.config.vulnerability_feed_path.clone() {
+        let vulnerability_service = state.services.vulnerability_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = vulnerability_service.import_feed(&feed_path).await {
+                tracing::error!(
+                    "Failed to import vulnerability feed {}: {}",
+                    feed_path.display(),
+                    e
+                );
+            }
+        });
+    }
+
     // Create discovery cleanup task
     let cleanup_state = state.clone();
     tokio::spawn(async move {
@@ -99,11 +134,11 @@ async fn main() -> anyhow::Result<()> {
     let api_router = if let Some(static_path) = &web_external_path {
         Router::new()
             .nest_service("/", ServeDir::new(static_path))
-            .merge(create_router())
+            .merge(create_router(state.clone()))
             .with_state(state)
     } else {
         tracing::info!("Server is not serving web assets due to no web_external_path");
-        create_router().with_state(state)
+        create_router(state.clone()).with_state(state)
     };
 
     // Create main app
@@ -1,20 +1,32 @@
 use crate::daemon::discovery::service::base::Discovery;
+use crate::daemon::discovery::service::docker::DockerScanDiscovery;
+use crate::daemon::discovery::service::kubernetes::KubernetesScanDiscovery;
+use crate::daemon::discovery::service::libvirt::LibvirtScanDiscovery;
 use crate::daemon::discovery::service::network::NetworkScanDiscovery;
 use crate::daemon::runtime::types::DaemonAppState;
 use crate::server::daemons::types::api::{
     DaemonDiscoveryCancellationRequest, DaemonDiscoveryCancellationResponse,
 };
+use crate::server::discovery::types::base::DiscoveryType;
+use crate::server::services::types::trace::HostMatchTrace;
 use crate::server::{
     daemons::types::api::{DaemonDiscoveryRequest, DaemonDiscoveryResponse},
     shared::types::api::{ApiError, ApiResponse, ApiResult},
 };
-use axum::{Router, extract::State, response::Json, routing::post};
+use axum::{
+    Router,
+    extract::State,
+    response::Json,
+    routing::{get, post},
+};
 use std::sync::Arc;
+use uuid::Uuid;
 
 pub fn create_router() -> Router<Arc<DaemonAppState>> {
     Router::new()
         .route("/initiate", post(handle_discovery_request))
         .route("/cancel", post(handle_cancel_request))
+        .route("/traces", get(get_match_traces))
 }
 
 async fn handle_discovery_request(
@@ -24,19 +36,71 @@ async fn handle_discovery_request(
     let session_id = request.session_id;
     tracing::info!("Received discovery request for session {}", session_id);
 
-    let discovery = Arc::new(Discovery::new(
-        state.services.discovery_service.clone(),
-        state.services.discovery_manager.clone(),
-        NetworkScanDiscovery::default(),
-    ));
+    let discovery_service = state.services.discovery_service.clone();
+    let discovery_manager = state.services.discovery_manager.clone();
+
+    match request.discovery_type {
+        DiscoveryType::Kubernetes => {
+            let kubeconfig_path = discovery_service.config_store.get_kubeconfig_path().await?;
+
+            Arc::new(Discovery::new(
+                discovery_service,
+                discovery_manager,
+                KubernetesScanDiscovery::new(kubeconfig_path),
+            ))
+            .discover_on_network(request)
+            .await?;
+        }
+        DiscoveryType::Libvirt { .. } => {
+            let host_id = own_host_id(&state).await?;
+            let libvirt_uri = discovery_service.config_store.get_libvirt_uri().await?;
 
-    discovery.discover_on_network(request).await?;
+            Arc::new(Discovery::new(
+                discovery_service,
+                discovery_manager,
+                LibvirtScanDiscovery::new(host_id, libvirt_uri),
+            ))
+            .discover_on_network(request)
+            .await?;
+        }
+        DiscoveryType::Docker { .. } => {
+            let host_id = own_host_id(&state).await?;
+
+            Arc::new(Discovery::new(
+                discovery_service,
+                discovery_manager,
+                DockerScanDiscovery::new(host_id),
+            ))
+            .discover_on_network(request)
+            .await?;
+        }
+        _ => {
+            Arc::new(Discovery::new(
+                discovery_service,
+                discovery_manager,
+                NetworkScanDiscovery::default(),
+            ))
+            .discover_on_network(request)
+            .await?;
+        }
+    }
 
     Ok(Json(ApiResponse::success(DaemonDiscoveryResponse {
         session_id,
     })))
 }
 
+/// Containers and guests are always attached to the daemon's own host, whatever the request says
+async fn own_host_id(state: &DaemonAppState) -> ApiResult<Uuid> {
+    state
+        .services
+        .discovery_service
+        .config_store
+        .get_host_id()
+        .await?
+        .ok_or_else(|| ApiError::bad_request("Daemon host has not been registered"))
+}
+
 async fn handle_cancel_request(
     State(state): State<Arc<DaemonAppState>>,
     Json(request): Json<DaemonDiscoveryCancellationRequest>,
@@ -66,3 +130,19 @@ async fn handle_cancel_request(
         ))
     }
 }
+
+/// Service matching traces for every host processed in the last discovery session. Only recorded
+/// when the daemon runs with --record-match-traces.
+async fn get_match_traces(
+    State(state): State<Arc<DaemonAppState>>,
+) -> ApiResult<Json<ApiResponse<Vec<HostMatchTrace>>>> {
+    let traces = state
+        .services
+        .discovery_service
+        .match_traces
+        .read()
+        .await
+        .clone();
+
+    Ok(Json(ApiResponse::success(traces)))
+}
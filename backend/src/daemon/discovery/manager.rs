@@ -36,6 +36,13 @@ impl DaemonDiscoverySessionManager {
         }
     }
 
+    /// Wait until the current discovery task, if any, has finished
+    pub async fn wait_for_current_session(&self) {
+        while self.is_discovery_running().await {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
     /// Set the current discovery task for cancellation
     pub async fn start_new_session(&self) -> CancellationToken {
         *self.cancellation_token.write().await = CancellationToken::new();
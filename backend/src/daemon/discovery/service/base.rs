@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     net::IpAddr,
     sync::{Arc, atomic::AtomicUsize},
     time::Duration,
@@ -8,39 +9,51 @@ use crate::server::hosts::types::ports::TransportProtocol;
 use crate::server::services::types::endpoints::Endpoint;
 use crate::{
     daemon::discovery::{
-        manager::DaemonDiscoverySessionManager, types::base::DiscoveryCriticalError,
+        manager::DaemonDiscoverySessionManager,
+        types::{base::DiscoveryCriticalError, dhcp::DhcpFingerprints},
     },
     server::{
         discovery::types::{
             api::InitiateDiscoveryRequest,
             base::{DiscoveryMetadata, DiscoveryType},
         },
+        entity_changes::types::{DAEMON_ID_HEADER, DISCOVERY_SESSION_HEADER},
         groups::types::Group,
+        mac_vendors::{registry::MacVendorRegistry, types::MacVendorOverride},
+        reconciliation::types::HostObservation,
+        scan_evidence::types::ScanEvidenceBase,
+        service_definitions::types::PublishedServiceDefinition,
         services::types::{
-            base::{
-                DiscoverySessionServiceMatchParams, ServiceMatchBaselineParams,
-                ServiceMatchServiceParams,
-            },
+            base::{ServiceMatchBaselineParams, ServiceMatchOutcome},
             endpoints::EndpointResponse,
             patterns::MatchConfidence,
+            trace::HostMatchTrace,
+            versions::PortBanner,
         },
     },
 };
 use anyhow::{Error, anyhow};
 use axum::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD_NO_PAD};
 use chrono::Utc;
 use dhcproto::v4::{self, Decodable, Encodable, Encoder, Message, MessageType};
 use rand::{Rng, SeedableRng};
+use reqwest::header::{HeaderMap, HeaderValue};
 use rsntp::AsyncSntpClient;
+use sha2::Digest;
 use snmp2::{AsyncSession, Oid};
 use std::net::SocketAddr;
-use tokio::{net::TcpStream, sync::RwLock, time::timeout};
+use tokio::{io::AsyncReadExt, net::TcpStream, sync::RwLock, time::timeout};
 use tokio_util::sync::CancellationToken;
 
 use tokio::net::UdpSocket;
 
 use trust_dns_resolver::TokioAsyncResolver;
 use trust_dns_resolver::config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts};
+use trust_dns_resolver::proto::{
+    op::{Message as DnsMessage, Query as DnsQuery},
+    rr::{Name, RData, RecordType},
+};
 use uuid::Uuid;
 
 use crate::{
@@ -55,24 +68,31 @@ use crate::{
         hosts::types::{
             api::HostWithServicesRequest,
             base::{Host, HostBase},
+            devices::DeviceFingerprint,
+            identity::HostIdentity,
+            interfaces::InterfaceBase,
             ports::{Port, PortBase},
             targets::HostTarget,
         },
         services::{
-            definitions::{ServiceDefinitionRegistry, gateway::Gateway},
+            definitions::ServiceDefinitionRegistry,
             types::{
                 base::Service,
                 bindings::Binding,
                 definitions::{ServiceDefinition, ServiceDefinitionExt},
             },
         },
-        shared::types::{api::ApiResponse, metadata::HasId},
+        shared::types::api::ApiResponse,
         subnets::types::base::Subnet,
     },
 };
 
 pub const SCAN_TIMEOUT: Duration = Duration::from_millis(800);
 
+/// UDP ports with a protocol-specific probe. Other UDP discovery ports can't be told apart from
+/// closed ones, so they're never scanned.
+const PROBED_UDP_PORTS: [u16; 4] = [53, 67, 123, 161];
+
 pub trait HasDiscoveryType {
     fn discovery_type(&self) -> DiscoveryType;
 }
@@ -140,7 +160,7 @@ where
         ip: IpAddr,
         cancel: CancellationToken,
         filter_endpoint_ports: Option<Vec<PortBase>>,
-    ) -> Result<(Vec<PortBase>, Vec<EndpointResponse>), Error> {
+    ) -> Result<(Vec<PortBase>, Vec<EndpointResponse>, Vec<PortBanner>), Error> {
         if cancel.is_cancelled() {
             return Err(anyhow!("Operation cancelled"));
         }
@@ -149,7 +169,7 @@ where
         let mut endpoint_responses = Vec::new();
 
         // Scan TCP ports sequentially (not concurrently)
-        let tcp_ports = Self::scan_tcp_ports(ip, cancel.clone()).await?;
+        let (tcp_ports, banners) = Self::scan_tcp_ports(ip, cancel.clone()).await?;
         open_ports.extend(tcp_ports);
 
         if cancel.is_cancelled() {
@@ -175,20 +195,22 @@ where
             endpoint_responses.len()
         );
 
-        Ok((open_ports, endpoint_responses))
+        Ok((open_ports, endpoint_responses, banners))
     }
 
+    /// Returns open ports, and the banners of open ports needed to read service versions
     pub async fn scan_tcp_ports(
         ip: IpAddr,
         cancel: CancellationToken,
-    ) -> Result<Vec<PortBase>, Error> {
-        let discovery_ports = Service::all_discovery_ports();
-        let ports: Vec<u16> = discovery_ports
+    ) -> Result<(Vec<PortBase>, Vec<PortBanner>), Error> {
+        let banner_ports = Service::all_banner_ports();
+        let ports: Vec<u16> = Self::probed_ports()
             .iter()
             .filter(|p| p.protocol() == TransportProtocol::Tcp)
             .map(|p| p.number())
             .collect();
         let mut open_ports = Vec::new();
+        let mut banners = Vec::new();
 
         for port in ports {
             if cancel.is_cancelled() {
@@ -196,9 +218,16 @@ where
             }
 
             match timeout(SCAN_TIMEOUT, TcpStream::connect((ip, port))).await {
-                Ok(Ok(_)) => {
-                    open_ports.push(PortBase::new_tcp(port));
+                Ok(Ok(mut stream)) => {
+                    let port_base = PortBase::new_tcp(port);
+                    open_ports.push(port_base);
                     tracing::debug!("Found open TCP port {}:{}", ip, port);
+
+                    if banner_ports.contains(&port_base)
+                        && let Some(banner) = Self::read_banner(&mut stream).await
+                    {
+                        banners.push(PortBanner { port_base, banner });
+                    }
                 }
                 Ok(Err(e)) => {
                     if DiscoveryCriticalError::is_critical_error(e.to_string()) {
@@ -211,15 +240,42 @@ where
             }
         }
 
-        Ok(open_ports)
+        Ok((open_ports, banners))
+    }
+
+    /// Read whatever the service sends on connecting, without making a request
+    async fn read_banner(stream: &mut TcpStream) -> Option<String> {
+        let mut buffer = [0u8; 512];
+
+        match timeout(SCAN_TIMEOUT, stream.read(&mut buffer)).await {
+            Ok(Ok(read)) if read > 0 => {
+                Some(String::from_utf8_lossy(&buffer[..read]).trim().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Every port scan_ports_and_endpoints actually probes: discovery ports, and the ports whose
+    /// banners are needed to read versions
+    pub fn probed_ports() -> Vec<PortBase> {
+        let mut ports: Vec<PortBase> = Service::all_discovery_ports()
+            .into_iter()
+            .chain(Service::all_banner_ports())
+            .filter(|p| {
+                p.protocol() == TransportProtocol::Tcp || PROBED_UDP_PORTS.contains(&p.number())
+            })
+            .collect();
+
+        ports.sort_by_key(|p| (p.number(), p.protocol()));
+        ports.dedup();
+        ports
     }
 
     pub async fn scan_udp_ports(
         ip: IpAddr,
         cancel: CancellationToken,
     ) -> Result<Vec<PortBase>, anyhow::Error> {
-        let discovery_ports = Service::all_discovery_ports();
-        let ports: Vec<u16> = discovery_ports
+        let ports: Vec<u16> = Self::probed_ports()
             .iter()
             .filter(|p| p.protocol() == TransportProtocol::Udp)
             .map(|p| p.number())
@@ -305,10 +361,19 @@ where
 
             match client.get(&url).send().await {
                 Ok(response) if response.status().is_success() => {
+                    let headers = response
+                        .headers()
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                        })
+                        .collect();
+
                     if let Ok(text) = response.text().await {
                         // Return single response that can be checked by all patterns
                         responses.push(EndpointResponse {
                             endpoint: endpoint_with_ip,
+                            headers,
                             response: text,
                         });
                     }
@@ -494,6 +559,389 @@ where
             }
         }
     }
+
+    /// Gather the mDNS and SSDP data used to classify end-user devices. DHCP parameter lists can't
+    /// be queried, they're only seen by the DHCP listener.
+    pub async fn probe_device_fingerprint(ip: IpAddr) -> DeviceFingerprint {
+        let (mdns_services, ssdp) =
+            tokio::join!(Self::query_mdns_services(ip), Self::query_ssdp(ip));
+
+        let (ssdp_server, ssdp_types) = ssdp.unwrap_or_else(|e| {
+            tracing::debug!("SSDP query failed for {}: {}", ip, e);
+            (None, Vec::new())
+        });
+
+        DeviceFingerprint {
+            mdns_services: mdns_services.unwrap_or_else(|e| {
+                tracing::debug!("mDNS query failed for {}: {}", ip, e);
+                Vec::new()
+            }),
+            ssdp_server,
+            ssdp_types,
+            ..Default::default()
+        }
+    }
+
+    /// Ask the host directly which mDNS service types it advertises. Queries from a port other
+    /// than 5353 get a unicast reply, so this works across subnets without joining the multicast
+    /// group.
+    pub async fn query_mdns_services(ip: IpAddr) -> Result<Vec<String>, Error> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+
+        let mut query = DnsMessage::new();
+        query.add_query(DnsQuery::query(
+            Name::from_ascii("_services._dns-sd._udp.local.")?,
+            RecordType::PTR,
+        ));
+        socket
+            .send_to(&query.to_vec()?, SocketAddr::new(ip, 5353))
+            .await?;
+
+        let mut response_buf = [0u8; 4096];
+        let len = match timeout(SCAN_TIMEOUT, socket.recv_from(&mut response_buf)).await {
+            Ok(Ok((len, _))) => len,
+            _ => return Ok(Vec::new()),
+        };
+
+        let response = DnsMessage::from_vec(&response_buf[..len])?;
+
+        let mut services: Vec<String> = response
+            .answers()
+            .iter()
+            .filter_map(|record| match record.data() {
+                Some(RData::PTR(ptr)) => Some(
+                    ptr.0
+                        .to_ascii()
+                        .trim_end_matches('.')
+                        .trim_end_matches(".local")
+                        .to_string(),
+                ),
+                _ => None,
+            })
+            .collect();
+        services.sort();
+        services.dedup();
+
+        tracing::debug!("mDNS services for {}: {:?}", ip, services);
+        Ok(services)
+    }
+
+    /// Send an SSDP search to the host, returning the SERVER header and the search targets it
+    /// answered with
+    pub async fn query_ssdp(ip: IpAddr) -> Result<(Option<String>, Vec<String>), Error> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        let target = SocketAddr::new(ip, 1900);
+
+        let search = format!(
+            "M-SEARCH * HTTP/1.1\r\nHOST: {}:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: ssdp:all\r\n\r\n",
+            ip
+        );
+        socket.send_to(search.as_bytes(), target).await?;
+
+        let mut server = None;
+        let mut types = Vec::new();
+        let mut response_buf = [0u8; 2048];
+
+        // Devices answer once per device and service type
+        while let Ok(Ok((len, _))) =
+            timeout(SCAN_TIMEOUT, socket.recv_from(&mut response_buf)).await
+        {
+            let response = String::from_utf8_lossy(&response_buf[..len]);
+
+            for line in response.lines() {
+                let Some((header, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim();
+
+                match header.trim().to_ascii_uppercase().as_str() {
+                    "SERVER" if server.is_none() && !value.is_empty() => {
+                        server = Some(value.to_string())
+                    }
+                    "ST" if value.starts_with("urn:") && !types.iter().any(|t| t == value) => {
+                        types.push(value.to_string())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        tracing::debug!("SSDP for {}: server {:?}, types {:?}", ip, server, types);
+        Ok((server, types))
+    }
+
+    /// Gather identifiers which are the same on every address the host is reachable through, so
+    /// the server can spot one machine discovered as several hosts
+    pub async fn probe_host_identity(ip: IpAddr, open_ports: &[PortBase]) -> HostIdentity {
+        let tcp_ports: Vec<u16> = open_ports
+            .iter()
+            .filter(|p| p.protocol() == TransportProtocol::Tcp)
+            .map(|p| p.number())
+            .collect();
+        let mut identity = HostIdentity::default();
+
+        if tcp_ports.contains(&22) {
+            match Self::read_ssh_host_key(ip, 22).await {
+                Ok(Some(key)) => identity.ssh_host_keys.push(key),
+                Ok(None) => {}
+                Err(e) => tracing::debug!("SSH host key exchange failed for {}:22: {}", ip, e),
+            }
+        }
+
+        for port in tcp_ports.iter().filter(|p| TLS_PORTS.contains(p)) {
+            match Self::read_tls_certificate(ip, *port).await {
+                Ok(Some(fingerprint)) if !identity.tls_certificates.contains(&fingerprint) => {
+                    identity.tls_certificates.push(fingerprint)
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("TLS handshake failed for {}:{}: {}", ip, port, e),
+            }
+        }
+
+        let udp_snmp = open_ports
+            .iter()
+            .any(|p| p.protocol() == TransportProtocol::Udp && p.number() == 161);
+        if udp_snmp {
+            identity.snmp_sys_name = Self::query_snmp_sys_name(ip).await.unwrap_or_else(|e| {
+                tracing::debug!("SNMP sysName query failed for {}: {}", ip, e);
+                None
+            });
+        }
+
+        identity
+    }
+
+    /// Start an SSH key exchange, far enough for the server to send its host key. Returns the key's
+    /// fingerprint in the format ssh-keygen prints.
+    pub async fn read_ssh_host_key(ip: IpAddr, port: u16) -> Result<Option<String>, Error> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut stream = timeout(SCAN_TIMEOUT, TcpStream::connect((ip, port))).await??;
+
+        // Servers may send other lines before their identification string
+        let mut identification = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            timeout(SCAN_TIMEOUT, stream.read_exact(&mut byte)).await??;
+            identification.push(byte[0]);
+            if byte[0] == b'\n' {
+                if identification.starts_with(b"SSH-") {
+                    break;
+                }
+                identification.clear();
+            }
+            if identification.len() > 255 {
+                return Ok(None);
+            }
+        }
+        stream.write_all(b"SSH-2.0-NetVisor\r\n").await?;
+
+        let mut rng = rand::rngs::StdRng::from_os_rng();
+        let mut kex_init = vec![SSH_MSG_KEXINIT];
+        kex_init.extend(rng.random::<[u8; 16]>());
+        for name_list in [
+            "curve25519-sha256,curve25519-sha256@libssh.org",
+            "ssh-ed25519,ecdsa-sha2-nistp256,rsa-sha2-512,rsa-sha2-256,ssh-rsa",
+            "aes128-ctr,aes256-ctr,aes128-gcm@openssh.com,chacha20-poly1305@openssh.com",
+            "aes128-ctr,aes256-ctr,aes128-gcm@openssh.com,chacha20-poly1305@openssh.com",
+            "hmac-sha2-256,hmac-sha1",
+            "hmac-sha2-256,hmac-sha1",
+            "none",
+            "none",
+            "",
+            "",
+        ] {
+            write_ssh_string(&mut kex_init, name_list.as_bytes());
+        }
+        // first_kex_packet_follows, reserved
+        kex_init.push(0);
+        kex_init.extend([0u8; 4]);
+
+        // Any 32 bytes are a valid curve25519 public key, and the exchange is never completed
+        let mut ecdh_init = vec![SSH_MSG_KEX_ECDH_INIT];
+        write_ssh_string(&mut ecdh_init, &rng.random::<[u8; 32]>());
+
+        stream.write_all(&ssh_packet(&kex_init)).await?;
+        stream.write_all(&ssh_packet(&ecdh_init)).await?;
+
+        // The server's KEXINIT comes first, then its reply with the host key
+        for _ in 0..4 {
+            let mut length = [0u8; 4];
+            timeout(SCAN_TIMEOUT, stream.read_exact(&mut length)).await??;
+            let length = u32::from_be_bytes(length) as usize;
+            if !(2..=SSH_MAX_PACKET).contains(&length) {
+                return Ok(None);
+            }
+
+            let mut packet = vec![0u8; length];
+            timeout(SCAN_TIMEOUT, stream.read_exact(&mut packet)).await??;
+            let padding = packet[0] as usize;
+            let Some(payload) = packet.get(1..length.saturating_sub(padding)) else {
+                return Ok(None);
+            };
+
+            match payload.first() {
+                Some(&SSH_MSG_KEX_ECDH_REPLY) => {
+                    let Some(host_key) = read_ssh_string(&payload[1..]) else {
+                        return Ok(None);
+                    };
+                    let digest = sha2::Sha256::digest(host_key);
+                    return Ok(Some(format!("SHA256:{}", STANDARD_NO_PAD.encode(digest))));
+                }
+                Some(&SSH_MSG_DISCONNECT) => return Ok(None),
+                _ => continue,
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Complete a TLS handshake without verifying the server, returning the hex SHA256 fingerprint
+    /// of its leaf certificate
+    pub async fn read_tls_certificate(ip: IpAddr, port: u16) -> Result<Option<String>, Error> {
+        tokio::task::spawn_blocking(move || {
+            let config = rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+                .with_no_client_auth();
+            let mut connection =
+                rustls::ClientConnection::new(Arc::new(config), rustls::ServerName::IpAddress(ip))?;
+
+            let mut socket =
+                std::net::TcpStream::connect_timeout(&SocketAddr::new(ip, port), SCAN_TIMEOUT)?;
+            socket.set_read_timeout(Some(SCAN_TIMEOUT))?;
+            socket.set_write_timeout(Some(SCAN_TIMEOUT))?;
+
+            while connection.is_handshaking() {
+                connection.complete_io(&mut socket)?;
+            }
+
+            Ok(connection
+                .peer_certificates()
+                .and_then(|certificates| certificates.first())
+                .map(|certificate| {
+                    sha2::Sha256::digest(&certificate.0)
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect()
+                }))
+        })
+        .await?
+    }
+
+    /// SNMP sysName.0, which is usually the device's configured hostname
+    pub async fn query_snmp_sys_name(ip: IpAddr) -> Result<Option<String>, Error> {
+        let target = format!("{}:161", ip);
+        let mut session = AsyncSession::new_v2c(&target, b"public", 0).await?;
+        let sys_name_oid =
+            Oid::from(&[1, 3, 6, 1, 2, 1, 1, 5, 0]).map_err(|e| anyhow!("Invalid Oid: {:?}", e))?;
+
+        let mut response = timeout(Duration::from_millis(2000), session.get(&sys_name_oid))
+            .await?
+            .map_err(|e| anyhow!("SNMP error: {}", e))?;
+
+        Ok(match response.varbinds.next() {
+            Some((_, snmp2::Value::OctetString(name))) => {
+                let name = String::from_utf8_lossy(name).trim().to_string();
+                (!name.is_empty()).then_some(name)
+            }
+            _ => None,
+        })
+    }
+
+    /// VLAN tag of the host's interface holding the address, from its IF-MIB ifName (ie Vlan20
+    /// or eth0.20), looked up through the IP-MIB address table
+    pub async fn query_snmp_vlan_tag(ip: IpAddr) -> Result<Option<u16>, Error> {
+        let IpAddr::V4(ipv4) = ip else {
+            return Ok(None);
+        };
+
+        let target = format!("{}:161", ip);
+        let mut session = AsyncSession::new_v2c(&target, b"public", 0).await?;
+
+        // ipAdEntIfIndex.<address>
+        let mut if_index_oid: Vec<u64> = vec![1, 3, 6, 1, 2, 1, 4, 20, 1, 2];
+        if_index_oid.extend(ipv4.octets().map(u64::from));
+        let if_index_oid =
+            Oid::from(&if_index_oid[..]).map_err(|e| anyhow!("Invalid Oid: {:?}", e))?;
+
+        let if_index = {
+            let mut response = timeout(Duration::from_millis(2000), session.get(&if_index_oid))
+                .await?
+                .map_err(|e| anyhow!("SNMP error: {}", e))?;
+
+            match response.varbinds.next() {
+                Some((_, snmp2::Value::Integer(index))) if index > 0 => index as u64,
+                _ => return Ok(None),
+            }
+        };
+
+        // ifName.<ifIndex>
+        let if_name_oid = Oid::from(&[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 1, if_index])
+            .map_err(|e| anyhow!("Invalid Oid: {:?}", e))?;
+
+        let mut response = timeout(Duration::from_millis(2000), session.get(&if_name_oid))
+            .await?
+            .map_err(|e| anyhow!("SNMP error: {}", e))?;
+
+        Ok(match response.varbinds.next() {
+            Some((_, snmp2::Value::OctetString(name))) => {
+                InterfaceBase::vlan_tag_from_name(&String::from_utf8_lossy(name))
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Ports which usually speak TLS from the first byte
+const TLS_PORTS: [u16; 6] = [443, 465, 636, 993, 995, 8443];
+
+const SSH_MSG_DISCONNECT: u8 = 1;
+const SSH_MSG_KEXINIT: u8 = 20;
+const SSH_MSG_KEX_ECDH_INIT: u8 = 30;
+const SSH_MSG_KEX_ECDH_REPLY: u8 = 31;
+const SSH_MAX_PACKET: usize = 35000;
+
+/// Frame an unencrypted SSH binary packet, padded to a multiple of 8 bytes
+fn ssh_packet(payload: &[u8]) -> Vec<u8> {
+    let mut padding = 8 - (payload.len() + 5) % 8;
+    if padding < 4 {
+        padding += 8;
+    }
+
+    let mut packet = Vec::with_capacity(payload.len() + padding + 5);
+    packet.extend(((payload.len() + padding + 1) as u32).to_be_bytes());
+    packet.push(padding as u8);
+    packet.extend(payload);
+    packet.extend(vec![0u8; padding]);
+    packet
+}
+
+fn write_ssh_string(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend((value.len() as u32).to_be_bytes());
+    buffer.extend(value);
+}
+
+fn read_ssh_string(buffer: &[u8]) -> Option<&[u8]> {
+    let length = u32::from_be_bytes(buffer.get(..4)?.try_into().ok()?) as usize;
+    buffer.get(4..4 + length)
+}
+
+/// Certificate verifier for reading identity certificates, which are often self-signed
+struct AcceptAnyCertificate;
+
+impl rustls::client::ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
 }
 
 impl<T> AsRef<DaemonDiscoveryService> for Discovery<T> {
@@ -526,6 +974,15 @@ pub struct DaemonDiscoveryService {
     pub client: reqwest::Client,
     pub utils: PlatformDaemonUtils,
     pub current_session: Arc<RwLock<Option<DiscoverySession>>>,
+    /// Service matching traces for every host processed in the last discovery session, if
+    /// record_match_traces is set
+    pub match_traces: Arc<RwLock<Vec<HostMatchTrace>>>,
+    /// Evidence for processed hosts which haven't been reported to the server yet
+    pub pending_evidence: Arc<RwLock<Vec<ScanEvidenceBase>>>,
+    /// Scopes processed hosts were completely scanned in, which haven't been reported yet
+    pub pending_observations: Arc<RwLock<Vec<HostObservation>>>,
+    /// DHCP parameter request lists seen by the DHCP listener, if it's enabled
+    pub dhcp_fingerprints: Arc<DhcpFingerprints>,
 }
 
 impl DaemonDiscoveryService {
@@ -535,6 +992,10 @@ impl DaemonDiscoveryService {
             client: reqwest::Client::new(),
             utils: create_system_utils(),
             current_session: Arc::new(RwLock::new(None)),
+            match_traces: Arc::new(RwLock::new(Vec::new())),
+            pending_evidence: Arc::new(RwLock::new(Vec::new())),
+            pending_observations: Arc::new(RwLock::new(Vec::new())),
+            dhcp_fingerprints: Arc::new(DhcpFingerprints::default()),
         }
     }
 
@@ -546,6 +1007,25 @@ impl DaemonDiscoveryService {
             .cloned()
             .ok_or_else(|| anyhow!("No active discovery session"))
     }
+
+    /// Headers attributing entity changes to this daemon and its current discovery session
+    pub async fn change_headers(&self) -> Result<HeaderMap, Error> {
+        let mut headers = HeaderMap::new();
+        let daemon_id = self.config_store.get_id().await?;
+        headers.insert(
+            DAEMON_ID_HEADER,
+            HeaderValue::from_str(&daemon_id.to_string())?,
+        );
+
+        if let Ok(session) = self.get_session().await {
+            headers.insert(
+                DISCOVERY_SESSION_HEADER,
+                HeaderValue::from_str(&session.info.session_id.to_string())?,
+            );
+        }
+
+        Ok(headers)
+    }
 }
 
 impl AsRef<DaemonDiscoveryService> for DaemonDiscoveryService {
@@ -585,6 +1065,10 @@ pub trait DiscoversNetworkedEntities:
         let mut current_session = self.as_ref().current_session.write().await;
         *current_session = Some(session);
 
+        self.as_ref().match_traces.write().await.clear();
+        self.as_ref().pending_evidence.write().await.clear();
+        self.as_ref().pending_observations.write().await.clear();
+
         Ok(())
     }
 
@@ -609,6 +1093,14 @@ pub trait DiscoversNetworkedEntities:
             request.session_id
         );
 
+        // Discovery can still run against compiled definitions if the server is unreachable
+        if let Err(e) = self.sync_service_definitions().await {
+            tracing::warn!("Could not sync service definitions: {}", e);
+        }
+        if let Err(e) = self.sync_mac_vendor_overrides().await {
+            tracing::warn!("Could not sync MAC vendor overrides: {}", e);
+        }
+
         self.initialize_discovery_session(total_to_scan, request, daemon_id)
             .await?;
 
@@ -624,6 +1116,82 @@ pub trait DiscoversNetworkedEntities:
         Ok(())
     }
 
+    /// Register the server's current service definitions so they are used for matching and
+    /// included in the scanned ports and endpoints, in place of those compiled into the daemon
+    async fn sync_service_definitions(&self) -> Result<(), Error> {
+        let server_target = self.as_ref().config_store.get_server_endpoint().await?;
+        let daemon_id = self.as_ref().config_store.get_id().await?;
+
+        let response = self
+            .as_ref()
+            .client
+            .get(format!(
+                "{}/api/service-definitions/published?daemon_id={}",
+                server_target, daemon_id
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to get service definitions: HTTP {}",
+                response.status()
+            );
+        }
+
+        let api_response: ApiResponse<Vec<PublishedServiceDefinition>> = response.json().await?;
+
+        let definitions = api_response
+            .data
+            .ok_or_else(|| anyhow!("No service definitions in response"))?;
+
+        ServiceDefinitionRegistry::set_published_service_definitions(&definitions);
+
+        tracing::debug!(
+            "Synced {} service definitions from server",
+            definitions.len()
+        );
+        Ok(())
+    }
+
+    /// Register the MAC vendor overrides of the daemon's network so they are used when matching
+    /// vendors
+    async fn sync_mac_vendor_overrides(&self) -> Result<(), Error> {
+        let server_target = self.as_ref().config_store.get_server_endpoint().await?;
+        let daemon_id = self.as_ref().config_store.get_id().await?;
+
+        let response = self
+            .as_ref()
+            .client
+            .get(format!(
+                "{}/api/mac-vendors/daemon?daemon_id={}",
+                server_target, daemon_id
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to get MAC vendor overrides: HTTP {}",
+                response.status()
+            );
+        }
+
+        let api_response: ApiResponse<Vec<MacVendorOverride>> = response.json().await?;
+
+        let overrides = api_response
+            .data
+            .ok_or_else(|| anyhow!("No MAC vendor overrides in response"))?;
+
+        MacVendorRegistry::set_overrides(&overrides);
+
+        tracing::debug!(
+            "Synced {} MAC vendor overrides from server",
+            overrides.len()
+        );
+        Ok(())
+    }
+
     async fn finish_discovery(
         &self,
         discovery_result: Result<(), Error>,
@@ -712,6 +1280,9 @@ pub trait DiscoversNetworkedEntities:
             .await?
             .ok_or_else(|| anyhow::anyhow!("Network ID not set"))?;
 
+        let min_match_score = self.as_ref().config_store.get_min_match_score().await?;
+        let record_match_traces = self.as_ref().config_store.get_record_match_traces().await?;
+
         let session = self.as_ref().get_session().await?;
         let gateway_ips = session.gateway_ips.clone();
         let discovery_type = self.discovery_type();
@@ -735,109 +1306,94 @@ pub trait DiscoversNetworkedEntities:
                 metadata: vec![DiscoveryMetadata::new(discovery_type, daemon_id)],
             },
             virtualization: None,
+            device_fingerprint: DeviceFingerprint::default(),
+            identity: HostIdentity::default(),
+            device_classification: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         });
 
-        let services = self.discover_services(
+        let (services, trace) = self.discover_services(
             &mut host,
             &params,
             &gateway_ips,
             &daemon_id,
-            &network_id,
             &discovery_type,
+            min_match_score,
+            record_match_traces,
         )?;
 
+        let mut evidence =
+            ScanEvidenceBase::new(host.id, &params, &gateway_ips, daemon_id, discovery_type);
+        if record_match_traces {
+            evidence.match_trace = trace.definitions.clone();
+            self.as_ref().match_traces.write().await.push(trace);
+        }
+        self.as_ref().pending_evidence.write().await.push(evidence);
+
         tracing::info!("Processed host for ip {}", interface.base.ip_address);
         Ok(Some((host, services)))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn discover_services(
         &self,
         host: &mut Host,
         baseline_params: &ServiceMatchBaselineParams,
         gateway_ips: &[IpAddr],
         daemon_id: &Uuid,
-        network_id: &Uuid,
         discovery_type: &DiscoveryType,
-    ) -> Result<Vec<Service>, Error> {
-        let ServiceMatchBaselineParams { all_ports, .. } = baseline_params;
+        min_match_score: u32,
+        record_match_traces: bool,
+    ) -> Result<(Vec<Service>, HostMatchTrace), Error> {
+        let ServiceMatchOutcome {
+            matches,
+            unbound_ports,
+            traces,
+        } = Service::match_definitions(
+            &host.id,
+            baseline_params,
+            gateway_ips,
+            daemon_id,
+            &host.base.network_id,
+            discovery_type,
+            min_match_score,
+            record_match_traces,
+        );
 
         let mut services = Vec::new();
 
-        // Need to track which ports are bound vs open for services to bind to
-        let mut l4_unbound_ports = all_ports.to_vec();
-
-        let mut sorted_service_definitions: Vec<Box<dyn ServiceDefinition>> =
-            ServiceDefinitionRegistry::all_service_definitions()
-                .into_iter()
-                .collect();
-
-        sorted_service_definitions.sort_by_key(|s| {
-            if !ServiceDefinitionExt::is_generic(s) {
-                0 // Highest priority - non-generic services
-            } else if ServiceDefinitionExt::is_generic(s) && s.id() != Gateway.id() {
-                1 // Generic services that aren't Gateway
-            } else {
-                2 // Generic gateways need to go last, as other services may be classified as gateway first
-            }
-        });
-
-        // Add services from detected ports
-        for service_definition in sorted_service_definitions {
-            let service_params = ServiceMatchServiceParams {
-                service_definition,
-                matched_services: &services,
-                unbound_ports: &l4_unbound_ports,
-            };
-
-            let params: DiscoverySessionServiceMatchParams<'_> =
-                DiscoverySessionServiceMatchParams {
-                    service_params,
-                    baseline_params,
-                    daemon_id,
-                    discovery_type,
-                    network_id,
-                    gateway_ips,
-                    host_id: &host.id,
-                };
-
-            if let Some((service, mut result)) = Service::from_discovery(params) {
-                // If there's a endpoint match + host target is hostname or none, use a binding as the host target
-                if let (Some(binding), true) = (
-                    service.base.bindings.iter().find(|b| {
-                        match b {
-                            Binding::Interface { .. } => false,
-                            Binding::Port { port_id, .. } => {
-                                if let Some(port) = host.get_port(port_id) {
-                                    return result
-                                        .endpoint
-                                        .iter()
-                                        .any(|e| e.port_base == port.base);
-                                }
-                                false
+        for (service, mut result) in matches {
+            // If there's a endpoint match + host target is hostname or none, use a binding as the host target
+            if let (Some(binding), true) = (
+                service.base.bindings.iter().find(|b| {
+                    match b {
+                        Binding::Interface { .. } => false,
+                        Binding::Port { port_id, .. } => {
+                            if let Some(port) = host.get_port(port_id) {
+                                return result.endpoint.iter().any(|e| e.port_base == port.base);
                             }
-                        };
-                        false
-                    }),
-                    matches!(host.base.target, HostTarget::Hostname | HostTarget::None),
-                ) {
-                    host.base.target = HostTarget::ServiceBinding(binding.id())
-                }
-
-                // Add any bound ports to host ports array, remove from open ports
-                let bound_port_bases: Vec<PortBase> = result.ports.iter().map(|p| p.base).collect();
-
-                host.base.ports.append(&mut result.ports);
-
-                // Add new service
-                l4_unbound_ports.retain(|p| !bound_port_bases.contains(p));
-                services.push(service);
+                            false
+                        }
+                    };
+                    false
+                }),
+                matches!(host.base.target, HostTarget::Hostname | HostTarget::None),
+            ) {
+                host.base.target = HostTarget::ServiceBinding(binding.id())
             }
+
+            // Add any bound ports to host ports array
+            host.base.ports.append(&mut result.ports);
+            services.push(service);
         }
 
         services.sort_by_key(|a| {
             std::cmp::Reverse(match &a.base.source {
-                EntitySource::DiscoveryWithMatch { details, .. } => details.confidence,
-                _ => MatchConfidence::NotApplicable,
+                EntitySource::DiscoveryWithMatch { details, .. } => {
+                    (details.confidence, details.score)
+                }
+                _ => (MatchConfidence::NotApplicable, 0),
             })
         });
 
@@ -852,9 +1408,16 @@ pub trait DiscoversNetworkedEntities:
 
         host.base
             .ports
-            .extend(l4_unbound_ports.into_iter().map(Port::new));
+            .extend(unbound_ports.into_iter().map(Port::new));
 
-        Ok(services)
+        let trace = HostMatchTrace {
+            host_id: host.id,
+            hostname: host.base.hostname.clone(),
+            ip_address: baseline_params.interface.base.ip_address,
+            definitions: traces,
+        };
+
+        Ok((services, trace))
     }
 
     async fn periodic_scan_update(
@@ -934,7 +1497,10 @@ pub trait InitiatesOwnDiscovery:
             .as_ref()
             .client
             .post(format!("{}/api/discovery/daemon-initiate", server_target))
-            .json(&InitiateDiscoveryRequest { daemon_id })
+            .json(&InitiateDiscoveryRequest {
+                daemon_id,
+                discovery_type: Some(self.discovery_type()),
+            })
             .send()
             .await?;
 
@@ -977,11 +1543,39 @@ pub trait CreatesDiscoveredEntities:
 
         tracing::info!("Creating host {}", host.base.name);
 
+        // Evidence is keyed by interface, as discovery may reassign the host's id after processing
+        let evidence: Vec<ScanEvidenceBase> = {
+            let mut pending = self.as_ref().pending_evidence.write().await;
+            let (evidence, remaining) = pending
+                .drain(..)
+                .partition(|e| host.base.interfaces.iter().any(|i| i.id == e.interface_id));
+            *pending = remaining;
+            evidence
+        };
+
+        let observations: Vec<HostObservation> = {
+            let mut pending = self.as_ref().pending_observations.write().await;
+            let (observations, remaining) = pending.drain(..).partition(|o| {
+                host.base
+                    .interfaces
+                    .iter()
+                    .any(|i| i.base.ip_address == o.ip_address && i.base.subnet_id == o.subnet_id)
+            });
+            *pending = remaining;
+            observations
+        };
+
         let response = self
             .as_ref()
             .client
             .post(format!("{}/api/hosts", server_target))
-            .json(&HostWithServicesRequest { host, services })
+            .headers(self.as_ref().change_headers().await?)
+            .json(&HostWithServicesRequest {
+                host,
+                services,
+                evidence,
+                observations,
+            })
             .send()
             .await?;
 
@@ -1001,7 +1595,7 @@ pub trait CreatesDiscoveredEntities:
             anyhow::bail!("Failed to create host: {}", error_msg);
         }
 
-        let HostWithServicesRequest { host, services } = api_response
+        let HostWithServicesRequest { host, services, .. } = api_response
             .data
             .ok_or_else(|| anyhow::anyhow!("No host data in successful response"))?;
 
@@ -1015,6 +1609,7 @@ pub trait CreatesDiscoveredEntities:
             .as_ref()
             .client
             .post(format!("{}/api/subnets", server_target))
+            .headers(self.as_ref().change_headers().await?)
             .json(&subnet)
             .send()
             .await?;
@@ -1049,6 +1644,7 @@ pub trait CreatesDiscoveredEntities:
             .as_ref()
             .client
             .post(format!("{}/api/services", server_target))
+            .headers(self.as_ref().change_headers().await?)
             .json(&service)
             .send()
             .await?;
@@ -1083,6 +1679,7 @@ pub trait CreatesDiscoveredEntities:
             .as_ref()
             .client
             .post(format!("{}/api/groups", server_target))
+            .headers(self.as_ref().change_headers().await?)
             .json(&group)
             .send()
             .await?;
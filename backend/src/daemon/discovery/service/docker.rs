@@ -1,22 +1,26 @@
 use anyhow::anyhow;
 use anyhow::{Error, Result};
 use axum::async_trait;
-use bollard::{
-    Docker,
-    query_parameters::{InspectContainerOptions, ListContainersOptions, ListNetworksOptions},
-    secret::{ContainerInspectResponse, ContainerSummary, PortTypeEnum},
-};
+use bollard::secret::{ContainerInspectResponse, ContainerSummary, PortTypeEnum};
 use futures::future::try_join_all;
 use futures::stream::{self, StreamExt};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
-use std::{collections::HashMap, net::IpAddr, sync::OnceLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    sync::OnceLock,
+};
 use strum::IntoDiscriminant;
 use tokio_util::sync::CancellationToken;
 
 use crate::daemon::discovery::service::base::{HasDiscoveryType, InitiatesOwnDiscovery};
 use crate::daemon::discovery::types::base::DiscoverySessionUpdate;
+use crate::daemon::discovery::types::container_runtime::{
+    ContainerRuntimeClient, connect_container_runtime,
+};
+use crate::daemon::discovery::types::cri::POD_NAMESPACE_LABEL;
 use crate::daemon::utils::base::DaemonUtils;
 use crate::server::discovery::types::base::{DiscoveryMetadata, DiscoveryType};
 use crate::server::hosts::types::base::HostBase;
@@ -25,9 +29,12 @@ use crate::server::hosts::types::ports::Port;
 use crate::server::services::types::base::{Service, ServiceBase, ServiceMatchBaselineParams};
 use crate::server::services::types::bindings::{Binding, BindingDiscriminants};
 use crate::server::services::types::definitions::ServiceDefinition;
-use crate::server::services::types::endpoints::{Endpoint, EndpointResponse};
+use crate::server::services::types::endpoints::{Endpoint, EndpointResponse, ResponseHeaders};
 use crate::server::services::types::patterns::MatchDetails;
-use crate::server::services::types::virtualization::{DockerVirtualization, ServiceVirtualization};
+use crate::server::services::types::virtualization::{
+    ContainerPublishedPort, ContainerRuntime, ContainerVirtualization, ContainerVolume,
+    ServiceVirtualization,
+};
 use crate::server::subnets::types::base::{
     Subnet, SubnetBase, SubnetType, SubnetTypeDiscriminants,
 };
@@ -45,14 +52,17 @@ use crate::{
         },
     },
 };
+use chrono::DateTime;
 use cidr::IpCidr;
 use mac_address::MacAddress;
 use uuid::Uuid;
 
 type IpPortHashMap = HashMap<IpAddr, Vec<PortBase>>;
 
+/// Container discovery against whichever runtime is available on the host. The Docker discovery
+/// type covers Docker, Podman and containerd; the runtime is recorded on each container's service.
 pub struct DockerScanDiscovery {
-    docker_client: OnceLock<Docker>,
+    runtime_client: OnceLock<Box<dyn ContainerRuntimeClient>>,
     host_id: Uuid,
 }
 
@@ -67,7 +77,7 @@ impl HasDiscoveryType for Discovery<DockerScanDiscovery> {
 impl Default for DockerScanDiscovery {
     fn default() -> Self {
         Self {
-            docker_client: OnceLock::new(),
+            runtime_client: OnceLock::new(),
             host_id: Uuid::nil(),
         }
     }
@@ -76,10 +86,20 @@ impl Default for DockerScanDiscovery {
 impl DockerScanDiscovery {
     pub fn new(host_id: Uuid) -> Self {
         Self {
-            docker_client: OnceLock::new(),
+            runtime_client: OnceLock::new(),
             host_id,
         }
     }
+
+    /// Use the given runtime for this discovery. Fails if a runtime is already set.
+    pub fn set_runtime_client(
+        &self,
+        runtime_client: Box<dyn ContainerRuntimeClient>,
+    ) -> Result<()> {
+        self.runtime_client
+            .set(runtime_client)
+            .map_err(|_| anyhow!("Failed to set container runtime client"))
+    }
 }
 
 impl InitiatesOwnDiscovery for Discovery<DockerScanDiscovery> {}
@@ -101,11 +121,13 @@ impl DiscoversNetworkedEntities for Discovery<DockerScanDiscovery> {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Network ID not set"))?;
 
-        let docker = self.new_local_docker_client().await?;
+        let containerd_namespace = self
+            .as_ref()
+            .config_store
+            .get_containerd_namespace()
+            .await?;
         self.domain
-            .docker_client
-            .set(docker.clone())
-            .map_err(|_| anyhow!("Failed to set docker client"))?;
+            .set_runtime_client(connect_container_runtime(containerd_namespace).await?)?;
 
         let container_list = self.get_containers_to_scan().await?;
 
@@ -157,14 +179,9 @@ impl DiscoversNetworkedEntities for Discovery<DockerScanDiscovery> {
     }
 
     async fn get_gateway_ips(&self) -> Result<Vec<IpAddr>, Error> {
-        let docker = self
-            .domain
-            .docker_client
-            .get()
-            .ok_or_else(|| anyhow!("Docker client unavailable"))?;
-
-        let gateway_ips: Vec<IpAddr> = docker
-            .list_networks(None::<ListNetworksOptions>)
+        let gateway_ips: Vec<IpAddr> = self
+            .runtime_client()?
+            .list_networks()
             .await?
             .iter()
             .filter_map(|n| {
@@ -227,16 +244,12 @@ pub struct ProcessContainerParams<'a> {
 }
 
 impl Discovery<DockerScanDiscovery> {
-    /// Create a new Docker discovery instance connecting to a remote Docker daemon
-    pub async fn new_local_docker_client(&self) -> Result<Docker, Error> {
-        tracing::debug!("Connecting to Docker daemon");
-
-        let client = Docker::connect_with_local_defaults()
-            .map_err(|e| anyhow::anyhow!("Failed to connect to Docker: {}", e))?;
-
-        client.ping().await?;
-
-        Ok(client)
+    fn runtime_client(&self) -> Result<&dyn ContainerRuntimeClient, Error> {
+        self.domain
+            .runtime_client
+            .get()
+            .map(|c| c.as_ref())
+            .ok_or_else(|| anyhow!("Container runtime client unavailable"))
     }
 
     /// Create docker daemon service which has all discovered containers in containers field
@@ -254,8 +267,17 @@ impl Discovery<DockerScanDiscovery> {
 
         let docker_service_definition = crate::server::services::definitions::docker_daemon::Docker;
 
+        // Podman and containerd share the docker daemon definition, but keep their own name
+        let runtime = self.runtime_client()?.runtime();
+        let name = match runtime {
+            ContainerRuntime::Docker => {
+                ServiceDefinition::name(&docker_service_definition).to_string()
+            }
+            _ => runtime.to_string(),
+        };
+
         let docker_service = Service::new(ServiceBase {
-            name: ServiceDefinition::name(&docker_service_definition).to_string(),
+            name,
             service_definition: Box::new(docker_service_definition),
             bindings: vec![],
             host_id,
@@ -263,8 +285,13 @@ impl Discovery<DockerScanDiscovery> {
             virtualization: None,
             source: EntitySource::DiscoveryWithMatch {
                 metadata: vec![DiscoveryMetadata::new(DiscoveryType::SelfReport, daemon_id)],
-                details: MatchDetails::new_certain("Docker daemon self-report"),
+                details: MatchDetails::new_certain(&format!("{} daemon self-report", runtime)),
             },
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         });
 
         let mut temp_docker_daemon_host = Host::new(HostBase::default());
@@ -397,11 +424,11 @@ impl Discovery<DockerScanDiscovery> {
         let ProcessContainerParams {
             containers_interfaces_and_subnets,
             container,
+            container_summary,
             scanned_count,
             discovered_count,
             cancel,
             docker_service_id,
-            ..
         } = params;
 
         scanned_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -416,6 +443,11 @@ impl Discovery<DockerScanDiscovery> {
 
         let host_ip = self.as_ref().utils.get_own_ip_address()?;
 
+        let virtualization = Some(ServiceVirtualization::Container(
+            self.get_container_virtualization(container, container_summary, docker_service_id)
+                .await,
+        ));
+
         if let Some(Some(p)) = container.config.as_ref().map(|c| c.exposed_ports.as_ref()) {
             let open_ports: Vec<PortBase> = p
                 .keys()
@@ -444,14 +476,8 @@ impl Discovery<DockerScanDiscovery> {
                     interface,
                     all_ports: &open_ports,
                     endpoint_responses: &endpoint_responses,
-                    virtualization: &Some(ServiceVirtualization::Docker(DockerVirtualization {
-                        container_name: container
-                            .name
-                            .clone()
-                            .map(|n| n.trim_start_matches("/").to_string()),
-                        container_id: container.id.clone(),
-                        service_id: **docker_service_id,
-                    })),
+                    banners: &[],
+                    virtualization: &virtualization,
                 };
 
                 if let Ok(Some((mut host, services))) = self.process_host(params, None).await {
@@ -507,6 +533,11 @@ impl Discovery<DockerScanDiscovery> {
         let (host_ip_to_host_ports, container_ips_to_container_ports, host_to_container_port_map) =
             self.get_ports_from_container(container_summary, container_interfaces_and_subnets);
 
+        let virtualization = Some(ServiceVirtualization::Container(
+            self.get_container_virtualization(container, container_summary, docker_service_id)
+                .await,
+        ));
+
         for (interface, subnet) in container_interfaces_and_subnets {
             if cancel.is_cancelled() {
                 return Err(Error::msg("Discovery was cancelled"));
@@ -544,16 +575,8 @@ impl Discovery<DockerScanDiscovery> {
                         interface,
                         all_ports: container_ports_on_interface,
                         endpoint_responses: &endpoint_responses,
-                        virtualization: &Some(ServiceVirtualization::Docker(
-                            DockerVirtualization {
-                                container_name: container
-                                    .name
-                                    .clone()
-                                    .map(|n| n.trim_start_matches("/").to_string()),
-                                container_id: container.id.clone(),
-                                service_id: **docker_service_id,
-                            },
-                        )),
+                        banners: &[],
+                        virtualization: &virtualization,
                     },
                     None,
                 )
@@ -721,16 +744,7 @@ impl Discovery<DockerScanDiscovery> {
     }
 
     pub async fn get_containers_to_scan(&self) -> Result<Vec<ContainerSummary>, Error> {
-        let docker = self
-            .domain
-            .docker_client
-            .get()
-            .ok_or_else(|| anyhow!("Docker client unavailable"))?;
-
-        docker
-            .list_containers(None::<ListContainersOptions>)
-            .await
-            .map_err(|e| anyhow!(e))
+        self.runtime_client()?.list_containers().await
     }
 
     pub async fn get_subnets_from_docker_networks(
@@ -738,14 +752,9 @@ impl Discovery<DockerScanDiscovery> {
         daemon_id: Uuid,
         network_id: Uuid,
     ) -> Result<Vec<Subnet>> {
-        let docker = self
-            .domain
-            .docker_client
-            .get()
-            .ok_or_else(|| anyhow!("Docker client unavailable"))?;
-
-        let subnets: Vec<Subnet> = docker
-            .list_networks(None::<ListNetworksOptions>)
+        let subnets: Vec<Subnet> = self
+            .runtime_client()?
+            .list_networks()
             .await?
             .into_iter()
             .filter_map(|n| {
@@ -764,12 +773,15 @@ impl Discovery<DockerScanDiscovery> {
                                 network_id,
                                 name: network_name.clone(),
                                 subnet_type: SubnetType::DockerBridge,
+                                vlan_id: None,
                                 source: EntitySource::Discovery {
                                     metadata: vec![DiscoveryMetadata::new(
                                         self.discovery_type(),
                                         daemon_id,
                                     )],
                                 },
+                                tags: Vec::new(),
+                                custom_fields: BTreeMap::new(),
                             }));
                         }
                         None
@@ -781,14 +793,105 @@ impl Discovery<DockerScanDiscovery> {
         Ok(subnets)
     }
 
+    /// Build the docker virtualization for a container, including the image and runtime
+    /// details used for container inventory
+    pub async fn get_container_virtualization(
+        &self,
+        container: &ContainerInspectResponse,
+        container_summary: &ContainerSummary,
+        docker_service_id: &Uuid,
+    ) -> ContainerVirtualization {
+        let image = container
+            .config
+            .as_ref()
+            .and_then(|c| c.image.clone())
+            .or_else(|| container_summary.image.clone());
+
+        // Repo digests are only known for images pulled from a registry
+        let runtime_client = self.domain.runtime_client.get();
+
+        let image_digest = match (runtime_client, &container.image) {
+            (Some(client), Some(image_id)) => client
+                .get_image_digests(image_id)
+                .await
+                .ok()
+                .and_then(|digests| {
+                    digests
+                        .iter()
+                        .find_map(|d| d.split_once('@').map(|(_, digest)| digest.to_string()))
+                }),
+            _ => None,
+        };
+
+        let published_ports = container_summary
+            .ports
+            .iter()
+            .flatten()
+            .filter_map(|p| {
+                Some(ContainerPublishedPort {
+                    host_ip: p.ip.clone(),
+                    host_port: p.public_port?,
+                    container_port: p.private_port,
+                    protocol: p.typ.map(|t| t.to_string()).unwrap_or("tcp".to_string()),
+                })
+            })
+            .collect();
+
+        let volumes = container
+            .mounts
+            .iter()
+            .flatten()
+            .filter_map(|m| {
+                Some(ContainerVolume {
+                    source: m.source.clone().filter(|s| !s.is_empty()),
+                    destination: m.destination.clone()?,
+                    mount_type: m.typ.map(|t| t.to_string()).filter(|t| !t.is_empty()),
+                    read_only: !m.rw.unwrap_or(true),
+                })
+            })
+            .collect();
+
+        ContainerVirtualization {
+            container_name: container
+                .name
+                .clone()
+                .map(|n| n.trim_start_matches("/").to_string()),
+            container_id: container.id.clone(),
+            service_id: *docker_service_id,
+            runtime: runtime_client.map(|c| c.runtime()).unwrap_or_default(),
+            namespace: container
+                .config
+                .as_ref()
+                .and_then(|c| c.labels.as_ref())
+                .and_then(|l| l.get(POD_NAMESPACE_LABEL).cloned()),
+            image,
+            image_digest,
+            created: container_summary
+                .created
+                .and_then(|c| DateTime::from_timestamp(c, 0)),
+            restart_policy: container
+                .host_config
+                .as_ref()
+                .and_then(|c| c.restart_policy.as_ref())
+                .and_then(|r| r.name)
+                .map(|n| n.to_string())
+                .filter(|n| !n.is_empty()),
+            health: container
+                .state
+                .as_ref()
+                .and_then(|s| s.health.as_ref())
+                .and_then(|h| h.status)
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty()),
+            published_ports,
+            volumes,
+        }
+    }
+
     pub async fn get_containers_and_summaries(
         &self,
     ) -> Result<Vec<(ContainerInspectResponse, ContainerSummary)>, Error> {
-        let docker = self
-            .domain
-            .docker_client
-            .get()
-            .ok_or_else(|| anyhow!("Docker client unavailable"))?;
+        let runtime_client = self.runtime_client()?;
 
         let container_summaries = self.get_containers_to_scan().await?;
 
@@ -796,7 +899,7 @@ impl Discovery<DockerScanDiscovery> {
             .iter()
             .filter_map(|c| {
                 if let Some(id) = &c.id {
-                    return Some(docker.inspect_container(id, None::<InspectContainerOptions>));
+                    return Some(runtime_client.inspect_container(id));
                 }
                 None
             })
@@ -829,11 +932,7 @@ impl Discovery<DockerScanDiscovery> {
                 .push((*host_ip, *host_port));
         }
 
-        let docker = self
-            .domain
-            .docker_client
-            .get()
-            .ok_or_else(|| anyhow!("Docker client unavailable"))?;
+        let runtime_client = self.runtime_client()?;
 
         let all_endpoints = Service::all_discovery_endpoints();
 
@@ -845,7 +944,7 @@ impl Discovery<DockerScanDiscovery> {
         }
 
         tracing::debug!(
-            "Scanning {} unique endpoints for container {} at {} using docker exec (deduplicated from {} total)",
+            "Scanning {} unique endpoints for container {} at {} using container exec (deduplicated from {} total)",
             unique_endpoints.len(),
             container_name,
             interface.base.ip_address,
@@ -854,7 +953,7 @@ impl Discovery<DockerScanDiscovery> {
 
         let mut endpoint_responses = Vec::new();
 
-        // Only make one docker exec per unique (port, path) combination
+        // Only make one container exec per unique (port, path) combination
         for ((container_port, path), endpoint) in unique_endpoints {
             if cancel.is_cancelled() {
                 break;
@@ -866,52 +965,22 @@ impl Discovery<DockerScanDiscovery> {
             );
 
             // Execute curl with -i to include headers, or wget with -S
-            let exec = docker
-                .create_exec(
+            let exec = runtime_client
+                .exec(
                     container_name,
-                    bollard::exec::CreateExecOptions {
-                        cmd: Some(vec![
-                            "sh",
-                            "-c",
-                            &format!(
-                                "curl -i -s -m 1 -L --max-redirs 2 {} 2>/dev/null || wget -S -q -O- -T 1 {} 2>&1 || echo ''",
-                                url, url
-                            ),
-                        ]),
-                        attach_stdout: Some(true),
-                        attach_stderr: Some(false),
-                        ..Default::default()
-                    },
+                    &format!(
+                        "curl -i -s -m 1 -L --max-redirs 2 {} 2>/dev/null || wget -S -q -O- -T 1 {} 2>&1 || echo ''",
+                        url, url
+                    ),
                 )
                 .await;
 
-            let Ok(exec_result) = exec else {
-                continue;
-            };
-
-            if let Ok(bollard::exec::StartExecResults::Attached { mut output, .. }) =
-                docker.start_exec(&exec_result.id, None).await
-            {
-                use futures::StreamExt;
-                let mut full_response = String::new();
-
-                while let Some(Ok(msg)) = output.next().await {
-                    match msg {
-                        bollard::container::LogOutput::StdOut { message } => {
-                            full_response.push_str(&String::from_utf8_lossy(&message));
-                        }
-                        bollard::container::LogOutput::StdErr { message } => {
-                            // wget outputs headers to stderr with -S flag
-                            full_response.push_str(&String::from_utf8_lossy(&message));
-                        }
-                        _ => {}
-                    }
-                }
-
+            if let Ok(full_response) = exec {
                 let full_response = full_response.trim();
 
                 // Parse response to check status code and extract body
-                if let Some((status_code, response_body)) = Self::parse_http_response(full_response)
+                if let Some((status_code, headers, response_body)) =
+                    Self::parse_http_response(full_response)
                 {
                     // Only accept 2xx-3xx status codes
                     if (199..400).contains(&status_code) {
@@ -937,6 +1006,7 @@ impl Discovery<DockerScanDiscovery> {
 
                                 endpoint_responses.push(EndpointResponse {
                                     endpoint: host_endpoint,
+                                    headers: headers.clone(),
                                     response: response_body.clone(),
                                 });
                             }
@@ -949,9 +1019,9 @@ impl Discovery<DockerScanDiscovery> {
         Ok(endpoint_responses)
     }
 
-    /// Parse HTTP response to extract status code and body
-    /// Returns (status_code, body) if successful
-    fn parse_http_response(response: &str) -> Option<(u16, String)> {
+    /// Parse HTTP response to extract status code, headers and body
+    /// Returns (status_code, headers, body) if successful
+    fn parse_http_response(response: &str) -> Option<(u16, ResponseHeaders, String)> {
         if response.is_empty() {
             return None;
         }
@@ -964,10 +1034,20 @@ impl Discovery<DockerScanDiscovery> {
         match parsed_response.parse(response_bytes) {
             Ok(httparse::Status::Complete(headers_len)) => {
                 let status_code = parsed_response.code?;
+                let headers = parsed_response
+                    .headers
+                    .iter()
+                    .map(|h| {
+                        (
+                            h.name.to_lowercase(),
+                            String::from_utf8_lossy(h.value).to_string(),
+                        )
+                    })
+                    .collect();
                 let body = &response_bytes[headers_len..];
                 let body_str = String::from_utf8_lossy(body).to_string();
 
-                Some((status_code, body_str))
+                Some((status_code, headers, body_str))
             }
             Ok(httparse::Status::Partial) => {
                 // Not enough data, might be incomplete response
@@ -1043,7 +1123,7 @@ impl Discovery<DockerScanDiscovery> {
         )
     }
 
-    fn get_container_interfaces(
+    pub fn get_container_interfaces(
         &self,
         containers: &[(ContainerInspectResponse, ContainerSummary)],
         subnets: &[Subnet],
@@ -1109,6 +1189,9 @@ impl Discovery<DockerScanDiscovery> {
                                                 ip_address,
                                                 mac_address,
                                                 name: Some(network_name.to_owned()),
+                                                mac_vendor: None,
+                                                randomized_mac: false,
+                                                vlan_tag: None,
                                             }),
                                             subnet.clone(),
                                         ));
@@ -0,0 +1,919 @@
+use anyhow::anyhow;
+use anyhow::{Error, Result};
+use axum::async_trait;
+use cidr::{IpCidr, Ipv4Cidr};
+use futures::future::try_join_all;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use strum::IntoDiscriminant;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+use uuid::Uuid;
+
+use crate::daemon::discovery::service::base::{
+    CreatesDiscoveredEntities, DiscoversNetworkedEntities, Discovery, HasDiscoveryType,
+};
+use crate::daemon::discovery::types::base::DiscoverySessionUpdate;
+use crate::daemon::discovery::types::kubernetes::{
+    ClusterInventory, KubeIngress, KubeNode, KubeService, KubernetesClient,
+};
+use crate::daemon::utils::base::DaemonUtils;
+use crate::server::daemons::types::api::DaemonDiscoveryRequest;
+use crate::server::discovery::types::base::{DiscoveryMetadata, DiscoveryType, EntitySource};
+use crate::server::hosts::types::base::{Host, HostBase};
+use crate::server::hosts::types::devices::DeviceFingerprint;
+use crate::server::hosts::types::identity::HostIdentity;
+use crate::server::hosts::types::interfaces::{Interface, InterfaceBase};
+use crate::server::hosts::types::ports::{Port, PortBase};
+use crate::server::hosts::types::targets::HostTarget;
+use crate::server::services::definitions::ServiceDefinitionRegistry;
+use crate::server::services::definitions::kubernetes::Kubernetes;
+use crate::server::services::definitions::web_service::WebService;
+use crate::server::services::types::base::{Service, ServiceBase, ServiceMatchBaselineParams};
+use crate::server::services::types::bindings::Binding;
+use crate::server::services::types::definitions::ServiceDefinition;
+use crate::server::services::types::patterns::{MatchConfidence, MatchDetails, MatchReason};
+use crate::server::services::types::virtualization::{
+    KubernetesVirtualization, ServiceVirtualization,
+};
+use crate::server::subnets::types::base::{
+    Subnet, SubnetBase, SubnetType, SubnetTypeDiscriminants,
+};
+
+pub struct KubernetesScanDiscovery {
+    kubeconfig_path: Option<String>,
+    inventory: OnceLock<ClusterInventory>,
+}
+
+impl HasDiscoveryType for Discovery<KubernetesScanDiscovery> {
+    fn discovery_type(&self) -> DiscoveryType {
+        DiscoveryType::Kubernetes
+    }
+}
+
+impl KubernetesScanDiscovery {
+    pub fn new(kubeconfig_path: Option<String>) -> Self {
+        Self {
+            kubeconfig_path,
+            inventory: OnceLock::new(),
+        }
+    }
+}
+
+impl CreatesDiscoveredEntities for Discovery<KubernetesScanDiscovery> {}
+
+#[async_trait]
+impl DiscoversNetworkedEntities for Discovery<KubernetesScanDiscovery> {
+    async fn start_discovery_session(
+        &self,
+        request: DaemonDiscoveryRequest,
+        cancel: CancellationToken,
+    ) -> Result<(), Error> {
+        let client = KubernetesClient::load(self.domain.kubeconfig_path.as_deref())?;
+
+        tracing::info!("Reading cluster inventory from {}", client.api_server());
+
+        let inventory = client.get_cluster_inventory().await?;
+
+        let total_to_scan =
+            inventory.nodes.len() + inventory.services.len() + inventory.ingresses.len();
+
+        self.domain
+            .inventory
+            .set(inventory)
+            .map_err(|_| anyhow!("Failed to set cluster inventory"))?;
+
+        self.start_discovery(total_to_scan, request).await?;
+
+        let subnets = self.discover_create_subnets().await?;
+
+        let discovery_result = self.process_cluster(&subnets, cancel.clone()).await;
+
+        self.finish_discovery(discovery_result, cancel.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_gateway_ips(&self) -> Result<Vec<IpAddr>, Error> {
+        self.as_ref()
+            .utils
+            .get_own_routing_table_gateway_ips()
+            .await
+    }
+
+    async fn discover_create_subnets(&self) -> Result<Vec<Subnet>, Error> {
+        let (network_id, daemon_id) = self.network_and_daemon_ids().await?;
+
+        let inventory = self.inventory()?;
+
+        let (_, host_subnets) = self
+            .as_ref()
+            .utils
+            .get_own_interfaces(self.discovery_type(), daemon_id, network_id)
+            .await?;
+
+        let subnets = Self::cluster_subnets(inventory, host_subnets, network_id, daemon_id);
+
+        let subnet_futures = subnets.iter().map(|subnet| self.create_subnet(subnet));
+        let subnets = try_join_all(subnet_futures).await?;
+
+        Ok(subnets)
+    }
+}
+
+impl Discovery<KubernetesScanDiscovery> {
+    fn inventory(&self) -> Result<&ClusterInventory, Error> {
+        self.domain
+            .inventory
+            .get()
+            .ok_or_else(|| anyhow!("Cluster inventory unavailable"))
+    }
+
+    async fn network_and_daemon_ids(&self) -> Result<(Uuid, Uuid), Error> {
+        let daemon_id = self.as_ref().config_store.get_id().await?;
+        let network_id = self
+            .as_ref()
+            .config_store
+            .get_network_id()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Network ID not set"))?;
+
+        Ok((network_id, daemon_id))
+    }
+
+    fn source(daemon_id: Uuid) -> EntitySource {
+        EntitySource::Discovery {
+            metadata: vec![DiscoveryMetadata::new(DiscoveryType::Kubernetes, daemon_id)],
+        }
+    }
+
+    /// Subnets for the cluster: the daemon's own subnets, a /24 for node and load balancer IPs
+    /// outside of them, each node's pod CIDRs, the ClusterIP range, and within it the range of each
+    /// namespace's ClusterIPs
+    pub fn cluster_subnets(
+        inventory: &ClusterInventory,
+        host_subnets: Vec<Subnet>,
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> Vec<Subnet> {
+        // Docker bridges on the daemon host aren't relevant to the cluster
+        let mut subnets: Vec<Subnet> = host_subnets
+            .into_iter()
+            .filter(|s| s.base.subnet_type.discriminant() != SubnetTypeDiscriminants::DockerBridge)
+            .collect();
+
+        let new_subnet = |cidr: IpCidr, name: String, subnet_type: SubnetType| {
+            Subnet::new(SubnetBase {
+                cidr,
+                network_id,
+                name,
+                description: None,
+                subnet_type,
+                vlan_id: None,
+                source: Self::source(daemon_id),
+                tags: Vec::new(),
+                custom_fields: BTreeMap::new(),
+            })
+        };
+
+        let routable_ips: Vec<IpAddr> = inventory
+            .nodes
+            .iter()
+            .flat_map(|n| n.ip_addresses())
+            .chain(
+                inventory
+                    .services
+                    .iter()
+                    .flat_map(|s| s.load_balancer_ips()),
+            )
+            .chain(
+                inventory
+                    .ingresses
+                    .iter()
+                    .flat_map(|i| i.load_balancer_ips()),
+            )
+            .collect();
+
+        for ip in routable_ips {
+            if let IpAddr::V4(ipv4) = ip
+                && !subnets.iter().any(|s| s.base.cidr.contains(&ip))
+                && let Ok(cidr) =
+                    Ipv4Cidr::new(std::net::Ipv4Addr::from(u32::from(ipv4) & 0xFFFF_FF00), 24)
+            {
+                let cidr = IpCidr::V4(cidr);
+                subnets.push(new_subnet(cidr, cidr.to_string(), SubnetType::Lan));
+            }
+        }
+
+        for node in &inventory.nodes {
+            for cidr in node.pod_cidrs() {
+                if !subnets.iter().any(|s| s.base.cidr == cidr) {
+                    subnets.push(new_subnet(
+                        cidr,
+                        format!("{} pods", node.metadata.name),
+                        SubnetType::Kubernetes,
+                    ));
+                }
+            }
+        }
+
+        if let Some(cidr) = inventory.service_cidr()
+            && !subnets.iter().any(|s| s.base.cidr == cidr)
+        {
+            subnets.push(new_subnet(
+                cidr,
+                "Kubernetes Services".to_string(),
+                SubnetType::Kubernetes,
+            ));
+        }
+
+        // Namespaces whose ClusterIPs interleave share a range, and so a subnet
+        for namespace in &inventory.namespaces {
+            let name = namespace.metadata.name.clone();
+            let Some(cidr) = inventory.namespace_cidr(&Some(name.clone())) else {
+                continue;
+            };
+
+            match subnets.iter_mut().find(|s| s.base.cidr == cidr) {
+                Some(existing) if existing.base.subnet_type == SubnetType::Kubernetes => {
+                    let namespaces = match existing.base.description.take() {
+                        Some(description) => format!("{}, {}", description, name),
+                        None => format!("ClusterIPs for namespaces {}", name),
+                    };
+                    existing.base.description = Some(namespaces);
+                }
+                Some(_) => {}
+                None => {
+                    let mut namespace_subnet =
+                        new_subnet(cidr, format!("{} namespace", name), SubnetType::Kubernetes);
+                    namespace_subnet.base.description =
+                        Some(format!("ClusterIPs for namespaces {}", name));
+                    subnets.push(namespace_subnet);
+                }
+            }
+        }
+
+        subnets
+    }
+
+    fn interfaces_for_ips(subnets: &[Subnet], ips: &[IpAddr]) -> Vec<(Interface, Subnet)> {
+        ips.iter()
+            .filter_map(|ip| {
+                // Prefer the most specific subnet containing the IP
+                subnets
+                    .iter()
+                    .filter(|s| s.base.cidr.contains(ip))
+                    .max_by_key(|s| s.base.cidr.network_length())
+                    .map(|subnet| {
+                        (
+                            Interface::new(InterfaceBase {
+                                subnet_id: subnet.id,
+                                ip_address: *ip,
+                                mac_address: None,
+                                name: None,
+                                mac_vendor: None,
+                                randomized_mac: false,
+                                vlan_tag: None,
+                            }),
+                            subnet.clone(),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Interfaces for a k8s service's ClusterIP and load balancer IPs. The ClusterIP is placed in
+    /// its namespace's subnet, as namespace ranges can nest inside each other.
+    pub fn service_interfaces(
+        inventory: &ClusterInventory,
+        service: &KubeService,
+        subnets: &[Subnet],
+    ) -> Vec<(Interface, Subnet)> {
+        let Some(cluster_ip) = service.cluster_ip() else {
+            return Vec::new();
+        };
+
+        let ips: Vec<IpAddr> = std::iter::once(cluster_ip)
+            .chain(service.load_balancer_ips())
+            .collect();
+
+        let mut interfaces_and_subnets = Self::interfaces_for_ips(subnets, &ips);
+
+        let namespace_subnet = inventory
+            .namespace_cidr(&service.metadata.namespace)
+            .and_then(|cidr| subnets.iter().find(|s| s.base.cidr == cidr));
+
+        if let (Some(namespace_subnet), Some((interface, subnet))) =
+            (namespace_subnet, interfaces_and_subnets.first_mut())
+            && interface.base.ip_address == cluster_ip
+        {
+            interface.base.subnet_id = namespace_subnet.id;
+            *subnet = namespace_subnet.clone();
+        }
+
+        interfaces_and_subnets
+    }
+
+    fn ports_for_service(service: &KubeService) -> Vec<PortBase> {
+        let mut ports: Vec<PortBase> = service
+            .spec
+            .ports
+            .iter()
+            .map(|p| match p.protocol.as_deref() {
+                Some("UDP") => PortBase::new_udp(p.port),
+                _ => PortBase::new_tcp(p.port),
+            })
+            .collect();
+        ports.dedup();
+        ports
+    }
+
+    /// Definition for the workload behind a k8s service, based on the images its pods run
+    pub fn match_service_images(
+        inventory: &ClusterInventory,
+        service: &KubeService,
+    ) -> Option<(Box<dyn ServiceDefinition>, MatchDetails)> {
+        inventory
+            .images_for_service(service)
+            .iter()
+            .find_map(|image| {
+                ServiceDefinitionRegistry::find_by_image(image).map(|definition| {
+                    let details = MatchDetails::new(
+                        MatchReason::Reason(format!(
+                            "Pod image {} matches {}",
+                            image,
+                            definition.name()
+                        )),
+                        MatchConfidence::Medium.min_score(),
+                    );
+                    (definition, details)
+                })
+            })
+    }
+
+    async fn process_cluster(
+        &self,
+        subnets: &[Subnet],
+        cancel: CancellationToken,
+    ) -> Result<(), Error> {
+        let inventory = self.inventory()?;
+
+        self.report_discovery_update(DiscoverySessionUpdate::scanning(0, 0))
+            .await?;
+
+        let control_plane_service_id = self.process_nodes(&inventory.nodes, subnets).await?;
+
+        let mut last_reported_scan_count: usize = 0;
+        let mut last_reported_discovery_count: usize = 0;
+
+        for service in &inventory.services {
+            if cancel.is_cancelled() {
+                return Err(Error::msg("Kubernetes discovery session was cancelled"));
+            }
+
+            if let Err(e) = self
+                .process_service(inventory, service, subnets, control_plane_service_id)
+                .await
+            {
+                tracing::warn!(
+                    "Error processing Kubernetes service {}: {}",
+                    service.metadata.name,
+                    e
+                );
+            }
+
+            (last_reported_scan_count, last_reported_discovery_count) = self
+                .periodic_scan_update(5, last_reported_scan_count, last_reported_discovery_count)
+                .await?;
+        }
+
+        for ingress in &inventory.ingresses {
+            if cancel.is_cancelled() {
+                return Err(Error::msg("Kubernetes discovery session was cancelled"));
+            }
+
+            if let Err(e) = self
+                .process_ingress(inventory, ingress, subnets, control_plane_service_id)
+                .await
+            {
+                tracing::warn!(
+                    "Error processing Kubernetes ingress {}: {}",
+                    ingress.metadata.name,
+                    e
+                );
+            }
+
+            (last_reported_scan_count, last_reported_discovery_count) = self
+                .periodic_scan_update(5, last_reported_scan_count, last_reported_discovery_count)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create hosts for cluster nodes. Control plane nodes get a Kubernetes service, the ID of which
+    /// is returned so workloads can reference it through their virtualization. Managed clusters
+    /// don't report their control plane as a node, so it's represented by a host for the API server.
+    async fn process_nodes(&self, nodes: &[KubeNode], subnets: &[Subnet]) -> Result<Uuid, Error> {
+        let (network_id, daemon_id) = self.network_and_daemon_ids().await?;
+        let session = self.as_ref().get_session().await?;
+
+        let mut control_plane_service_id = None;
+
+        for node in nodes {
+            session
+                .scanned_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+            let Some((host, services)) = Self::node_host(node, subnets, network_id, daemon_id)
+            else {
+                tracing::warn!(
+                    "Kubernetes node {} has no usable addresses",
+                    node.metadata.name
+                );
+                continue;
+            };
+
+            let (_, created_services) = self.create_host(host, services).await?;
+
+            session
+                .discovered_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+            if control_plane_service_id.is_none() {
+                control_plane_service_id = created_services.first().map(|s| s.id);
+            }
+        }
+
+        if let Some(service_id) = control_plane_service_id {
+            return Ok(service_id);
+        }
+
+        let (host, services) = Self::api_server_host(
+            &self.inventory()?.api_server,
+            subnets,
+            network_id,
+            daemon_id,
+        )?;
+        let (_, created_services) = self.create_host(host, services).await?;
+
+        created_services
+            .first()
+            .map(|s| s.id)
+            .ok_or_else(|| anyhow!("Kubernetes API server service could not be created"))
+    }
+
+    /// Host for a cluster node, with a Kubernetes service if it's a control plane node. None if the
+    /// node has no address in any of the subnets.
+    pub fn node_host(
+        node: &KubeNode,
+        subnets: &[Subnet],
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> Option<(Host, Vec<Service>)> {
+        let interfaces: Vec<Interface> = Self::interfaces_for_ips(subnets, &node.ip_addresses())
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+
+        if interfaces.is_empty() {
+            return None;
+        }
+
+        let mut host = Self::new_host(
+            node.metadata.name.clone(),
+            Some(node.hostname().unwrap_or(node.metadata.name.clone())),
+            "Kubernetes node",
+            interfaces,
+            network_id,
+            daemon_id,
+        );
+
+        let services = if node.is_control_plane() {
+            vec![Self::add_kubernetes_service(
+                &mut host,
+                PortBase::new_tcp(6443),
+                "Kubernetes API control plane node",
+                network_id,
+                daemon_id,
+            )]
+        } else {
+            Vec::new()
+        };
+
+        Some((host, services))
+    }
+
+    /// Host for the API server of a cluster whose control plane isn't one of its nodes, addressed
+    /// by the server URL from the kubeconfig or service account
+    pub fn api_server_host(
+        api_server: &str,
+        subnets: &[Subnet],
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> Result<(Host, Vec<Service>), Error> {
+        let url = Url::parse(api_server)
+            .map_err(|e| anyhow!("Invalid Kubernetes API server URL {}: {}", api_server, e))?;
+        let server_host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("Kubernetes API server URL {} has no host", api_server))?;
+        let port = url.port_or_known_default().unwrap_or(443);
+
+        let (hostname, interfaces) = match server_host
+            .trim_matches(|c| c == '[' || c == ']')
+            .parse::<IpAddr>()
+        {
+            Ok(ip) => (
+                None,
+                Self::interfaces_for_ips(subnets, &[ip])
+                    .into_iter()
+                    .map(|(i, _)| i)
+                    .collect(),
+            ),
+            Err(_) => (Some(server_host.to_string()), Vec::new()),
+        };
+
+        let mut host = Self::new_host(
+            "Kubernetes API".to_string(),
+            hostname,
+            "Kubernetes API server, not one of the cluster's nodes",
+            interfaces,
+            network_id,
+            daemon_id,
+        );
+
+        let service = Self::add_kubernetes_service(
+            &mut host,
+            PortBase::new_tcp(port),
+            "Kubernetes API server the cluster was read from",
+            network_id,
+            daemon_id,
+        );
+
+        Ok((host, vec![service]))
+    }
+
+    fn new_host(
+        name: String,
+        hostname: Option<String>,
+        description: &str,
+        interfaces: Vec<Interface>,
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> Host {
+        let target = if hostname.is_some() {
+            HostTarget::Hostname
+        } else {
+            HostTarget::None
+        };
+
+        Host::new(HostBase {
+            name,
+            hostname,
+            target,
+            network_id,
+            description: Some(description.to_string()),
+            interfaces,
+            services: Vec::new(),
+            ports: Vec::new(),
+            source: Self::source(daemon_id),
+            virtualization: None,
+            device_fingerprint: DeviceFingerprint::default(),
+            identity: HostIdentity::default(),
+            device_classification: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        })
+    }
+
+    /// Add a Kubernetes API service on a port to the host, returning it
+    fn add_kubernetes_service(
+        host: &mut Host,
+        port_base: PortBase,
+        reason: &str,
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> Service {
+        let api_port = Port::new(port_base);
+
+        let kubernetes_service = Service::new(ServiceBase {
+            name: ServiceDefinition::name(&Kubernetes).to_string(),
+            service_definition: Box::new(Kubernetes),
+            bindings: vec![Binding::new_port(api_port.id, None)],
+            host_id: host.id,
+            network_id,
+            virtualization: None,
+            source: EntitySource::DiscoveryWithMatch {
+                metadata: vec![DiscoveryMetadata::new(DiscoveryType::Kubernetes, daemon_id)],
+                details: MatchDetails::new_certain(reason),
+            },
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        });
+
+        host.base.ports.push(api_port);
+        host.add_service(kubernetes_service.id);
+        kubernetes_service
+    }
+
+    async fn process_service(
+        &self,
+        inventory: &ClusterInventory,
+        service: &KubeService,
+        subnets: &[Subnet],
+        control_plane_service_id: Uuid,
+    ) -> Result<(), Error> {
+        let session = self.as_ref().get_session().await?;
+        session
+            .scanned_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Headless services have no ClusterIP and are reachable only through their pods
+        let interfaces_and_subnets = Self::service_interfaces(inventory, service, subnets);
+
+        let Some((interface, subnet)) = interfaces_and_subnets.first() else {
+            return Ok(());
+        };
+
+        let namespace = service.namespace();
+        let name = format!("{}.{}", service.metadata.name, namespace);
+        let ports = Self::ports_for_service(service);
+
+        let virtualization = Some(ServiceVirtualization::Kubernetes(
+            KubernetesVirtualization {
+                namespace,
+                kind: "Service".to_string(),
+                name: service.metadata.name.clone(),
+                service_id: control_plane_service_id,
+            },
+        ));
+
+        let (mut host, services) = match Self::match_service_images(inventory, service) {
+            Some((service_definition, details)) => {
+                let (network_id, daemon_id) = self.network_and_daemon_ids().await?;
+                Self::image_matched_host(
+                    interface,
+                    &ports,
+                    vec![(name.clone(), service_definition, details)],
+                    &virtualization,
+                    network_id,
+                    daemon_id,
+                )
+            }
+            None => self
+                .process_host(
+                    ServiceMatchBaselineParams {
+                        subnet,
+                        interface,
+                        all_ports: &ports,
+                        endpoint_responses: &vec![],
+                        banners: &[],
+                        virtualization: &virtualization,
+                    },
+                    None,
+                )
+                .await?
+                .ok_or_else(|| anyhow!("Service {} could not be processed", name))?,
+        };
+
+        interfaces_and_subnets.iter().skip(1).for_each(|(i, _)| {
+            if !host.base.interfaces.contains(i) {
+                host.base.interfaces.push(i.clone())
+            }
+        });
+
+        host.base.hostname = Some(format!("{}.svc", name));
+        host.base.name = name;
+        if matches!(host.base.target, HostTarget::None) {
+            host.base.target = HostTarget::Hostname;
+        }
+
+        self.create_host(host, services).await?;
+
+        session
+            .discovered_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    async fn process_ingress(
+        &self,
+        inventory: &ClusterInventory,
+        ingress: &KubeIngress,
+        subnets: &[Subnet],
+        control_plane_service_id: Uuid,
+    ) -> Result<(), Error> {
+        let session = self.as_ref().get_session().await?;
+        session
+            .scanned_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let interfaces_and_subnets =
+            Self::interfaces_for_ips(subnets, &ingress.load_balancer_ips());
+
+        let Some((interface, _)) = interfaces_and_subnets.first() else {
+            return Ok(());
+        };
+
+        let namespace = ingress.metadata.namespace.clone();
+
+        let virtualization = Some(ServiceVirtualization::Kubernetes(
+            KubernetesVirtualization {
+                namespace: namespace.clone().unwrap_or("default".to_string()),
+                kind: "Ingress".to_string(),
+                name: ingress.metadata.name.clone(),
+                service_id: control_plane_service_id,
+            },
+        ));
+
+        let (network_id, daemon_id) = self.network_and_daemon_ids().await?;
+        let (mut host, services) = Self::ingress_host(
+            inventory,
+            ingress,
+            interface,
+            &virtualization,
+            network_id,
+            daemon_id,
+        );
+
+        interfaces_and_subnets.iter().skip(1).for_each(|(i, _)| {
+            if !host.base.interfaces.contains(i) {
+                host.base.interfaces.push(i.clone())
+            }
+        });
+
+        host.base.name = format!(
+            "{}.{}",
+            ingress.metadata.name,
+            namespace.unwrap_or("default".to_string())
+        );
+        host.base.target = HostTarget::None;
+
+        self.create_host(host, services).await?;
+
+        session
+            .discovered_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Host for an ingress's load balancer. The ingress is the web service listening on the HTTP(S)
+    /// ports; the backends it routes to are only reached through it, so they're bound to the
+    /// interface rather than each claiming the same ports.
+    pub fn ingress_host(
+        inventory: &ClusterInventory,
+        ingress: &KubeIngress,
+        interface: &Interface,
+        virtualization: &Option<ServiceVirtualization>,
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> (Host, Vec<Service>) {
+        let namespace = ingress.metadata.namespace.clone();
+
+        let mut ports = vec![PortBase::Http];
+        if ingress.has_tls() {
+            ports.push(PortBase::Https);
+        }
+
+        let entry_point = (
+            ingress.metadata.name.clone(),
+            Box::new(WebService) as Box<dyn ServiceDefinition>,
+            MatchDetails::new_certain(&format!(
+                "Kubernetes ingress {} serves HTTP on its load balancer",
+                ingress.metadata.name
+            )),
+        );
+
+        let (mut host, mut services) = Self::image_matched_host(
+            interface,
+            &ports,
+            vec![entry_point],
+            virtualization,
+            network_id,
+            daemon_id,
+        );
+
+        let mut backends = ingress.backends();
+        backends.sort();
+        backends.dedup();
+
+        for (rule_host, backend_name) in backends {
+            let name = rule_host.unwrap_or(backend_name.clone());
+
+            let (service_definition, details) = inventory
+                .find_service(&namespace, &backend_name)
+                .and_then(|s| Self::match_service_images(inventory, s))
+                .unwrap_or_else(|| {
+                    (
+                        Box::new(WebService) as Box<dyn ServiceDefinition>,
+                        MatchDetails {
+                            reason: MatchReason::Reason(format!(
+                                "Ingress {} routes to service {}",
+                                ingress.metadata.name, backend_name
+                            )),
+                            confidence: MatchConfidence::NotApplicable,
+                            score: 0,
+                        },
+                    )
+                });
+
+            let backend = Self::discovered_service(
+                &host,
+                (name, service_definition, details),
+                vec![Binding::new_interface(interface.id)],
+                virtualization,
+                daemon_id,
+            );
+            host.add_service(backend.id);
+            services.push(backend);
+        }
+
+        (host, services)
+    }
+
+    /// Build a host for services whose definitions were already identified from cluster metadata
+    /// rather than port scanning. Services are bound to every port on the host.
+    pub fn image_matched_host(
+        interface: &Interface,
+        ports: &[PortBase],
+        services: Vec<(String, Box<dyn ServiceDefinition>, MatchDetails)>,
+        virtualization: &Option<ServiceVirtualization>,
+        network_id: Uuid,
+        daemon_id: Uuid,
+    ) -> (Host, Vec<Service>) {
+        let mut host = Host::new(HostBase {
+            name: "Unknown Device".to_string(),
+            hostname: None,
+            target: HostTarget::None,
+            network_id,
+            description: None,
+            interfaces: vec![interface.clone()],
+            services: Vec::new(),
+            ports: ports.iter().map(|p| Port::new(*p)).collect(),
+            source: Self::source(daemon_id),
+            virtualization: None,
+            device_fingerprint: DeviceFingerprint::default(),
+            identity: HostIdentity::default(),
+            device_classification: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        });
+
+        let port_ids: Vec<Uuid> = host.base.ports.iter().map(|p| p.id).collect();
+
+        let bindings = || -> Vec<Binding> {
+            if port_ids.is_empty() {
+                vec![Binding::new_interface(interface.id)]
+            } else {
+                port_ids
+                    .iter()
+                    .map(|port_id| Binding::new_port(*port_id, Some(interface.id)))
+                    .collect()
+            }
+        };
+
+        let services: Vec<Service> = services
+            .into_iter()
+            .map(|service| {
+                Self::discovered_service(&host, service, bindings(), virtualization, daemon_id)
+            })
+            .collect();
+
+        services.iter().for_each(|s| host.add_service(s.id));
+
+        (host, services)
+    }
+
+    fn discovered_service(
+        host: &Host,
+        (name, service_definition, details): (String, Box<dyn ServiceDefinition>, MatchDetails),
+        bindings: Vec<Binding>,
+        virtualization: &Option<ServiceVirtualization>,
+        daemon_id: Uuid,
+    ) -> Service {
+        Service::new(ServiceBase {
+            host_id: host.id,
+            network_id: host.base.network_id,
+            name,
+            service_definition,
+            bindings,
+            virtualization: virtualization.clone(),
+            source: EntitySource::DiscoveryWithMatch {
+                metadata: vec![DiscoveryMetadata::new(DiscoveryType::Kubernetes, daemon_id)],
+                details,
+            },
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        })
+    }
+}
@@ -0,0 +1,391 @@
+use anyhow::anyhow;
+use anyhow::{Error, Result};
+use axum::async_trait;
+use cidr::IpCidr;
+use futures::future::try_join_all;
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use strum::IntoDiscriminant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::daemon::discovery::service::base::{
+    CreatesDiscoveredEntities, DiscoversNetworkedEntities, Discovery, HasDiscoveryType,
+    InitiatesOwnDiscovery,
+};
+use crate::daemon::discovery::types::base::DiscoverySessionUpdate;
+use crate::daemon::discovery::types::libvirt::{LibvirtDomain, LibvirtDomainAddress, VirshClient};
+use crate::daemon::utils::base::DaemonUtils;
+use crate::server::daemons::types::api::DaemonDiscoveryRequest;
+use crate::server::discovery::types::base::{DiscoveryMetadata, DiscoveryType, EntitySource};
+use crate::server::hosts::types::base::{Host, HostBase};
+use crate::server::hosts::types::interfaces::{Interface, InterfaceBase};
+use crate::server::hosts::types::virtualization::{HostVirtualization, LibvirtVirtualization};
+use crate::server::services::definitions::libvirt::Libvirt;
+use crate::server::services::types::base::{Service, ServiceBase, ServiceMatchBaselineParams};
+use crate::server::services::types::bindings::Binding;
+use crate::server::services::types::definitions::ServiceDefinition;
+use crate::server::services::types::patterns::MatchDetails;
+use crate::server::subnets::types::base::{
+    Subnet, SubnetBase, SubnetType, SubnetTypeDiscriminants,
+};
+
+pub struct LibvirtScanDiscovery {
+    virsh: VirshClient,
+    host_id: Uuid,
+}
+
+impl HasDiscoveryType for Discovery<LibvirtScanDiscovery> {
+    fn discovery_type(&self) -> DiscoveryType {
+        DiscoveryType::Libvirt {
+            host_id: self.domain.host_id,
+        }
+    }
+}
+
+impl LibvirtScanDiscovery {
+    pub fn new(host_id: Uuid, libvirt_uri: Option<String>) -> Self {
+        Self {
+            virsh: VirshClient::new(libvirt_uri),
+            host_id,
+        }
+    }
+
+    pub async fn is_available(&self) -> bool {
+        self.virsh.is_available().await
+    }
+}
+
+impl CreatesDiscoveredEntities for Discovery<LibvirtScanDiscovery> {}
+
+impl InitiatesOwnDiscovery for Discovery<LibvirtScanDiscovery> {}
+
+#[async_trait]
+impl DiscoversNetworkedEntities for Discovery<LibvirtScanDiscovery> {
+    async fn start_discovery_session(
+        &self,
+        request: DaemonDiscoveryRequest,
+        cancel: CancellationToken,
+    ) -> Result<(), Error> {
+        tracing::info!("Listing libvirt domains from {}", self.domain.virsh.uri());
+
+        let domain_names = self.domain.virsh.list_domains().await?;
+
+        self.start_discovery(domain_names.len(), request).await?;
+
+        let subnets = self.discover_create_subnets().await?;
+
+        let (_, services) = self.create_libvirt_service(&subnets).await?;
+
+        let libvirt_service = services
+            .first()
+            .ok_or_else(|| anyhow!("Libvirt service was not created, aborting"))?;
+
+        let discovery_result = self
+            .scan_and_process_domains(cancel.clone(), domain_names, &subnets, &libvirt_service.id)
+            .await;
+
+        self.finish_discovery(discovery_result, cancel.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_gateway_ips(&self) -> Result<Vec<IpAddr>, Error> {
+        self.as_ref()
+            .utils
+            .get_own_routing_table_gateway_ips()
+            .await
+    }
+
+    async fn discover_create_subnets(&self) -> Result<Vec<Subnet>, Error> {
+        let daemon_id = self.as_ref().config_store.get_id().await?;
+        let network_id = self
+            .as_ref()
+            .config_store
+            .get_network_id()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Network ID not set"))?;
+
+        let (_, host_subnets) = self
+            .as_ref()
+            .utils
+            .get_own_interfaces(self.discovery_type(), daemon_id, network_id)
+            .await?;
+
+        let mut subnets: Vec<Subnet> = host_subnets
+            .into_iter()
+            .filter(|s| s.base.subnet_type.discriminant() != SubnetTypeDiscriminants::DockerBridge)
+            .collect();
+
+        // Libvirt networks which aren't up on the hypervisor still have guests to account for
+        for network in self.domain.virsh.list_networks().await? {
+            for cidr in network.cidrs {
+                if matches!(cidr, IpCidr::V4(_)) && !subnets.iter().any(|s| s.base.cidr == cidr) {
+                    subnets.push(Subnet::new(SubnetBase {
+                        cidr,
+                        network_id,
+                        name: network.name.clone(),
+                        description: Some(format!("Libvirt network {}", network.name)),
+                        subnet_type: SubnetType::from_interface_name(
+                            network.bridge.as_deref().unwrap_or_default(),
+                        ),
+                        vlan_id: None,
+                        source: EntitySource::Discovery {
+                            metadata: vec![DiscoveryMetadata::new(
+                                self.discovery_type(),
+                                daemon_id,
+                            )],
+                        },
+                        tags: Vec::new(),
+                        custom_fields: BTreeMap::new(),
+                    }));
+                }
+            }
+        }
+
+        let subnet_futures = subnets.iter().map(|subnet| self.create_subnet(subnet));
+        let subnets = try_join_all(subnet_futures).await?;
+
+        Ok(subnets)
+    }
+}
+
+impl Discovery<LibvirtScanDiscovery> {
+    /// Most specific created subnet containing an IP
+    fn subnet_for_ip(subnets: &[Subnet], ip: &IpAddr) -> Option<Subnet> {
+        subnets
+            .iter()
+            .filter(|s| s.base.cidr.contains(ip))
+            .max_by_key(|s| s.base.cidr.network_length())
+            .cloned()
+    }
+
+    /// Create libvirt service on the hypervisor host, bound to the hypervisor's interfaces so VMs
+    /// can be linked to it on each subnet they share
+    pub async fn create_libvirt_service(
+        &self,
+        subnets: &[Subnet],
+    ) -> Result<(Host, Vec<Service>), Error> {
+        let daemon_id = self.as_ref().config_store.get_id().await?;
+        let network_id = self
+            .as_ref()
+            .config_store
+            .get_network_id()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Network ID not set"))?;
+
+        let (own_interfaces, _) = self
+            .as_ref()
+            .utils
+            .get_own_interfaces(self.discovery_type(), daemon_id, network_id)
+            .await?;
+
+        // Interfaces from get_own_interfaces reference subnets before they were created on the server
+        let interfaces: Vec<Interface> = own_interfaces
+            .into_iter()
+            .filter_map(|mut i| {
+                let subnet = Self::subnet_for_ip(subnets, &i.base.ip_address)?;
+                i.base.subnet_id = subnet.id;
+                Some(i)
+            })
+            .collect();
+
+        let host_id = self.domain.host_id;
+
+        let libvirt_service = Service::new(ServiceBase {
+            name: ServiceDefinition::name(&Libvirt).to_string(),
+            service_definition: Box::new(Libvirt),
+            bindings: interfaces
+                .iter()
+                .map(|i| Binding::new_interface(i.id))
+                .collect(),
+            host_id,
+            network_id,
+            virtualization: None,
+            source: EntitySource::DiscoveryWithMatch {
+                metadata: vec![DiscoveryMetadata::new(self.discovery_type(), daemon_id)],
+                details: MatchDetails::new_certain("Libvirt hypervisor self-report"),
+            },
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
+        });
+
+        let mut temp_hypervisor_host = Host::new(HostBase::default());
+        temp_hypervisor_host.id = host_id;
+        temp_hypervisor_host.base.network_id = network_id;
+        temp_hypervisor_host.base.interfaces = interfaces;
+        temp_hypervisor_host.base.source = EntitySource::Discovery {
+            metadata: vec![DiscoveryMetadata::new(self.discovery_type(), daemon_id)],
+        };
+        temp_hypervisor_host.base.services = vec![libvirt_service.id];
+
+        self.create_host(temp_hypervisor_host, vec![libvirt_service])
+            .await
+    }
+
+    async fn scan_and_process_domains(
+        &self,
+        cancel: CancellationToken,
+        domain_names: Vec<String>,
+        subnets: &[Subnet],
+        libvirt_service_id: &Uuid,
+    ) -> Result<(), Error> {
+        let concurrent_scans = self.as_ref().config_store.get_concurrent_scans().await?;
+
+        self.report_discovery_update(DiscoverySessionUpdate::scanning(0, 0))
+            .await?;
+
+        let results = stream::iter(domain_names.into_iter())
+            .map(|name| {
+                let cancel = cancel.clone();
+                async move {
+                    self.process_single_domain(&name, subnets, libvirt_service_id, cancel)
+                        .await
+                        .map_err(|e| anyhow!("Domain {}: {}", name, e))
+                }
+            })
+            .buffer_unordered(concurrent_scans);
+
+        let mut stream_pin = Box::pin(results);
+        let mut last_reported_scan_count: usize = 0;
+        let mut last_reported_discovery_count: usize = 0;
+
+        while let Some(result) = stream_pin.next().await {
+            if cancel.is_cancelled() {
+                tracing::warn!("Libvirt discovery session was cancelled");
+                return Err(Error::msg("Libvirt discovery session was cancelled"));
+            }
+
+            if let Err(e) = result {
+                tracing::warn!("Error processing libvirt domain: {}", e)
+            }
+
+            (last_reported_scan_count, last_reported_discovery_count) = self
+                .periodic_scan_update(5, last_reported_scan_count, last_reported_discovery_count)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Match the addresses reported for a domain to its interfaces and the subnets they're on
+    fn get_domain_interfaces(
+        domain: &LibvirtDomain,
+        addresses: &[LibvirtDomainAddress],
+        subnets: &[Subnet],
+    ) -> Vec<(Interface, Subnet)> {
+        addresses
+            .iter()
+            .filter_map(|address| {
+                let subnet = Self::subnet_for_ip(subnets, &address.ip)?;
+
+                let domain_interface = domain.interfaces.iter().find(|i| {
+                    (address.mac_address.is_some() && i.mac_address == address.mac_address)
+                        || i.target.as_deref() == Some(address.interface.as_str())
+                });
+
+                let interface = Interface::new(InterfaceBase {
+                    subnet_id: subnet.id,
+                    ip_address: address.ip,
+                    mac_address: address
+                        .mac_address
+                        .or(domain_interface.and_then(|i| i.mac_address)),
+                    name: domain_interface
+                        .and_then(|i| i.source_name())
+                        .or(Some(address.interface.clone())),
+                    mac_vendor: None,
+                    randomized_mac: false,
+                    vlan_tag: None,
+                });
+
+                Some((interface, subnet))
+            })
+            .collect()
+    }
+
+    async fn process_single_domain(
+        &self,
+        name: &str,
+        subnets: &[Subnet],
+        libvirt_service_id: &Uuid,
+        cancel: CancellationToken,
+    ) -> Result<Option<(Host, Vec<Service>)>> {
+        let session = self.as_ref().get_session().await?;
+        session
+            .scanned_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        if cancel.is_cancelled() {
+            return Err(Error::msg("Discovery was cancelled"));
+        }
+
+        let domain = self.domain.virsh.get_domain(name).await?;
+
+        tracing::info!("Processing libvirt domain {}", domain.name);
+
+        if !domain.running {
+            tracing::info!(
+                "Libvirt domain {} is not running, its addresses can't be determined",
+                domain.name
+            );
+            return Ok(None);
+        }
+
+        let addresses = self.domain.virsh.get_domain_addresses(name).await;
+        let domain_interfaces = Self::get_domain_interfaces(&domain, &addresses, subnets);
+
+        let Some((interface, subnet)) = domain_interfaces.first() else {
+            tracing::info!("No addresses found for libvirt domain {}", domain.name);
+            return Ok(None);
+        };
+
+        let (all_ports, endpoint_responses, banners) = tokio::spawn(
+            Self::scan_ports_and_endpoints(interface.base.ip_address, cancel.clone(), None),
+        )
+        .await
+        .map_err(|e| anyhow!("Scan task panicked: {}", e))??;
+
+        let Some((mut host, services)) = self
+            .process_host(
+                ServiceMatchBaselineParams {
+                    subnet,
+                    interface,
+                    all_ports: &all_ports,
+                    endpoint_responses: &endpoint_responses,
+                    banners: &banners,
+                    virtualization: &None,
+                },
+                None,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        domain_interfaces.iter().skip(1).for_each(|(i, _)| {
+            if !host.base.interfaces.contains(i) {
+                host.base.interfaces.push(i.clone())
+            }
+        });
+
+        host.base.name = domain.name.clone();
+        host.base.virtualization = Some(HostVirtualization::Libvirt(LibvirtVirtualization {
+            vm_name: Some(domain.name.clone()),
+            vm_id: domain.uuid.clone(),
+            service_id: *libvirt_service_id,
+        }));
+
+        session
+            .discovered_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let (created_host, created_services) = self.create_host(host, services).await?;
+
+        Ok(Some((created_host, created_services)))
+    }
+}
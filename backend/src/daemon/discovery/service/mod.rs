@@ -1,4 +1,6 @@
 pub mod base;
 pub mod docker;
+pub mod kubernetes;
+pub mod libvirt;
 pub mod network;
 pub mod self_report;
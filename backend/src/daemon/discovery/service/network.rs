@@ -5,9 +5,11 @@ use crate::daemon::discovery::service::base::{
 use crate::daemon::discovery::types::base::{DiscoveryCriticalError, DiscoverySessionUpdate};
 use crate::server::discovery::types::base::DiscoveryType;
 use crate::server::hosts::types::{
+    devices::DeviceFingerprint,
     interfaces::{Interface, InterfaceBase},
-    ports::PortBase,
+    ports::{PortBase, TransportProtocol},
 };
+use crate::server::reconciliation::types::HostObservation;
 use crate::server::services::types::base::ServiceMatchBaselineParams;
 use crate::server::subnets::types::base::SubnetTypeDiscriminants;
 use crate::{
@@ -15,7 +17,7 @@ use crate::{
     server::{
         daemons::types::api::DaemonDiscoveryRequest,
         hosts::types::base::Host,
-        services::types::endpoints::EndpointResponse,
+        services::types::{endpoints::EndpointResponse, versions::PortBanner},
         subnets::types::base::{Subnet, SubnetType},
     },
 };
@@ -122,10 +124,16 @@ impl Discovery<NetworkScanDiscovery> {
         );
 
         let concurrent_scans = self.as_ref().config_store.get_concurrent_scans().await?;
+        let device_fingerprinting = self
+            .as_ref()
+            .config_store
+            .get_device_fingerprinting()
+            .await?;
 
         tracing::info!("Using up to {} concurrent scans", concurrent_scans);
 
         let session = self.as_ref().get_session().await?;
+        let session_id = session.info.session_id;
 
         let scanned_count = session.scanned_count.clone();
         let discovered_count: Arc<std::sync::atomic::AtomicUsize> =
@@ -136,7 +144,8 @@ impl Discovery<NetworkScanDiscovery> {
             .await?;
 
         // Process all IPs concurrently, combining discovery and processing
-        let results = stream::iter(self.determine_scan_order(&subnet.base.cidr))
+        let scan_range = subnet.scan_range();
+        let results = stream::iter(Self::determine_scan_order(&scan_range))
             .map(async |ip| {
                 let cancel = cancel.clone();
                 let subnet = subnet.clone();
@@ -145,34 +154,79 @@ impl Discovery<NetworkScanDiscovery> {
                 match self.scan_host(ip, scanned_count, cancel).await {
                     Ok(None) => Ok(None),
                     Err(e) => Err(e),
-                    Ok(Some((all_ports, endpoint_responses))) => {
+                    Ok(Some((all_ports, endpoint_responses, banners))) => {
                         let hostname = self.get_hostname_for_ip(ip).await?;
+                        let mut device_fingerprint = if device_fingerprinting {
+                            Self::probe_device_fingerprint(ip).await
+                        } else {
+                            DeviceFingerprint::default()
+                        };
+                        let identity = Self::probe_host_identity(ip, &all_ports).await;
+                        let snmp = all_ports
+                            .iter()
+                            .any(|p| p.protocol() == TransportProtocol::Udp && p.number() == 161);
+                        let vlan_tag = if snmp {
+                            Self::query_snmp_vlan_tag(ip).await.unwrap_or_else(|e| {
+                                tracing::debug!("SNMP VLAN query failed for {}: {}", ip, e);
+                                None
+                            })
+                        } else {
+                            None
+                        };
 
                         let mac = match subnet.base.subnet_type {
                             SubnetType::VpnTunnel => None, // ARP doesn't work through VPN tunnels
                             _ => self.as_ref().utils.get_mac_address_for_ip(ip).await?,
                         };
 
+                        if let Some(mac) = mac
+                            && let Some(parameter_list) =
+                                self.as_ref().dhcp_fingerprints.parameter_list(&mac).await
+                        {
+                            device_fingerprint.dhcp_parameter_list = parameter_list;
+                        }
+
                         let interface = Interface::new(InterfaceBase {
                             name: None,
                             subnet_id: subnet.id,
                             ip_address: ip,
                             mac_address: mac,
+                            mac_vendor: None,
+                            randomized_mac: false,
+                            vlan_tag,
                         });
 
-                        if let Ok(Some((host, services))) = self
+                        if let Ok(Some((mut host, services))) = self
                             .process_host(
                                 ServiceMatchBaselineParams {
                                     subnet: &subnet,
                                     interface: &interface,
                                     all_ports: &all_ports,
                                     endpoint_responses: &endpoint_responses,
+                                    banners: &banners,
                                     virtualization: &None,
                                 },
                                 hostname,
                             )
                             .await
                         {
+                            host.base.device_fingerprint = device_fingerprint;
+                            host.base.identity = identity;
+
+                            // Every probed port was scanned, so the host's reported ports and
+                            // services are everything open on this interface
+                            self.as_ref().pending_observations.write().await.push(
+                                HostObservation {
+                                    session_id,
+                                    subnet_id: subnet.id,
+                                    ip_address: ip,
+                                    discovery_type: self.discovery_type(),
+                                    scanned_ports: Self::probed_ports()
+                                        .iter()
+                                        .map(|p| p.config())
+                                        .collect(),
+                                },
+                            );
                             discovered_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                             if let Ok((created_host, _)) = self.create_host(host, services).await {
                                 return Ok::<Option<Host>, Error>(Some(created_host));
@@ -228,7 +282,7 @@ impl Discovery<NetworkScanDiscovery> {
         ip: IpAddr,
         scanned_count: Arc<std::sync::atomic::AtomicUsize>,
         cancel: CancellationToken,
-    ) -> Result<Option<(Vec<PortBase>, Vec<EndpointResponse>)>> {
+    ) -> Result<Option<(Vec<PortBase>, Vec<EndpointResponse>, Vec<PortBanner>)>> {
         // Check cancellation at the start
         if cancel.is_cancelled() {
             return Err(Error::msg("Discovery was cancelled"));
@@ -246,7 +300,7 @@ impl Discovery<NetworkScanDiscovery> {
         }
 
         match scan_result {
-            Ok((open_ports, endpoint_responses)) => {
+            Ok((open_ports, endpoint_responses, banners)) => {
                 if !open_ports.is_empty() || !endpoint_responses.is_empty() {
                     tracing::info!(
                         "Processing host {} with {} open ports and {} endpoint responses",
@@ -261,7 +315,7 @@ impl Discovery<NetworkScanDiscovery> {
                         return Err(Error::msg("Discovery was cancelled"));
                     }
 
-                    Ok(Some((open_ports, endpoint_responses)))
+                    Ok(Some((open_ports, endpoint_responses, banners)))
                 } else {
                     tracing::debug!("No open ports found on {}", ip);
                     scanned_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -293,7 +347,7 @@ impl Discovery<NetworkScanDiscovery> {
     }
 
     /// Figure out what order to scan IPs in given allocation patterns
-    fn determine_scan_order(&self, subnet: &IpCidr) -> impl Iterator<Item = IpAddr> {
+    fn determine_scan_order(subnet: &IpCidr) -> impl Iterator<Item = IpAddr> {
         let mut ips: Vec<IpAddr> = subnet.iter().map(|ip| ip.address()).collect();
 
         // Sort by likelihood of being active hosts - highest probability first
@@ -338,3 +392,47 @@ impl Discovery<NetworkScanDiscovery> {
         ips.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::ipnetwork::IpNetwork;
+    use std::net::Ipv4Addr;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_vpn_tunnel_scan_targets() {
+        let tunnel = Subnet::from_discovery(
+            "wg0".to_string(),
+            &"10.8.0.2/32".parse::<IpNetwork>().unwrap(),
+            Uuid::new_v4(),
+            &DiscoveryType::Network,
+            Uuid::new_v4(),
+        )
+        .unwrap();
+
+        // The subnet keeps the tunnel's own address, but the scan covers its peers
+        assert_eq!(tunnel.base.subnet_type, SubnetType::VpnTunnel);
+        assert_eq!(tunnel.base.cidr.to_string(), "10.8.0.2");
+        let targets: Vec<IpAddr> =
+            Discovery::<NetworkScanDiscovery>::determine_scan_order(&tunnel.scan_range()).collect();
+        assert_eq!(targets.len(), 256);
+        assert_eq!(targets[0], IpAddr::V4(Ipv4Addr::new(10, 8, 0, 1)));
+        assert!(targets.contains(&IpAddr::V4(Ipv4Addr::new(10, 8, 0, 5))));
+
+        // Other subnets are scanned as they are
+        let lan = Subnet::from_discovery(
+            "eth0".to_string(),
+            &"192.168.1.20/28".parse::<IpNetwork>().unwrap(),
+            Uuid::new_v4(),
+            &DiscoveryType::Network,
+            Uuid::new_v4(),
+        )
+        .unwrap();
+        assert_eq!(lan.scan_range(), lan.base.cidr);
+        assert_eq!(
+            Discovery::<NetworkScanDiscovery>::determine_scan_order(&lan.scan_range()).count(),
+            16
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     daemon::discovery::service::{
         base::{CreatesDiscoveredEntities, Discovery, HasDiscoveryType, InitiatesOwnDiscovery},
         docker::DockerScanDiscovery,
+        libvirt::LibvirtScanDiscovery,
     },
     server::{
         daemons::types::api::DaemonDiscoveryRequest,
@@ -25,6 +26,8 @@ use crate::{
     server::{
         hosts::types::{
             base::{Host, HostBase},
+            devices::DeviceFingerprint,
+            identity::HostIdentity,
             targets::HostTarget,
         },
         services::types::base::Service,
@@ -33,6 +36,7 @@ use crate::{
 use anyhow::{Error, Result};
 use futures::future::try_join_all;
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, Ipv4Addr},
     result::Result::Ok,
     sync::Arc,
@@ -57,9 +61,9 @@ impl Discovery<SelfReportDiscovery> {
         let utils = &self.as_ref().utils;
 
         let host_id = config_store.get_host_id().await?;
-        let docker_ok = utils.get_own_docker_socket().await?;
+        let container_runtime = utils.get_own_container_runtime().await?;
 
-        if let (Some(host_id), true) = (host_id, docker_ok) {
+        if let (Some(host_id), Some(_)) = (host_id, container_runtime) {
             let docker_discovery = Arc::new(Discovery::new(
                 self.service.clone(),
                 self.manager.clone(),
@@ -79,6 +83,41 @@ impl Discovery<SelfReportDiscovery> {
         Ok(())
     }
 
+    /// Discover the daemon host's libvirt guests, if it's a hypervisor
+    pub async fn run_self_report_libvirt_discovery(&self) -> Result<(), Error> {
+        let config_store = &self.as_ref().config_store;
+
+        let Some(host_id) = config_store.get_host_id().await? else {
+            return Ok(());
+        };
+
+        let libvirt = LibvirtScanDiscovery::new(host_id, config_store.get_libvirt_uri().await?);
+        if !libvirt.is_available().await {
+            tracing::debug!("Libvirt is not available, skipping libvirt discovery");
+            return Ok(());
+        }
+
+        // Only one discovery session runs at a time, so let container discovery finish first
+        self.manager.wait_for_current_session().await;
+
+        let libvirt_discovery = Arc::new(Discovery::new(
+            self.service.clone(),
+            self.manager.clone(),
+            libvirt,
+        ));
+
+        let session_id = libvirt_discovery.initiate_own_discovery().await?;
+
+        let request = DaemonDiscoveryRequest {
+            session_id,
+            discovery_type: DiscoveryType::Libvirt { host_id },
+        };
+
+        libvirt_discovery.discover_on_network(request).await?;
+
+        Ok(())
+    }
+
     pub async fn run_self_report_discovery(&self) -> Result<(), Error> {
         let config_store = &self.as_ref().config_store;
         let utils = &self.as_ref().utils;
@@ -155,6 +194,11 @@ impl Discovery<SelfReportDiscovery> {
                 metadata: vec![DiscoveryMetadata::new(DiscoveryType::SelfReport, daemon_id)],
             },
             virtualization: None,
+            device_fingerprint: DeviceFingerprint::default(),
+            identity: HostIdentity::default(),
+            device_classification: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         };
 
         let host = Host::new(host_base);
@@ -181,6 +225,11 @@ impl Discovery<SelfReportDiscovery> {
                 metadata: vec![DiscoveryMetadata::new(DiscoveryType::SelfReport, daemon_id)],
                 details: MatchDetails::new_certain("NetVisor Daemon self-report"),
             },
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         });
 
         services.push(daemon_service);
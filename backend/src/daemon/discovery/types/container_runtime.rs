@@ -0,0 +1,290 @@
+use std::path::Path;
+
+use anyhow::{Error, Result, anyhow};
+use axum::async_trait;
+use bollard::{
+    API_DEFAULT_VERSION, Docker,
+    exec::{CreateExecOptions, StartExecResults},
+    query_parameters::{InspectContainerOptions, ListContainersOptions, ListNetworksOptions},
+    secret::{ContainerInspectResponse, ContainerSummary, Network, Port, PortTypeEnum},
+};
+use chrono::DateTime;
+use futures::StreamExt;
+
+use crate::{
+    daemon::discovery::types::cri::CriClient,
+    server::services::types::virtualization::ContainerRuntime,
+};
+
+const CONTAINERD_SOCKET: &str = "/run/containerd/containerd.sock";
+const ROOTFUL_PODMAN_SOCKET: &str = "/run/podman/podman.sock";
+const SOCKET_TIMEOUT_SECS: u64 = 120;
+
+/// Container operations needed by container discovery. Container, network and image data use the
+/// Docker Engine API models; runtimes without that API translate into them.
+#[async_trait]
+pub trait ContainerRuntimeClient: Send + Sync {
+    fn runtime(&self) -> ContainerRuntime;
+
+    /// Running containers
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>, Error>;
+
+    async fn inspect_container(&self, id: &str) -> Result<ContainerInspectResponse, Error>;
+
+    async fn list_networks(&self) -> Result<Vec<Network>, Error>;
+
+    /// Registry digests of an image, ie "postgres@sha256:..."
+    async fn get_image_digests(&self, image: &str) -> Result<Vec<String>, Error>;
+
+    /// Run a shell command inside a container, returning stdout and stderr
+    async fn exec(&self, container: &str, command: &str) -> Result<String, Error>;
+}
+
+/// Connect to the first available runtime on this host: the Docker socket (which may be served by
+/// Podman), then the Podman API socket, then containerd's CRI API. The namespace limits containerd
+/// discovery to pods in that Kubernetes namespace.
+pub async fn connect_container_runtime(
+    containerd_namespace: Option<String>,
+) -> Result<Box<dyn ContainerRuntimeClient>, Error> {
+    if let Ok(docker) = Docker::connect_with_local_defaults()
+        && docker.ping().await.is_ok()
+    {
+        let runtime = if is_podman(&docker).await {
+            ContainerRuntime::Podman
+        } else {
+            ContainerRuntime::Docker
+        };
+        tracing::debug!("Connected to {} through the Docker socket", runtime);
+        return Ok(Box::new(DockerApiClient { docker, runtime }));
+    }
+
+    for socket in podman_sockets() {
+        if !Path::new(&socket).exists() {
+            continue;
+        }
+
+        if let Ok(docker) =
+            Docker::connect_with_socket(&socket, SOCKET_TIMEOUT_SECS, API_DEFAULT_VERSION)
+            && docker.ping().await.is_ok()
+        {
+            tracing::debug!("Connected to Podman at {}", socket);
+            return Ok(Box::new(DockerApiClient {
+                docker,
+                runtime: ContainerRuntime::Podman,
+            }));
+        }
+    }
+
+    if Path::new(CONTAINERD_SOCKET).exists()
+        && let Ok(cri) = CriClient::connect(CONTAINERD_SOCKET, containerd_namespace).await
+        && let Ok((name, version)) = cri.version().await
+    {
+        tracing::debug!("Connected to {} {} through CRI", name, version);
+        return Ok(Box::new(cri));
+    }
+
+    Err(anyhow!("No Docker, Podman or containerd runtime found"))
+}
+
+async fn is_podman(docker: &Docker) -> bool {
+    docker
+        .version()
+        .await
+        .ok()
+        .and_then(|v| v.components)
+        .is_some_and(|components| components.iter().any(|c| c.name.contains("Podman")))
+}
+
+/// Podman API sockets, rootless first
+fn podman_sockets() -> Vec<String> {
+    let mut sockets = Vec::new();
+
+    if let Ok(host) = std::env::var("CONTAINER_HOST")
+        && let Some(path) = host.strip_prefix("unix://")
+    {
+        sockets.push(path.to_string());
+    }
+
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        sockets.push(format!("{}/podman/podman.sock", runtime_dir));
+    }
+
+    sockets.push(ROOTFUL_PODMAN_SOCKET.to_string());
+    sockets
+}
+
+/// Docker Engine API, served by Docker or by Podman's compatibility layer
+pub struct DockerApiClient {
+    docker: Docker,
+    runtime: ContainerRuntime,
+}
+
+#[async_trait]
+impl ContainerRuntimeClient for DockerApiClient {
+    fn runtime(&self) -> ContainerRuntime {
+        self.runtime
+    }
+
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>, Error> {
+        self.docker
+            .list_containers(None::<ListContainersOptions>)
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+
+    async fn inspect_container(&self, id: &str) -> Result<ContainerInspectResponse, Error> {
+        self.docker
+            .inspect_container(id, None::<InspectContainerOptions>)
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+
+    async fn list_networks(&self) -> Result<Vec<Network>, Error> {
+        self.docker
+            .list_networks(None::<ListNetworksOptions>)
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+
+    async fn get_image_digests(&self, image: &str) -> Result<Vec<String>, Error> {
+        Ok(self
+            .docker
+            .inspect_image(image)
+            .await?
+            .repo_digests
+            .unwrap_or_default())
+    }
+
+    async fn exec(&self, container: &str, command: &str) -> Result<String, Error> {
+        let exec = self
+            .docker
+            .create_exec(
+                container,
+                CreateExecOptions {
+                    cmd: Some(vec!["sh", "-c", command]),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(false),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let mut full_response = String::new();
+
+        if let StartExecResults::Attached { mut output, .. } =
+            self.docker.start_exec(&exec.id, None).await?
+        {
+            while let Some(Ok(msg)) = output.next().await {
+                match msg {
+                    bollard::container::LogOutput::StdOut { message }
+                    | bollard::container::LogOutput::StdErr { message } => {
+                        full_response.push_str(&String::from_utf8_lossy(&message));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(full_response)
+    }
+}
+
+/// Build the container list entry the Docker API would return from an inspect response
+pub fn summary_from_inspect(container: &ContainerInspectResponse) -> ContainerSummary {
+    let ports = container
+        .network_settings
+        .as_ref()
+        .and_then(|n| n.ports.as_ref())
+        .map(|port_map| {
+            port_map
+                .iter()
+                .filter_map(|(key, bindings)| {
+                    let (number, protocol) = key.split_once('/').unwrap_or((key, "tcp"));
+                    let private_port = number.parse::<u16>().ok()?;
+                    let typ = match protocol {
+                        "udp" => PortTypeEnum::UDP,
+                        "sctp" => PortTypeEnum::SCTP,
+                        _ => PortTypeEnum::TCP,
+                    };
+
+                    let published: Vec<Port> = bindings
+                        .iter()
+                        .flatten()
+                        .map(|b| Port {
+                            ip: b.host_ip.clone().filter(|ip| !ip.is_empty()),
+                            private_port,
+                            public_port: b.host_port.as_ref().and_then(|p| p.parse().ok()),
+                            typ: Some(typ),
+                        })
+                        .collect();
+
+                    if published.is_empty() {
+                        Some(vec![Port {
+                            ip: None,
+                            private_port,
+                            public_port: None,
+                            typ: Some(typ),
+                        }])
+                    } else {
+                        Some(published)
+                    }
+                })
+                .flatten()
+                .collect()
+        });
+
+    ContainerSummary {
+        id: container.id.clone(),
+        names: container.name.clone().map(|n| vec![n]),
+        image: container.config.as_ref().and_then(|c| c.image.clone()),
+        image_id: container.image.clone(),
+        // BollardDate is a string or a chrono date depending on bollard's enabled features; both
+        // serialize to RFC 3339
+        created: serde_json::to_value(container.created.as_ref())
+            .ok()
+            .and_then(|c| c.as_str().map(DateTime::parse_from_rfc3339))
+            .and_then(|c| c.ok())
+            .map(|c| c.timestamp()),
+        ports,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_from_inspect() {
+        let inspect = r#"[{
+            "Id": "3f1c0e8a9b7d",
+            "Created": "2024-05-01T12:00:00Z",
+            "Name": "grafana",
+            "Image": "docker.io/grafana/grafana:10.2.3",
+            "Config": { "Image": "docker.io/grafana/grafana:10.2.3" },
+            "NetworkSettings": {
+                "Ports": {
+                    "3000/tcp": [{ "HostIp": "0.0.0.0", "HostPort": "3000" }],
+                    "9100/udp": null
+                },
+                "Networks": {
+                    "unknown-eth0": { "IPAddress": "10.4.0.12", "MacAddress": "aa:bb:cc:dd:ee:ff" }
+                }
+            }
+        }]"#;
+
+        let containers: Vec<ContainerInspectResponse> = serde_json::from_str(inspect).unwrap();
+        let summary = summary_from_inspect(&containers[0]);
+
+        assert_eq!(summary.id.as_deref(), Some("3f1c0e8a9b7d"));
+        assert_eq!(summary.created, Some(1714564800));
+
+        let mut ports = summary.ports.unwrap();
+        ports.sort_by_key(|p| p.private_port);
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].public_port, Some(3000));
+        assert_eq!(ports[0].ip.as_deref(), Some("0.0.0.0"));
+        assert_eq!(ports[1].typ, Some(PortTypeEnum::UDP));
+        assert_eq!(ports[1].public_port, None);
+    }
+}
@@ -0,0 +1,902 @@
+use anyhow::{Error, Result, anyhow};
+use axum::async_trait;
+use bollard::secret::{ContainerInspectResponse, ContainerSummary, Network};
+use chrono::DateTime;
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use tokio::net::UnixStream;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, Endpoint, Uri},
+};
+
+use crate::{
+    daemon::discovery::types::container_runtime::{ContainerRuntimeClient, summary_from_inspect},
+    server::services::types::virtualization::ContainerRuntime,
+};
+
+/// Label the kubelet sets on every container with the Kubernetes namespace of its pod
+pub const POD_NAMESPACE_LABEL: &str = "io.kubernetes.pod.namespace";
+
+const EXEC_TIMEOUT_SECS: i64 = 10;
+
+/// containerd through the Kubernetes Container Runtime Interface its CRI plugin serves on the
+/// containerd socket. Container, network and image data is translated into the Docker Engine API
+/// models. When a namespace is given only containers in pods of that Kubernetes namespace are
+/// listed.
+pub struct CriClient {
+    channel: Channel,
+    namespace: Option<String>,
+}
+
+impl CriClient {
+    pub async fn connect(socket: &str, namespace: Option<String>) -> Result<Self, Error> {
+        let socket = socket.to_string();
+
+        // gRPC needs a URI but the connector ignores it and dials the socket
+        let channel =
+            Endpoint::from_static("http://[::]:50051")
+                .connect_with_connector(tower::service_fn(move |_: Uri| {
+                    let socket = socket.clone();
+                    async move {
+                        Ok::<_, std::io::Error>(TokioIo::new(UnixStream::connect(socket).await?))
+                    }
+                }))
+                .await?;
+
+        Ok(Self { channel, namespace })
+    }
+
+    async fn call<Req, Res>(&self, method: &'static str, request: Req) -> Result<Res, Error>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Res: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = Grpc::new(self.channel.clone());
+        grpc.ready()
+            .await
+            .map_err(|e| anyhow!("containerd unavailable: {}", e))?;
+
+        let response = grpc
+            .unary(
+                tonic::Request::new(request),
+                PathAndQuery::from_static(method),
+                ProstCodec::<Req, Res>::default(),
+            )
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", method, e.message()))?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Runtime name and version, ie ("containerd", "v1.7.13")
+    pub async fn version(&self) -> Result<(String, String), Error> {
+        let response: api::VersionResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/Version",
+                api::VersionRequest::default(),
+            )
+            .await?;
+
+        Ok((response.runtime_name, response.runtime_version))
+    }
+
+    async fn find_containers(&self, id: Option<&str>) -> Result<Vec<api::Container>, Error> {
+        let label_selector = self
+            .namespace
+            .iter()
+            .map(|n| (POD_NAMESPACE_LABEL.to_string(), n.clone()))
+            .collect();
+
+        let response: api::ListContainersResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/ListContainers",
+                api::ListContainersRequest {
+                    filter: Some(api::ContainerFilter {
+                        id: id.unwrap_or_default().to_string(),
+                        state: Some(api::ContainerStateValue {
+                            state: api::ContainerState::Running as i32,
+                        }),
+                        label_selector,
+                    }),
+                },
+            )
+            .await?;
+
+        Ok(response.containers)
+    }
+}
+
+#[async_trait]
+impl ContainerRuntimeClient for CriClient {
+    fn runtime(&self) -> ContainerRuntime {
+        ContainerRuntime::Containerd
+    }
+
+    async fn list_containers(&self) -> Result<Vec<ContainerSummary>, Error> {
+        let mut summaries = Vec::new();
+
+        for container in self.find_containers(None).await? {
+            summaries.push(summary_from_inspect(
+                &self.inspect_container(&container.id).await?,
+            ));
+        }
+
+        Ok(summaries)
+    }
+
+    async fn inspect_container(&self, id: &str) -> Result<ContainerInspectResponse, Error> {
+        let container = self
+            .find_containers(Some(id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Container {} not found", id))?;
+
+        let status: api::ContainerStatusResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/ContainerStatus",
+                api::ContainerStatusRequest {
+                    container_id: container.id.clone(),
+                    verbose: false,
+                },
+            )
+            .await?;
+
+        // Verbose sandbox status includes the pod config, which holds the port mappings
+        let sandbox: api::PodSandboxStatusResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/PodSandboxStatus",
+                api::PodSandboxStatusRequest {
+                    pod_sandbox_id: container.pod_sandbox_id.clone(),
+                    verbose: true,
+                },
+            )
+            .await?;
+
+        let networks = self.list_networks().await.unwrap_or_default();
+
+        inspect_from_cri(
+            &container,
+            &status.status.unwrap_or_default(),
+            &sandbox.status.unwrap_or_default(),
+            sandbox.info.get("info").map(String::as_str),
+            &networks,
+        )
+    }
+
+    async fn list_networks(&self) -> Result<Vec<Network>, Error> {
+        let response: api::StatusResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/Status",
+                api::StatusRequest { verbose: true },
+            )
+            .await?;
+
+        match response.info.get("cniconfig") {
+            Some(cniconfig) => networks_from_cni_config(cniconfig),
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn get_image_digests(&self, image: &str) -> Result<Vec<String>, Error> {
+        let response: api::ImageStatusResponse = self
+            .call(
+                "/runtime.v1.ImageService/ImageStatus",
+                api::ImageStatusRequest {
+                    image: Some(api::ImageSpec {
+                        image: image.to_string(),
+                    }),
+                    verbose: false,
+                },
+            )
+            .await?;
+
+        Ok(response.image.map(|i| i.repo_digests).unwrap_or_default())
+    }
+
+    async fn exec(&self, container: &str, command: &str) -> Result<String, Error> {
+        let response: api::ExecSyncResponse = self
+            .call(
+                "/runtime.v1.RuntimeService/ExecSync",
+                api::ExecSyncRequest {
+                    container_id: container.to_string(),
+                    cmd: vec!["sh".to_string(), "-c".to_string(), command.to_string()],
+                    timeout: EXEC_TIMEOUT_SECS,
+                },
+            )
+            .await?;
+
+        Ok(format!(
+            "{}{}",
+            String::from_utf8_lossy(&response.stdout),
+            String::from_utf8_lossy(&response.stderr)
+        ))
+    }
+}
+
+/// Port mappings from the pod config in containerd's verbose sandbox info
+#[derive(Debug, Deserialize, Default)]
+struct SandboxInfo {
+    #[serde(default)]
+    config: SandboxConfig,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SandboxConfig {
+    #[serde(default)]
+    port_mappings: Vec<PortMapping>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PortMapping {
+    /// Protocol enum value: 0 is TCP, 1 UDP and 2 SCTP
+    #[serde(default)]
+    protocol: i32,
+    #[serde(default)]
+    container_port: u16,
+    #[serde(default)]
+    host_port: u16,
+    #[serde(default)]
+    host_ip: String,
+}
+
+/// Build the inspect response the Docker API would return for a CRI container, using its pod's
+/// network and port mappings. The pod IP is attached to the CNI network whose subnet contains it.
+fn inspect_from_cri(
+    container: &api::Container,
+    status: &api::ContainerStatus,
+    sandbox: &api::PodSandboxStatus,
+    sandbox_info: Option<&str>,
+    networks: &[Network],
+) -> Result<ContainerInspectResponse, Error> {
+    let host_network = sandbox
+        .linux
+        .as_ref()
+        .and_then(|l| l.namespaces.as_ref())
+        .and_then(|n| n.options.as_ref())
+        .is_some_and(|o| o.network == api::NamespaceMode::Node as i32);
+
+    let sandbox_info: SandboxInfo = sandbox_info
+        .and_then(|info| serde_json::from_str(info).ok())
+        .unwrap_or_default();
+
+    let mut ports = Map::new();
+    for mapping in sandbox_info.config.port_mappings {
+        let protocol = match mapping.protocol {
+            1 => "udp",
+            2 => "sctp",
+            _ => "tcp",
+        };
+        let bindings = if mapping.host_port == 0 {
+            Value::Null
+        } else {
+            json!([{ "HostIp": mapping.host_ip, "HostPort": mapping.host_port.to_string() }])
+        };
+        ports.insert(format!("{}/{}", mapping.container_port, protocol), bindings);
+    }
+
+    let mut container_networks = Map::new();
+    if !host_network {
+        let pod_ips = sandbox.network.iter().flat_map(|n| {
+            std::iter::once(n.ip.as_str()).chain(n.additional_ips.iter().map(|ip| ip.ip.as_str()))
+        });
+
+        for ip in pod_ips.filter(|ip| !ip.is_empty()) {
+            let network_name = ip
+                .parse()
+                .ok()
+                .and_then(|ip| {
+                    networks.iter().find(|n| {
+                        n.ipam
+                            .iter()
+                            .flat_map(|ipam| ipam.config.iter().flatten())
+                            .filter_map(|c| c.subnet.as_deref())
+                            .filter_map(|s| s.parse::<cidr::IpCidr>().ok())
+                            .any(|cidr| cidr.contains(&ip))
+                    })
+                })
+                .and_then(|n| n.name.clone())
+                .unwrap_or("cni".to_string());
+
+            container_networks.insert(network_name, json!({ "IPAddress": ip }));
+        }
+    }
+
+    let mounts: Vec<Value> = status
+        .mounts
+        .iter()
+        .map(|m| {
+            json!({
+                "Type": "bind",
+                "Source": m.host_path,
+                "Destination": m.container_path,
+                "RW": !m.readonly,
+            })
+        })
+        .collect();
+
+    let image = status
+        .image
+        .as_ref()
+        .map(|i| i.image.clone())
+        .filter(|i| !i.is_empty())
+        .or_else(|| container.image.as_ref().map(|i| i.image.clone()));
+
+    let inspect = json!({
+        "Id": container.id,
+        "Name": container.metadata.as_ref().map(|m| m.name.clone()),
+        "Created": DateTime::from_timestamp_nanos(container.created_at).to_rfc3339(),
+        "Image": container.image_ref,
+        "Config": {
+            "Image": image,
+            "Labels": container.labels,
+        },
+        "State": { "Status": "running", "Running": true },
+        "HostConfig": { "NetworkMode": if host_network { "host" } else { "default" } },
+        "Mounts": mounts,
+        "NetworkSettings": {
+            "Ports": ports,
+            "Networks": container_networks,
+        },
+    });
+
+    serde_json::from_value(inspect).map_err(|e| anyhow!("Could not translate CRI container: {}", e))
+}
+
+/// Build Docker API networks from the CNI network configs in containerd's verbose status. Each
+/// config's source is a CNI network config or config list; subnets come from the IPAM section of
+/// its plugins.
+fn networks_from_cni_config(cniconfig: &str) -> Result<Vec<Network>, Error> {
+    let cniconfig: Value = serde_json::from_str(cniconfig)
+        .map_err(|e| anyhow!("Could not parse CNI config: {}", e))?;
+
+    let networks = cniconfig
+        .get("Networks")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|n| n.pointer("/Config/Source").and_then(Value::as_str))
+        .filter_map(|source| serde_json::from_str::<Value>(source).ok())
+        .filter_map(|source| {
+            let name = source.get("name").and_then(Value::as_str)?;
+
+            let plugins = match source.get("plugins").and_then(Value::as_array) {
+                Some(plugins) => plugins.iter().collect(),
+                None => vec![&source],
+            };
+
+            let configs: Vec<Value> = plugins
+                .iter()
+                .filter_map(|p| p.get("ipam"))
+                .flat_map(|ipam| {
+                    // host-local IPAM takes either range sets or a single subnet
+                    let ranges = ipam
+                        .get("ranges")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_array)
+                        .flatten();
+
+                    ranges
+                        .chain(ipam.get("subnet").map(|_| ipam))
+                        .filter_map(|range| {
+                            Some(json!({
+                                "Subnet": range.get("subnet")?.as_str()?,
+                                "Gateway": range.get("gateway").and_then(Value::as_str),
+                            }))
+                        })
+                        .collect::<Vec<Value>>()
+                })
+                .collect();
+
+            if configs.is_empty() {
+                return None;
+            }
+
+            serde_json::from_value(json!({ "Name": name, "IPAM": { "Config": configs } })).ok()
+        })
+        .collect();
+
+    Ok(networks)
+}
+
+/// The subset of the CRI runtime.v1 API used for discovery
+mod api {
+    use std::collections::HashMap;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum ContainerState {
+        Created = 0,
+        Running = 1,
+        Exited = 2,
+        Unknown = 3,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum NamespaceMode {
+        Pod = 0,
+        Container = 1,
+        Node = 2,
+        Target = 3,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VersionRequest {
+        #[prost(string, tag = "1")]
+        pub version: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VersionResponse {
+        #[prost(string, tag = "1")]
+        pub version: String,
+        #[prost(string, tag = "2")]
+        pub runtime_name: String,
+        #[prost(string, tag = "3")]
+        pub runtime_version: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListContainersRequest {
+        #[prost(message, optional, tag = "1")]
+        pub filter: Option<ContainerFilter>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerFilter {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(message, optional, tag = "2")]
+        pub state: Option<ContainerStateValue>,
+        #[prost(map = "string, string", tag = "4")]
+        pub label_selector: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerStateValue {
+        #[prost(enumeration = "ContainerState", tag = "1")]
+        pub state: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListContainersResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub containers: Vec<Container>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Container {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub pod_sandbox_id: String,
+        #[prost(message, optional, tag = "3")]
+        pub metadata: Option<ContainerMetadata>,
+        #[prost(message, optional, tag = "4")]
+        pub image: Option<ImageSpec>,
+        #[prost(string, tag = "5")]
+        pub image_ref: String,
+        #[prost(enumeration = "ContainerState", tag = "6")]
+        pub state: i32,
+        /// Nanoseconds since the epoch
+        #[prost(int64, tag = "7")]
+        pub created_at: i64,
+        #[prost(map = "string, string", tag = "8")]
+        pub labels: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerMetadata {
+        #[prost(string, tag = "1")]
+        pub name: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ImageSpec {
+        #[prost(string, tag = "1")]
+        pub image: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerStatusRequest {
+        #[prost(string, tag = "1")]
+        pub container_id: String,
+        #[prost(bool, tag = "2")]
+        pub verbose: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerStatusResponse {
+        #[prost(message, optional, tag = "1")]
+        pub status: Option<ContainerStatus>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContainerStatus {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(message, optional, tag = "8")]
+        pub image: Option<ImageSpec>,
+        #[prost(message, repeated, tag = "14")]
+        pub mounts: Vec<Mount>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Mount {
+        #[prost(string, tag = "1")]
+        pub container_path: String,
+        #[prost(string, tag = "2")]
+        pub host_path: String,
+        #[prost(bool, tag = "3")]
+        pub readonly: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PodSandboxStatusRequest {
+        #[prost(string, tag = "1")]
+        pub pod_sandbox_id: String,
+        #[prost(bool, tag = "2")]
+        pub verbose: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PodSandboxStatusResponse {
+        #[prost(message, optional, tag = "1")]
+        pub status: Option<PodSandboxStatus>,
+        #[prost(map = "string, string", tag = "2")]
+        pub info: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PodSandboxStatus {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(message, optional, tag = "5")]
+        pub network: Option<PodSandboxNetworkStatus>,
+        #[prost(message, optional, tag = "6")]
+        pub linux: Option<LinuxPodSandboxStatus>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PodSandboxNetworkStatus {
+        #[prost(string, tag = "1")]
+        pub ip: String,
+        #[prost(message, repeated, tag = "2")]
+        pub additional_ips: Vec<PodIp>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PodIp {
+        #[prost(string, tag = "1")]
+        pub ip: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LinuxPodSandboxStatus {
+        #[prost(message, optional, tag = "1")]
+        pub namespaces: Option<Namespace>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Namespace {
+        #[prost(message, optional, tag = "2")]
+        pub options: Option<NamespaceOption>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct NamespaceOption {
+        #[prost(enumeration = "NamespaceMode", tag = "1")]
+        pub network: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StatusRequest {
+        #[prost(bool, tag = "1")]
+        pub verbose: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StatusResponse {
+        #[prost(map = "string, string", tag = "2")]
+        pub info: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ImageStatusRequest {
+        #[prost(message, optional, tag = "1")]
+        pub image: Option<ImageSpec>,
+        #[prost(bool, tag = "2")]
+        pub verbose: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ImageStatusResponse {
+        #[prost(message, optional, tag = "1")]
+        pub image: Option<Image>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Image {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, repeated, tag = "3")]
+        pub repo_digests: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ExecSyncRequest {
+        #[prost(string, tag = "1")]
+        pub container_id: String,
+        #[prost(string, repeated, tag = "2")]
+        pub cmd: Vec<String>,
+        /// Seconds, 0 for no timeout
+        #[prost(int64, tag = "3")]
+        pub timeout: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ExecSyncResponse {
+        #[prost(bytes = "vec", tag = "1")]
+        pub stdout: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub stderr: Vec<u8>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::daemon::{
+        discovery::{
+            manager::DaemonDiscoverySessionManager,
+            service::{
+                base::{DaemonDiscoveryService, Discovery},
+                docker::DockerScanDiscovery,
+            },
+        },
+        shared::storage::{AppConfig, ConfigStore},
+    };
+
+    const CNI_CONFIG: &str = r#"{
+        "PluginDirs": ["/opt/cni/bin"],
+        "Networks": [
+            { "Config": { "Name": "cni-loopback", "Source": "{\"cniVersion\":\"0.3.1\",\"name\":\"cni-loopback\",\"type\":\"loopback\"}" }, "IFName": "lo" },
+            { "Config": { "Name": "k8s-pod-network", "Source": "{\"cniVersion\":\"0.4.0\",\"name\":\"k8s-pod-network\",\"plugins\":[{\"type\":\"bridge\",\"bridge\":\"cni0\",\"ipam\":{\"type\":\"host-local\",\"ranges\":[[{\"subnet\":\"10.88.0.0/16\",\"gateway\":\"10.88.0.1\"}]]}},{\"type\":\"portmap\",\"capabilities\":{\"portMappings\":true}}]}" }, "IFName": "eth0" }
+        ]
+    }"#;
+
+    const SANDBOX_INFO: &str = r#"{
+        "pid": 4242,
+        "config": {
+            "metadata": { "name": "grafana-7d9c", "namespace": "monitoring" },
+            "port_mappings": [
+                { "container_port": 3000, "host_port": 3000, "host_ip": "0.0.0.0" },
+                { "protocol": 1, "container_port": 9100 }
+            ]
+        }
+    }"#;
+
+    fn cri_container() -> api::Container {
+        api::Container {
+            id: "c0ffee".to_string(),
+            pod_sandbox_id: "pod-1".to_string(),
+            metadata: Some(api::ContainerMetadata {
+                name: "grafana".to_string(),
+            }),
+            image: Some(api::ImageSpec {
+                image: "docker.io/grafana/grafana:10.2.3".to_string(),
+            }),
+            image_ref: "sha256:5e1f".to_string(),
+            state: api::ContainerState::Running as i32,
+            created_at: 1_714_564_800_000_000_000,
+            labels: HashMap::from([(POD_NAMESPACE_LABEL.to_string(), "monitoring".to_string())]),
+        }
+    }
+
+    fn cri_status() -> api::ContainerStatus {
+        api::ContainerStatus {
+            id: "c0ffee".to_string(),
+            image: None,
+            mounts: vec![api::Mount {
+                container_path: "/var/lib/grafana".to_string(),
+                host_path: "/var/lib/kubelet/pods/1/volumes/data".to_string(),
+                readonly: false,
+            }],
+        }
+    }
+
+    fn cri_sandbox(network: api::NamespaceMode) -> api::PodSandboxStatus {
+        api::PodSandboxStatus {
+            id: "pod-1".to_string(),
+            network: Some(api::PodSandboxNetworkStatus {
+                ip: "10.88.0.5".to_string(),
+                additional_ips: vec![],
+            }),
+            linux: Some(api::LinuxPodSandboxStatus {
+                namespaces: Some(api::Namespace {
+                    options: Some(api::NamespaceOption {
+                        network: network as i32,
+                    }),
+                }),
+            }),
+        }
+    }
+
+    /// Runtime serving translated CRI data, standing in for containerd
+    struct FakeCri {
+        containers: Vec<ContainerInspectResponse>,
+        networks: Vec<Network>,
+    }
+
+    #[async_trait]
+    impl ContainerRuntimeClient for FakeCri {
+        fn runtime(&self) -> ContainerRuntime {
+            ContainerRuntime::Containerd
+        }
+
+        async fn list_containers(&self) -> Result<Vec<ContainerSummary>, Error> {
+            Ok(self.containers.iter().map(summary_from_inspect).collect())
+        }
+
+        async fn inspect_container(&self, id: &str) -> Result<ContainerInspectResponse, Error> {
+            self.containers
+                .iter()
+                .find(|c| c.id.as_deref() == Some(id))
+                .cloned()
+                .ok_or_else(|| anyhow!("Container {} not found", id))
+        }
+
+        async fn list_networks(&self) -> Result<Vec<Network>, Error> {
+            Ok(self.networks.clone())
+        }
+
+        async fn get_image_digests(&self, _image: &str) -> Result<Vec<String>, Error> {
+            Ok(vec!["docker.io/grafana/grafana@sha256:d1g3st".to_string()])
+        }
+
+        async fn exec(&self, _container: &str, _command: &str) -> Result<String, Error> {
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_networks_from_cni_config() {
+        let networks = networks_from_cni_config(CNI_CONFIG).unwrap();
+
+        // The loopback network has no IPAM so isn't a subnet
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].name.as_deref(), Some("k8s-pod-network"));
+
+        let config = networks[0].ipam.as_ref().unwrap().config.as_ref().unwrap();
+        assert_eq!(config[0].subnet.as_deref(), Some("10.88.0.0/16"));
+        assert_eq!(config[0].gateway.as_deref(), Some("10.88.0.1"));
+    }
+
+    #[test]
+    fn test_inspect_from_cri() {
+        let networks = networks_from_cni_config(CNI_CONFIG).unwrap();
+        let inspect = inspect_from_cri(
+            &cri_container(),
+            &cri_status(),
+            &cri_sandbox(api::NamespaceMode::Pod),
+            Some(SANDBOX_INFO),
+            &networks,
+        )
+        .unwrap();
+
+        let network_mode = inspect.host_config.as_ref().unwrap().network_mode.clone();
+        assert_eq!(network_mode.as_deref(), Some("default"));
+
+        let pod_networks = inspect.network_settings.as_ref().unwrap().networks.clone();
+        let endpoint = &pod_networks.unwrap()["k8s-pod-network"];
+        assert_eq!(endpoint.ip_address.as_deref(), Some("10.88.0.5"));
+
+        let summary = summary_from_inspect(&inspect);
+        assert_eq!(summary.created, Some(1714564800));
+        assert_eq!(summary.image_id.as_deref(), Some("sha256:5e1f"));
+
+        let mut ports = summary.ports.unwrap();
+        ports.sort_by_key(|p| p.private_port);
+        assert_eq!(ports[0].public_port, Some(3000));
+        assert_eq!(ports[1].public_port, None);
+        assert_eq!(ports[1].typ, Some(bollard::secret::PortTypeEnum::UDP));
+    }
+
+    #[test]
+    fn test_inspect_from_cri_host_network() {
+        let inspect = inspect_from_cri(
+            &cri_container(),
+            &cri_status(),
+            &cri_sandbox(api::NamespaceMode::Node),
+            None,
+            &[],
+        )
+        .unwrap();
+
+        let network_mode = inspect.host_config.as_ref().unwrap().network_mode.clone();
+        assert_eq!(network_mode.as_deref(), Some("host"));
+        assert!(
+            inspect
+                .network_settings
+                .and_then(|n| n.networks)
+                .unwrap_or_default()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_container_discovery_from_cri() {
+        let networks = networks_from_cni_config(CNI_CONFIG).unwrap();
+        let container = inspect_from_cri(
+            &cri_container(),
+            &cri_status(),
+            &cri_sandbox(api::NamespaceMode::Pod),
+            Some(SANDBOX_INFO),
+            &networks,
+        )
+        .unwrap();
+
+        let config_store = Arc::new(ConfigStore::new(
+            PathBuf::from("/tmp/netvisor-cri-test/config.json"),
+            AppConfig::default(),
+        ));
+        let discovery = Discovery::new(
+            Arc::new(DaemonDiscoveryService::new(config_store)),
+            Arc::new(DaemonDiscoverySessionManager::new()),
+            DockerScanDiscovery::new(Uuid::new_v4()),
+        );
+        discovery
+            .domain
+            .set_runtime_client(Box::new(FakeCri {
+                containers: vec![container],
+                networks,
+            }))
+            .unwrap();
+
+        let subnets = discovery
+            .get_subnets_from_docker_networks(Uuid::new_v4(), Uuid::new_v4())
+            .await
+            .unwrap();
+        assert_eq!(subnets.len(), 1);
+        assert_eq!(subnets[0].base.cidr.to_string(), "10.88.0.0/16");
+
+        let containers = discovery.get_containers_and_summaries().await.unwrap();
+        assert_eq!(containers.len(), 1);
+
+        let interfaces = discovery.get_container_interfaces(&containers, &subnets, &mut []);
+        let (interface, subnet) = &interfaces["c0ffee"][0];
+        assert_eq!(interface.base.ip_address.to_string(), "10.88.0.5");
+        assert_eq!(interface.base.name.as_deref(), Some("k8s-pod-network"));
+        assert_eq!(subnet.id, subnets[0].id);
+
+        let (inspect, summary) = &containers[0];
+        let virtualization = discovery
+            .get_container_virtualization(inspect, summary, &Uuid::new_v4())
+            .await;
+        assert_eq!(virtualization.runtime, ContainerRuntime::Containerd);
+        assert_eq!(virtualization.namespace.as_deref(), Some("monitoring"));
+        assert_eq!(
+            virtualization.image.as_deref(),
+            Some("docker.io/grafana/grafana:10.2.3")
+        );
+        assert_eq!(
+            virtualization.image_digest.as_deref(),
+            Some("sha256:d1g3st")
+        );
+        assert_eq!(virtualization.published_ports.len(), 1);
+        assert_eq!(virtualization.published_ports[0].host_port, 3000);
+        assert_eq!(virtualization.volumes.len(), 1);
+        assert!(!virtualization.volumes[0].read_only);
+    }
+}
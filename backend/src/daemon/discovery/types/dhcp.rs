@@ -0,0 +1,145 @@
+use anyhow::{Error, Result};
+use dhcproto::{
+    Decodable, Decoder,
+    v4::{DhcpOption, Message, MessageType, Opcode, OptionCode},
+};
+use mac_address::MacAddress;
+use std::collections::HashMap;
+use tokio::{net::UdpSocket, sync::RwLock};
+
+/// Port DHCP clients send their DISCOVER and REQUEST messages to
+pub const DHCP_SERVER_PORT: u16 = 67;
+
+/// DHCP option 55 parameter request lists of clients in the daemon's broadcast domain, by MAC
+/// address. Clients broadcast DISCOVER and REQUEST messages, so they reach any host listening on
+/// port 67, not only the DHCP server.
+#[derive(Default)]
+pub struct DhcpFingerprints {
+    parameter_lists: RwLock<HashMap<MacAddress, Vec<u8>>>,
+}
+
+impl DhcpFingerprints {
+    /// Parameter request list last seen from a client
+    pub async fn parameter_list(&self, mac: &MacAddress) -> Option<Vec<u8>> {
+        self.parameter_lists.read().await.get(mac).cloned()
+    }
+
+    /// Listen for broadcast requests on port 67 until the socket fails
+    pub async fn listen(&self) -> Result<(), Error> {
+        let socket = UdpSocket::bind(("0.0.0.0", DHCP_SERVER_PORT)).await?;
+        tracing::info!("Listening for DHCP requests on port {}", DHCP_SERVER_PORT);
+        self.record_requests(socket).await
+    }
+
+    /// Record the parameter request list of every DHCP request received on the socket
+    pub async fn record_requests(&self, socket: UdpSocket) -> Result<(), Error> {
+        let mut buf = [0u8; 1500];
+
+        loop {
+            let (len, _) = socket.recv_from(&mut buf).await?;
+
+            if let Some((mac, parameter_list)) = Self::parse_request(&buf[..len]) {
+                tracing::debug!(
+                    "DHCP client {} requested parameters {:?}",
+                    mac,
+                    parameter_list
+                );
+                self.parameter_lists
+                    .write()
+                    .await
+                    .insert(mac, parameter_list);
+            }
+        }
+    }
+
+    /// Client MAC address and parameter request list of a DHCP DISCOVER or REQUEST. Replies from
+    /// servers and requests without the option are ignored.
+    pub fn parse_request(packet: &[u8]) -> Option<(MacAddress, Vec<u8>)> {
+        let message = Message::decode(&mut Decoder::new(packet)).ok()?;
+
+        if message.opcode() != Opcode::BootRequest
+            || !matches!(
+                message.opts().msg_type(),
+                Some(MessageType::Discover | MessageType::Request)
+            )
+        {
+            return None;
+        }
+
+        let mac: [u8; 6] = message.chaddr().get(..6)?.try_into().ok()?;
+
+        match message.opts().get(OptionCode::ParameterRequestList)? {
+            DhcpOption::ParameterRequestList(codes) => Some((
+                MacAddress::new(mac),
+                codes.iter().map(|code| u8::from(*code)).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        server::hosts::types::devices::{DeviceClassifier, DeviceFingerprint, DeviceType},
+        tests::DHCP_DISCOVER_FIXTURE,
+    };
+    use std::{sync::Arc, time::Duration};
+
+    #[tokio::test]
+    async fn test_dhcp_fingerprint_from_captured_discover() {
+        let packet = std::fs::read(DHCP_DISCOVER_FIXTURE).unwrap();
+        let mac = MacAddress::new([0x8c, 0x71, 0xf8, 0x4a, 0x2e, 0x19]);
+
+        let fingerprints = Arc::new(DhcpFingerprints::default());
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let listen_addr = socket.local_addr().unwrap();
+        let listener = tokio::spawn({
+            let fingerprints = fingerprints.clone();
+            async move { fingerprints.record_requests(socket).await }
+        });
+
+        // Server replies carry the same options, but don't describe the client
+        let mut reply = packet.clone();
+        reply[0] = 2;
+        assert!(DhcpFingerprints::parse_request(&reply).is_none());
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client.send_to(&reply, listen_addr).await.unwrap();
+        client.send_to(&packet, listen_addr).await.unwrap();
+
+        let mut parameter_list = None;
+        for _ in 0..50 {
+            parameter_list = fingerprints.parameter_list(&mac).await;
+            if parameter_list.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        listener.abort();
+
+        let parameter_list = parameter_list.expect("DISCOVER should have been recorded");
+        assert_eq!(parameter_list, vec![1, 3, 6, 15, 26, 28, 51, 58, 59, 43]);
+
+        // The Android parameter list adds to the phone's hostname
+        let classification = DeviceClassifier::classify(
+            Some("Galaxy-S23"),
+            &[],
+            &DeviceFingerprint {
+                dhcp_parameter_list: parameter_list,
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(classification.device_type, DeviceType::Phone);
+        assert_eq!(classification.score, 55);
+        assert!(
+            classification
+                .reasons
+                .iter()
+                .any(|r| r.starts_with("DHCP fingerprint"))
+        );
+    }
+}
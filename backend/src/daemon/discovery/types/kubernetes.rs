@@ -0,0 +1,971 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Error, Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use cidr::IpCidr;
+use serde::{Deserialize, de::DeserializeOwned};
+
+pub const IN_CLUSTER_TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+pub const IN_CLUSTER_CA_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/ca.crt";
+
+const CONTROL_PLANE_LABELS: [&str; 2] = [
+    "node-role.kubernetes.io/control-plane",
+    "node-role.kubernetes.io/master",
+];
+
+/// Minimal read-only client for the Kubernetes API server
+pub struct KubernetesClient {
+    api_server: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl KubernetesClient {
+    /// Use the in-cluster service account if the daemon is running in a pod, otherwise the provided
+    /// kubeconfig, $KUBECONFIG, or ~/.kube/config in that order
+    pub fn load(kubeconfig_path: Option<&str>) -> Result<Self> {
+        if kubeconfig_path.is_none()
+            && std::env::var("KUBERNETES_SERVICE_HOST").is_ok()
+            && Path::new(IN_CLUSTER_TOKEN_PATH).exists()
+        {
+            return Self::in_cluster();
+        }
+
+        let path = match kubeconfig_path {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::var("KUBECONFIG") {
+                Ok(path) if !path.is_empty() => PathBuf::from(path),
+                _ => directories_next::BaseDirs::new()
+                    .map(|d| d.home_dir().join(".kube").join("config"))
+                    .ok_or_else(|| anyhow!("Could not determine home directory for kubeconfig"))?,
+            },
+        };
+
+        Self::from_kubeconfig(&path)
+    }
+
+    pub fn in_cluster() -> Result<Self> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST")
+            .map_err(|_| anyhow!("KUBERNETES_SERVICE_HOST is not set"))?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or("443".to_string());
+        let token = std::fs::read_to_string(IN_CLUSTER_TOKEN_PATH)?;
+        let ca = std::fs::read(IN_CLUSTER_CA_PATH)?;
+
+        let host = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{}]", ip),
+            _ => host,
+        };
+
+        let client = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(&ca)?)
+            .build()?;
+
+        Ok(Self {
+            api_server: format!("https://{}:{}", host, port),
+            token: Some(token.trim().to_string()),
+            client,
+        })
+    }
+
+    pub fn from_kubeconfig(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Could not read kubeconfig {}: {}", path.display(), e))?;
+        let kubeconfig: Kubeconfig = serde_yaml::from_str(&contents)
+            .map_err(|e| anyhow!("Could not parse kubeconfig {}: {}", path.display(), e))?;
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::from_parsed_kubeconfig(kubeconfig, base_dir)
+    }
+
+    fn from_parsed_kubeconfig(kubeconfig: Kubeconfig, base_dir: &Path) -> Result<Self> {
+        let context_name = kubeconfig
+            .current_context
+            .clone()
+            .or_else(|| kubeconfig.contexts.first().map(|c| c.name.clone()))
+            .ok_or_else(|| anyhow!("Kubeconfig has no contexts"))?;
+
+        let context = kubeconfig
+            .contexts
+            .iter()
+            .find(|c| c.name == context_name)
+            .map(|c| &c.context)
+            .ok_or_else(|| anyhow!("Kubeconfig context {} not found", context_name))?;
+
+        let cluster = kubeconfig
+            .clusters
+            .iter()
+            .find(|c| c.name == context.cluster)
+            .map(|c| &c.cluster)
+            .ok_or_else(|| anyhow!("Kubeconfig cluster {} not found", context.cluster))?;
+
+        let user = kubeconfig
+            .users
+            .iter()
+            .find(|u| Some(&u.name) == context.user.as_ref())
+            .map(|u| u.user.clone())
+            .unwrap_or_default();
+
+        let read_file = |file: &str| -> Result<Vec<u8>> {
+            let path = base_dir.join(file);
+            std::fs::read(&path).map_err(|e| anyhow!("Could not read {}: {}", path.display(), e))
+        };
+
+        let mut builder = reqwest::Client::builder();
+
+        let ca = match (
+            &cluster.certificate_authority_data,
+            &cluster.certificate_authority,
+        ) {
+            (Some(data), _) => Some(BASE64.decode(data.trim())?),
+            (None, Some(file)) => Some(read_file(file)?),
+            _ => None,
+        };
+
+        if let Some(ca) = ca {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&ca)?);
+        }
+
+        if cluster.insecure_skip_tls_verify {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        let client_certificate = match (&user.client_certificate_data, &user.client_certificate) {
+            (Some(data), _) => Some(BASE64.decode(data.trim())?),
+            (None, Some(file)) => Some(read_file(file)?),
+            _ => None,
+        };
+
+        let client_key = match (&user.client_key_data, &user.client_key) {
+            (Some(data), _) => Some(BASE64.decode(data.trim())?),
+            (None, Some(file)) => Some(read_file(file)?),
+            _ => None,
+        };
+
+        if let (Some(mut certificate), Some(key)) = (client_certificate, client_key) {
+            certificate.push(b'\n');
+            certificate.extend(key);
+            builder = builder.identity(reqwest::Identity::from_pem(&certificate)?);
+        }
+
+        let token = match (&user.token, &user.token_file) {
+            (Some(token), _) => Some(token.trim().to_string()),
+            (None, Some(file)) => Some(String::from_utf8(read_file(file)?)?.trim().to_string()),
+            _ => None,
+        };
+
+        Ok(Self {
+            api_server: cluster.server.trim_end_matches('/').to_string(),
+            token,
+            client: builder.build()?,
+        })
+    }
+
+    pub fn api_server(&self) -> &str {
+        &self.api_server
+    }
+
+    async fn list<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, Error> {
+        let mut request = self.client.get(format!("{}{}", self.api_server, path));
+
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Kubernetes API request {} failed: HTTP {}",
+                path,
+                response.status()
+            );
+        }
+
+        let list: KubeList<T> = response.json().await?;
+        Ok(list.items)
+    }
+
+    /// Read everything discovery needs from the cluster
+    pub async fn get_cluster_inventory(&self) -> Result<ClusterInventory, Error> {
+        let nodes = self.list("/api/v1/nodes").await?;
+        let namespaces = self.list("/api/v1/namespaces").await?;
+        let services = self.list("/api/v1/services").await?;
+        let pods = self.list("/api/v1/pods").await?;
+
+        // Ingresses are optional - RBAC may not allow reading them
+        let ingresses = match self.list("/apis/networking.k8s.io/v1/ingresses").await {
+            Ok(ingresses) => ingresses,
+            Err(e) => {
+                tracing::warn!("Could not list Kubernetes ingresses: {}", e);
+                Vec::new()
+            }
+        };
+
+        Ok(ClusterInventory {
+            api_server: self.api_server.clone(),
+            nodes,
+            namespaces,
+            services,
+            ingresses,
+            pods,
+        })
+    }
+}
+
+/// Snapshot of the cluster objects used to build hosts, subnets and services
+#[derive(Debug, Clone, Default)]
+pub struct ClusterInventory {
+    pub api_server: String,
+    pub nodes: Vec<KubeNode>,
+    pub namespaces: Vec<KubeNamespace>,
+    pub services: Vec<KubeService>,
+    pub ingresses: Vec<KubeIngress>,
+    pub pods: Vec<KubePod>,
+}
+
+impl ClusterInventory {
+    /// The smallest CIDR containing every ClusterIP, as the API does not expose the service range.
+    /// Never narrower than a /24 so a single service still gets a sensible subnet.
+    pub fn service_cidr(&self) -> Option<IpCidr> {
+        Self::covering_cidr(self.services.iter().filter_map(|s| s.cluster_ip()), 24)
+    }
+
+    /// The smallest CIDR containing the ClusterIPs of a namespace's services
+    pub fn namespace_cidr(&self, namespace: &Option<String>) -> Option<IpCidr> {
+        Self::covering_cidr(
+            self.services
+                .iter()
+                .filter(|s| &s.metadata.namespace == namespace)
+                .filter_map(|s| s.cluster_ip()),
+            32,
+        )
+    }
+
+    /// The smallest IPv4 CIDR containing every IP, with a prefix no longer than max_prefix
+    fn covering_cidr(ips: impl Iterator<Item = IpAddr>, max_prefix: u32) -> Option<IpCidr> {
+        let ips: Vec<u32> = ips
+            .filter_map(|ip| match ip {
+                IpAddr::V4(ip) => Some(u32::from(ip)),
+                IpAddr::V6(_) => None,
+            })
+            .collect();
+
+        let first = *ips.first()?;
+
+        let common_prefix = ips
+            .iter()
+            .map(|ip| (ip ^ first).leading_zeros())
+            .min()
+            .unwrap_or(32)
+            .min(max_prefix) as u8;
+
+        let mask = u32::MAX.checked_shl(32 - common_prefix as u32).unwrap_or(0);
+        let network = std::net::Ipv4Addr::from(first & mask);
+
+        IpCidr::new(IpAddr::V4(network), common_prefix).ok()
+    }
+
+    /// Pods in the service's namespace whose labels satisfy the service selector
+    pub fn pods_for_service(&self, service: &KubeService) -> Vec<&KubePod> {
+        if service.spec.selector.is_empty() {
+            return Vec::new();
+        }
+
+        self.pods
+            .iter()
+            .filter(|p| p.metadata.namespace == service.metadata.namespace)
+            .filter(|p| {
+                service
+                    .spec
+                    .selector
+                    .iter()
+                    .all(|(k, v)| p.metadata.labels.get(k) == Some(v))
+            })
+            .collect()
+    }
+
+    /// Container images of the pods backing a service, deduplicated
+    pub fn images_for_service(&self, service: &KubeService) -> Vec<String> {
+        let mut images: Vec<String> = self
+            .pods_for_service(service)
+            .iter()
+            .flat_map(|p| p.spec.containers.iter().map(|c| c.image.clone()))
+            .collect();
+
+        images.sort();
+        images.dedup();
+        images
+    }
+
+    pub fn find_service(&self, namespace: &Option<String>, name: &str) -> Option<&KubeService> {
+        self.services
+            .iter()
+            .find(|s| &s.metadata.namespace == namespace && s.metadata.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KubeList<T> {
+    #[serde(default = "Vec::new")]
+    items: Vec<T>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ObjectMeta {
+    pub name: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeNamespace {
+    pub metadata: ObjectMeta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeNode {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: KubeNodeSpec,
+    #[serde(default)]
+    pub status: KubeNodeStatus,
+}
+
+impl KubeNode {
+    pub fn is_control_plane(&self) -> bool {
+        CONTROL_PLANE_LABELS
+            .iter()
+            .any(|l| self.metadata.labels.contains_key(*l))
+    }
+
+    pub fn addresses_of_type(&self, address_type: &str) -> Vec<&str> {
+        self.status
+            .addresses
+            .iter()
+            .filter(|a| a.address_type == address_type)
+            .map(|a| a.address.as_str())
+            .collect()
+    }
+
+    /// InternalIP and ExternalIP addresses of the node
+    pub fn ip_addresses(&self) -> Vec<IpAddr> {
+        let mut ips: Vec<IpAddr> = self
+            .addresses_of_type("InternalIP")
+            .into_iter()
+            .chain(self.addresses_of_type("ExternalIP"))
+            .filter_map(|a| a.parse::<IpAddr>().ok())
+            .collect();
+        ips.dedup();
+        ips
+    }
+
+    pub fn hostname(&self) -> Option<String> {
+        self.addresses_of_type("Hostname")
+            .first()
+            .map(|h| h.to_string())
+    }
+
+    pub fn pod_cidrs(&self) -> Vec<IpCidr> {
+        let mut cidrs: Vec<&String> = self.spec.pod_cidrs.iter().collect();
+        if let Some(pod_cidr) = &self.spec.pod_cidr {
+            cidrs.push(pod_cidr);
+        }
+
+        let mut cidrs: Vec<IpCidr> = cidrs.into_iter().filter_map(|c| c.parse().ok()).collect();
+        cidrs.sort_by_key(|c| c.to_string());
+        cidrs.dedup();
+        cidrs
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeNodeSpec {
+    #[serde(default, rename = "podCIDR")]
+    pub pod_cidr: Option<String>,
+    #[serde(default, rename = "podCIDRs")]
+    pub pod_cidrs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeNodeStatus {
+    #[serde(default)]
+    pub addresses: Vec<KubeNodeAddress>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeNodeAddress {
+    #[serde(rename = "type")]
+    pub address_type: String,
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeService {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: KubeServiceSpec,
+    #[serde(default)]
+    pub status: KubeLoadBalancerStatus,
+}
+
+impl KubeService {
+    /// ClusterIP of the service, None for headless services
+    pub fn cluster_ip(&self) -> Option<IpAddr> {
+        self.spec
+            .cluster_ip
+            .as_ref()
+            .and_then(|ip| ip.parse::<IpAddr>().ok())
+    }
+
+    pub fn load_balancer_ips(&self) -> Vec<IpAddr> {
+        let mut ips: Vec<IpAddr> = self
+            .status
+            .load_balancer
+            .ingress
+            .iter()
+            .filter_map(|i| i.ip.as_ref())
+            .chain(self.spec.load_balancer_ip.iter())
+            .chain(self.spec.external_ips.iter())
+            .filter_map(|ip| ip.parse::<IpAddr>().ok())
+            .collect();
+        ips.sort();
+        ips.dedup();
+        ips
+    }
+
+    pub fn namespace(&self) -> String {
+        self.metadata
+            .namespace
+            .clone()
+            .unwrap_or("default".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeServiceSpec {
+    #[serde(default, rename = "type")]
+    pub service_type: Option<String>,
+    #[serde(default, rename = "clusterIP")]
+    pub cluster_ip: Option<String>,
+    #[serde(default, rename = "loadBalancerIP")]
+    pub load_balancer_ip: Option<String>,
+    #[serde(default, rename = "externalIPs")]
+    pub external_ips: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<KubeServicePort>,
+    #[serde(default)]
+    pub selector: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeServicePort {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub port: u16,
+    #[serde(default)]
+    pub protocol: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeLoadBalancerStatus {
+    #[serde(default)]
+    pub load_balancer: KubeLoadBalancer,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeLoadBalancer {
+    #[serde(default)]
+    pub ingress: Vec<KubeLoadBalancerIngress>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeLoadBalancerIngress {
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngress {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: KubeIngressSpec,
+    #[serde(default)]
+    pub status: KubeLoadBalancerStatus,
+}
+
+impl KubeIngress {
+    pub fn load_balancer_ips(&self) -> Vec<IpAddr> {
+        let mut ips: Vec<IpAddr> = self
+            .status
+            .load_balancer
+            .ingress
+            .iter()
+            .filter_map(|i| i.ip.as_ref())
+            .filter_map(|ip| ip.parse::<IpAddr>().ok())
+            .collect();
+        ips.sort();
+        ips.dedup();
+        ips
+    }
+
+    pub fn has_tls(&self) -> bool {
+        !self.spec.tls.is_empty()
+    }
+
+    /// (rule host, backend service name) pairs
+    pub fn backends(&self) -> Vec<(Option<String>, String)> {
+        self.spec
+            .rules
+            .iter()
+            .flat_map(|r| {
+                r.http
+                    .iter()
+                    .flat_map(|h| h.paths.iter())
+                    .filter_map(|p| p.backend.service.as_ref())
+                    .map(|s| (r.host.clone(), s.name.clone()))
+            })
+            .chain(
+                self.spec
+                    .default_backend
+                    .as_ref()
+                    .and_then(|b| b.service.as_ref())
+                    .map(|s| (None, s.name.clone())),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeIngressSpec {
+    #[serde(default)]
+    pub default_backend: Option<KubeIngressBackend>,
+    #[serde(default)]
+    pub rules: Vec<KubeIngressRule>,
+    #[serde(default)]
+    pub tls: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngressRule {
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub http: Option<KubeIngressHttp>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngressHttp {
+    #[serde(default)]
+    pub paths: Vec<KubeIngressPath>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngressPath {
+    pub backend: KubeIngressBackend,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngressBackend {
+    #[serde(default)]
+    pub service: Option<KubeIngressServiceBackend>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeIngressServiceBackend {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubePod {
+    pub metadata: ObjectMeta,
+    #[serde(default)]
+    pub spec: KubePodSpec,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubePodSpec {
+    #[serde(default)]
+    pub containers: Vec<KubeContainer>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KubeContainer {
+    pub name: String,
+    #[serde(default)]
+    pub image: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Kubeconfig {
+    #[serde(default)]
+    current_context: Option<String>,
+    #[serde(default)]
+    clusters: Vec<NamedKubeconfigCluster>,
+    #[serde(default)]
+    contexts: Vec<NamedKubeconfigContext>,
+    #[serde(default)]
+    users: Vec<NamedKubeconfigUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NamedKubeconfigCluster {
+    name: String,
+    cluster: KubeconfigCluster,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct KubeconfigCluster {
+    server: String,
+    #[serde(default)]
+    certificate_authority: Option<String>,
+    #[serde(default)]
+    certificate_authority_data: Option<String>,
+    #[serde(default)]
+    insecure_skip_tls_verify: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NamedKubeconfigContext {
+    name: String,
+    context: KubeconfigContext,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KubeconfigContext {
+    cluster: String,
+    #[serde(default)]
+    user: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NamedKubeconfigUser {
+    name: String,
+    #[serde(default)]
+    user: KubeconfigUser,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct KubeconfigUser {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    token_file: Option<String>,
+    #[serde(default)]
+    client_certificate: Option<String>,
+    #[serde(default)]
+    client_certificate_data: Option<String>,
+    #[serde(default)]
+    client_key: Option<String>,
+    #[serde(default)]
+    client_key_data: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, path::Path};
+
+    use axum::{
+        Json, Router,
+        http::{HeaderMap, StatusCode, Uri},
+    };
+    use serde_json::Value;
+
+    use crate::{
+        daemon::discovery::{
+            service::{base::Discovery, kubernetes::KubernetesScanDiscovery},
+            types::kubernetes::KubernetesClient,
+        },
+        server::{
+            hosts::types::ports::PortBase,
+            services::{
+                definitions::ServiceDefinitionRegistry,
+                types::{bindings::Binding, definitions::ServiceDefinition},
+            },
+            subnets::types::base::SubnetType,
+        },
+        tests::{KUBERNETES_API_FIXTURE, subnet},
+    };
+
+    type KubernetesDiscovery = Discovery<KubernetesScanDiscovery>;
+
+    const TOKEN: &str = "test-token";
+
+    /// Serve the fixture from a fake API server, returning its address
+    async fn fake_api_server() -> SocketAddr {
+        let fixture: Value = serde_json::from_str(
+            &std::fs::read_to_string(Path::new(KUBERNETES_API_FIXTURE))
+                .expect("Failed to read Kubernetes API fixture"),
+        )
+        .expect("Failed to parse Kubernetes API fixture");
+
+        let app = Router::new().fallback(move |uri: Uri, headers: HeaderMap| {
+            let fixture = fixture.clone();
+            async move {
+                let authorized = headers.get("authorization").and_then(|h| h.to_str().ok())
+                    == Some(&format!("Bearer {}", TOKEN));
+
+                if !authorized {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+
+                fixture
+                    .get(uri.path())
+                    .cloned()
+                    .map(Json)
+                    .ok_or(StatusCode::NOT_FOUND)
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    fn write_kubeconfig(addr: SocketAddr) -> std::path::PathBuf {
+        let kubeconfig = format!(
+            r#"
+apiVersion: v1
+kind: Config
+current-context: test
+clusters:
+  - name: test-cluster
+    cluster:
+      server: http://{}
+contexts:
+  - name: test
+    context:
+      cluster: test-cluster
+      user: test-user
+users:
+  - name: test-user
+    user:
+      token: {}
+"#,
+            addr, TOKEN
+        );
+
+        let path = std::env::temp_dir().join(format!("kubeconfig-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, kubeconfig).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_inventory_from_fake_api_server() {
+        let addr = fake_api_server().await;
+        let kubeconfig_path = write_kubeconfig(addr);
+
+        let client = KubernetesClient::from_kubeconfig(&kubeconfig_path).unwrap();
+        let inventory = client.get_cluster_inventory().await.unwrap();
+        std::fs::remove_file(&kubeconfig_path).ok();
+
+        assert_eq!(inventory.nodes.len(), 2);
+        assert_eq!(inventory.namespaces.len(), 3);
+        assert!(inventory.nodes[0].is_control_plane());
+        assert!(!inventory.nodes[1].is_control_plane());
+        assert_eq!(
+            inventory.nodes[1].pod_cidrs(),
+            vec!["10.244.1.0/24".parse().unwrap()]
+        );
+
+        assert_eq!(
+            inventory.service_cidr(),
+            Some("10.96.0.0/24".parse().unwrap())
+        );
+
+        let grafana = inventory
+            .find_service(&Some("monitoring".to_string()), "grafana")
+            .unwrap();
+        assert_eq!(
+            grafana.load_balancer_ips(),
+            vec!["192.168.1.240".parse::<std::net::IpAddr>().unwrap()]
+        );
+
+        let headless = inventory
+            .find_service(&Some("monitoring".to_string()), "grafana-headless")
+            .unwrap();
+        assert!(headless.cluster_ip().is_none());
+
+        // Pod images are matched against service definitions
+        let definition = inventory
+            .images_for_service(grafana)
+            .iter()
+            .find_map(|image| ServiceDefinitionRegistry::find_by_image(image))
+            .expect("Grafana image should match a service definition");
+        assert_eq!(definition.name(), "Grafana");
+
+        let ingress = &inventory.ingresses[0];
+        assert!(ingress.has_tls());
+        assert_eq!(
+            ingress.backends(),
+            vec![(
+                Some("grafana.example.com".to_string()),
+                "grafana".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_client_rejected_without_token() {
+        let addr = fake_api_server().await;
+        let kubeconfig_path = write_kubeconfig(addr);
+
+        let contents = std::fs::read_to_string(&kubeconfig_path)
+            .unwrap()
+            .replace(TOKEN, "wrong-token");
+        std::fs::write(&kubeconfig_path, contents).unwrap();
+
+        let client = KubernetesClient::from_kubeconfig(&kubeconfig_path).unwrap();
+        let result = client.get_cluster_inventory().await;
+        std::fs::remove_file(&kubeconfig_path).ok();
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_hosts_services_and_subnets() {
+        let addr = fake_api_server().await;
+        let kubeconfig_path = write_kubeconfig(addr);
+
+        let client = KubernetesClient::from_kubeconfig(&kubeconfig_path).unwrap();
+        let inventory = client.get_cluster_inventory().await.unwrap();
+        std::fs::remove_file(&kubeconfig_path).ok();
+
+        let network_id = uuid::Uuid::new_v4();
+        let daemon_id = uuid::Uuid::new_v4();
+        let lan = subnet(&network_id);
+
+        let subnets = KubernetesDiscovery::cluster_subnets(
+            &inventory,
+            vec![lan.clone()],
+            network_id,
+            daemon_id,
+        );
+        let subnet_with_cidr = |cidr: &str| {
+            subnets
+                .iter()
+                .find(|s| s.base.cidr == cidr.parse().unwrap())
+                .unwrap_or_else(|| panic!("No subnet for {}", cidr))
+        };
+
+        // Pod, service and namespace ranges all get subnets
+        assert_eq!(
+            subnet_with_cidr("10.244.1.0/24").base.subnet_type,
+            SubnetType::Kubernetes
+        );
+        assert_eq!(
+            subnet_with_cidr("10.96.0.0/24").base.name,
+            "Kubernetes Services"
+        );
+        let kube_system = subnet_with_cidr("10.96.0.10/32");
+        assert_eq!(kube_system.base.name, "kube-system namespace");
+
+        // Only the control plane node runs the Kubernetes API
+        let (control_plane, control_plane_services) =
+            KubernetesDiscovery::node_host(&inventory.nodes[0], &subnets, network_id, daemon_id)
+                .unwrap();
+        assert_eq!(control_plane.base.name, "k8s-control-1");
+        assert_eq!(control_plane.base.interfaces[0].base.subnet_id, lan.id);
+        assert_eq!(control_plane_services.len(), 1);
+        assert_eq!(
+            control_plane_services[0].base.service_definition.id(),
+            "Kubernetes"
+        );
+        assert_eq!(control_plane.base.ports[0].base, PortBase::new_tcp(6443));
+
+        let (_, worker_services) =
+            KubernetesDiscovery::node_host(&inventory.nodes[1], &subnets, network_id, daemon_id)
+                .unwrap();
+        assert!(worker_services.is_empty());
+
+        // Clusters without a visible control plane node get a host for the API server instead
+        let (api_host, api_services) = KubernetesDiscovery::api_server_host(
+            "https://k8s.example.com:8443",
+            &subnets,
+            network_id,
+            daemon_id,
+        )
+        .unwrap();
+        assert_eq!(api_host.base.hostname.as_deref(), Some("k8s.example.com"));
+        assert_eq!(api_host.base.ports[0].base, PortBase::new_tcp(8443));
+        assert_eq!(api_services[0].base.service_definition.id(), "Kubernetes");
+
+        // ClusterIPs are placed in their namespace's subnet
+        let kube_dns = inventory
+            .find_service(&Some("kube-system".to_string()), "kube-dns")
+            .unwrap();
+        let kube_dns_interfaces =
+            KubernetesDiscovery::service_interfaces(&inventory, kube_dns, &subnets);
+        assert_eq!(kube_dns_interfaces[0].1.id, kube_system.id);
+
+        // Services are matched from their pods' images, and bound to their ports on every address
+        let grafana = inventory
+            .find_service(&Some("monitoring".to_string()), "grafana")
+            .unwrap();
+        let grafana_interfaces =
+            KubernetesDiscovery::service_interfaces(&inventory, grafana, &subnets);
+        assert_eq!(grafana_interfaces.len(), 2);
+        assert_eq!(grafana_interfaces[1].0.base.subnet_id, lan.id);
+
+        let (definition, details) =
+            KubernetesDiscovery::match_service_images(&inventory, grafana).unwrap();
+        let (host, services) = KubernetesDiscovery::image_matched_host(
+            &grafana_interfaces[0].0,
+            &[PortBase::new_tcp(3000)],
+            vec![("grafana.monitoring".to_string(), definition, details)],
+            &None,
+            network_id,
+            daemon_id,
+        );
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].base.service_definition.id(), "Grafana");
+        assert_eq!(host.base.services, vec![services[0].id]);
+        assert!(matches!(
+            services[0].base.bindings[0],
+            Binding::Port { port_id, .. } if port_id == host.base.ports[0].id
+        ));
+
+        // Only the ingress listens on its ports, the backends it routes to are bound to its address
+        let ingress_interface = &grafana_interfaces[1].0;
+        let (ingress_host, ingress_services) = KubernetesDiscovery::ingress_host(
+            &inventory,
+            &inventory.ingresses[0],
+            ingress_interface,
+            &None,
+            network_id,
+            daemon_id,
+        );
+        assert_eq!(ingress_host.base.ports.len(), 2);
+        assert_eq!(ingress_services.len(), 2);
+        assert_eq!(ingress_services[0].base.bindings.len(), 2);
+        assert!(
+            ingress_services[0]
+                .base
+                .bindings
+                .iter()
+                .all(|b| matches!(b, Binding::Port { .. }))
+        );
+        assert_eq!(ingress_services[1].base.name, "grafana.example.com");
+        assert_eq!(ingress_services[1].base.service_definition.id(), "Grafana");
+        assert!(matches!(
+            ingress_services[1].base.bindings[..],
+            [Binding::Interface { interface_id, .. }] if interface_id == ingress_interface.id
+        ));
+    }
+}
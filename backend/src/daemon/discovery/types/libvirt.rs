@@ -0,0 +1,395 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use anyhow::{Error, Result, anyhow};
+use cidr::IpCidr;
+use mac_address::MacAddress;
+use tokio::process::Command;
+
+pub const DEFAULT_LIBVIRT_URI: &str = "qemu:///system";
+
+/// Address sources tried in order by `virsh domifaddr`. Leases only cover libvirt managed networks,
+/// ARP covers bridged guests the hypervisor has talked to, and the agent needs qemu-guest-agent.
+const DOMIFADDR_SOURCES: [&str; 3] = ["lease", "arp", "agent"];
+
+/// Reads hypervisor state through the `virsh` CLI
+pub struct VirshClient {
+    uri: String,
+}
+
+impl VirshClient {
+    pub fn new(uri: Option<String>) -> Self {
+        Self {
+            uri: uri.unwrap_or(DEFAULT_LIBVIRT_URI.to_string()),
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Whether virsh is installed and can connect to the hypervisor
+    pub async fn is_available(&self) -> bool {
+        self.run(&["uri"]).await.is_ok()
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String, Error> {
+        let output = Command::new("virsh")
+            .arg("-c")
+            .arg(&self.uri)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to run virsh: {}", e))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "virsh {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    pub async fn list_domains(&self) -> Result<Vec<String>, Error> {
+        Ok(parse_name_list(
+            &self.run(&["list", "--all", "--name"]).await?,
+        ))
+    }
+
+    pub async fn get_domain(&self, name: &str) -> Result<LibvirtDomain, Error> {
+        let xml = self.run(&["dumpxml", name]).await?;
+        let mut domain = LibvirtDomain::from_xml(&xml)?;
+
+        domain.running = self
+            .run(&["domstate", name])
+            .await
+            .map(|s| s.trim() == "running")
+            .unwrap_or(false);
+
+        Ok(domain)
+    }
+
+    /// Addresses of a running domain's interfaces, merged across all address sources
+    pub async fn get_domain_addresses(&self, name: &str) -> Vec<LibvirtDomainAddress> {
+        let mut addresses: Vec<LibvirtDomainAddress> = Vec::new();
+
+        for source in DOMIFADDR_SOURCES {
+            match self
+                .run(&["domifaddr", name, "--source", source])
+                .await
+                .map(|output| parse_domifaddr(&output))
+            {
+                Ok(found) => found.into_iter().for_each(|a| {
+                    if !addresses.iter().any(|existing| existing.ip == a.ip) {
+                        addresses.push(a)
+                    }
+                }),
+                Err(e) => tracing::debug!("No {} addresses for domain {}: {}", source, name, e),
+            }
+        }
+
+        addresses
+    }
+
+    pub async fn list_networks(&self) -> Result<Vec<LibvirtNetwork>, Error> {
+        let names = parse_name_list(&self.run(&["net-list", "--all", "--name"]).await?);
+
+        let mut networks = Vec::new();
+        for name in names {
+            match self.run(&["net-dumpxml", &name]).await {
+                Ok(xml) => networks.push(LibvirtNetwork::from_xml(&xml)?),
+                Err(e) => tracing::warn!("Could not read libvirt network {}: {}", name, e),
+            }
+        }
+
+        Ok(networks)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibvirtDomain {
+    pub name: String,
+    pub uuid: Option<String>,
+    pub running: bool,
+    pub interfaces: Vec<LibvirtDomainInterface>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibvirtDomainInterface {
+    /// "bridge", "network", "direct", etc.
+    pub interface_type: String,
+    pub mac_address: Option<MacAddress>,
+    /// Libvirt network the interface is attached to, if any
+    pub network: Option<String>,
+    /// Host bridge or device the interface is attached to
+    pub bridge: Option<String>,
+    /// Tap device on the hypervisor, ie vnet0
+    pub target: Option<String>,
+}
+
+impl LibvirtDomainInterface {
+    /// Name of whatever the interface is attached to on the hypervisor
+    pub fn source_name(&self) -> Option<String> {
+        self.network.clone().or(self.bridge.clone())
+    }
+}
+
+impl LibvirtDomain {
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|e| anyhow!("Could not parse libvirt domain XML: {}", e))?;
+        let root = document.root_element();
+
+        let child_text = |tag: &str| {
+            root.children()
+                .find(|n| n.has_tag_name(tag))
+                .and_then(|n| n.text())
+                .map(|t| t.trim().to_string())
+        };
+
+        let name = child_text("name").ok_or_else(|| anyhow!("Libvirt domain has no name"))?;
+
+        let interfaces = root
+            .descendants()
+            .filter(|n| n.has_tag_name("devices"))
+            .flat_map(|devices| devices.children().filter(|n| n.has_tag_name("interface")))
+            .map(|interface| {
+                let attribute = |tag: &str, attr: &str| {
+                    interface
+                        .children()
+                        .find(|n| n.has_tag_name(tag))
+                        .and_then(|n| n.attribute(attr))
+                        .map(|a| a.to_string())
+                };
+
+                let source = interface.children().find(|n| n.has_tag_name("source"));
+
+                LibvirtDomainInterface {
+                    interface_type: interface.attribute("type").unwrap_or_default().to_string(),
+                    mac_address: attribute("mac", "address").and_then(|m| m.parse().ok()),
+                    network: source
+                        .and_then(|s| s.attribute("network"))
+                        .map(|a| a.to_string()),
+                    bridge: source
+                        .and_then(|s| s.attribute("bridge").or(s.attribute("dev")))
+                        .map(|a| a.to_string()),
+                    target: attribute("target", "dev"),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            uuid: child_text("uuid"),
+            running: false,
+            interfaces,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibvirtNetwork {
+    pub name: String,
+    pub bridge: Option<String>,
+    pub cidrs: Vec<IpCidr>,
+}
+
+impl LibvirtNetwork {
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|e| anyhow!("Could not parse libvirt network XML: {}", e))?;
+        let root = document.root_element();
+
+        let name = root
+            .children()
+            .find(|n| n.has_tag_name("name"))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .ok_or_else(|| anyhow!("Libvirt network has no name"))?;
+
+        let bridge = root
+            .children()
+            .find(|n| n.has_tag_name("bridge"))
+            .and_then(|n| n.attribute("name"))
+            .map(|a| a.to_string());
+
+        let cidrs = root
+            .children()
+            .filter(|n| n.has_tag_name("ip"))
+            .filter_map(|ip| {
+                let address = ip.attribute("address")?.parse::<IpAddr>().ok()?;
+
+                let prefix = match (ip.attribute("prefix"), ip.attribute("netmask")) {
+                    (Some(prefix), _) => prefix.parse::<u8>().ok()?,
+                    (None, Some(netmask)) => {
+                        u32::from(netmask.parse::<Ipv4Addr>().ok()?).count_ones() as u8
+                    }
+                    (None, None) => return None,
+                };
+
+                IpCidr::new(network_address(address, prefix)?, prefix).ok()
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            bridge,
+            cidrs,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibvirtDomainAddress {
+    pub interface: String,
+    pub mac_address: Option<MacAddress>,
+    pub ip: IpAddr,
+}
+
+fn parse_name_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// Parse the table printed by `virsh domifaddr`. Continuation rows for additional addresses on the
+/// same interface use "-" for the name and MAC columns.
+pub fn parse_domifaddr(output: &str) -> Vec<LibvirtDomainAddress> {
+    let mut addresses = Vec::new();
+    let mut last_interface = (String::new(), None);
+
+    for line in output
+        .lines()
+        .skip_while(|l| !l.trim_start().starts_with("---"))
+        .skip(1)
+    {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+
+        let [name, mac, _protocol, address] = columns[..] else {
+            continue;
+        };
+
+        if name != "-" {
+            last_interface = (name.to_string(), mac.parse::<MacAddress>().ok());
+        }
+
+        if let Some(Ok(ip)) = address.split('/').next().map(|a| a.parse::<IpAddr>()) {
+            addresses.push(LibvirtDomainAddress {
+                interface: last_interface.0.clone(),
+                mac_address: last_interface.1,
+                ip,
+            });
+        }
+    }
+
+    addresses
+}
+
+fn network_address(address: IpAddr, prefix: u8) -> Option<IpAddr> {
+    match address {
+        IpAddr::V4(ip) => {
+            let host_bits = 32u32.checked_sub(prefix as u32)?;
+            let mask = u32::MAX.checked_shl(host_bits).unwrap_or(0);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from(ip) & mask)))
+        }
+        IpAddr::V6(ip) => {
+            let host_bits = 128u32.checked_sub(prefix as u32)?;
+            let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0);
+            Some(IpAddr::V6(std::net::Ipv6Addr::from(u128::from(ip) & mask)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::daemon::discovery::types::libvirt::{
+        LibvirtDomain, LibvirtNetwork, network_address, parse_domifaddr,
+    };
+
+    const DOMAIN_XML: &str = r#"
+<domain type='kvm' id='3'>
+  <name>media-vm</name>
+  <uuid>4dea22b3-1d52-d8f3-2516-782e98ab3fa0</uuid>
+  <memory unit='KiB'>4194304</memory>
+  <devices>
+    <disk type='file' device='disk'>
+      <source file='/var/lib/libvirt/images/media-vm.qcow2'/>
+      <target dev='vda' bus='virtio'/>
+    </disk>
+    <interface type='network'>
+      <mac address='52:54:00:8d:21:4a'/>
+      <source network='default' bridge='virbr0'/>
+      <target dev='vnet0'/>
+      <model type='virtio'/>
+    </interface>
+    <interface type='bridge'>
+      <mac address='52:54:00:11:22:33'/>
+      <source bridge='br0'/>
+      <target dev='vnet1'/>
+      <model type='virtio'/>
+    </interface>
+  </devices>
+</domain>
+"#;
+
+    const NETWORK_XML: &str = r#"
+<network>
+  <name>default</name>
+  <uuid>9a05da11-e96b-47f3-8253-a3a482e445f5</uuid>
+  <forward mode='nat'/>
+  <bridge name='virbr0' stp='on' delay='0'/>
+  <ip address='192.168.122.1' netmask='255.255.255.0'>
+    <dhcp>
+      <range start='192.168.122.2' end='192.168.122.254'/>
+    </dhcp>
+  </ip>
+</network>
+"#;
+
+    const DOMIFADDR: &str = " Name       MAC address          Protocol     Address
+-------------------------------------------------------------------------------
+ vnet0      52:54:00:8d:21:4a    ipv4         192.168.122.45/24
+ -          -                    ipv6         fe80::5054:ff:fe8d:214a/64
+ vnet1      52:54:00:11:22:33    ipv4         192.168.1.80/24
+";
+
+    #[test]
+    fn test_libvirt_xml_and_address_parsing() {
+        // Prefixes longer than the address have no network
+        assert_eq!(
+            network_address("192.168.122.1".parse().unwrap(), 24),
+            Some("192.168.122.0".parse().unwrap())
+        );
+        assert_eq!(network_address("192.168.122.1".parse().unwrap(), 33), None);
+        assert_eq!(network_address("fd00::1".parse().unwrap(), 129), None);
+
+        let domain = LibvirtDomain::from_xml(DOMAIN_XML).unwrap();
+        assert_eq!(domain.name, "media-vm");
+        assert_eq!(
+            domain.uuid.as_deref(),
+            Some("4dea22b3-1d52-d8f3-2516-782e98ab3fa0")
+        );
+        assert_eq!(domain.interfaces.len(), 2);
+        assert_eq!(
+            domain.interfaces[0].source_name().as_deref(),
+            Some("default")
+        );
+        assert_eq!(domain.interfaces[1].source_name().as_deref(), Some("br0"));
+        assert_eq!(domain.interfaces[1].target.as_deref(), Some("vnet1"));
+
+        let network = LibvirtNetwork::from_xml(NETWORK_XML).unwrap();
+        assert_eq!(network.bridge.as_deref(), Some("virbr0"));
+        assert_eq!(network.cidrs, vec!["192.168.122.0/24".parse().unwrap()]);
+
+        let addresses = parse_domifaddr(DOMIFADDR);
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses[1].interface, "vnet0");
+        assert_eq!(addresses[1].mac_address, domain.interfaces[0].mac_address);
+        assert_eq!(addresses[2].ip.to_string(), "192.168.1.80");
+    }
+}
@@ -1 +1,6 @@
 pub mod base;
+pub mod container_runtime;
+pub mod cri;
+pub mod dhcp;
+pub mod kubernetes;
+pub mod libvirt;
@@ -92,7 +92,7 @@ impl DaemonRuntimeService {
         tracing::info!("Server health check: {}", test_response.status());
 
         let daemon_id = self.config_store.get_id().await?;
-        let has_docker_client = self.utils.get_own_docker_socket().await?;
+        let container_runtime = self.utils.get_own_container_runtime().await?;
 
         // Check if already registered
         if let Some(existing_host_id) = self.config_store.get_host_id().await? {
@@ -120,11 +120,14 @@ impl DaemonRuntimeService {
         self.register_with_server(host_id, daemon_id, network_id)
             .await?;
 
-        // If has Docker, discover Docker services
-        if has_docker_client {
+        // If has a container runtime, discover container services
+        if container_runtime.is_some() {
             discovery.run_self_report_docker_discovery().await?;
         }
 
+        // If running on a hypervisor, discover its guests
+        discovery.run_self_report_libvirt_discovery().await?;
+
         tracing::info!("Daemon fully initialized!");
 
         Ok(())
@@ -22,6 +22,13 @@ pub struct CliArgs {
     pub log_level: Option<String>,
     pub heartbeat_interval: Option<u64>,
     pub concurrent_scans: Option<usize>,
+    pub kubeconfig_path: Option<String>,
+    pub libvirt_uri: Option<String>,
+    pub containerd_namespace: Option<String>,
+    pub min_match_score: Option<u32>,
+    pub record_match_traces: Option<bool>,
+    pub device_fingerprinting: Option<bool>,
+    pub dhcp_listener: Option<bool>,
 }
 
 /// Unified configuration struct that handles both startup and runtime config
@@ -39,6 +46,24 @@ pub struct AppConfig {
     pub heartbeat_interval: u64,
     pub bind_address: String,
     pub concurrent_scans: usize,
+    #[serde(default)]
+    pub kubeconfig_path: Option<String>,
+    #[serde(default)]
+    pub libvirt_uri: Option<String>,
+    #[serde(default)]
+    pub containerd_namespace: Option<String>,
+    /// Minimum evidence score for a service match to be accepted
+    #[serde(default)]
+    pub min_match_score: u32,
+    /// Keep service matching traces for hosts processed in the last discovery session
+    #[serde(default)]
+    pub record_match_traces: bool,
+    /// Query scanned hosts over mDNS and SSDP to classify end-user devices
+    #[serde(default = "default_device_fingerprinting")]
+    pub device_fingerprinting: bool,
+    /// Listen for DHCP requests on UDP port 67 to fingerprint clients by the options they request
+    #[serde(default)]
+    pub dhcp_listener: bool,
 
     // Runtime state
     pub id: Uuid,
@@ -46,6 +71,10 @@ pub struct AppConfig {
     pub host_id: Option<Uuid>,
 }
 
+fn default_device_fingerprinting() -> bool {
+    true
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -61,6 +90,13 @@ impl Default for AppConfig {
             last_heartbeat: None,
             host_id: None,
             concurrent_scans: 15,
+            kubeconfig_path: None,
+            libvirt_uri: None,
+            containerd_namespace: None,
+            min_match_score: 0,
+            record_match_traces: false,
+            device_fingerprinting: true,
+            dhcp_listener: false,
         }
     }
 }
@@ -115,6 +151,27 @@ impl AppConfig {
         if let Some(concurrent_scans) = cli_args.concurrent_scans {
             figment = figment.merge(("concurrent_scans", concurrent_scans));
         }
+        if let Some(kubeconfig_path) = cli_args.kubeconfig_path {
+            figment = figment.merge(("kubeconfig_path", kubeconfig_path));
+        }
+        if let Some(libvirt_uri) = cli_args.libvirt_uri {
+            figment = figment.merge(("libvirt_uri", libvirt_uri));
+        }
+        if let Some(containerd_namespace) = cli_args.containerd_namespace {
+            figment = figment.merge(("containerd_namespace", containerd_namespace));
+        }
+        if let Some(min_match_score) = cli_args.min_match_score {
+            figment = figment.merge(("min_match_score", min_match_score));
+        }
+        if let Some(record_match_traces) = cli_args.record_match_traces {
+            figment = figment.merge(("record_match_traces", record_match_traces));
+        }
+        if let Some(device_fingerprinting) = cli_args.device_fingerprinting {
+            figment = figment.merge(("device_fingerprinting", device_fingerprinting));
+        }
+        if let Some(dhcp_listener) = cli_args.dhcp_listener {
+            figment = figment.merge(("dhcp_listener", dhcp_listener));
+        }
 
         let config: AppConfig = figment
             .extract()
@@ -254,6 +311,41 @@ impl ConfigStore {
         Ok(config.concurrent_scans)
     }
 
+    pub async fn get_kubeconfig_path(&self) -> Result<Option<String>> {
+        let config = self.config.read().await;
+        Ok(config.kubeconfig_path.clone())
+    }
+
+    pub async fn get_libvirt_uri(&self) -> Result<Option<String>> {
+        let config = self.config.read().await;
+        Ok(config.libvirt_uri.clone())
+    }
+
+    pub async fn get_containerd_namespace(&self) -> Result<Option<String>> {
+        let config = self.config.read().await;
+        Ok(config.containerd_namespace.clone())
+    }
+
+    pub async fn get_min_match_score(&self) -> Result<u32> {
+        let config = self.config.read().await;
+        Ok(config.min_match_score)
+    }
+
+    pub async fn get_record_match_traces(&self) -> Result<bool> {
+        let config = self.config.read().await;
+        Ok(config.record_match_traces)
+    }
+
+    pub async fn get_device_fingerprinting(&self) -> Result<bool> {
+        let config = self.config.read().await;
+        Ok(config.device_fingerprinting)
+    }
+
+    pub async fn get_dhcp_listener(&self) -> Result<bool> {
+        let config = self.config.read().await;
+        Ok(config.dhcp_listener)
+    }
+
     pub async fn get_heartbeat_interval(&self) -> Result<u64> {
         let config = self.config.read().await;
         Ok(config.heartbeat_interval)
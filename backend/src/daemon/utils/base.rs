@@ -1,11 +1,12 @@
+use crate::daemon::discovery::types::container_runtime::connect_container_runtime;
 use crate::server::discovery::types::base::DiscoveryType;
 use crate::server::hosts::types::interfaces::{Interface, InterfaceBase};
+use crate::server::services::types::virtualization::ContainerRuntime;
 use crate::server::subnets::types::base::Subnet;
 use anyhow::Error;
 use anyhow::Result;
 use anyhow::anyhow;
 use async_trait::async_trait;
-use bollard::Docker;
 use cidr::IpCidr;
 use local_ip_address::local_ip;
 use mac_address::MacAddress;
@@ -86,10 +87,13 @@ pub trait DaemonUtils {
             // Find which subnet this IP belongs to
             if let Some(subnet) = subnet_map.values().find(|s| s.base.cidr.contains(&ip_addr)) {
                 interfaces_list.push(Interface::new(InterfaceBase {
+                    vlan_tag: InterfaceBase::vlan_tag_from_name(&interface_name),
                     name: Some(interface_name),
                     subnet_id: subnet.id,
                     ip_address: ip_addr,
                     mac_address,
+                    mac_vendor: None,
+                    randomized_mac: false,
                 }));
             }
         }
@@ -99,18 +103,12 @@ pub trait DaemonUtils {
         Ok((interfaces_list, subnets))
     }
 
-    async fn get_own_docker_socket(&self) -> Result<bool, Error> {
-        match Docker::connect_with_local_defaults() {
-            Ok(docker) => {
-                // Actually verify it's a Docker daemon by pinging it
-                if docker.ping().await.is_ok() {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Err(_) => Ok(false),
-        }
+    /// Container runtime (Docker, Podman or containerd) available on this host, if any
+    async fn get_own_container_runtime(&self) -> Result<Option<ContainerRuntime>, Error> {
+        Ok(connect_container_runtime(None)
+            .await
+            .ok()
+            .map(|client| client.runtime()))
     }
 
     async fn get_own_routing_table_gateway_ips(&self) -> Result<Vec<IpAddr>, Error> {
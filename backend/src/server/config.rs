@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, sync::Arc};
 
 use crate::server::discovery::manager::DiscoverySessionManager;
+use crate::server::reconciliation::types::ReconciliationPolicy;
 use crate::server::shared::{services::ServiceFactory, types::storage::StorageFactory};
 
 /// CLI arguments structure (for figment integration)
@@ -17,6 +18,10 @@ pub struct CliArgs {
     pub rust_log: Option<String>,
     pub database_url: Option<String>,
     pub integrated_daemon_url: Option<String>,
+    pub min_match_score: Option<u32>,
+    pub vulnerability_feed_path: Option<PathBuf>,
+    pub reconciliation_policy: Option<String>,
+    pub reconciliation_missed_scans: Option<u32>,
 }
 
 /// Flattened server configuration struct
@@ -43,6 +48,19 @@ pub struct ServerConfig {
 
     /// URL for daemon running in same docker stack or in other local context
     pub integrated_daemon_url: Option<String>,
+
+    /// Minimum evidence score for a service match to be accepted when reclassifying hosts
+    pub min_match_score: u32,
+
+    /// NVD or OSV JSON file, or directory of them, to match service versions against
+    pub vulnerability_feed_path: Option<PathBuf>,
+
+    /// What to do with discovered ports and services that discovery stops seeing
+    pub reconciliation_policy: ReconciliationPolicy,
+
+    /// Consecutive discovery sessions which must miss a port or service before the reconciliation
+    /// policy applies
+    pub reconciliation_missed_scans: u32,
 }
 
 impl Default for ServerConfig {
@@ -55,6 +73,10 @@ impl Default for ServerConfig {
             web_external_path: None,
             seed_test_user: false,
             integrated_daemon_url: None,
+            min_match_score: 0,
+            vulnerability_feed_path: None,
+            reconciliation_policy: ReconciliationPolicy::Keep,
+            reconciliation_missed_scans: 3,
         }
     }
 }
@@ -83,6 +105,18 @@ impl ServerConfig {
         if let Some(integrated_daemon_url) = cli_args.integrated_daemon_url {
             figment = figment.merge(("integrated_daemon_url", integrated_daemon_url));
         }
+        if let Some(min_match_score) = cli_args.min_match_score {
+            figment = figment.merge(("min_match_score", min_match_score));
+        }
+        if let Some(vulnerability_feed_path) = cli_args.vulnerability_feed_path {
+            figment = figment.merge(("vulnerability_feed_path", vulnerability_feed_path));
+        }
+        if let Some(reconciliation_policy) = cli_args.reconciliation_policy {
+            figment = figment.merge(("reconciliation_policy", reconciliation_policy));
+        }
+        if let Some(reconciliation_missed_scans) = cli_args.reconciliation_missed_scans {
+            figment = figment.merge(("reconciliation_missed_scans", reconciliation_missed_scans));
+        }
 
         let config: ServerConfig = figment
             .extract()
@@ -0,0 +1,109 @@
+use crate::server::{
+    config::AppState,
+    custom_fields::types::CustomFieldDefinition,
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_definition))
+        .route("/", get(get_all_definitions))
+        .route("/:id", put(update_definition))
+        .route("/:id", delete(delete_definition))
+}
+
+async fn create_definition(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CustomFieldDefinition>,
+) -> ApiResult<Json<ApiResponse<CustomFieldDefinition>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "Custom field validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.custom_field_service;
+
+    let definition = service
+        .create_definition(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(definition)))
+}
+
+async fn get_all_definitions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<CustomFieldDefinition>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.custom_field_service;
+
+    let definitions = service.get_all_definitions(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(definitions)))
+}
+
+async fn update_definition(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CustomFieldDefinition>,
+) -> ApiResult<Json<ApiResponse<CustomFieldDefinition>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "Custom field validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.custom_field_service;
+
+    let mut definition = service
+        .get_definition(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Custom field '{}' not found", &id)))?;
+
+    // Values are keyed by definition, so definitions can't move between networks
+    let network_id = definition.base.network_id;
+    definition.base = request.base;
+    definition.base.network_id = network_id;
+
+    let updated_definition = service
+        .update_definition(definition)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(updated_definition)))
+}
+
+async fn delete_definition(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.custom_field_service;
+
+    if service.get_definition(&id).await?.is_none() {
+        return Err(ApiError::not_found(&format!(
+            "Custom field '{}' not found",
+            &id
+        )));
+    }
+
+    service.delete_definition(&id).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
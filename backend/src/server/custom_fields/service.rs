@@ -0,0 +1,148 @@
+use anyhow::{Result, anyhow};
+use std::{collections::BTreeMap, sync::Arc};
+use uuid::Uuid;
+
+use crate::server::{
+    custom_fields::{
+        storage::CustomFieldDefinitionStorage,
+        types::{CustomFieldDefinition, CustomFieldType, normalize_tags},
+    },
+    entity_changes::types::EntityType,
+};
+
+pub struct CustomFieldService {
+    storage: Arc<dyn CustomFieldDefinitionStorage>,
+}
+
+impl CustomFieldService {
+    pub fn new(storage: Arc<dyn CustomFieldDefinitionStorage>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn create_definition(
+        &self,
+        definition: CustomFieldDefinition,
+    ) -> Result<CustomFieldDefinition> {
+        let definition = if definition.id == Uuid::nil() {
+            CustomFieldDefinition::new(definition.base)
+        } else {
+            definition
+        };
+
+        self.validate_definition(&definition).await?;
+        self.storage.create(&definition).await?;
+
+        tracing::info!(
+            "Created custom field {}: {}",
+            definition.base.name,
+            definition.id
+        );
+        Ok(definition)
+    }
+
+    pub async fn get_definition(&self, id: &Uuid) -> Result<Option<CustomFieldDefinition>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn get_all_definitions(
+        &self,
+        network_id: &Uuid,
+    ) -> Result<Vec<CustomFieldDefinition>> {
+        self.storage.get_all(network_id).await
+    }
+
+    /// Changing a field's type doesn't touch existing values. They're checked against the new
+    /// type next time the entity is saved.
+    pub async fn update_definition(
+        &self,
+        mut definition: CustomFieldDefinition,
+    ) -> Result<CustomFieldDefinition> {
+        self.validate_definition(&definition).await?;
+
+        definition.updated_at = chrono::Utc::now();
+        self.storage.update(&definition).await?;
+
+        tracing::info!(
+            "Updated custom field {}: {}",
+            definition.base.name,
+            definition.id
+        );
+        Ok(definition)
+    }
+
+    pub async fn delete_definition(&self, id: &Uuid) -> Result<()> {
+        self.storage.delete(id).await?;
+        tracing::info!("Deleted custom field {}", id);
+        Ok(())
+    }
+
+    /// Check the tags and custom field values of an entity, tidying up tags
+    pub async fn validate_entity_fields(
+        &self,
+        network_id: &Uuid,
+        entity_type: EntityType,
+        tags: &mut Vec<String>,
+        custom_fields: &BTreeMap<Uuid, String>,
+    ) -> Result<()> {
+        normalize_tags(tags)?;
+
+        if custom_fields.is_empty() {
+            return Ok(());
+        }
+
+        let definitions = self.storage.get_all(network_id).await?;
+        for (id, value) in custom_fields {
+            let definition = definitions
+                .iter()
+                .find(|d| &d.id == id)
+                .ok_or_else(|| anyhow!("Custom field '{}' doesn't exist in this network", id))?;
+
+            if !definition.base.entity_types.contains(&entity_type) {
+                return Err(anyhow!(
+                    "Custom field '{}' can't be set on a {}",
+                    definition.base.name,
+                    entity_type
+                ));
+            }
+            definition.validate_value(value)?;
+        }
+
+        Ok(())
+    }
+
+    async fn validate_definition(&self, definition: &CustomFieldDefinition) -> Result<()> {
+        let base = &definition.base;
+
+        if base.entity_types.is_empty() {
+            return Err(anyhow!(
+                "Custom field must apply to at least one entity type"
+            ));
+        }
+        if let Some(entity_type) = base.entity_types.iter().find(|t| {
+            !matches!(
+                t,
+                EntityType::Host | EntityType::Service | EntityType::Subnet
+            )
+        }) {
+            return Err(anyhow!("Custom fields can't be set on a {}", entity_type));
+        }
+        if base.field_type == CustomFieldType::Enum && base.options.is_empty() {
+            return Err(anyhow!("Enum custom fields need at least one option"));
+        }
+
+        if let Some(existing) = self
+            .storage
+            .get_all(&base.network_id)
+            .await?
+            .into_iter()
+            .find(|d| d.id != definition.id && d.base.name.eq_ignore_ascii_case(&base.name))
+        {
+            return Err(anyhow!(
+                "A custom field named '{}' already exists",
+                existing.base.name
+            ));
+        }
+
+        Ok(())
+    }
+}
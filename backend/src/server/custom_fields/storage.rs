@@ -0,0 +1,152 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::{
+    custom_fields::types::{CustomFieldDefinition, CustomFieldDefinitionBase, CustomFieldType},
+    entity_changes::types::EntityType,
+};
+
+#[async_trait]
+pub trait CustomFieldDefinitionStorage: Send + Sync {
+    async fn create(&self, definition: &CustomFieldDefinition) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<CustomFieldDefinition>>;
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<CustomFieldDefinition>>;
+    async fn update(&self, definition: &CustomFieldDefinition) -> Result<()>;
+    /// Delete a definition along with the values entities have for it
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresCustomFieldDefinitionStorage {
+    pool: PgPool,
+}
+
+impl PostgresCustomFieldDefinitionStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomFieldDefinitionStorage for PostgresCustomFieldDefinitionStorage {
+    async fn create(&self, definition: &CustomFieldDefinition) -> Result<()> {
+        let field_type_str: &'static str = definition.base.field_type.into();
+        let options_json = serde_json::to_value(&definition.base.options)?;
+        let entity_types_json = serde_json::to_value(&definition.base.entity_types)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO custom_field_definitions (
+                id, network_id, name, field_type, options, entity_types, description,
+                created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(definition.id)
+        .bind(definition.base.network_id)
+        .bind(&definition.base.name)
+        .bind(field_type_str)
+        .bind(options_json)
+        .bind(entity_types_json)
+        .bind(&definition.base.description)
+        .bind(definition.created_at)
+        .bind(definition.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<CustomFieldDefinition>> {
+        let row = sqlx::query("SELECT * FROM custom_field_definitions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(row_to_definition).transpose()
+    }
+
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<CustomFieldDefinition>> {
+        let rows = sqlx::query(
+            "SELECT * FROM custom_field_definitions WHERE network_id = $1 ORDER BY name",
+        )
+        .bind(network_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_definition).collect()
+    }
+
+    async fn update(&self, definition: &CustomFieldDefinition) -> Result<()> {
+        let field_type_str: &'static str = definition.base.field_type.into();
+        let options_json = serde_json::to_value(&definition.base.options)?;
+        let entity_types_json = serde_json::to_value(&definition.base.entity_types)?;
+
+        sqlx::query(
+            r#"
+            UPDATE custom_field_definitions SET
+                name = $2, field_type = $3, options = $4, entity_types = $5, description = $6,
+                updated_at = $7
+            WHERE id = $1
+            "#,
+        )
+        .bind(definition.id)
+        .bind(&definition.base.name)
+        .bind(field_type_str)
+        .bind(options_json)
+        .bind(entity_types_json)
+        .bind(&definition.base.description)
+        .bind(definition.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for table in ["hosts", "services", "subnets"] {
+            sqlx::query(&format!(
+                "UPDATE {table} SET custom_fields = custom_fields - $1 WHERE custom_fields ? $1"
+            ))
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM custom_field_definitions WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn row_to_definition(row: sqlx::postgres::PgRow) -> Result<CustomFieldDefinition, Error> {
+    let field_type: CustomFieldType =
+        serde_json::from_value(Value::String(row.get::<String, _>("field_type")))
+            .or(Err(Error::msg("Failed to deserialize field_type")))?;
+    let options: Vec<String> = serde_json::from_value(row.get::<Value, _>("options"))
+        .or(Err(Error::msg("Failed to deserialize options")))?;
+    let entity_types: Vec<EntityType> = serde_json::from_value(row.get::<Value, _>("entity_types"))
+        .or(Err(Error::msg("Failed to deserialize entity_types")))?;
+
+    Ok(CustomFieldDefinition {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: CustomFieldDefinitionBase {
+            network_id: row.get("network_id"),
+            name: row.get("name"),
+            field_type,
+            options,
+            entity_types,
+            description: row.get("description"),
+        },
+    })
+}
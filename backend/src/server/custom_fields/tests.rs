@@ -0,0 +1,247 @@
+use serial_test::serial;
+use std::collections::HashMap;
+
+use crate::{
+    server::{
+        custom_fields::types::{
+            CustomFieldDefinition, CustomFieldDefinitionBase, CustomFieldType, EntityFilter,
+        },
+        entity_changes::types::EntityType,
+        hosts::types::{api::HostQuery, base::Host},
+        services::types::bindings::Binding,
+        topology::types::{
+            api::{TagColor, TopologyRequestOptions},
+            nodes::NodeType,
+        },
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_custom_fields_and_tags() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let definition = |name: &str, field_type, entity_types: Vec<EntityType>| {
+        CustomFieldDefinition::new(CustomFieldDefinitionBase {
+            network_id: network.id,
+            name: name.to_string(),
+            field_type,
+            options: vec![],
+            entity_types,
+            description: None,
+        })
+    };
+    let custom_fields = &services.custom_field_service;
+
+    let owner = custom_fields
+        .create_definition(definition(
+            "Owner",
+            CustomFieldType::Text,
+            vec![EntityType::Host, EntityType::Service],
+        ))
+        .await
+        .unwrap();
+    let warranty = custom_fields
+        .create_definition(definition(
+            "Warranty",
+            CustomFieldType::Date,
+            vec![EntityType::Host],
+        ))
+        .await
+        .unwrap();
+    let mut criticality = definition("Criticality", CustomFieldType::Enum, vec![EntityType::Host]);
+
+    // Enums need options, names are unique and groups can't have fields
+    assert!(
+        custom_fields
+            .create_definition(criticality.clone())
+            .await
+            .is_err()
+    );
+    criticality.base.options = vec!["Low".to_string(), "High".to_string()];
+    let criticality = custom_fields.create_definition(criticality).await.unwrap();
+    assert!(
+        custom_fields
+            .create_definition(definition(
+                "owner",
+                CustomFieldType::Url,
+                vec![EntityType::Host]
+            ))
+            .await
+            .is_err()
+    );
+    assert!(
+        custom_fields
+            .create_definition(definition(
+                "Rack",
+                CustomFieldType::Text,
+                vec![EntityType::Group]
+            ))
+            .await
+            .is_err()
+    );
+
+    let mut tags = vec![" rack-2 ".to_string(), "rack-2".to_string(), String::new()];
+    let mut values = [
+        (owner.id, "Networking team".to_string()),
+        (warranty.id, "2027-03-31".to_string()),
+        (criticality.id, "High".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    custom_fields
+        .validate_entity_fields(&network.id, EntityType::Host, &mut tags, &values)
+        .await
+        .unwrap();
+    assert_eq!(tags, vec!["rack-2".to_string()]);
+
+    // Values must parse as the field's type and the field must apply to the entity
+    for (id, bad_value) in [(warranty.id, "next year"), (criticality.id, "Medium")] {
+        let mut bad_values = values.clone();
+        bad_values.insert(id, bad_value.to_string());
+        assert!(
+            custom_fields
+                .validate_entity_fields(&network.id, EntityType::Host, &mut vec![], &bad_values)
+                .await
+                .is_err()
+        );
+    }
+    assert!(
+        custom_fields
+            .validate_entity_fields(&network.id, EntityType::Service, &mut vec![], &values)
+            .await
+            .is_err()
+    );
+
+    let subnet = services
+        .subnet_service
+        .create_subnet(subnet(&network.id))
+        .await
+        .unwrap();
+
+    // Interfaces only show up in the topology with a service bound to them
+    let bound_service = |host: &Host| {
+        let mut svc = service(&network.id, &host.id);
+        svc.base.bindings = vec![Binding::new_port(
+            host.base.ports[0].id,
+            Some(host.base.interfaces[0].id),
+        )];
+        svc
+    };
+
+    let mut tagged = host(&network.id);
+    tagged.base.interfaces = vec![interface(&subnet.id)];
+    tagged.base.tags = tags.clone();
+    tagged.base.custom_fields = values.clone();
+    let tagged_service = bound_service(&tagged);
+    let (tagged, _) = services
+        .host_service
+        .create_host_with_services(tagged, vec![tagged_service])
+        .await
+        .unwrap();
+    let mut untagged = host(&network.id);
+    untagged.base.interfaces = vec![interface(&subnet.id)];
+    untagged.base.interfaces[0].base.ip_address = "192.168.1.101".parse().unwrap();
+    untagged.base.interfaces[0].base.mac_address = None;
+    let untagged_service = bound_service(&untagged);
+    let (untagged, _) = services
+        .host_service
+        .create_host_with_services(untagged, vec![untagged_service])
+        .await
+        .unwrap();
+
+    let params: HashMap<String, String> = [
+        ("tags".to_string(), "rack-2".to_string()),
+        (format!("field.{}", criticality.id), "High".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    let hosts = services
+        .host_service
+        .query_hosts(&HostQuery::from_params(network.id, &params).unwrap())
+        .await
+        .unwrap();
+    let matching: Vec<_> = hosts.items.iter().map(|h| h.id).collect();
+    assert_eq!(matching, vec![tagged.id]);
+
+    let mut tagged_subnet = crate::tests::subnet(&network.id);
+    tagged_subnet.base.cidr = "10.0.0.0/24".parse().unwrap();
+    tagged_subnet.base.tags = vec!["rack-2".to_string()];
+    let tagged_subnet = services
+        .subnet_service
+        .create_subnet(tagged_subnet)
+        .await
+        .unwrap();
+    let filter = EntityFilter::from_params(
+        &[("tags".to_string(), "rack-2".to_string())]
+            .into_iter()
+            .collect(),
+    )
+    .unwrap();
+    let subnets = services
+        .subnet_service
+        .get_filtered_subnets(&network.id, &filter)
+        .await
+        .unwrap();
+    assert_eq!(
+        subnets.iter().map(|s| s.id).collect::<Vec<_>>(),
+        vec![tagged_subnet.id]
+    );
+
+    // Tags can hide or colour nodes in the topology
+    let graph = services
+        .topology_service
+        .build_graph(TopologyRequestOptions {
+            network_ids: vec![network.id],
+            tag_colors: vec![TagColor {
+                tag: "rack-2".to_string(),
+                color: "orange".to_string(),
+            }],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let tagged_node = graph
+        .node_weights()
+        .find(|n| matches!(n.node_type, NodeType::InterfaceNode { host_id, .. } if host_id == tagged.id))
+        .unwrap();
+    assert_eq!(tagged_node.color.as_deref(), Some("orange"));
+
+    let graph = services
+        .topology_service
+        .build_graph(TopologyRequestOptions {
+            network_ids: vec![network.id],
+            hide_tags: vec!["rack-2".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let host_ids: Vec<_> = graph
+        .node_weights()
+        .filter_map(|n| match n.node_type {
+            NodeType::InterfaceNode { host_id, .. } => Some(host_id),
+            _ => None,
+        })
+        .collect();
+    assert!(!host_ids.contains(&tagged.id));
+    assert!(host_ids.contains(&untagged.id));
+
+    // Deleting a definition removes its values
+    custom_fields.delete_definition(&owner.id).await.unwrap();
+    let tagged = services
+        .host_service
+        .get_host(&tagged.id)
+        .await
+        .unwrap()
+        .unwrap();
+    values.remove(&owner.id);
+    assert_eq!(tagged.base.custom_fields, values);
+}
@@ -0,0 +1,150 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum_macros::{Display, IntoStaticStr};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::server::{
+    entity_changes::types::EntityType, shared::types::api::deserialize_empty_string_as_none,
+};
+
+/// Longest tag which can be put on an entity
+pub const MAX_TAG_LENGTH: usize = 50;
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    /// YYYY-MM-DD
+    Date,
+    /// One of the definition's options
+    Enum,
+    Url,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CustomFieldDefinitionBase {
+    pub network_id: Uuid,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub field_type: CustomFieldType,
+    /// Allowed values of Enum fields
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Entities the field can be set on
+    pub entity_types: Vec<EntityType>,
+    #[serde(default, deserialize_with = "deserialize_empty_string_as_none")]
+    #[validate(length(min = 0, max = 500))]
+    pub description: Option<String>,
+}
+
+/// A field which can be filled in on hosts, services or subnets of a network, ie asset tag,
+/// owner or warranty date. Values are stored on the entities keyed by the definition's id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomFieldDefinition {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: CustomFieldDefinitionBase,
+}
+
+impl CustomFieldDefinition {
+    pub fn new(base: CustomFieldDefinitionBase) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+
+    /// Values are stored as strings, so check they parse as the field's type
+    pub fn validate_value(&self, value: &str) -> Result<()> {
+        let valid = match self.base.field_type {
+            CustomFieldType::Text => value.chars().count() <= 500,
+            CustomFieldType::Number => value.parse::<f64>().is_ok_and(|n| n.is_finite()),
+            CustomFieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            CustomFieldType::Enum => self.base.options.iter().any(|o| o == value),
+            CustomFieldType::Url => url::Url::parse(value).is_ok(),
+        };
+
+        if !valid {
+            return Err(anyhow!(
+                "'{}' is not a valid {} value for field '{}'",
+                value,
+                self.base.field_type,
+                self.base.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Trim tags, dropping empty and repeated ones
+pub fn normalize_tags(tags: &mut Vec<String>) -> Result<()> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter() {
+        let tag = tag.trim();
+        if tag.is_empty() || normalized.iter().any(|t| t == tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(anyhow!(
+                "Tag '{}' is longer than {} characters",
+                tag,
+                MAX_TAG_LENGTH
+            ));
+        }
+        normalized.push(tag.to_string());
+    }
+    *tags = normalized;
+    Ok(())
+}
+
+/// Filter on tags and custom field values for list endpoints. Taken from the `tags` query
+/// parameter, a comma separated list of tags which must all be present, and `field.<id>`
+/// parameters which must equal the entity's value for that field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityFilter {
+    pub tags: Vec<String>,
+    pub custom_fields: Vec<(Uuid, String)>,
+}
+
+impl EntityFilter {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self> {
+        let tags = params
+            .get("tags")
+            .map(|tags| {
+                tags.split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let custom_fields = params
+            .iter()
+            .filter_map(|(key, value)| key.strip_prefix("field.").map(|id| (id, value)))
+            .map(|(id, value)| {
+                Uuid::parse_str(id)
+                    .map(|id| (id, value.clone()))
+                    .map_err(|_| anyhow!("Invalid custom field id '{}'", id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            tags,
+            custom_fields,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.custom_fields.is_empty()
+    }
+}
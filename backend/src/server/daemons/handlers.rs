@@ -24,6 +24,26 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .route("/:id", get(get_daemon))
 }
 
+/// The registered daemon making a request, from its `daemon_id` query parameter. The API has no
+/// authentication otherwise, so this only keeps endpoints that are just for daemons from
+/// answering callers that don't know a daemon's id.
+pub async fn require_daemon(
+    state: &AppState,
+    params: &HashMap<String, String>,
+) -> ApiResult<Daemon> {
+    let daemon_id = params
+        .get("daemon_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::unauthorized("daemon_id query parameter required"))?;
+
+    state
+        .services
+        .daemon_service
+        .get_daemon(&daemon_id)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Unknown daemon"))
+}
+
 /// Register a new daemon
 async fn register_daemon(
     State(state): State<Arc<AppState>>,
@@ -1,3 +1,4 @@
+use crate::daemon::discovery::types::base::DiscoveryPhase;
 use crate::server::{
     config::AppState,
     daemons::types::{
@@ -6,6 +7,7 @@ use crate::server::{
     },
     discovery::types::{api::InitiateDiscoveryRequest, base::DiscoveryType},
     shared::types::api::{ApiError, ApiResponse, ApiResult},
+    snapshots::types::{NetworkSnapshotBase, SnapshotTrigger},
 };
 use axum::{
     Router,
@@ -16,6 +18,7 @@ use axum::{
     },
     routing::{get, post},
 };
+use chrono::Utc;
 use futures::Stream;
 use std::{convert::Infallible, sync::Arc};
 use tokio::sync::broadcast;
@@ -35,7 +38,58 @@ async fn receive_discovery_update(
     State(state): State<Arc<AppState>>,
     Json(update): Json<DiscoveryUpdatePayload>,
 ) -> ApiResult<Json<ApiResponse<()>>> {
-    state.discovery_manager.update_session(update).await?;
+    let is_complete = matches!(update.phase, DiscoveryPhase::Complete);
+    let session_id = update.session_id;
+    let daemon_id = state.discovery_manager.update_session(update).await?;
+
+    // Snapshot the network, look for hosts discovered more than once, and match newly discovered
+    // service versions against the vulnerability feed
+    if is_complete
+        && let Some(daemon) = state.services.daemon_service.get_daemon(&daemon_id).await?
+    {
+        let network_id = daemon.base.network_id;
+        let snapshot_service = state.services.snapshot_service.clone();
+        tokio::spawn(async move {
+            let snapshot = NetworkSnapshotBase {
+                network_id,
+                name: format!("Discovery {}", Utc::now().format("%Y-%m-%d %H:%M")),
+                trigger: SnapshotTrigger::Discovery { session_id },
+            };
+            if let Err(e) = snapshot_service.create_snapshot(snapshot).await {
+                tracing::error!(
+                    "Failed to snapshot network {} after discovery session {}: {}",
+                    network_id,
+                    session_id,
+                    e
+                );
+            }
+        });
+
+        let merge_suggestion_service = state.services.merge_suggestion_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = merge_suggestion_service.analyse_network(&network_id).await {
+                tracing::error!(
+                    "Failed to analyse network {} for duplicate hosts: {}",
+                    network_id,
+                    e
+                );
+            }
+        });
+
+        let vulnerability_service = state.services.vulnerability_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = vulnerability_service
+                .match_network(&daemon.base.network_id)
+                .await
+            {
+                tracing::error!(
+                    "Failed to match vulnerabilities for network {}: {}",
+                    daemon.base.network_id,
+                    e
+                );
+            }
+        });
+    }
 
     Ok(Json(ApiResponse::success(())))
 }
@@ -73,7 +127,7 @@ async fn user_initiate_discovery(
         .send_discovery_request(
             &daemon,
             DaemonDiscoveryRequest {
-                discovery_type: DiscoveryType::Network,
+                discovery_type: request.discovery_type.unwrap_or(DiscoveryType::Network),
                 session_id,
             },
         )
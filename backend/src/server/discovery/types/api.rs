@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::server::discovery::types::base::DiscoveryType;
+
 // Request from frontend to server
 #[derive(Debug, Serialize, Clone, Copy, Deserialize)]
 pub struct InitiateDiscoveryRequest {
     pub daemon_id: Uuid,
+    // Defaults to a network scan
+    #[serde(default)]
+    pub discovery_type: Option<DiscoveryType>,
 }
 
 // Response from server to frontend
@@ -59,4 +59,6 @@ pub enum DiscoveryType {
     Network,
     Docker { host_id: Uuid },
     Proxmox { host_id: Uuid },
+    Libvirt { host_id: Uuid },
+    Kubernetes,
 }
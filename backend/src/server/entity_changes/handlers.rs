@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    config::AppState,
+    entity_changes::types::{
+        ChangeActor, ChangeContext, DAEMON_ID_HEADER, DISCOVERY_SESSION_HEADER, USER_ID_HEADER,
+    },
+};
+
+/// Middleware which attributes changes made while handling a request to the daemon or user
+/// identified by its headers
+pub async fn change_context(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let context = context_from_headers(&state, request.headers()).await;
+    context.scope(next.run(request)).await
+}
+
+/// Headers are only claims, so they're checked against the server's state before being trusted.
+/// A daemon is credited only with the discovery session the server started on it, as session ids
+/// are only shared with the daemon running the session, and a user has to exist. Changes with
+/// claims that don't check out are attributed to the system.
+async fn context_from_headers(state: &AppState, headers: &HeaderMap) -> ChangeContext {
+    let header_uuid = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| Uuid::parse_str(v).ok())
+    };
+
+    if let (Some(daemon_id), Some(session_id)) = (
+        header_uuid(DAEMON_ID_HEADER),
+        header_uuid(DISCOVERY_SESSION_HEADER),
+    ) {
+        match state.discovery_manager.get_session(&session_id).await {
+            Some(session) if session.daemon_id == daemon_id => {
+                return ChangeContext {
+                    actor: ChangeActor::Daemon { daemon_id },
+                    discovery_session_id: Some(session_id),
+                };
+            }
+            _ => tracing::warn!(
+                "Daemon {} is not running discovery session {}, attributing changes to the system",
+                daemon_id,
+                session_id
+            ),
+        }
+    } else if let Some(user_id) = header_uuid(USER_ID_HEADER) {
+        match state.services.user_service.get_user(&user_id).await {
+            Ok(Some(_)) => {
+                return ChangeContext {
+                    actor: ChangeActor::User { user_id },
+                    discovery_session_id: None,
+                };
+            }
+            _ => tracing::warn!(
+                "Unknown user {}, attributing changes to the system",
+                user_id
+            ),
+        }
+    }
+
+    ChangeContext::default()
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::entity_changes::{
+    storage::EntityChangeStorage,
+    types::{ChangeAction, ChangeContext, ChangeTracked, EntityChange, EntityType, diff_values},
+};
+
+pub struct EntityChangeService {
+    storage: Arc<dyn EntityChangeStorage>,
+}
+
+impl EntityChangeService {
+    pub fn new(storage: Arc<dyn EntityChangeStorage>) -> Self {
+        Self { storage }
+    }
+
+    /// Change to an entity's history, attributed to the current request's actor. Passed to the
+    /// entity's storage to be written with the entity. Updates and upserts which didn't change
+    /// any fields aren't recorded.
+    pub fn change<T: ChangeTracked>(
+        &self,
+        action: ChangeAction,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Result<Option<EntityChange>> {
+        let Some(entity) = after.or(before) else {
+            return Ok(None);
+        };
+
+        let changes = diff_values(
+            before.map(T::stored_value).transpose()?.as_ref(),
+            after.map(T::stored_value).transpose()?.as_ref(),
+        );
+
+        if changes.is_empty() && matches!(action, ChangeAction::Update | ChangeAction::Upsert) {
+            return Ok(None);
+        }
+
+        let ChangeContext {
+            actor,
+            discovery_session_id,
+        } = ChangeContext::current();
+
+        let change = EntityChange {
+            id: Uuid::new_v4(),
+            entity_type: T::entity_type(),
+            entity_id: entity.entity_id(),
+            network_id: entity.network_id(),
+            action,
+            actor,
+            discovery_session_id,
+            changes,
+            changed_at: chrono::Utc::now(),
+        };
+
+        tracing::debug!(
+            "Recording {} of {} {}: {} field changes",
+            change.action,
+            change.entity_type,
+            change.entity_id,
+            change.changes.len()
+        );
+        Ok(Some(change))
+    }
+
+    pub async fn get_history(
+        &self,
+        entity_type: EntityType,
+        entity_id: &Uuid,
+    ) -> Result<Vec<EntityChange>> {
+        self.storage.get_for_entity(entity_type, entity_id).await
+    }
+}
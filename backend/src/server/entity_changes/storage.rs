@@ -0,0 +1,112 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::entity_changes::types::{
+    ChangeAction, ChangeActor, EntityChange, EntityType, FieldChange,
+};
+
+#[async_trait]
+pub trait EntityChangeStorage: Send + Sync {
+    /// Changes to an entity, oldest first
+    async fn get_for_entity(
+        &self,
+        entity_type: EntityType,
+        entity_id: &Uuid,
+    ) -> Result<Vec<EntityChange>>;
+}
+
+pub struct PostgresEntityChangeStorage {
+    pool: PgPool,
+}
+
+impl PostgresEntityChangeStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EntityChangeStorage for PostgresEntityChangeStorage {
+    async fn get_for_entity(
+        &self,
+        entity_type: EntityType,
+        entity_id: &Uuid,
+    ) -> Result<Vec<EntityChange>> {
+        let entity_type_str: &'static str = entity_type.into();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM entity_changes
+            WHERE entity_type = $1 AND entity_id = $2
+            ORDER BY changed_at
+            "#,
+        )
+        .bind(entity_type_str)
+        .bind(entity_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_entity_change).collect()
+    }
+}
+
+/// Append a change to an entity's history, if there is one. Called by entity storages with the
+/// transaction writing the entity, so history is only recorded for writes that commit.
+pub async fn append_change(conn: &mut PgConnection, change: Option<&EntityChange>) -> Result<()> {
+    let Some(change) = change else {
+        return Ok(());
+    };
+
+    let entity_type_str: &'static str = change.entity_type.into();
+    let action_str: &'static str = change.action.into();
+    let actor_json = serde_json::to_value(&change.actor)?;
+    let changes_json = serde_json::to_value(&change.changes)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO entity_changes (
+            id, entity_type, entity_id, network_id, action, actor,
+            discovery_session_id, changes, changed_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(change.id)
+    .bind(entity_type_str)
+    .bind(change.entity_id)
+    .bind(change.network_id)
+    .bind(action_str)
+    .bind(actor_json)
+    .bind(change.discovery_session_id)
+    .bind(changes_json)
+    .bind(change.changed_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+fn row_to_entity_change(row: sqlx::postgres::PgRow) -> Result<EntityChange, Error> {
+    let entity_type: EntityType = serde_json::from_value(Value::String(row.get("entity_type")))
+        .or(Err(Error::msg("Failed to deserialize entity_type")))?;
+    let action: ChangeAction = serde_json::from_value(Value::String(row.get("action")))
+        .or(Err(Error::msg("Failed to deserialize action")))?;
+    let actor: ChangeActor = serde_json::from_value(row.get::<Value, _>("actor"))
+        .or(Err(Error::msg("Failed to deserialize actor")))?;
+    let changes: Vec<FieldChange> = serde_json::from_value(row.get::<Value, _>("changes"))
+        .or(Err(Error::msg("Failed to deserialize changes")))?;
+
+    Ok(EntityChange {
+        id: row.get("id"),
+        entity_type,
+        entity_id: row.get("entity_id"),
+        network_id: row.get("network_id"),
+        action,
+        actor,
+        discovery_session_id: row.get("discovery_session_id"),
+        changes,
+        changed_at: row.get("changed_at"),
+    })
+}
@@ -0,0 +1,193 @@
+use axum::{Router, body::Body, http::Request, middleware, routing::get};
+use serial_test::serial;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::{
+    server::{
+        config::{AppState, ServerConfig},
+        discovery::manager::DiscoverySessionManager,
+        entity_changes::{
+            handlers::change_context,
+            types::{
+                ChangeAction, ChangeActor, ChangeContext, DAEMON_ID_HEADER,
+                DISCOVERY_SESSION_HEADER, EntityType, USER_ID_HEADER,
+            },
+        },
+        hosts::types::ports::{Port, PortBase},
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_entity_change_history() {
+    let (_storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let context = ChangeContext {
+        actor: ChangeActor::User { user_id: user.id },
+        discovery_session_id: None,
+    };
+
+    let port = Port::new(PortBase::new_tcp(7420));
+    let host_id = context
+        .scope(async {
+            let (created, _) = services
+                .host_service
+                .create_host_with_services(host(&network.id), vec![])
+                .await
+                .unwrap();
+
+            let mut updated = created.clone();
+            updated.base.ports.push(port);
+            services.host_service.update_host(updated).await.unwrap();
+
+            // Updating without changes doesn't add an entry
+            let unchanged = services
+                .host_service
+                .get_host(&created.id)
+                .await
+                .unwrap()
+                .unwrap();
+            services.host_service.update_host(unchanged).await.unwrap();
+
+            created.id
+        })
+        .await;
+
+    let history = services
+        .entity_change_service
+        .get_history(EntityType::Host, &host_id)
+        .await
+        .unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].action, ChangeAction::Create);
+    assert_eq!(history[0].network_id, network.id);
+    assert_eq!(history[1].action, ChangeAction::Update);
+    assert!(
+        history
+            .iter()
+            .all(|c| c.actor == ChangeActor::User { user_id: user.id })
+    );
+
+    // Ports are keyed by id rather than position
+    let port_change = history[1]
+        .changes
+        .iter()
+        .find(|c| c.path == format!("ports[{}]", port.id))
+        .expect("port addition should be recorded");
+    assert!(port_change.before.is_none());
+    assert!(port_change.after.is_some());
+
+    // Changes outside a request context are attributed to the system
+    services
+        .host_service
+        .delete_host(&host_id, true)
+        .await
+        .unwrap();
+
+    let history = services
+        .entity_change_service
+        .get_history(EntityType::Host, &host_id)
+        .await
+        .unwrap();
+    let deletion = history.last().unwrap();
+    assert_eq!(deletion.action, ChangeAction::Delete);
+    assert_eq!(deletion.actor, ChangeActor::System);
+    assert!(deletion.changes.iter().all(|c| c.after.is_none()));
+
+    let unknown = services
+        .entity_change_service
+        .get_history(EntityType::Host, &Uuid::new_v4())
+        .await
+        .unwrap();
+    assert!(unknown.is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_change_context_verifies_headers() {
+    let (storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let daemon_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let discovery_manager = DiscoverySessionManager::new();
+    discovery_manager
+        .create_session(session_id, daemon_id)
+        .await
+        .unwrap();
+
+    let state = Arc::new(AppState {
+        config: ServerConfig::default(),
+        storage,
+        services,
+        discovery_manager,
+    });
+
+    // Responds with the actor changes would be attributed to
+    let app = Router::new()
+        .route(
+            "/",
+            get(|| async { format!("{:?}", ChangeContext::current().actor) }),
+        )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            change_context,
+        ))
+        .with_state(state);
+
+    let actor = |headers: Vec<(&'static str, Uuid)>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::builder().uri("/");
+            for (name, value) in headers {
+                request = request.header(name, value.to_string());
+            }
+            let response = app
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+
+    assert_eq!(
+        actor(vec![
+            (DAEMON_ID_HEADER, daemon_id),
+            (DISCOVERY_SESSION_HEADER, session_id)
+        ])
+        .await,
+        format!("{:?}", ChangeActor::Daemon { daemon_id })
+    );
+    assert_eq!(
+        actor(vec![(USER_ID_HEADER, user.id)]).await,
+        format!("{:?}", ChangeActor::User { user_id: user.id })
+    );
+
+    // Claims the server can't back up are attributed to the system
+    let system = format!("{:?}", ChangeActor::System);
+    assert_eq!(actor(vec![(DAEMON_ID_HEADER, daemon_id)]).await, system);
+    assert_eq!(
+        actor(vec![
+            (DAEMON_ID_HEADER, Uuid::new_v4()),
+            (DISCOVERY_SESSION_HEADER, session_id)
+        ])
+        .await,
+        system
+    );
+    assert_eq!(actor(vec![(USER_ID_HEADER, Uuid::new_v4())]).await, system);
+}
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use strum_macros::{Display, IntoStaticStr};
+use uuid::Uuid;
+
+use crate::server::{
+    groups::types::Group, hosts::types::base::Host, services::types::base::Service,
+    subnets::types::base::Subnet,
+};
+
+/// Sent by the UI to identify the user making a change
+pub const USER_ID_HEADER: &str = "x-netvisor-user-id";
+/// Sent by daemons to identify themselves when reporting discovered entities
+pub const DAEMON_ID_HEADER: &str = "x-netvisor-daemon-id";
+/// Sent by daemons alongside DAEMON_ID_HEADER while a discovery session is running
+pub const DISCOVERY_SESSION_HEADER: &str = "x-netvisor-discovery-session-id";
+
+/// Fields which change on every write, so aren't worth recording
+const IGNORED_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+pub enum EntityType {
+    Host,
+    Service,
+    Subnet,
+    Group,
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+pub enum ChangeAction {
+    Create,
+    Update,
+    /// Discovery data merged into an existing entity
+    Upsert,
+    /// Another entity merged into this one, or this one merged into another and removed
+    Consolidate,
+    /// Interfaces, ports or services moved out of this entity into another, or into this one
+    Split,
+    Delete,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ChangeActor {
+    User {
+        user_id: Uuid,
+    },
+    Daemon {
+        daemon_id: Uuid,
+    },
+    /// Changes made by the server itself, ie reclassification or seeding
+    #[default]
+    System,
+}
+
+/// Who is making changes in the current request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeContext {
+    pub actor: ChangeActor,
+    pub discovery_session_id: Option<Uuid>,
+}
+
+tokio::task_local! {
+    static CHANGE_CONTEXT: ChangeContext;
+}
+
+impl ChangeContext {
+    /// Context of the request being handled, or System outside of a request
+    pub fn current() -> Self {
+        CHANGE_CONTEXT
+            .try_with(|context| context.clone())
+            .unwrap_or_default()
+    }
+
+    /// Run a future with this as the current context
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CHANGE_CONTEXT.scope(self, future).await
+    }
+}
+
+/// A field which differs between two versions of an entity. Paths address nested fields with
+/// ".", and elements of lists of entities with their id, ie "ports[<id>]" or
+/// "interfaces[<id>].mac_address". Elements of other lists are recorded as added or removed at the
+/// list's path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityChange {
+    pub id: Uuid,
+    pub entity_type: EntityType,
+    pub entity_id: Uuid,
+    pub network_id: Uuid,
+    pub action: ChangeAction,
+    pub actor: ChangeActor,
+    pub discovery_session_id: Option<Uuid>,
+    pub changes: Vec<FieldChange>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Entities whose changes are recorded
+pub trait ChangeTracked: Serialize + DeserializeOwned {
+    fn entity_type() -> EntityType;
+    fn entity_id(&self) -> Uuid;
+    fn network_id(&self) -> Uuid;
+
+    /// The entity as it will read back from storage, ie with custom ports matching a predefined
+    /// port deserialized as the predefined one, so changes aren't recorded for equivalent values
+    fn stored_value(&self) -> serde_json::Result<Value> {
+        let stored: Self = serde_json::from_value(serde_json::to_value(self)?)?;
+        serde_json::to_value(stored)
+    }
+}
+
+impl ChangeTracked for Host {
+    fn entity_type() -> EntityType {
+        EntityType::Host
+    }
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+    fn network_id(&self) -> Uuid {
+        self.base.network_id
+    }
+}
+
+impl ChangeTracked for Service {
+    fn entity_type() -> EntityType {
+        EntityType::Service
+    }
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+    fn network_id(&self) -> Uuid {
+        self.base.network_id
+    }
+}
+
+impl ChangeTracked for Subnet {
+    fn entity_type() -> EntityType {
+        EntityType::Subnet
+    }
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+    fn network_id(&self) -> Uuid {
+        self.base.network_id
+    }
+}
+
+impl ChangeTracked for Group {
+    fn entity_type() -> EntityType {
+        EntityType::Group
+    }
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+    fn network_id(&self) -> Uuid {
+        self.base.network_id
+    }
+}
+
+/// Field-level differences between two serialized versions of an entity. A missing version is
+/// treated as having no fields, so creates and deletes record every top-level field.
+pub fn diff_values(before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    let empty = Value::Object(Map::new());
+    let mut changes = Vec::new();
+    diff_into(
+        "",
+        Some(before.unwrap_or(&empty)),
+        Some(after.unwrap_or(&empty)),
+        &mut changes,
+    );
+    changes
+}
+
+fn diff_into(
+    path: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    out: &mut Vec<FieldChange>,
+) {
+    // Null and missing fields are equivalent, and null isn't preserved once a change is stored
+    let before = before.filter(|v| !v.is_null());
+    let after = after.filter(|v| !v.is_null());
+
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                if path.is_empty() && IGNORED_FIELDS.contains(&key.as_str()) {
+                    continue;
+                }
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_into(&field_path, before.get(key), after.get(key), out);
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after)))
+            if before
+                .iter()
+                .chain(after.iter())
+                .all(|v| element_id(v).is_some()) =>
+        {
+            for element in before {
+                let id = element_id(element);
+                let matching = after.iter().find(|a| element_id(a) == id);
+                let element_path = format!("{}[{}]", path, id.unwrap_or_default());
+                diff_into(&element_path, Some(element), matching, out);
+            }
+            for element in after {
+                let id = element_id(element);
+                if !before.iter().any(|b| element_id(b) == id) {
+                    let element_path = format!("{}[{}]", path, id.unwrap_or_default());
+                    diff_into(&element_path, None, Some(element), out);
+                }
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) => {
+            for removed in before.iter().filter(|b| !after.contains(b)) {
+                out.push(FieldChange {
+                    path: path.to_string(),
+                    before: Some(removed.clone()),
+                    after: None,
+                });
+            }
+            for added in after.iter().filter(|a| !before.contains(a)) {
+                out.push(FieldChange {
+                    path: path.to_string(),
+                    before: None,
+                    after: Some(added.clone()),
+                });
+            }
+        }
+        (before, after) if before != after => out.push(FieldChange {
+            path: path.to_string(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn element_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_values() {
+        let port_22 = json!({ "id": "a", "number": 22, "protocol": "Tcp" });
+        let port_3389 = json!({ "id": "b", "number": 3389, "protocol": "Tcp" });
+
+        let before = json!({
+            "name": "box",
+            "updated_at": "2025-01-01",
+            "ports": [port_22],
+            "services": ["s1"],
+            "interfaces": [{ "id": "i", "mac_address": null }]
+        });
+        let after = json!({
+            "name": "box",
+            "updated_at": "2025-01-02",
+            "ports": [port_22, port_3389],
+            "services": ["s2"],
+            "interfaces": [{ "id": "i", "mac_address": "AA:BB:CC:DD:EE:FF" }]
+        });
+
+        let changes = diff_values(Some(&before), Some(&after));
+
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    path: "interfaces[i].mac_address".to_string(),
+                    before: None,
+                    after: Some(json!("AA:BB:CC:DD:EE:FF")),
+                },
+                FieldChange {
+                    path: "ports[b]".to_string(),
+                    before: None,
+                    after: Some(port_3389),
+                },
+                FieldChange {
+                    path: "services".to_string(),
+                    before: Some(json!("s1")),
+                    after: None,
+                },
+                FieldChange {
+                    path: "services".to_string(),
+                    before: None,
+                    after: Some(json!("s2")),
+                },
+            ]
+        );
+
+        // Creates record each top-level field
+        let created = diff_values(None, Some(&after));
+        assert_eq!(created.len(), 4);
+        assert!(created.iter().all(|c| c.before.is_none()));
+    }
+}
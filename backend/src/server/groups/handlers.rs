@@ -1,5 +1,6 @@
 use crate::server::{
     config::AppState,
+    entity_changes::types::{EntityChange, EntityType},
     groups::types::Group,
     shared::types::api::{ApiError, ApiResponse, ApiResult},
 };
@@ -18,6 +19,7 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .route("/", get(get_all_groups))
         .route("/:id", put(update_group))
         .route("/:id", delete(delete_group))
+        .route("/:id/history", get(get_group_history))
 }
 
 async fn create_group(
@@ -74,3 +76,16 @@ async fn delete_group(
     service.delete_group(&id).await?;
     Ok(Json(ApiResponse::success(())))
 }
+
+async fn get_group_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<EntityChange>>>> {
+    let history = state
+        .services
+        .entity_change_service
+        .get_history(EntityType::Group, &id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(history)))
+}
@@ -1,25 +1,40 @@
-use crate::server::groups::{storage::GroupStorage, types::Group};
+use crate::server::{
+    entity_changes::{service::EntityChangeService, types::ChangeAction},
+    groups::{storage::GroupStorage, types::Group},
+};
 use anyhow::Result;
 use std::sync::Arc;
 use uuid::Uuid;
 
 pub struct GroupService {
     group_storage: Arc<dyn GroupStorage>,
+    entity_change_service: Arc<EntityChangeService>,
 }
 
 impl GroupService {
-    pub fn new(group_storage: Arc<dyn GroupStorage>) -> Self {
-        Self { group_storage }
+    pub fn new(
+        group_storage: Arc<dyn GroupStorage>,
+        entity_change_service: Arc<EntityChangeService>,
+    ) -> Self {
+        Self {
+            group_storage,
+            entity_change_service,
+        }
     }
 
     /// Create a new group
     pub async fn create_group(&self, group: Group) -> Result<Group> {
-        let created_group = if group.id == Uuid::nil() {
-            self.group_storage.create(&Group::new(group.base)).await?
+        let group = if group.id == Uuid::nil() {
+            Group::new(group.base)
         } else {
-            self.group_storage.create(&group).await?
+            group
         };
 
+        let change = self
+            .entity_change_service
+            .change(ChangeAction::Create, None, Some(&group))?;
+        let created_group = self.group_storage.create(&group, change.as_ref()).await?;
+
         tracing::info!(
             "Created group {}: {}",
             created_group.base.name,
@@ -40,10 +55,17 @@ impl GroupService {
 
     /// Update group
     pub async fn update_group(&self, mut group: Group) -> Result<Group> {
+        let current_group = self.get_group(&group.id).await?;
+
         let now = chrono::Utc::now();
         group.updated_at = now;
 
-        self.group_storage.update(&group).await?;
+        let change = self.entity_change_service.change(
+            ChangeAction::Update,
+            current_group.as_ref(),
+            Some(&group),
+        )?;
+        self.group_storage.update(&group, change.as_ref()).await?;
 
         tracing::info!("Updated group {}: {}", group.base.name, group.id);
         Ok(group)
@@ -57,7 +79,10 @@ impl GroupService {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Group not found"))?;
 
-        self.group_storage.delete(id).await?;
+        let change = self
+            .entity_change_service
+            .change(ChangeAction::Delete, Some(&group), None)?;
+        self.group_storage.delete(id, change.as_ref()).await?;
         tracing::info!("Deleted group {}: {}", group.base.name, group.id);
         Ok(())
     }
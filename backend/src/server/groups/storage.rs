@@ -1,5 +1,6 @@
 use crate::server::{
     discovery::types::base::EntitySource,
+    entity_changes::{storage::append_change, types::EntityChange},
     groups::types::{Group, GroupBase, GroupType},
 };
 use anyhow::{Error, Result};
@@ -9,11 +10,11 @@ use uuid::Uuid;
 
 #[async_trait]
 pub trait GroupStorage: Send + Sync {
-    async fn create(&self, group: &Group) -> Result<Group>;
+    async fn create(&self, group: &Group, change: Option<&EntityChange>) -> Result<Group>;
     async fn get_by_id(&self, id: &Uuid) -> Result<Option<Group>>;
     async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Group>>;
-    async fn update(&self, group: &Group) -> Result<()>;
-    async fn delete(&self, id: &Uuid) -> Result<()>;
+    async fn update(&self, group: &Group, change: Option<&EntityChange>) -> Result<()>;
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()>;
 }
 
 pub struct PostgresGroupStorage {
@@ -28,10 +29,11 @@ impl PostgresGroupStorage {
 
 #[async_trait]
 impl GroupStorage for PostgresGroupStorage {
-    async fn create(&self, group: &Group) -> Result<Group> {
+    async fn create(&self, group: &Group, change: Option<&EntityChange>) -> Result<Group> {
         let group_type_json = serde_json::to_value(&group.base.group_type)?;
         let source_json = serde_json::to_value(&group.base.source)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO groups (
@@ -49,9 +51,12 @@ impl GroupStorage for PostgresGroupStorage {
         .bind(chrono::Utc::now())
         .bind(group.base.network_id)
         .bind(&group.base.color)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(group.clone())
     }
 
@@ -81,10 +86,11 @@ impl GroupStorage for PostgresGroupStorage {
         Ok(groups)
     }
 
-    async fn update(&self, group: &Group) -> Result<()> {
+    async fn update(&self, group: &Group, change: Option<&EntityChange>) -> Result<()> {
         let group_type_json = serde_json::to_value(&group.base.group_type)?;
         let source_json = serde_json::to_value(&group.base.source)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             UPDATE groups SET 
@@ -100,18 +106,25 @@ impl GroupStorage for PostgresGroupStorage {
         .bind(source_json)
         .bind(chrono::Utc::now())
         .bind(&group.base.color)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM groups WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 }
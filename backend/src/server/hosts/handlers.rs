@@ -1,8 +1,15 @@
 use crate::server::{
     config::AppState,
-    hosts::types::{api::HostWithServicesRequest, base::Host},
-    services::types::base::Service,
-    shared::types::api::{ApiError, ApiResponse, ApiResult},
+    entity_changes::types::{EntityChange, EntityType},
+    hosts::types::{
+        api::{HostQuery, HostWithServicesRequest, SplitHostRequest, SplitHostResponse},
+        base::Host,
+    },
+    services::types::{base::Service, trace::HostMatchTrace},
+    shared::types::{
+        api::{ApiError, ApiResponse, ApiResult},
+        query::Page,
+    },
 };
 use axum::{
     Router,
@@ -22,15 +29,18 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .route("/", get(get_all_hosts))
         .route("/", put(update_host))
         .route("/:id", delete(delete_host))
+        .route("/:id/explain-match", get(explain_host_match))
+        .route("/:id/history", get(get_host_history))
         .route(
             "/:destination_host/consolidate/:other_host",
             put(consolidate_hosts),
         )
+        .route("/:id/split", post(split_host))
 }
 
 async fn create_host(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<HostWithServicesRequest>,
+    Json(mut request): Json<HostWithServicesRequest>,
 ) -> ApiResult<Json<ApiResponse<HostWithServicesRequest>>> {
     let host_service = &state.services.host_service;
 
@@ -52,35 +62,104 @@ async fn create_host(
         }
     }
 
-    let (host, services) = host_service
+    validate_custom_fields(&state, &mut request).await?;
+
+    let observed_interfaces = request.host.base.interfaces.clone();
+    let observed_ports = request.host.base.ports.clone();
+    let (mut host, services) = host_service
         .create_host_with_services(request.host, request.services)
         .await?;
 
+    for observation in &request.observations {
+        host = state
+            .services
+            .reconciliation_service
+            .reconcile_host(
+                host,
+                observation,
+                &observed_interfaces,
+                &observed_ports,
+                &services,
+                state.config.reconciliation_policy,
+                state.config.reconciliation_missed_scans,
+            )
+            .await?;
+    }
+
+    // Evidence is only used to re-run matching later, so it can't fail host creation
+    if let Err(e) = state
+        .services
+        .scan_evidence_service
+        .record_evidence(&host, request.evidence)
+        .await
+    {
+        tracing::warn!("Failed to record scan evidence for host {}: {}", host.id, e);
+    }
+
     Ok(Json(ApiResponse::success(HostWithServicesRequest {
         host,
         services,
+        evidence: Vec::new(),
+        observations: Vec::new(),
     })))
 }
 
 async fn get_all_hosts(
     State(state): State<Arc<AppState>>,
     Query(params): Query<HashMap<String, String>>,
-) -> ApiResult<Json<ApiResponse<Vec<Host>>>> {
+) -> ApiResult<Json<ApiResponse<Page<Host>>>> {
     let network_id = params
         .get("network_id")
         .and_then(|id| Uuid::parse_str(id).ok())
         .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
 
-    let service = &state.services.host_service;
-    let hosts = service.get_all_hosts(&network_id).await?;
+    let query = HostQuery::from_params(network_id, &params)
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    let hosts = state.services.host_service.query_hosts(&query).await?;
 
     Ok(Json(ApiResponse::success(hosts)))
 }
 
+/// Check tags and custom field values of a host and its services
+async fn validate_custom_fields(
+    state: &AppState,
+    request: &mut HostWithServicesRequest,
+) -> ApiResult<()> {
+    let custom_field_service = &state.services.custom_field_service;
+    let network_id = request.host.base.network_id;
+
+    custom_field_service
+        .validate_entity_fields(
+            &network_id,
+            EntityType::Host,
+            &mut request.host.base.tags,
+            &request.host.base.custom_fields,
+        )
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    for service in request.services.iter_mut() {
+        custom_field_service
+            .validate_entity_fields(
+                &network_id,
+                EntityType::Service,
+                &mut service.base.tags,
+                &service.base.custom_fields,
+            )
+            .await
+            .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+    }
+
+    Ok(())
+}
+
 async fn update_host(
     State(state): State<Arc<AppState>>,
     Json(mut request): Json<HostWithServicesRequest>,
 ) -> ApiResult<Json<ApiResponse<Host>>> {
+    validate_custom_fields(&state, &mut request).await?;
+
     let host_service = &state.services.host_service;
     let service_service = &state.services.service_service;
 
@@ -130,6 +209,29 @@ async fn consolidate_hosts(
     Ok(Json(ApiResponse::success(updated_host)))
 }
 
+async fn split_host(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SplitHostRequest>,
+) -> ApiResult<Json<ApiResponse<SplitHostResponse>>> {
+    let host_service = &state.services.host_service;
+
+    let host = host_service
+        .get_host(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Host '{}' not found", &id)))?;
+
+    let (host, destination_host) = host_service
+        .split_host(host, request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(SplitHostResponse {
+        host,
+        destination_host,
+    })))
+}
+
 async fn delete_host(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
@@ -145,3 +247,62 @@ async fn delete_host(
 
     Ok(Json(ApiResponse::success(())))
 }
+
+/// The evaluation of every definition for each of a host's scanned interfaces. Uses the trace
+/// recorded when the daemon matched the services, or re-runs matching over the scan evidence if
+/// the daemon didn't record traces.
+async fn explain_host_match(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<HostMatchTrace>>>> {
+    let host = state
+        .services
+        .host_service
+        .get_host(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Host '{}' not found", &id)))?;
+
+    let evidence = state
+        .services
+        .scan_evidence_service
+        .get_evidence_for_host(&id)
+        .await?;
+
+    if evidence.is_empty() {
+        return Err(ApiError::not_found(&format!(
+            "No scan evidence recorded for host '{}'",
+            &id
+        )));
+    }
+
+    let traces = evidence
+        .into_iter()
+        .map(|e| HostMatchTrace {
+            host_id: host.id,
+            hostname: host.base.hostname.clone(),
+            ip_address: e.base.ip_address,
+            definitions: if e.base.match_trace.is_empty() {
+                e.base
+                    .match_services(&host, state.config.min_match_score, true)
+                    .traces
+            } else {
+                e.base.match_trace
+            },
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(traces)))
+}
+
+async fn get_host_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<EntityChange>>>> {
+    let history = state
+        .services
+        .entity_change_service
+        .get_history(EntityType::Host, &id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(history)))
+}
@@ -1,13 +1,25 @@
 use crate::server::{
     daemons::service::DaemonService,
     discovery::types::base::{EntitySource, EntitySourceDiscriminants},
-    hosts::{storage::HostStorage, types::base::Host},
+    entity_changes::{service::EntityChangeService, types::ChangeAction},
+    hosts::{
+        storage::HostStorage,
+        types::{
+            api::{HostQuery, SplitHostRequest},
+            base::{Host, HostBase},
+        },
+    },
     services::{service::ServiceService, types::base::Service},
+    shared::types::query::Page,
+    vlans::service::VlanService,
 };
 use anyhow::{Error, Result, anyhow};
 use futures::future::{join_all, try_join_all};
 use itertools::{Either, Itertools};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
 use strum::IntoDiscriminant;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -16,6 +28,8 @@ pub struct HostService {
     storage: Arc<dyn HostStorage>,
     service_service: Arc<ServiceService>,
     daemon_service: Arc<DaemonService>,
+    entity_change_service: Arc<EntityChangeService>,
+    vlan_service: OnceLock<Arc<VlanService>>,
     host_locks: Arc<Mutex<HashMap<Uuid, Arc<Mutex<()>>>>>,
 }
 
@@ -24,15 +38,22 @@ impl HostService {
         storage: Arc<dyn HostStorage>,
         service_service: Arc<ServiceService>,
         daemon_service: Arc<DaemonService>,
+        entity_change_service: Arc<EntityChangeService>,
     ) -> Self {
         Self {
             storage,
             service_service,
             daemon_service,
+            entity_change_service,
+            vlan_service: OnceLock::new(),
             host_locks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    pub fn set_vlan_service(&self, vlan_service: Arc<VlanService>) -> Result<(), Arc<VlanService>> {
+        self.vlan_service.set(vlan_service)
+    }
+
     async fn get_host_lock(&self, host_id: &Uuid) -> Arc<Mutex<()>> {
         let mut locks = self.host_locks.lock().await;
         locks
@@ -49,6 +70,10 @@ impl HostService {
         self.storage.get_all(network_id).await
     }
 
+    pub async fn query_hosts(&self, query: &HostQuery) -> Result<Page<Host>> {
+        self.storage.query(query).await
+    }
+
     pub async fn create_host_with_services(
         &self,
         host: Host,
@@ -94,12 +119,14 @@ impl HostService {
     }
 
     /// Create a new host
-    async fn create_host(&self, host: Host, network_id: &Uuid) -> Result<Host> {
+    async fn create_host(&self, mut host: Host, network_id: &Uuid) -> Result<Host> {
         let lock = self.get_host_lock(&host.id).await;
         let _guard = lock.lock().await;
 
         tracing::debug!("Creating host {:?}", host);
 
+        host.refresh_mac_details();
+
         let all_hosts = self.storage.get_all(network_id).await?;
 
         let host_from_storage = match all_hosts.into_iter().find(|h| host.eq(h)) {
@@ -118,10 +145,14 @@ impl HostService {
                     existing_host.id
                 );
 
-                self.upsert_host(existing_host, host).await?
+                self.upsert_host(existing_host, host, ChangeAction::Upsert)
+                    .await?
             }
             _ => {
-                self.storage.create(&host).await?;
+                let change =
+                    self.entity_change_service
+                        .change(ChangeAction::Create, None, Some(&host))?;
+                self.storage.create(&host, change.as_ref()).await?;
                 tracing::info!("Created host {}: {}", host.base.name, host.id);
                 tracing::debug!("Result: {:?}", host);
                 host
@@ -144,22 +175,45 @@ impl HostService {
 
         self.update_host_services(&current_host, &host).await?;
 
+        host.refresh_mac_details();
+
+        let services = self.service_service.get_services_for_host(&host.id).await?;
+        host.refresh_device_classification(&services);
         host.updated_at = chrono::Utc::now();
 
-        self.storage.update(&host).await?;
+        let change = self.entity_change_service.change(
+            ChangeAction::Update,
+            Some(&current_host),
+            Some(&host),
+        )?;
+        self.storage.update(&host, change.as_ref()).await?;
 
         tracing::info!("Updated host {:?}: {:?}", host.base.name, host.id);
         tracing::debug!("Result: {:?}", host);
 
+        // Tagged interfaces put their subnets on the VLAN with the tag
+        if let Some(vlan_service) = self.vlan_service.get() {
+            vlan_service.link_host_subnets(&host).await?;
+        }
+
         Ok(host)
     }
 
-    /// Merge new discovery data with existing host
-    async fn upsert_host(&self, mut existing_host: Host, new_host_data: Host) -> Result<Host> {
+    /// Merge new discovery data with existing host, recording the merge under the given action
+    async fn upsert_host(
+        &self,
+        mut existing_host: Host,
+        new_host_data: Host,
+        action: ChangeAction,
+    ) -> Result<Host> {
+        let host_before_upsert = existing_host.clone();
         let mut interface_updates = 0;
         let mut port_updates = 0;
         let mut hostname_update = false;
         let mut description_update = false;
+        let mut virtualization_update = false;
+        let fingerprint_update = !new_host_data.base.device_fingerprint.is_empty();
+        let identity_update = !new_host_data.base.identity.is_empty();
 
         tracing::debug!(
             "Upserting new host data {:?} to host {:?}",
@@ -169,13 +223,35 @@ impl HostService {
 
         // Merge interfaces - add any new interfaces not already present
         for new_host_data_interface in new_host_data.base.interfaces {
-            if !existing_host
+            match existing_host
                 .base
                 .interfaces
-                .contains(&new_host_data_interface)
+                .iter_mut()
+                .find(|i| **i == new_host_data_interface)
             {
-                interface_updates += 1;
-                existing_host.base.interfaces.push(new_host_data_interface);
+                Some(existing_interface) => {
+                    // Prefer a stable MAC over a missing or randomized one
+                    if existing_interface.base.stable_mac_address().is_none()
+                        && new_host_data_interface.base.stable_mac_address().is_some()
+                    {
+                        interface_updates += 1;
+                        existing_interface.base.mac_address =
+                            new_host_data_interface.base.mac_address;
+                        existing_interface
+                            .base
+                            .refresh_mac_details(&existing_host.base.network_id);
+                    }
+                    if existing_interface.base.vlan_tag.is_none()
+                        && new_host_data_interface.base.vlan_tag.is_some()
+                    {
+                        interface_updates += 1;
+                        existing_interface.base.vlan_tag = new_host_data_interface.base.vlan_tag;
+                    }
+                }
+                None => {
+                    interface_updates += 1;
+                    existing_host.base.interfaces.push(new_host_data_interface);
+                }
             }
         }
 
@@ -201,6 +277,22 @@ impl HostService {
             existing_host.base.description = new_host_data.base.description;
         }
 
+        if existing_host.base.virtualization.is_none()
+            && new_host_data.base.virtualization.is_some()
+        {
+            virtualization_update = true;
+            existing_host.base.virtualization = new_host_data.base.virtualization;
+        }
+
+        existing_host
+            .base
+            .device_fingerprint
+            .merge(new_host_data.base.device_fingerprint);
+        existing_host
+            .base
+            .identity
+            .merge(new_host_data.base.identity);
+
         // Update entity source for new discovery session data
         existing_host.base.source = match (existing_host.base.source, new_host_data.base.source) {
             (
@@ -235,7 +327,12 @@ impl HostService {
         existing_host.updated_at = chrono::Utc::now();
 
         // Update the existing host
-        self.storage.update(&existing_host).await?;
+        let change = self.entity_change_service.change(
+            action,
+            Some(&host_before_upsert),
+            Some(&existing_host),
+        )?;
+        self.storage.update(&existing_host, change.as_ref()).await?;
         let mut data = Vec::new();
 
         if port_updates > 0 {
@@ -250,6 +347,15 @@ impl HostService {
         if description_update {
             data.push("new description".to_string())
         }
+        if virtualization_update {
+            data.push("virtualization".to_string())
+        }
+        if fingerprint_update {
+            data.push("device fingerprint".to_string())
+        }
+        if identity_update {
+            data.push("identity".to_string())
+        }
 
         if !data.is_empty() {
             tracing::info!(
@@ -312,7 +418,11 @@ impl HostService {
 
         // Add bindings, interfaces, sources from old host to new
         let updated_host = self
-            .upsert_host(destination_host.clone(), other_host.clone())
+            .upsert_host(
+                destination_host.clone(),
+                other_host.clone(),
+                ChangeAction::Consolidate,
+            )
             .await?;
 
         // Update host_id and interface/port binding IDs to what's available on new host
@@ -356,12 +466,279 @@ impl HostService {
         let _updated_services = try_join_all(update_futures).await?;
 
         // Delete host, ignore services because they are just being moved to other host
-        self.delete_host(&other_host.id, false).await?;
+        self.remove_host(&other_host.id, false, ChangeAction::Consolidate)
+            .await?;
         tracing::info!("Consolidated host {} into {}", other_host, updated_host);
         tracing::debug!("Result: {:?}", updated_host);
         Ok(updated_host)
     }
 
+    /// Move interfaces, ports and services off a host, ie to undo a bad consolidation. Bindings are
+    /// remapped onto whichever host ends up with the interface or port they point at, and bindings
+    /// left pointing at the other host are dropped. Returns the updated host and destination host.
+    pub async fn split_host(&self, host: Host, request: SplitHostRequest) -> Result<(Host, Host)> {
+        if request.interface_ids.is_empty()
+            && request.port_ids.is_empty()
+            && request.service_ids.is_empty()
+        {
+            return Err(anyhow!("Select interfaces, ports or services to split off"));
+        }
+
+        if let Some(id) = request
+            .interface_ids
+            .iter()
+            .find(|id| host.get_interface(&Some(**id)).is_none())
+        {
+            return Err(anyhow!("Interface {} not found on host {}", id, host));
+        }
+        if let Some(id) = request
+            .port_ids
+            .iter()
+            .find(|id| host.get_port(id).is_none())
+        {
+            return Err(anyhow!("Port {} not found on host {}", id, host));
+        }
+        if let Some(id) = request
+            .service_ids
+            .iter()
+            .find(|id| !host.base.services.contains(id))
+        {
+            return Err(anyhow!("Service {} not found on host {}", id, host));
+        }
+
+        let existing_destination = match request.destination_host_id {
+            Some(id) if id == host.id => {
+                return Err(anyhow!("Can't split a host into itself"));
+            }
+            Some(id) => {
+                let destination = self
+                    .get_host(&id)
+                    .await?
+                    .ok_or_else(|| anyhow!("Host '{}' not found", id))?;
+                if destination.base.network_id != host.base.network_id {
+                    return Err(anyhow!("Can't split a host into a host on another network"));
+                }
+                Some(destination)
+            }
+            None => None,
+        };
+
+        let lock = self.get_host_lock(&host.id).await;
+        let _guard1 = lock.lock().await;
+        let destination_lock = match &existing_destination {
+            Some(destination) => Some(self.get_host_lock(&destination.id).await),
+            None => None,
+        };
+        let _guard2 = match &destination_lock {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
+        };
+
+        tracing::debug!("Splitting {:?} off host {:?}", request, host);
+
+        let (moved_interfaces, kept_interfaces): (Vec<_>, Vec<_>) = host
+            .base
+            .interfaces
+            .iter()
+            .cloned()
+            .partition(|i| request.interface_ids.contains(&i.id));
+        let (moved_ports, kept_ports): (Vec<_>, Vec<_>) = host
+            .base
+            .ports
+            .iter()
+            .cloned()
+            .partition(|p| request.port_ids.contains(&p.id));
+
+        let mut updated_host = host.clone();
+        updated_host.base.interfaces = kept_interfaces;
+        updated_host.base.ports = kept_ports;
+        updated_host
+            .base
+            .services
+            .retain(|id| !request.service_ids.contains(id));
+        updated_host.updated_at = chrono::Utc::now();
+
+        let mut destination_host = match &existing_destination {
+            Some(destination) => destination.clone(),
+            None => Host::new(HostBase {
+                name: request
+                    .name
+                    .clone()
+                    .filter(|n| !n.trim().is_empty())
+                    .unwrap_or_else(|| format!("{} (split)", host.base.name)),
+                network_id: host.base.network_id,
+                source: host.base.source.clone(),
+                ..HostBase::default()
+            }),
+        };
+        for interface in moved_interfaces {
+            if !destination_host.base.interfaces.contains(&interface) {
+                destination_host.base.interfaces.push(interface);
+            }
+        }
+        for port in moved_ports {
+            if !destination_host.base.ports.contains(&port) {
+                destination_host.base.ports.push(port);
+            }
+        }
+        for service_id in &request.service_ids {
+            if !destination_host.base.services.contains(service_id) {
+                destination_host.base.services.push(*service_id);
+            }
+        }
+        destination_host.refresh_mac_details();
+        destination_host.updated_at = chrono::Utc::now();
+
+        let destination_services = match &existing_destination {
+            Some(destination) => {
+                self.service_service
+                    .get_services_for_host(&destination.id)
+                    .await?
+            }
+            None => Vec::new(),
+        };
+
+        // Work out where every service ends up before writing anything, so the destination is
+        // written once with its final service list
+        let mut service_updates = Vec::new();
+        let mut service_folds = Vec::new();
+        let mut original_services = Vec::new();
+        for service in self.service_service.get_services_for_host(&host.id).await? {
+            if request.service_ids.contains(&service.id) {
+                let moved = self
+                    .service_service
+                    .reassign_service_interface_bindings(service.clone(), &host, &destination_host)
+                    .await;
+
+                // Fold into an equivalent service the destination already has, as consolidation does
+                if let Some(existing_service) = destination_services.iter().find(|s| **s == moved) {
+                    destination_host.base.services.retain(|id| *id != moved.id);
+                    original_services.push(existing_service.clone());
+                    service_folds.push((existing_service.clone(), moved));
+                } else {
+                    service_updates.push(moved);
+                }
+                original_services.push(service);
+            } else {
+                let kept = self
+                    .service_service
+                    .reassign_service_interface_bindings(service.clone(), &host, &updated_host)
+                    .await;
+
+                if kept.base.bindings != service.base.bindings {
+                    service_updates.push(kept);
+                    original_services.push(service);
+                }
+            }
+        }
+
+        // Services reference their host, so the destination has to exist before they're moved
+        let destination_change = self.entity_change_service.change(
+            ChangeAction::Split,
+            existing_destination.as_ref(),
+            Some(&destination_host),
+        )?;
+        if existing_destination.is_some() {
+            self.storage
+                .update(&destination_host, destination_change.as_ref())
+                .await?;
+        } else {
+            self.storage
+                .create(&destination_host, destination_change.as_ref())
+                .await?;
+        }
+
+        if let Err(e) = self
+            .move_split_services(service_folds, service_updates)
+            .await
+        {
+            tracing::warn!(
+                "Failed to move services from host {} to host {}, rolling back split: {}",
+                host,
+                destination_host,
+                e
+            );
+            self.roll_back_split(
+                &destination_host,
+                existing_destination.as_ref(),
+                &original_services,
+            )
+            .await?;
+            return Err(e);
+        }
+
+        let change = self.entity_change_service.change(
+            ChangeAction::Split,
+            Some(&host),
+            Some(&updated_host),
+        )?;
+        self.storage.update(&updated_host, change.as_ref()).await?;
+
+        tracing::info!("Split host {} off host {}", destination_host, updated_host);
+        tracing::debug!(
+            "Result - host: {:?}, destination host: {:?}",
+            updated_host,
+            destination_host
+        );
+
+        Ok((updated_host, destination_host))
+    }
+
+    /// Write the services of a split: fold moved services into their destination equivalents, then
+    /// update the rest
+    async fn move_split_services(
+        &self,
+        service_folds: Vec<(Service, Service)>,
+        service_updates: Vec<Service>,
+    ) -> Result<()> {
+        for (existing_service, moved) in service_folds {
+            self.service_service
+                .upsert_service(existing_service, moved.clone())
+                .await?;
+            self.service_service.delete_service(&moved.id).await?;
+        }
+        for service in service_updates {
+            self.service_service.update_service(service).await?;
+        }
+        Ok(())
+    }
+
+    /// Undo a split whose services failed to move: put the services back as they were, then remove
+    /// the destination if the split created it or restore it otherwise
+    async fn roll_back_split(
+        &self,
+        destination_host: &Host,
+        existing_destination: Option<&Host>,
+        original_services: &[Service],
+    ) -> Result<()> {
+        self.service_service
+            .restore_services(original_services)
+            .await?;
+
+        match existing_destination {
+            Some(existing_destination) => {
+                let change = self.entity_change_service.change(
+                    ChangeAction::Split,
+                    Some(destination_host),
+                    Some(existing_destination),
+                )?;
+                self.storage
+                    .update(existing_destination, change.as_ref())
+                    .await
+            }
+            None => {
+                let change = self.entity_change_service.change(
+                    ChangeAction::Delete,
+                    Some(destination_host),
+                    None,
+                )?;
+                self.storage
+                    .delete(&destination_host.id, change.as_ref())
+                    .await
+            }
+        }
+    }
+
     async fn update_host_services(&self, current_host: &Host, updates: &Host) -> Result<(), Error> {
         let services = self
             .service_service
@@ -411,6 +788,17 @@ impl HostService {
     }
 
     pub async fn delete_host(&self, id: &Uuid, delete_services: bool) -> Result<()> {
+        self.remove_host(id, delete_services, ChangeAction::Delete)
+            .await
+    }
+
+    /// Remove a host, recording the removal in its change history under the given action
+    async fn remove_host(
+        &self,
+        id: &Uuid,
+        delete_services: bool,
+        action: ChangeAction,
+    ) -> Result<()> {
         let host = self
             .get_host(id)
             .await?
@@ -425,7 +813,10 @@ impl HostService {
             }
         }
 
-        self.storage.delete(id).await?;
+        let change = self
+            .entity_change_service
+            .change(action, Some(&host), None)?;
+        self.storage.delete(id, change.as_ref()).await?;
         tracing::info!(
             "Deleted host {}: {}; deleted service + associated subnet/group bindings: {}",
             host.base.name,
@@ -1,25 +1,39 @@
 use crate::server::{
     discovery::types::base::EntitySource,
+    entity_changes::{storage::append_change, types::EntityChange},
     hosts::types::{
+        api::HostQuery,
         base::{Host, HostBase},
+        devices::{DeviceClassification, DeviceFingerprint},
+        identity::HostIdentity,
         interfaces::Interface,
         ports::Port,
         targets::HostTarget,
         virtualization::HostVirtualization,
     },
+    shared::{
+        storage::query::{
+            like_pattern, push_entity_filter, push_list_options, push_source_filter,
+            service_definition_values,
+        },
+        types::query::Page,
+    },
 };
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use serde_json::json;
+use sqlx::{PgPool, QueryBuilder, Row};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait HostStorage: Send + Sync {
-    async fn create(&self, host: &Host) -> Result<()>;
+    async fn create(&self, host: &Host, change: Option<&EntityChange>) -> Result<()>;
     async fn get_by_id(&self, id: &Uuid) -> Result<Option<Host>>;
     async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Host>>;
-    async fn update(&self, host: &Host) -> Result<()>;
-    async fn delete(&self, id: &Uuid) -> Result<()>;
+    async fn query(&self, query: &HostQuery) -> Result<Page<Host>>;
+    async fn update(&self, host: &Host, change: Option<&EntityChange>) -> Result<()>;
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()>;
 }
 
 pub struct PostgresHostStorage {
@@ -34,21 +48,28 @@ impl PostgresHostStorage {
 
 #[async_trait]
 impl HostStorage for PostgresHostStorage {
-    async fn create(&self, host: &Host) -> Result<()> {
+    async fn create(&self, host: &Host, change: Option<&EntityChange>) -> Result<()> {
         let services_str = serde_json::to_value(&host.base.services)?;
         let interfaces_str = serde_json::to_value(&host.base.interfaces)?;
         let target_str = serde_json::to_value(host.base.target)?;
         let ports_str = serde_json::to_value(&host.base.ports)?;
         let source_str = serde_json::to_value(&host.base.source)?;
         let virtualization_str = serde_json::to_value(&host.base.virtualization)?;
+        let device_fingerprint_str = serde_json::to_value(&host.base.device_fingerprint)?;
+        let device_classification_str = serde_json::to_value(&host.base.device_classification)?;
+        let identity_str = serde_json::to_value(&host.base.identity)?;
+        let tags_str = serde_json::to_value(&host.base.tags)?;
+        let custom_fields_str = serde_json::to_value(&host.base.custom_fields)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO hosts (
                 id, name, hostname, target, description,
                 services, interfaces, ports, source, virtualization,
-                created_at, updated_at, network_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                created_at, updated_at, network_id, device_fingerprint, device_classification,
+                identity, tags, custom_fields
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
             "#,
         )
         .bind(host.id)
@@ -64,9 +85,17 @@ impl HostStorage for PostgresHostStorage {
         .bind(host.created_at)
         .bind(host.updated_at)
         .bind(host.base.network_id)
-        .execute(&self.pool)
+        .bind(device_fingerprint_str)
+        .bind(device_classification_str)
+        .bind(identity_str)
+        .bind(tags_str)
+        .bind(custom_fields_str)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -97,20 +126,68 @@ impl HostStorage for PostgresHostStorage {
         Ok(hosts)
     }
 
-    async fn update(&self, host: &Host) -> Result<()> {
+    async fn query(&self, query: &HostQuery) -> Result<Page<Host>> {
+        let mut builder = QueryBuilder::new("SELECT * FROM hosts WHERE network_id = ");
+        builder.push_bind(query.network_id);
+
+        if let Some(search) = &query.search {
+            builder
+                .push(" AND search_text LIKE ")
+                .push_bind(like_pattern(search));
+        }
+
+        if let Some(subnet_id) = query.subnet_id {
+            builder
+                .push(" AND interfaces @> ")
+                .push_bind(json!([{ "subnet_id": subnet_id }]));
+        }
+
+        // Hosts running a service of one of the definitions
+        for ids in query.definitions.id_sets() {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM services s WHERE s.host_id = hosts.id \
+                     AND s.service_definition = ANY(",
+                )
+                .push_bind(service_definition_values(&ids))
+                .push("))");
+        }
+
+        push_source_filter(&mut builder, &query.source);
+        push_entity_filter(&mut builder, &query.filter);
+        push_list_options(&mut builder, &query.options);
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+
+        let mut hosts = Vec::new();
+        for row in rows {
+            hosts.push(row_to_host(row)?);
+        }
+
+        Ok(query.options.page(hosts))
+    }
+
+    async fn update(&self, host: &Host, change: Option<&EntityChange>) -> Result<()> {
         let services_str = serde_json::to_value(&host.base.services)?;
         let interfaces_str = serde_json::to_value(&host.base.interfaces)?;
         let target_str = serde_json::to_value(host.base.target)?;
         let ports_str = serde_json::to_value(&host.base.ports)?;
         let source_str = serde_json::to_value(&host.base.source)?;
         let virtualization_str = serde_json::to_value(&host.base.virtualization)?;
+        let device_fingerprint_str = serde_json::to_value(&host.base.device_fingerprint)?;
+        let device_classification_str = serde_json::to_value(&host.base.device_classification)?;
+        let identity_str = serde_json::to_value(&host.base.identity)?;
+        let tags_str = serde_json::to_value(&host.base.tags)?;
+        let custom_fields_str = serde_json::to_value(&host.base.custom_fields)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             UPDATE hosts SET 
                 name = $2, hostname = $3, description = $4,
                 target = $5, interfaces = $6, ports = $7, source = $8, services = $9, virtualization = $10,
-                updated_at = $11
+                updated_at = $11, device_fingerprint = $12, device_classification = $13,
+                identity = $14, tags = $15, custom_fields = $16
             WHERE id = $1
             "#,
         )
@@ -125,18 +202,30 @@ impl HostStorage for PostgresHostStorage {
         .bind(services_str)
         .bind(virtualization_str)
         .bind(host.updated_at)
-        .execute(&self.pool)
+        .bind(device_fingerprint_str)
+        .bind(device_classification_str)
+        .bind(identity_str)
+        .bind(tags_str)
+        .bind(custom_fields_str)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM hosts WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 }
@@ -157,6 +246,21 @@ fn row_to_host(row: sqlx::postgres::PgRow) -> Result<Host, Error> {
     let virtualization: Option<HostVirtualization> =
         serde_json::from_value(row.get::<serde_json::Value, _>("virtualization"))
             .or(Err(Error::msg("Failed to deserialize virtualization")))?;
+    let device_fingerprint: DeviceFingerprint =
+        serde_json::from_value(row.get::<serde_json::Value, _>("device_fingerprint"))
+            .or(Err(Error::msg("Failed to deserialize device fingerprint")))?;
+    let device_classification: Option<DeviceClassification> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("device_classification")).or(
+            Err(Error::msg("Failed to deserialize device classification")),
+        )?;
+    let identity: HostIdentity =
+        serde_json::from_value(row.get::<serde_json::Value, _>("identity"))
+            .or(Err(Error::msg("Failed to deserialize identity")))?;
+    let tags: Vec<String> = serde_json::from_value(row.get::<serde_json::Value, _>("tags"))
+        .or(Err(Error::msg("Failed to deserialize tags")))?;
+    let custom_fields: BTreeMap<Uuid, String> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("custom_fields"))
+            .or(Err(Error::msg("Failed to deserialize custom_fields")))?;
 
     Ok(Host {
         id: row.get("id"),
@@ -173,6 +277,11 @@ fn row_to_host(row: sqlx::postgres::PgRow) -> Result<Host, Error> {
             virtualization,
             interfaces,
             source,
+            device_fingerprint,
+            device_classification,
+            identity,
+            tags,
+            custom_fields,
         },
     })
 }
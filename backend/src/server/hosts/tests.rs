@@ -1,9 +1,21 @@
 use serial_test::serial;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 use crate::{
     server::{
-        discovery::types::base::{DiscoveryMetadata, EntitySource},
+        discovery::types::base::{DiscoveryMetadata, DiscoveryType, EntitySource},
+        entity_changes::types::{ChangeAction, EntityType},
+        hosts::types::{
+            api::{HostQuery, SplitHostRequest},
+            devices::{DeviceClassification, DeviceFingerprint, DeviceType},
+            ports::{Port, PortBase},
+        },
         services::types::bindings::Binding,
+        shared::{
+            services::ServiceFactory,
+            types::{query::DEFAULT_PAGE_SIZE, storage::StorageFactory},
+        },
     },
     tests::*,
 };
@@ -181,3 +193,433 @@ async fn test_host_consolidation() {
 
     assert_eq!(svc_after.base.host_id, consolidated.id);
 }
+
+#[tokio::test]
+#[serial]
+async fn test_host_split() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(subnet_obj.clone())
+        .await
+        .unwrap();
+
+    // Two devices which shared a DHCP address were consolidated into one host
+    let mut merged = host(&network.id);
+    let mut other_interface = interface(&subnet_obj.id);
+    other_interface.base.ip_address = "192.168.1.101".parse().unwrap();
+    merged.base.interfaces = vec![interface(&subnet_obj.id), other_interface];
+    merged.base.ports.push(Port::new(PortBase::Https));
+
+    let mut kept_svc = service(&network.id, &merged.id);
+    kept_svc.base.bindings = vec![Binding::new_port(
+        merged.base.ports[0].id,
+        Some(merged.base.interfaces[0].id),
+    )];
+    let mut moved_svc = service(&network.id, &merged.id);
+    moved_svc.base.name = "Web Server".to_string();
+    moved_svc.base.bindings = vec![
+        Binding::new_port(merged.base.ports[1].id, Some(merged.base.interfaces[1].id)),
+        Binding::new_port(merged.base.ports[0].id, Some(merged.base.interfaces[0].id)),
+    ];
+
+    let (merged, created_svcs) = services
+        .host_service
+        .create_host_with_services(merged, vec![kept_svc, moved_svc])
+        .await
+        .unwrap();
+    let (kept_svc, moved_svc) = (&created_svcs[0], &created_svcs[1]);
+
+    let (host_after, split_off) = services
+        .host_service
+        .split_host(
+            merged.clone(),
+            SplitHostRequest {
+                interface_ids: vec![merged.base.interfaces[1].id],
+                port_ids: vec![merged.base.ports[1].id],
+                service_ids: vec![moved_svc.id],
+                name: Some("Printer".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(split_off.base.name, "Printer");
+    assert_eq!(
+        host_after.base.interfaces,
+        vec![merged.base.interfaces[0].clone()]
+    );
+    assert_eq!(
+        split_off.base.interfaces,
+        vec![merged.base.interfaces[1].clone()]
+    );
+    assert_eq!(host_after.base.ports, vec![merged.base.ports[0]]);
+    assert_eq!(split_off.base.ports, vec![merged.base.ports[1]]);
+    assert_eq!(host_after.base.services, vec![kept_svc.id]);
+    assert_eq!(split_off.base.services, vec![moved_svc.id]);
+
+    // The moved service keeps the binding to what moved with it, and loses the one left behind
+    let moved_after = services
+        .service_service
+        .get_service(&moved_svc.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(moved_after.base.host_id, split_off.id);
+    assert_eq!(moved_after.base.bindings, vec![moved_svc.base.bindings[0]]);
+
+    let kept_after = services
+        .service_service
+        .get_service(&kept_svc.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(kept_after.base.bindings, kept_svc.base.bindings);
+
+    let history = services
+        .entity_change_service
+        .get_history(EntityType::Host, &merged.id)
+        .await
+        .unwrap();
+    assert_eq!(history.last().unwrap().action, ChangeAction::Split);
+
+    // The destination's history starts with the split that created it
+    let destination_history = services
+        .entity_change_service
+        .get_history(EntityType::Host, &split_off.id)
+        .await
+        .unwrap();
+    assert_eq!(destination_history.len(), 1);
+    assert_eq!(destination_history[0].action, ChangeAction::Split);
+
+    // The moved service is no longer on the host, so it can't be split off again
+    assert!(
+        services
+            .host_service
+            .split_host(
+                host_after,
+                SplitHostRequest {
+                    service_ids: vec![moved_svc.id],
+                    ..Default::default()
+                },
+            )
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_host_split_rolls_back_on_failure() {
+    let (pool, database_url, _container) = setup_test_db().await;
+    let storage = StorageFactory::new(&database_url).await.unwrap();
+    let services = ServiceFactory::new(&storage, None).await.unwrap();
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(subnet_obj.clone())
+        .await
+        .unwrap();
+
+    let mut merged = host(&network.id);
+    let mut other_interface = interface(&subnet_obj.id);
+    other_interface.base.ip_address = "192.168.1.101".parse().unwrap();
+    merged.base.interfaces = vec![interface(&subnet_obj.id), other_interface];
+
+    let mut web_svc = service(&network.id, &merged.id);
+    web_svc.base.bindings = vec![Binding::new_interface(merged.base.interfaces[1].id)];
+    let (merged, _) = services
+        .host_service
+        .create_host_with_services(merged, vec![web_svc])
+        .await
+        .unwrap();
+
+    // Created after the first, so it's moved second
+    let mut failing_svc = service(&network.id, &merged.id);
+    failing_svc.base.name = "Inventory".to_string();
+    failing_svc.base.bindings = vec![Binding::new_interface(merged.base.interfaces[1].id)];
+    let (merged, _) = services
+        .host_service
+        .create_host_with_services(merged, vec![failing_svc])
+        .await
+        .unwrap();
+    let created_svcs = services
+        .service_service
+        .get_services_for_host(&merged.id)
+        .await
+        .unwrap();
+    let hosts_before = services
+        .host_service
+        .get_all_hosts(&network.id)
+        .await
+        .unwrap();
+
+    sqlx::query(
+        r#"
+        CREATE FUNCTION fail_service_move() RETURNS trigger AS $$
+        BEGIN
+            RAISE EXCEPTION 'service move failed';
+        END
+        $$ LANGUAGE plpgsql;
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "CREATE TRIGGER fail_service_move BEFORE UPDATE ON services FOR EACH ROW
+         WHEN (NEW.name = 'Inventory' AND NEW.host_id <> OLD.host_id)
+         EXECUTE FUNCTION fail_service_move()",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let result = services
+        .host_service
+        .split_host(
+            merged.clone(),
+            SplitHostRequest {
+                interface_ids: vec![merged.base.interfaces[1].id],
+                service_ids: created_svcs.iter().map(|s| s.id).collect(),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(result.is_err());
+
+    // Neither the destination nor the service moved before the failure survives the split
+    let hosts_after = services
+        .host_service
+        .get_all_hosts(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(hosts_after.len(), hosts_before.len());
+    let merged_after = services
+        .host_service
+        .get_host(&merged.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(merged_after.base.interfaces, merged.base.interfaces);
+    assert_eq!(merged_after.base.services, merged.base.services);
+
+    for svc in &created_svcs {
+        let svc_after = services
+            .service_service
+            .get_service(&svc.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(svc_after.base.host_id, merged.id);
+        assert_eq!(svc_after.base.bindings, svc.base.bindings);
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_host_device_classification() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let mut phone = host(&network.id);
+    phone.base.hostname = Some("Galaxy-S23.lan".to_string());
+    phone.base.device_fingerprint = DeviceFingerprint {
+        dhcp_parameter_list: vec![1, 3, 6, 15, 26, 28, 51, 58, 59, 43],
+        ..Default::default()
+    };
+
+    let (created, _) = services
+        .host_service
+        .create_host_with_services(phone, vec![])
+        .await
+        .unwrap();
+
+    let classification = created.base.device_classification.clone().unwrap();
+    assert_eq!(classification.device_type, DeviceType::Phone);
+    assert_eq!(classification.score, 55);
+
+    // Hosts serving the network aren't end-user devices
+    let mut server = host(&network.id);
+    server.base.hostname = Some("android-builder.lan".to_string());
+    server.base.interfaces[0].base.ip_address = "192.168.1.101".parse().unwrap();
+    server.base.interfaces[0].base.mac_address = None;
+    let svc = service(&network.id, &server.id);
+
+    let (created_server, _) = services
+        .host_service
+        .create_host_with_services(server, vec![svc])
+        .await
+        .unwrap();
+    assert!(created_server.base.device_classification.is_none());
+
+    // A device type set by a user is kept
+    let mut manual = created.clone();
+    manual.base.device_classification = Some(DeviceClassification {
+        device_type: DeviceType::Tablet,
+        score: 0,
+        reasons: vec![],
+        manual: true,
+    });
+
+    let updated = services.host_service.update_host(manual).await.unwrap();
+    assert_eq!(
+        updated.base.device_classification.unwrap().device_type,
+        DeviceType::Tablet
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_host_query() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let subnet = services
+        .subnet_service
+        .create_subnet(subnet(&network.id))
+        .await
+        .unwrap();
+    let daemon_id = Uuid::new_v4();
+
+    let mut alpha = host(&network.id);
+    alpha.base.name = "Alpha".to_string();
+    alpha.base.interfaces = vec![interface(&subnet.id)];
+    alpha.base.source = EntitySource::Discovery {
+        metadata: vec![DiscoveryMetadata::new(DiscoveryType::Network, daemon_id)],
+    };
+    let mut dns = service(&network.id, &alpha.id);
+    dns.base.bindings = vec![Binding::new_port(
+        alpha.base.ports[0].id,
+        Some(alpha.base.interfaces[0].id),
+    )];
+    let definition = dns.base.service_definition.id().to_string();
+    let category = dns.base.service_definition.category().to_string();
+    services
+        .host_service
+        .create_host_with_services(alpha, vec![dns])
+        .await
+        .unwrap();
+
+    for (name, ip, tag) in [
+        ("Bravo", "192.168.1.11", None),
+        ("Charlie", "192.168.1.12", Some("lab")),
+    ] {
+        let mut other = host(&network.id);
+        other.base.name = name.to_string();
+        other.base.interfaces = vec![interface(&subnet.id)];
+        other.base.interfaces[0].base.ip_address = ip.parse().unwrap();
+        other.base.interfaces[0].base.mac_address = None;
+        other.base.source = EntitySource::Manual;
+        other.base.tags = tag.map(|t| vec![t.to_string()]).unwrap_or_default();
+        services
+            .host_service
+            .create_host_with_services(other, vec![])
+            .await
+            .unwrap();
+    }
+
+    let query = |params: &[(&str, &str)]| {
+        let mut params: HashMap<String, String> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        params.insert("subnet_id".to_string(), subnet.id.to_string());
+        params
+            .entry("sort".to_string())
+            .or_insert("name".to_string());
+        params
+            .entry("order".to_string())
+            .or_insert("asc".to_string());
+        HostQuery::from_params(network.id, &params)
+    };
+    let names = |params: &[(&str, &str)]| {
+        let query = query(params).unwrap();
+        let host_service = services.host_service.clone();
+        async move {
+            host_service
+                .query_hosts(&query)
+                .await
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|h| h.base.name)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(names(&[]).await, vec!["Alpha", "Bravo", "Charlie"]);
+    assert_eq!(names(&[("q", "192.168.1.11")]).await, vec!["Bravo"]);
+    assert_eq!(names(&[("q", "01:02:03")]).await, vec!["Alpha"]);
+    assert_eq!(names(&[("q", "CHAR")]).await, vec!["Charlie"]);
+    assert_eq!(names(&[("q", "%")]).await, Vec::<String>::new());
+    assert_eq!(
+        names(&[("service_definition", &definition)]).await,
+        vec!["Alpha"]
+    );
+    assert_eq!(names(&[("category", &category)]).await, vec!["Alpha"]);
+    assert_eq!(
+        names(&[("source", "Manual")]).await,
+        vec!["Bravo", "Charlie"]
+    );
+    assert_eq!(
+        names(&[("daemon_id", &daemon_id.to_string())]).await,
+        vec!["Alpha"]
+    );
+    assert_eq!(names(&[("tags", "lab")]).await, vec!["Charlie"]);
+    assert_eq!(
+        names(&[("order", "desc")]).await,
+        vec!["Charlie", "Bravo", "Alpha"]
+    );
+
+    // Pages continue after the cursor of the previous one
+    let first = services
+        .host_service
+        .query_hosts(&query(&[("limit", "2")]).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(first.items.len(), 2);
+    let cursor = first.next_cursor.unwrap();
+    let second = services
+        .host_service
+        .query_hosts(&query(&[("limit", "2"), ("cursor", &cursor)]).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(second.items[0].base.name, "Charlie");
+    assert!(second.next_cursor.is_none());
+
+    assert_eq!(query(&[]).unwrap().options.limit, DEFAULT_PAGE_SIZE);
+    assert!(query(&[("limit", "0")]).is_err());
+    assert!(query(&[("sort", "size")]).is_err());
+    assert!(query(&[("cursor", "garbage")]).is_err());
+    assert!(query(&[("category", "Nonsense")]).is_err());
+}
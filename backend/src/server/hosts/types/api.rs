@@ -1,9 +1,81 @@
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
 
-use crate::server::{hosts::types::base::Host, services::types::base::Service};
+use crate::server::{
+    custom_fields::types::EntityFilter,
+    hosts::types::base::Host,
+    reconciliation::types::HostObservation,
+    scan_evidence::types::ScanEvidenceBase,
+    services::types::{api::ServiceDefinitionFilter, base::Service},
+    shared::types::query::{ListOptions, SourceFilter, parse_param},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostWithServicesRequest {
     pub host: Host,
     pub services: Vec<Service>,
+    /// Data the services were matched on, reported by daemons
+    #[serde(default)]
+    pub evidence: Vec<ScanEvidenceBase>,
+    /// Scopes the host was completely scanned in, for reconciling what discovery no longer sees
+    #[serde(default)]
+    pub observations: Vec<HostObservation>,
+}
+
+/// Interfaces, ports and services to move off a host. They move to an existing host when
+/// destination_host_id is set, otherwise to a new host.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SplitHostRequest {
+    #[serde(default)]
+    pub interface_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub port_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub service_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub destination_host_id: Option<Uuid>,
+    /// Name for the new host, ignored when moving to an existing host
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitHostResponse {
+    pub host: Host,
+    pub destination_host: Host,
+}
+
+/// Query parameters of the host list endpoint. `q` searches names, hostnames, IP and MAC
+/// addresses; the service definition filter matches hosts running such a service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostQuery {
+    pub network_id: Uuid,
+    pub search: Option<String>,
+    pub subnet_id: Option<Uuid>,
+    pub definitions: ServiceDefinitionFilter,
+    pub source: SourceFilter,
+    pub filter: EntityFilter,
+    pub options: ListOptions,
+}
+
+impl HostQuery {
+    pub fn from_params(network_id: Uuid, params: &HashMap<String, String>) -> Result<Self> {
+        Ok(Self {
+            network_id,
+            search: params
+                .get("q")
+                .map(|q| q.trim().to_string())
+                .filter(|q| !q.is_empty()),
+            subnet_id: params
+                .get("subnet_id")
+                .map(|id| parse_param("subnet_id", id))
+                .transpose()?,
+            definitions: ServiceDefinitionFilter::from_params(&network_id, params)?,
+            source: SourceFilter::from_params(params)?,
+            filter: EntityFilter::from_params(params)?,
+            options: ListOptions::from_params(params)?,
+        })
+    }
 }
@@ -1,6 +1,13 @@
 use crate::server::discovery::types::base::EntitySource;
+use crate::server::hosts::types::devices::{
+    DeviceClassification, DeviceClassifier, DeviceFingerprint,
+};
+use crate::server::hosts::types::identity::HostIdentity;
 use crate::server::hosts::types::virtualization::HostVirtualization;
+use crate::server::mac_vendors::types::is_randomized;
+use crate::server::services::types::base::Service;
 use crate::server::shared::types::api::deserialize_empty_string_as_none;
+use crate::server::shared::types::query::Pageable;
 use crate::server::subnets::types::base::Subnet;
 use crate::server::{
     hosts::types::ports::Port,
@@ -9,6 +16,7 @@ use crate::server::{
 use chrono::{DateTime, Utc};
 use mac_address::MacAddress;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::{hash::Hash, net::IpAddr};
 use uuid::Uuid;
@@ -34,6 +42,19 @@ pub struct HostBase {
     pub ports: Vec<Port>,
     pub source: EntitySource,
     pub virtualization: Option<HostVirtualization>,
+    #[serde(default)]
+    pub device_fingerprint: DeviceFingerprint,
+    /// What kind of end-user device the host is, for hosts without server-side services
+    #[serde(default)]
+    pub device_classification: Option<DeviceClassification>,
+    #[serde(default)]
+    pub identity: HostIdentity,
+    /// Free-form labels, ie "critical" or "rack-2"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Values of the network's custom fields, keyed by field definition id
+    #[serde(default)]
+    pub custom_fields: BTreeMap<Uuid, String>,
 }
 
 impl Default for HostBase {
@@ -49,6 +70,11 @@ impl Default for HostBase {
             ports: Vec::new(),
             source: EntitySource::Unknown,
             virtualization: None,
+            device_fingerprint: DeviceFingerprint::default(),
+            identity: HostIdentity::default(),
+            device_classification: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         }
     }
 }
@@ -107,9 +133,18 @@ impl PartialEq for Host {
             .map(|s| s.base.mac_address)
             .collect();
 
+        let hostname_match = matches!(
+            (&self.base.hostname, &other.base.hostname),
+            (Some(a), Some(b)) if a == b
+        );
+
+        // Randomized MACs rotate and aren't guaranteed to be unique, so they only identify a host
+        // alongside a matching hostname
         let mac_match = macs_a.iter().any(|mac_a| {
             macs_b.iter().any(|mac_b| match (mac_a, mac_b) {
-                (Some(a), Some(b)) => !invalid_macs.contains(a) && a == b,
+                (Some(a), Some(b)) => {
+                    !invalid_macs.contains(a) && a == b && (!is_randomized(a) || hostname_match)
+                }
                 (_, _) => false,
             })
         });
@@ -125,6 +160,24 @@ impl PartialEq for Host {
     }
 }
 
+impl Pageable for Host {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.base.name
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
 impl Display for Host {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}: {:?}", self.base.name, self.id)
@@ -142,6 +195,44 @@ impl Host {
         }
     }
 
+    /// Set the vendor and randomized flag of every interface from its MAC address
+    pub fn refresh_mac_details(&mut self) {
+        for interface in self.base.interfaces.iter_mut() {
+            interface.base.refresh_mac_details(&self.base.network_id);
+        }
+    }
+
+    /// Classify the device from its hostname, MAC vendors, fingerprint and services, unless a
+    /// user set the device type
+    pub fn refresh_device_classification(&mut self, services: &[Service]) {
+        if self
+            .base
+            .device_classification
+            .as_ref()
+            .is_some_and(|c| c.manual)
+        {
+            return;
+        }
+
+        self.base.device_classification = if DeviceClassifier::is_client_device(services) {
+            let mac_vendors: Vec<&str> = self
+                .base
+                .interfaces
+                .iter()
+                .filter_map(|i| i.base.mac_vendor.as_deref())
+                .collect();
+
+            DeviceClassifier::classify(
+                self.base.hostname.as_deref(),
+                &mac_vendors,
+                &self.base.device_fingerprint,
+                services,
+            )
+        } else {
+            None
+        };
+    }
+
     pub fn get_interface(&self, interface_id: &Option<Uuid>) -> Option<&Interface> {
         match interface_id {
             Some(id) => self.base.interfaces.iter().find(|i| &i.id == id),
@@ -0,0 +1,435 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, IntoStaticStr};
+
+use crate::server::services::types::{base::Service, categories::ServiceCategory};
+
+/// Score a device type needs before it's assigned to a host
+pub const MIN_DEVICE_SCORE: u32 = 30;
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumIter,
+    IntoStaticStr,
+)]
+pub enum DeviceType {
+    Phone,
+    Tablet,
+    Computer,
+    Wearable,
+    Tv,
+    MediaPlayer,
+    SmartSpeaker,
+    Camera,
+    SmartPlug,
+    GameConsole,
+    /// Sensors, relays and other smart home devices
+    IotDevice,
+}
+
+/// Data gathered from a host which identifies what kind of device it is, rather than what it serves
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DeviceFingerprint {
+    /// DHCP option 55 parameter request list, in the order the client sent it
+    #[serde(default)]
+    pub dhcp_parameter_list: Vec<u8>,
+    /// mDNS service types the host advertises, ie "_airplay._tcp"
+    #[serde(default)]
+    pub mdns_services: Vec<String>,
+    /// SERVER header of the host's SSDP responses
+    #[serde(default)]
+    pub ssdp_server: Option<String>,
+    /// Device and service types from the host's SSDP responses
+    #[serde(default)]
+    pub ssdp_types: Vec<String>,
+}
+
+impl DeviceFingerprint {
+    pub fn is_empty(&self) -> bool {
+        self.dhcp_parameter_list.is_empty()
+            && self.mdns_services.is_empty()
+            && self.ssdp_server.is_none()
+            && self.ssdp_types.is_empty()
+    }
+
+    /// Add data from a newer scan, keeping what the newer scan didn't see
+    pub fn merge(&mut self, other: DeviceFingerprint) {
+        if !other.dhcp_parameter_list.is_empty() {
+            self.dhcp_parameter_list = other.dhcp_parameter_list;
+        }
+        if other.ssdp_server.is_some() {
+            self.ssdp_server = other.ssdp_server;
+        }
+        for service in other.mdns_services {
+            if !self.mdns_services.contains(&service) {
+                self.mdns_services.push(service);
+            }
+        }
+        for ssdp_type in other.ssdp_types {
+            if !self.ssdp_types.contains(&ssdp_type) {
+                self.ssdp_types.push(ssdp_type);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DeviceClassification {
+    pub device_type: DeviceType,
+    pub score: u32,
+    /// Signals which contributed to the score
+    pub reasons: Vec<String>,
+    /// Set by a user, so it isn't replaced when the host is reclassified
+    #[serde(default)]
+    pub manual: bool,
+}
+
+enum DeviceSignal {
+    /// Substring of the lowercase hostname
+    Hostname(&'static str),
+    /// Substring of the lowercase MAC vendor of any interface
+    Vendor(&'static str),
+    /// Exact DHCP option 55 parameter request list
+    DhcpParameters(&'static [u8]),
+    /// Advertised mDNS service type
+    Mdns(&'static str),
+    /// Substring of the lowercase SSDP server header or device/service types
+    Ssdp(&'static str),
+    /// Service definition matched on the host
+    Definition(&'static str),
+}
+
+struct DeviceRule {
+    signal: DeviceSignal,
+    device_type: DeviceType,
+    weight: u32,
+}
+
+const fn rule(signal: DeviceSignal, device_type: DeviceType, weight: u32) -> DeviceRule {
+    DeviceRule {
+        signal,
+        device_type,
+        weight,
+    }
+}
+
+use DeviceSignal::*;
+use DeviceType::*;
+
+/// Weights reflect how specific a signal is: default hostnames and console/TV SSDP headers name
+/// the device outright, while vendors and DHCP fingerprints are shared across device types and
+/// only add confidence.
+/// Hostname patterns are default device names, not words people use when naming other hosts.
+static DEVICE_RULES: &[DeviceRule] = &[
+    // Hostname conventions
+    rule(Hostname("iphone"), Phone, 40),
+    rule(Hostname("galaxy-tab"), Tablet, 50),
+    rule(Hostname("galaxy"), Phone, 35),
+    rule(Hostname("android"), Phone, 30),
+    rule(Hostname("pixel"), Phone, 30),
+    rule(Hostname("oneplus"), Phone, 35),
+    rule(Hostname("redmi"), Phone, 35),
+    rule(Hostname("ipad"), Tablet, 40),
+    rule(Hostname("kindle"), Tablet, 35),
+    rule(Hostname("macbook"), Computer, 40),
+    rule(Hostname("imac"), Computer, 40),
+    rule(Hostname("desktop-"), Computer, 40),
+    rule(Hostname("laptop-"), Computer, 40),
+    rule(Hostname("thinkpad"), Computer, 40),
+    rule(Hostname("apple-watch"), Wearable, 40),
+    rule(Hostname("appletv"), MediaPlayer, 40),
+    rule(Hostname("apple-tv"), MediaPlayer, 40),
+    rule(Hostname("chromecast"), MediaPlayer, 40),
+    rule(Hostname("roku"), MediaPlayer, 40),
+    rule(Hostname("shield-android-tv"), MediaPlayer, 40),
+    rule(Hostname("webostv"), Tv, 40),
+    rule(Hostname("samsungtv"), Tv, 40),
+    rule(Hostname("samsung-tv"), Tv, 40),
+    rule(Hostname("bravia"), Tv, 40),
+    rule(Hostname("echo-dot"), SmartSpeaker, 40),
+    rule(Hostname("echo-show"), SmartSpeaker, 40),
+    rule(Hostname("google-home"), SmartSpeaker, 40),
+    rule(Hostname("nest-mini"), SmartSpeaker, 40),
+    rule(Hostname("nest-audio"), SmartSpeaker, 40),
+    rule(Hostname("homepod"), SmartSpeaker, 40),
+    rule(Hostname("sonos"), SmartSpeaker, 40),
+    rule(Hostname("ipcam"), Camera, 40),
+    rule(Hostname("doorbell"), Camera, 30),
+    rule(Hostname("reolink"), Camera, 40),
+    rule(Hostname("wyze"), Camera, 30),
+    rule(Hostname("tasmota"), SmartPlug, 35),
+    rule(Hostname("shelly"), SmartPlug, 35),
+    rule(Hostname("wemo"), SmartPlug, 40),
+    rule(Hostname("smartplug"), SmartPlug, 35),
+    rule(Hostname("smart-plug"), SmartPlug, 35),
+    rule(Hostname("esp_"), IotDevice, 30),
+    rule(Hostname("esp-"), IotDevice, 30),
+    rule(Hostname("esp32"), IotDevice, 30),
+    rule(Hostname("esp8266"), IotDevice, 30),
+    rule(Hostname("playstation"), GameConsole, 40),
+    rule(Hostname("ps4"), GameConsole, 40),
+    rule(Hostname("ps5"), GameConsole, 40),
+    rule(Hostname("xbox"), GameConsole, 40),
+    rule(Hostname("nintendo"), GameConsole, 40),
+    rule(Hostname("steamdeck"), GameConsole, 40),
+    // MAC vendors
+    rule(Vendor("espressif"), IotDevice, 15),
+    rule(Vendor("allterco"), SmartPlug, 20),
+    rule(Vendor("sonos"), SmartSpeaker, 30),
+    rule(Vendor("roku"), MediaPlayer, 30),
+    rule(Vendor("nintendo"), GameConsole, 40),
+    rule(Vendor("sony interactive"), GameConsole, 40),
+    rule(Vendor("ring llc"), Camera, 30),
+    rule(Vendor("wyze"), Camera, 30),
+    rule(Vendor("arlo"), Camera, 30),
+    rule(Vendor("hikvision"), Camera, 40),
+    rule(Vendor("dahua"), Camera, 40),
+    rule(Vendor("axis communications"), Camera, 40),
+    rule(Vendor("amazon technologies"), SmartSpeaker, 10),
+    rule(Vendor("fitbit"), Wearable, 30),
+    rule(Vendor("garmin"), Wearable, 20),
+    rule(Vendor("vizio"), Tv, 30),
+    rule(Vendor("hisense"), Tv, 20),
+    rule(Vendor("lg electronics"), Tv, 10),
+    rule(Vendor("raspberry pi"), Computer, 15),
+    rule(Vendor("intel corporate"), Computer, 15),
+    // DHCP fingerprints
+    rule(
+        DhcpParameters(&[1, 121, 3, 6, 15, 108, 114, 119, 252, 95, 44, 46]),
+        Phone,
+        15,
+    ),
+    rule(DhcpParameters(&[1, 121, 3, 6, 15, 119, 252]), Phone, 15),
+    rule(
+        DhcpParameters(&[1, 3, 6, 15, 26, 28, 51, 58, 59, 43]),
+        Phone,
+        20,
+    ),
+    rule(
+        DhcpParameters(&[1, 3, 6, 15, 26, 28, 51, 58, 59, 43, 114, 108]),
+        Phone,
+        20,
+    ),
+    rule(
+        DhcpParameters(&[1, 3, 6, 15, 31, 33, 43, 44, 46, 47, 119, 121, 249, 252]),
+        Computer,
+        30,
+    ),
+    rule(DhcpParameters(&[1, 3, 28, 6]), IotDevice, 25),
+    rule(
+        DhcpParameters(&[1, 3, 28, 6, 15, 44, 46, 47, 31, 33, 121, 43]),
+        IotDevice,
+        25,
+    ),
+    rule(DhcpParameters(&[1, 3, 6, 15, 28, 33]), MediaPlayer, 20),
+    // mDNS services
+    rule(Mdns("_apple-mobdev2._tcp"), Phone, 20),
+    rule(Mdns("_googlecast._tcp"), MediaPlayer, 30),
+    rule(Mdns("_airplay._tcp"), MediaPlayer, 20),
+    rule(Mdns("_amzn-wplay._tcp"), MediaPlayer, 20),
+    rule(Mdns("_androidtvremote2._tcp"), Tv, 30),
+    rule(Mdns("_raop._tcp"), SmartSpeaker, 15),
+    rule(Mdns("_spotify-connect._tcp"), SmartSpeaker, 15),
+    rule(Mdns("_sonos._tcp"), SmartSpeaker, 30),
+    rule(Mdns("_hap._tcp"), IotDevice, 25),
+    rule(Mdns("_esphomelib._tcp"), IotDevice, 30),
+    rule(Mdns("_rtsp._tcp"), Camera, 20),
+    rule(Mdns("_smb._tcp"), Computer, 15),
+    // SSDP
+    rule(Ssdp("urn:dial-multiscreen-org"), Tv, 20),
+    rule(Ssdp("mediarenderer"), Tv, 15),
+    rule(Ssdp("webos"), Tv, 40),
+    rule(Ssdp("tizen"), Tv, 40),
+    rule(Ssdp("bravia"), Tv, 40),
+    rule(Ssdp("roku"), MediaPlayer, 30),
+    rule(Ssdp("xbox"), GameConsole, 40),
+    rule(Ssdp("playstation"), GameConsole, 40),
+    rule(Ssdp("ipcamera"), Camera, 30),
+    rule(Ssdp("urn:belkin:device:controllee"), SmartPlug, 40),
+    rule(Ssdp("sonos"), SmartSpeaker, 30),
+    // Client-side service definitions
+    rule(Definition("Chromecast"), MediaPlayer, 40),
+    rule(Definition("Roku Media Player"), MediaPlayer, 40),
+    rule(Definition("Sonos Speaker"), SmartSpeaker, 40),
+    rule(Definition("Google Home"), SmartSpeaker, 40),
+    rule(Definition("Amazon Echo"), SmartSpeaker, 40),
+    rule(Definition("Ring Doorbell"), Camera, 40),
+    rule(Definition("Nest Thermostat"), IotDevice, 40),
+    rule(Definition("Nest Protect"), IotDevice, 40),
+];
+
+pub struct DeviceClassifier;
+
+impl DeviceClassifier {
+    /// Whether the host only runs services which are part of an end-user device, rather than
+    /// serving the network. Hosts without any server-side services are the usual case.
+    pub fn is_client_device(services: &[Service]) -> bool {
+        services.iter().all(|s| {
+            matches!(
+                s.base.service_definition.category(),
+                ServiceCategory::Workstation | ServiceCategory::Mobile | ServiceCategory::IoT
+            )
+        })
+    }
+
+    /// Score each device type from the host's signals, returning the best if it is unambiguous
+    /// and reaches MIN_DEVICE_SCORE
+    pub fn classify(
+        hostname: Option<&str>,
+        mac_vendors: &[&str],
+        fingerprint: &DeviceFingerprint,
+        services: &[Service],
+    ) -> Option<DeviceClassification> {
+        let hostname = hostname.map(|h| h.to_lowercase());
+        let mac_vendors: Vec<String> = mac_vendors.iter().map(|v| v.to_lowercase()).collect();
+        let ssdp: Vec<String> = fingerprint
+            .ssdp_server
+            .iter()
+            .chain(fingerprint.ssdp_types.iter())
+            .map(|s| s.to_lowercase())
+            .collect();
+        let mdns: Vec<String> = fingerprint
+            .mdns_services
+            .iter()
+            .map(|s| {
+                s.trim_end_matches('.')
+                    .trim_end_matches(".local")
+                    .to_lowercase()
+            })
+            .collect();
+
+        let mut scores: Vec<(DeviceType, u32, Vec<String>)> = Vec::new();
+
+        for rule in DEVICE_RULES {
+            let reason = match rule.signal {
+                Hostname(pattern) => hostname
+                    .as_ref()
+                    .filter(|h| h.contains(pattern))
+                    .map(|h| format!("Hostname {} contains \"{}\"", h, pattern)),
+                Vendor(pattern) => mac_vendors
+                    .iter()
+                    .find(|v| v.contains(pattern))
+                    .map(|v| format!("MAC vendor {}", v)),
+                DhcpParameters(parameters) => (fingerprint.dhcp_parameter_list == parameters)
+                    .then(|| format!("DHCP fingerprint {:?}", parameters)),
+                Mdns(service) => mdns
+                    .iter()
+                    .any(|s| s == service)
+                    .then(|| format!("Advertises mDNS service {}", service)),
+                Ssdp(pattern) => ssdp
+                    .iter()
+                    .find(|s| s.contains(pattern))
+                    .map(|s| format!("SSDP {}", s)),
+                Definition(definition) => services
+                    .iter()
+                    .any(|s| s.base.service_definition.id() == definition)
+                    .then(|| format!("Runs {}", definition)),
+            };
+
+            let Some(reason) = reason else {
+                continue;
+            };
+
+            match scores.iter_mut().find(|(t, _, _)| *t == rule.device_type) {
+                // Only count the same evidence once per device type, ie one SSDP header matched by
+                // several of the type's patterns. Reasons name the hostname pattern, so different
+                // patterns in a hostname, like "esp_" and "esp32", each count.
+                Some((_, score, reasons)) if !reasons.contains(&reason) => {
+                    *score += rule.weight;
+                    reasons.push(reason);
+                }
+                Some(_) => {}
+                None => scores.push((rule.device_type, rule.weight, vec![reason])),
+            }
+        }
+
+        scores.sort_by(|a, b| b.1.cmp(&a.1));
+
+        match scores.as_slice() {
+            [(device_type, score, reasons), rest @ ..]
+                if *score >= MIN_DEVICE_SCORE && rest.first().is_none_or(|r| r.1 < *score) =>
+            {
+                Some(DeviceClassification {
+                    device_type: *device_type,
+                    score: *score,
+                    reasons: reasons.clone(),
+                    manual: false,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_classification() {
+        let fingerprint = DeviceFingerprint::default();
+
+        let phone = DeviceClassifier::classify(Some("Johns-iPhone.lan"), &[], &fingerprint, &[]);
+        assert_eq!(phone.unwrap().device_type, Phone);
+
+        // More specific hostname conventions outweigh general ones
+        let tablet = DeviceClassifier::classify(
+            Some("Galaxy-Tab-S8"),
+            &["Samsung Electronics Co.,Ltd"],
+            &fingerprint,
+            &[],
+        );
+        assert_eq!(tablet.unwrap().device_type, Tablet);
+
+        // Weak signals combine
+        let esp = DeviceClassifier::classify(
+            None,
+            &["Espressif Inc."],
+            &DeviceFingerprint {
+                dhcp_parameter_list: vec![1, 3, 28, 6],
+                ..Default::default()
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(esp.device_type, IotDevice);
+        assert_eq!(esp.score, 40);
+        assert_eq!(esp.reasons.len(), 2);
+
+        let tv = DeviceClassifier::classify(
+            None,
+            &[],
+            &DeviceFingerprint {
+                ssdp_server: Some("WebOS/4.1.0 UPnP/1.0".to_string()),
+                mdns_services: vec!["_airplay._tcp.local.".to_string()],
+                ..Default::default()
+            },
+            &[],
+        );
+        assert_eq!(tv.unwrap().device_type, Tv);
+
+        // A vendor alone isn't enough
+        assert!(DeviceClassifier::classify(None, &["Apple, Inc."], &fingerprint, &[]).is_none());
+
+        // Common words in hostnames aren't default device names
+        assert!(
+            DeviceClassifier::classify(Some("camera-nvr.lan"), &[], &fingerprint, &[]).is_none()
+        );
+        assert!(
+            DeviceClassifier::classify(Some("echo-server.lan"), &[], &fingerprint, &[]).is_none()
+        );
+
+        // Hosts without server-side services are the ones classified
+        assert!(DeviceClassifier::is_client_device(&[]));
+        assert!(
+            DeviceClassifier::classify(None, &["Intel Corporate"], &fingerprint, &[]).is_none()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// Identifiers which belong to a machine rather than to one of its interfaces, so are the same
+/// whichever address the host was scanned through
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HostIdentity {
+    /// SHA256 fingerprints of SSH host keys, as printed by ssh-keygen -l, ie "SHA256:<base64>"
+    #[serde(default)]
+    pub ssh_host_keys: Vec<String>,
+    /// Hex SHA256 fingerprints of the TLS certificates the host presents
+    #[serde(default)]
+    pub tls_certificates: Vec<String>,
+    /// SNMP sysName.0
+    #[serde(default)]
+    pub snmp_sys_name: Option<String>,
+}
+
+impl HostIdentity {
+    pub fn is_empty(&self) -> bool {
+        self.ssh_host_keys.is_empty()
+            && self.tls_certificates.is_empty()
+            && self.snmp_sys_name.is_none()
+    }
+
+    /// Add identifiers from a newer scan, keeping what the newer scan didn't see
+    pub fn merge(&mut self, other: HostIdentity) {
+        if other.snmp_sys_name.is_some() {
+            self.snmp_sys_name = other.snmp_sys_name;
+        }
+        for key in other.ssh_host_keys {
+            if !self.ssh_host_keys.contains(&key) {
+                self.ssh_host_keys.push(key);
+            }
+        }
+        for certificate in other.tls_certificates {
+            if !self.tls_certificates.contains(&certificate) {
+                self.tls_certificates.push(certificate);
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::hash::Hash;
 use std::net::{IpAddr, Ipv4Addr};
 use uuid::Uuid;
 
+use crate::server::mac_vendors::{registry::MacVendorRegistry, types::is_randomized};
 use crate::server::subnets::types::base::Subnet;
 
 pub const ALL_INTERFACES_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
@@ -15,6 +16,16 @@ pub struct InterfaceBase {
     pub ip_address: IpAddr,
     pub mac_address: Option<MacAddress>,
     pub name: Option<String>,
+    /// Vendor of the MAC address, from user overrides or the OUI database
+    #[serde(default)]
+    pub mac_vendor: Option<String>,
+    /// MAC address is locally administered, ie randomized by a phone or laptop, so it doesn't
+    /// identify the vendor and may change between scans
+    #[serde(default)]
+    pub randomized_mac: bool,
+    /// 802.1Q tag of the interface, from its name (ie eth0.20), SNMP or set manually
+    #[serde(default)]
+    pub vlan_tag: Option<u16>,
 }
 
 impl InterfaceBase {
@@ -26,8 +37,41 @@ impl InterfaceBase {
             ip_address,
             mac_address: None,
             name: Some(subnet.base.name.clone()),
+            mac_vendor: None,
+            randomized_mac: false,
+            vlan_tag: None,
         }
     }
+
+    /// VLAN tag in an interface name, ie 20 in eth0.20, vlan20 or enp3s0.20@enp3s0
+    pub fn vlan_tag_from_name(name: &str) -> Option<u16> {
+        let name = name.split('@').next().unwrap_or(name).to_lowercase();
+
+        let tag = match name.rsplit_once('.') {
+            Some((_, tag)) => tag,
+            None => name.strip_prefix("vlan")?,
+        };
+
+        tag.parse::<u16>()
+            .ok()
+            .filter(|tag| (1..=4094).contains(tag))
+    }
+
+    /// Set vendor and randomized flag from the current MAC address, using the vendor overrides of
+    /// the interface's network
+    pub fn refresh_mac_details(&mut self, network_id: &Uuid) {
+        self.mac_vendor = self
+            .mac_address
+            .as_ref()
+            .and_then(|mac| MacVendorRegistry::lookup(network_id, mac));
+        self.randomized_mac = self.mac_address.as_ref().is_some_and(is_randomized);
+    }
+
+    /// MAC address which identifies the interface across scans, if it has one that isn't
+    /// randomized
+    pub fn stable_mac_address(&self) -> Option<MacAddress> {
+        self.mac_address.filter(|mac| !is_randomized(mac))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq)]
@@ -35,6 +79,9 @@ pub struct Interface {
     pub id: Uuid,
     #[serde(flatten)]
     pub base: InterfaceBase,
+    /// Discovery stopped seeing the address, see ReconciliationPolicy::MarkStale
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl Hash for Interface {
@@ -57,6 +104,7 @@ impl Interface {
         Self {
             id: Uuid::new_v4(),
             base,
+            stale: false,
         }
     }
 }
@@ -1,5 +1,7 @@
 pub mod api;
 pub mod base;
+pub mod devices;
+pub mod identity;
 pub mod interfaces;
 pub mod ports;
 pub mod targets;
@@ -35,6 +35,8 @@ pub enum TransportProtocol {
 pub struct Port {
     pub id: Uuid,
     pub base: PortBase,
+    /// Discovery stopped seeing the port open, see ReconciliationPolicy::MarkStale
+    pub stale: bool,
 }
 
 impl Hash for Port {
@@ -161,6 +163,7 @@ impl Port {
         Self {
             id: Uuid::new_v4(),
             base,
+            stale: false,
         }
     }
 }
@@ -170,6 +173,15 @@ impl PortBase {
         PortBase::Custom(PortConfig { number, protocol })
     }
 
+    /// Resolve a port config to the matching predefined port, if there is one
+    pub fn from_config(config: PortConfig) -> Self {
+        use strum::IntoEnumIterator;
+
+        PortBase::iter()
+            .find(|variant| !variant.is_custom() && variant.config() == config)
+            .unwrap_or(PortBase::Custom(config))
+    }
+
     pub fn new_tcp(number: u16) -> Self {
         PortBase::Custom(PortConfig {
             number,
@@ -381,7 +393,7 @@ impl Serialize for Port {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Port", 4)?;
+        let mut state = serializer.serialize_struct("Port", 5)?;
         state.serialize_field("id", &self.id)?;
 
         // Flatten the base fields directly into the Port
@@ -389,6 +401,7 @@ impl Serialize for Port {
         state.serialize_field("number", &config.number)?;
         state.serialize_field("protocol", &config.protocol)?;
         state.serialize_field("type", &self.base.id())?;
+        state.serialize_field("stale", &self.stale)?;
         state.end()
     }
 }
@@ -398,8 +411,6 @@ impl<'de> Deserialize<'de> for Port {
     where
         D: serde::Deserializer<'de>,
     {
-        use strum::IntoEnumIterator;
-
         #[derive(Deserialize)]
         struct TempPort {
             id: Uuid,
@@ -407,28 +418,21 @@ impl<'de> Deserialize<'de> for Port {
             protocol: TransportProtocol,
             #[serde(rename = "type")]
             _port_type: String,
+            #[serde(default)]
+            stale: bool,
         }
 
         let temp = TempPort::deserialize(deserializer)?;
 
-        // Try to find a matching predefined port
-        let base = PortBase::iter()
-            .find(|variant| {
-                // Skip Custom variants during iteration
-                if matches!(variant, PortBase::Custom(_)) {
-                    return false;
-                }
-                let config = variant.config();
-                config.number == temp.number && config.protocol == temp.protocol
-            })
-            .unwrap_or({
-                // If no predefined port matches, create a Custom variant
-                PortBase::Custom(PortConfig {
-                    number: temp.number,
-                    protocol: temp.protocol,
-                })
-            });
-
-        Ok(Port { id: temp.id, base })
+        let base = PortBase::from_config(PortConfig {
+            number: temp.number,
+            protocol: temp.protocol,
+        });
+
+        Ok(Port {
+            id: temp.id,
+            base,
+            stale: temp.stale,
+        })
     }
 }
@@ -13,6 +13,17 @@ use crate::server::shared::{
 #[serde(tag = "type", content = "details")]
 pub enum HostVirtualization {
     Proxmox(ProxmoxVirtualization),
+    Libvirt(LibvirtVirtualization),
+}
+
+impl HostVirtualization {
+    /// Service on the hypervisor host which manages the VM
+    pub fn service_id(&self) -> Uuid {
+        match self {
+            HostVirtualization::Proxmox(v) => v.service_id,
+            HostVirtualization::Libvirt(v) => v.service_id,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
@@ -22,6 +33,14 @@ pub struct ProxmoxVirtualization {
     pub service_id: Uuid,
 }
 
+#[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
+pub struct LibvirtVirtualization {
+    pub vm_name: Option<String>,
+    /// Libvirt domain UUID
+    pub vm_id: Option<String>,
+    pub service_id: Uuid,
+}
+
 impl HasId for HostVirtualization {
     fn id(&self) -> &'static str {
         self.into()
@@ -39,10 +58,16 @@ impl EntityMetadataProvider for HostVirtualization {
 
 impl TypeMetadataProvider for HostVirtualization {
     fn name(&self) -> &'static str {
-        "Proxmox"
+        match self {
+            HostVirtualization::Proxmox(_) => "Proxmox",
+            HostVirtualization::Libvirt(_) => "Libvirt",
+        }
     }
 
     fn description(&self) -> &'static str {
-        "A host running as a Proxmox VM"
+        match self {
+            HostVirtualization::Proxmox(_) => "A host running as a Proxmox VM",
+            HostVirtualization::Libvirt(_) => "A host running as a libvirt / KVM domain",
+        }
     }
 }
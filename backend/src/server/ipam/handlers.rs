@@ -0,0 +1,136 @@
+use crate::server::{
+    config::AppState,
+    ipam::types::{IpRange, SubnetIpam},
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(get_network_ipam))
+        .route("/subnets/:subnet_id", get(get_subnet_ipam))
+        .route("/subnets/:subnet_id/next-free", get(get_next_free_ip))
+        .route("/ranges", post(create_range))
+        .route("/ranges/:id", put(update_range))
+        .route("/ranges/:id", delete(delete_range))
+}
+
+async fn get_network_ipam(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<SubnetIpam>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.ipam_service;
+
+    let plans = service.get_network_ipam(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(plans)))
+}
+
+async fn get_subnet_ipam(
+    State(state): State<Arc<AppState>>,
+    Path(subnet_id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<SubnetIpam>>> {
+    let service = &state.services.ipam_service;
+
+    let plan = service
+        .get_subnet_ipam(&subnet_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Subnet '{}' not found", &subnet_id)))?;
+
+    Ok(Json(ApiResponse::success(plan)))
+}
+
+async fn get_next_free_ip(
+    State(state): State<Arc<AppState>>,
+    Path(subnet_id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Option<IpAddr>>>> {
+    let service = &state.services.ipam_service;
+
+    let plan = service
+        .get_subnet_ipam(&subnet_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Subnet '{}' not found", &subnet_id)))?;
+
+    Ok(Json(ApiResponse::success(plan.next_free_ip)))
+}
+
+async fn create_range(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<IpRange>,
+) -> ApiResult<Json<ApiResponse<IpRange>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "IP range validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.ipam_service;
+
+    let range = service
+        .create_range(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(range)))
+}
+
+async fn update_range(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<IpRange>,
+) -> ApiResult<Json<ApiResponse<IpRange>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "IP range validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.ipam_service;
+
+    let mut range = service
+        .get_range(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("IP range '{}' not found", &id)))?;
+
+    range.base = request.base;
+
+    let updated_range = service
+        .update_range(range)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(updated_range)))
+}
+
+async fn delete_range(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.ipam_service;
+
+    if service.get_range(&id).await?.is_none() {
+        return Err(ApiError::not_found(&format!(
+            "IP range '{}' not found",
+            &id
+        )));
+    }
+
+    service.delete_range(&id).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
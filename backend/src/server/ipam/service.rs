@@ -0,0 +1,164 @@
+use anyhow::{Result, anyhow};
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
+use uuid::Uuid;
+
+use crate::server::{
+    hosts::service::HostService,
+    ipam::{
+        storage::IpRangeStorage,
+        types::{IpRange, SubnetIpam, to_u128},
+    },
+    subnets::service::SubnetService,
+};
+
+pub struct IpamService {
+    storage: Arc<dyn IpRangeStorage>,
+    subnet_service: Arc<SubnetService>,
+    host_service: Arc<HostService>,
+}
+
+impl IpamService {
+    pub fn new(
+        storage: Arc<dyn IpRangeStorage>,
+        subnet_service: Arc<SubnetService>,
+        host_service: Arc<HostService>,
+    ) -> Self {
+        Self {
+            storage,
+            subnet_service,
+            host_service,
+        }
+    }
+
+    pub async fn get_subnet_ipam(&self, subnet_id: &Uuid) -> Result<Option<SubnetIpam>> {
+        let Some(subnet) = self.subnet_service.get_subnet(subnet_id).await? else {
+            return Ok(None);
+        };
+        let hosts = self
+            .host_service
+            .get_all_hosts(&subnet.base.network_id)
+            .await?;
+        let ranges = self.storage.get_for_subnet(subnet_id).await?;
+
+        Ok(Some(SubnetIpam::build(&subnet, &hosts, ranges)))
+    }
+
+    pub async fn get_network_ipam(&self, network_id: &Uuid) -> Result<Vec<SubnetIpam>> {
+        let subnets = self.subnet_service.get_all_subnets(network_id).await?;
+        let hosts = self.host_service.get_all_hosts(network_id).await?;
+
+        let subnet_ids: Vec<Uuid> = subnets.iter().map(|s| s.id).collect();
+        let mut ranges_by_subnet: HashMap<Uuid, Vec<IpRange>> = HashMap::new();
+        for range in self.storage.get_for_subnets(&subnet_ids).await? {
+            ranges_by_subnet
+                .entry(range.base.subnet_id)
+                .or_default()
+                .push(range);
+        }
+
+        Ok(subnets
+            .iter()
+            .map(|subnet| {
+                let ranges = ranges_by_subnet.remove(&subnet.id).unwrap_or_default();
+                SubnetIpam::build(subnet, &hosts, ranges)
+            })
+            .collect())
+    }
+
+    /// Lowest address in a subnet which isn't used, reserved or in a DHCP scope
+    pub async fn next_free_ip(&self, subnet_id: &Uuid) -> Result<Option<IpAddr>> {
+        Ok(self
+            .get_subnet_ipam(subnet_id)
+            .await?
+            .and_then(|ipam| ipam.next_free_ip))
+    }
+
+    pub async fn get_range(&self, id: &Uuid) -> Result<Option<IpRange>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn create_range(&self, range: IpRange) -> Result<IpRange> {
+        let range = if range.id == Uuid::nil() {
+            IpRange::new(range.base)
+        } else {
+            range
+        };
+
+        self.validate_range(&range).await?;
+        self.storage.create(&range).await?;
+
+        tracing::info!(
+            "Created {} {} - {} in subnet {}",
+            range.base.range_type,
+            range.base.start_ip,
+            range.base.end_ip,
+            range.base.subnet_id
+        );
+        Ok(range)
+    }
+
+    pub async fn update_range(&self, mut range: IpRange) -> Result<IpRange> {
+        self.validate_range(&range).await?;
+
+        range.updated_at = chrono::Utc::now();
+        self.storage.update(&range).await?;
+
+        tracing::info!(
+            "Updated {} {} - {} in subnet {}",
+            range.base.range_type,
+            range.base.start_ip,
+            range.base.end_ip,
+            range.base.subnet_id
+        );
+        Ok(range)
+    }
+
+    pub async fn delete_range(&self, id: &Uuid) -> Result<()> {
+        self.storage.delete(id).await?;
+        tracing::info!("Deleted IP range {}", id);
+        Ok(())
+    }
+
+    /// Ranges must sit inside their subnet and can't overlap other ranges in it
+    async fn validate_range(&self, range: &IpRange) -> Result<()> {
+        let subnet = self
+            .subnet_service
+            .get_subnet(&range.base.subnet_id)
+            .await?
+            .ok_or_else(|| anyhow!("Subnet '{}' not found", range.base.subnet_id))?;
+
+        let (start, end) = (range.base.start_ip, range.base.end_ip);
+        if !subnet.base.cidr.contains(&start) || !subnet.base.cidr.contains(&end) {
+            return Err(anyhow!(
+                "Range {} - {} is outside subnet {}",
+                start,
+                end,
+                subnet.base.cidr
+            ));
+        }
+        if to_u128(&start) > to_u128(&end) {
+            return Err(anyhow!("Range start {} is after its end {}", start, end));
+        }
+
+        // Reservations can be nested inside DHCP scopes, ie for addresses the DHCP server hands
+        // out statically, but otherwise ranges can't overlap
+        if let Some(overlapping) = self
+            .storage
+            .get_for_subnet(&subnet.id)
+            .await?
+            .into_iter()
+            .find(|r| r.id != range.id && r.overlaps(range) && !r.nests_with(range))
+        {
+            return Err(anyhow!(
+                "Range {} - {} overlaps {} {} - {}",
+                start,
+                end,
+                overlapping.base.range_type,
+                overlapping.base.start_ip,
+                overlapping.base.end_ip
+            ));
+        }
+
+        Ok(())
+    }
+}
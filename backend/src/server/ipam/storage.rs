@@ -0,0 +1,138 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::ipam::types::{IpRange, IpRangeBase, IpRangeType};
+
+#[async_trait]
+pub trait IpRangeStorage: Send + Sync {
+    async fn create(&self, range: &IpRange) -> Result<()>;
+    async fn update(&self, range: &IpRange) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<IpRange>>;
+    async fn get_for_subnet(&self, subnet_id: &Uuid) -> Result<Vec<IpRange>>;
+    async fn get_for_subnets(&self, subnet_ids: &[Uuid]) -> Result<Vec<IpRange>>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresIpRangeStorage {
+    pool: PgPool,
+}
+
+impl PostgresIpRangeStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IpRangeStorage for PostgresIpRangeStorage {
+    async fn create(&self, range: &IpRange) -> Result<()> {
+        let range_type_str: &'static str = range.base.range_type.into();
+
+        sqlx::query(
+            r#"
+            INSERT INTO ip_ranges (
+                id, subnet_id, range_type, start_ip, end_ip, host_id, note, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(range.id)
+        .bind(range.base.subnet_id)
+        .bind(range_type_str)
+        .bind(range.base.start_ip.to_string())
+        .bind(range.base.end_ip.to_string())
+        .bind(range.base.host_id)
+        .bind(&range.base.note)
+        .bind(range.created_at)
+        .bind(range.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, range: &IpRange) -> Result<()> {
+        let range_type_str: &'static str = range.base.range_type.into();
+
+        sqlx::query(
+            r#"
+            UPDATE ip_ranges SET
+                range_type = $2, start_ip = $3, end_ip = $4, host_id = $5, note = $6,
+                updated_at = $7
+            WHERE id = $1
+            "#,
+        )
+        .bind(range.id)
+        .bind(range_type_str)
+        .bind(range.base.start_ip.to_string())
+        .bind(range.base.end_ip.to_string())
+        .bind(range.base.host_id)
+        .bind(&range.base.note)
+        .bind(range.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<IpRange>> {
+        let row = sqlx::query("SELECT * FROM ip_ranges WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(row_to_ip_range(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_for_subnet(&self, subnet_id: &Uuid) -> Result<Vec<IpRange>> {
+        let rows = sqlx::query("SELECT * FROM ip_ranges WHERE subnet_id = $1 ORDER BY created_at")
+            .bind(subnet_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_ip_range).collect()
+    }
+
+    async fn get_for_subnets(&self, subnet_ids: &[Uuid]) -> Result<Vec<IpRange>> {
+        let rows =
+            sqlx::query("SELECT * FROM ip_ranges WHERE subnet_id = ANY($1) ORDER BY created_at")
+                .bind(subnet_ids)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter().map(row_to_ip_range).collect()
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM ip_ranges WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_ip_range(row: sqlx::postgres::PgRow) -> Result<IpRange, Error> {
+    let range_type: IpRangeType = serde_json::from_value(Value::String(row.get("range_type")))
+        .or(Err(Error::msg("Failed to deserialize range_type")))?;
+
+    Ok(IpRange {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: IpRangeBase {
+            subnet_id: row.get("subnet_id"),
+            range_type,
+            start_ip: row.get::<String, _>("start_ip").parse()?,
+            end_ip: row.get::<String, _>("end_ip").parse()?,
+            host_id: row.get("host_id"),
+            note: row.get("note"),
+        },
+    })
+}
@@ -0,0 +1,164 @@
+use serial_test::serial;
+use std::net::IpAddr;
+
+use crate::{
+    server::ipam::types::{IpRange, IpRangeBase, IpRangeType, IpamConflict, SubnetIpam},
+    tests::*,
+};
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_subnet_ipam() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(subnet_obj.clone())
+        .await
+        .unwrap();
+
+    let mut server = host(&network.id);
+    server.base.interfaces = vec![interface(&subnet_obj.id)];
+    let (server, _) = services
+        .host_service
+        .create_host_with_services(server, vec![])
+        .await
+        .unwrap();
+
+    let mut laptop = host(&network.id);
+    laptop.base.name = "Laptop".to_string();
+    laptop.base.interfaces = vec![interface(&subnet_obj.id)];
+    laptop.base.interfaces[0].base.ip_address = ip("192.168.1.101");
+    laptop.base.interfaces[0].base.mac_address = None;
+    let (laptop, _) = services
+        .host_service
+        .create_host_with_services(laptop, vec![])
+        .await
+        .unwrap();
+
+    let mut misconfigured = host(&network.id);
+    misconfigured.base.name = "Misconfigured".to_string();
+    misconfigured.base.interfaces = vec![interface(&subnet_obj.id)];
+    misconfigured.base.interfaces[0].base.ip_address = ip("10.0.0.5");
+    misconfigured.base.interfaces[0].base.mac_address = None;
+    let (misconfigured, _) = services
+        .host_service
+        .create_host_with_services(misconfigured, vec![])
+        .await
+        .unwrap();
+
+    let ipam = &services.ipam_service;
+    let range = |range_type, start: &str, end: &str| {
+        IpRange::new(IpRangeBase {
+            subnet_id: subnet_obj.id,
+            range_type,
+            start_ip: ip(start),
+            end_ip: ip(end),
+            host_id: None,
+            note: None,
+        })
+    };
+
+    ipam.create_range(range(IpRangeType::DhcpScope, "192.168.1.1", "192.168.1.50"))
+        .await
+        .unwrap();
+    let mut printer = range(IpRangeType::Reservation, "192.168.1.51", "192.168.1.51");
+    printer.base.note = Some("Printer".to_string());
+    ipam.create_range(printer).await.unwrap();
+    let mut server_reservation = range(IpRangeType::Reservation, "192.168.1.101", "192.168.1.101");
+    server_reservation.base.host_id = Some(server.id);
+    let server_reservation = ipam.create_range(server_reservation).await.unwrap();
+
+    // Ranges can't overlap or leave the subnet
+    assert!(
+        ipam.create_range(range(
+            IpRangeType::Reservation,
+            "192.168.1.50",
+            "192.168.1.55"
+        ))
+        .await
+        .is_err()
+    );
+    assert!(
+        ipam.create_range(range(IpRangeType::DhcpScope, "192.168.2.1", "192.168.2.50"))
+            .await
+            .is_err()
+    );
+
+    // Reservations can sit inside a DHCP scope, but not across its edge or on another reservation
+    ipam.create_range(range(
+        IpRangeType::Reservation,
+        "192.168.1.10",
+        "192.168.1.11",
+    ))
+    .await
+    .unwrap();
+    assert!(
+        ipam.create_range(range(
+            IpRangeType::Reservation,
+            "192.168.1.11",
+            "192.168.1.12"
+        ))
+        .await
+        .is_err()
+    );
+    assert!(
+        ipam.create_range(range(IpRangeType::DhcpScope, "192.168.1.5", "192.168.1.20"))
+            .await
+            .is_err()
+    );
+
+    let plan = ipam.get_subnet_ipam(&subnet_obj.id).await.unwrap().unwrap();
+    assert_eq!(plan.total, 254);
+    assert_eq!(plan.used, 2);
+    assert_eq!(plan.dhcp, 48);
+    assert_eq!(plan.reserved, 3);
+    assert_eq!(plan.free, 201);
+    assert_eq!(plan.next_free_ip, Some(ip("192.168.1.52")));
+    assert_eq!(plan.ranges.len(), 4);
+
+    let network_plans = ipam.get_network_ipam(&network.id).await.unwrap();
+    let network_plan = network_plans
+        .iter()
+        .find(|p| p.subnet_id == subnet_obj.id)
+        .unwrap();
+    assert_eq!(network_plan.ranges.len(), 4);
+
+    assert_eq!(plan.conflicts.len(), 2);
+    assert!(plan.conflicts.contains(&IpamConflict::OutsideSubnet {
+        ip_address: ip("10.0.0.5"),
+        host_id: misconfigured.id,
+        interface_id: misconfigured.base.interfaces[0].id,
+    }));
+    assert!(
+        plan.conflicts
+            .contains(&IpamConflict::ReservedForOtherHost {
+                ip_address: ip("192.168.1.101"),
+                host_id: laptop.id,
+                range_id: server_reservation.id,
+            })
+    );
+
+    // Hosts sharing an address are normally merged on create, so build the plan directly
+    let mut duplicate = laptop.clone();
+    duplicate.id = uuid::Uuid::new_v4();
+    let hosts = vec![server, laptop.clone(), duplicate.clone()];
+    let plan = SubnetIpam::build(&subnet_obj, &hosts, vec![]);
+    assert!(plan.conflicts.contains(&IpamConflict::DuplicateIp {
+        ip_address: ip("192.168.1.101"),
+        host_ids: vec![laptop.id, duplicate.id],
+    }));
+    assert_eq!(plan.used, 2);
+}
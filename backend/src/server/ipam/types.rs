@@ -0,0 +1,333 @@
+use chrono::{DateTime, Utc};
+use cidr::IpCidr;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use strum_macros::{Display, IntoStaticStr};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::server::{
+    hosts::types::base::Host, shared::types::api::deserialize_empty_string_as_none,
+    subnets::types::base::Subnet,
+};
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+pub enum IpRangeType {
+    /// Addresses handed out by a DHCP server
+    DhcpScope,
+    /// Addresses set aside, ie for static assignment
+    Reservation,
+}
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IpRangeBase {
+    pub subnet_id: Uuid,
+    pub range_type: IpRangeType,
+    pub start_ip: IpAddr,
+    /// Same as start_ip for a single address
+    pub end_ip: IpAddr,
+    /// Host a reservation is for. Other hosts using a reserved address are reported as conflicts.
+    #[serde(default)]
+    pub host_id: Option<Uuid>,
+    #[serde(default, deserialize_with = "deserialize_empty_string_as_none")]
+    #[validate(length(min = 0, max = 500))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpRange {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: IpRangeBase,
+}
+
+impl IpRange {
+    pub fn new(base: IpRangeBase) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        ip.is_ipv4() == self.base.start_ip.is_ipv4()
+            && (to_u128(&self.base.start_ip)..=to_u128(&self.base.end_ip)).contains(&to_u128(ip))
+    }
+
+    pub fn overlaps(&self, other: &IpRange) -> bool {
+        self.base.start_ip.is_ipv4() == other.base.start_ip.is_ipv4()
+            && to_u128(&self.base.start_ip) <= to_u128(&other.base.end_ip)
+            && to_u128(&other.base.start_ip) <= to_u128(&self.base.end_ip)
+    }
+
+    /// Whether one of the ranges is a reservation lying entirely inside the other, a DHCP scope
+    pub fn nests_with(&self, other: &IpRange) -> bool {
+        let inside = |reservation: &IpRange, scope: &IpRange| {
+            reservation.base.range_type == IpRangeType::Reservation
+                && scope.base.range_type == IpRangeType::DhcpScope
+                && scope.contains(&reservation.base.start_ip)
+                && scope.contains(&reservation.base.end_ip)
+        };
+
+        inside(self, other) || inside(other, self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+pub enum AddressState {
+    Free,
+    DhcpScope,
+    Reserved,
+    Used,
+}
+
+/// A run of consecutive addresses in the same state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBlock {
+    pub start_ip: IpAddr,
+    pub end_ip: IpAddr,
+    pub state: AddressState,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsedAddress {
+    pub ip_address: IpAddr,
+    pub host_id: Uuid,
+    pub interface_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum IpamConflict {
+    /// More than one host has an interface with the address
+    DuplicateIp {
+        ip_address: IpAddr,
+        host_ids: Vec<Uuid>,
+    },
+    /// An interface assigned to the subnet has an address outside its CIDR
+    OutsideSubnet {
+        ip_address: IpAddr,
+        host_id: Uuid,
+        interface_id: Uuid,
+    },
+    /// A host is using an address reserved for a different host
+    ReservedForOtherHost {
+        ip_address: IpAddr,
+        host_id: Uuid,
+        range_id: Uuid,
+    },
+}
+
+/// The address plan of a subnet: what's used by hosts, reserved, handed out by DHCP and free
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubnetIpam {
+    pub subnet_id: Uuid,
+    pub cidr: IpCidr,
+    /// Assignable addresses, excluding the network and broadcast addresses of IPv4 subnets
+    pub total: u64,
+    pub used: u64,
+    pub reserved: u64,
+    pub dhcp: u64,
+    pub free: u64,
+    /// Percentage of assignable addresses used by hosts
+    pub utilisation: f64,
+    pub next_free_ip: Option<IpAddr>,
+    pub used_addresses: Vec<UsedAddress>,
+    pub ranges: Vec<IpRange>,
+    pub blocks: Vec<AddressBlock>,
+    pub conflicts: Vec<IpamConflict>,
+}
+
+impl SubnetIpam {
+    pub fn build(subnet: &Subnet, hosts: &[Host], ranges: Vec<IpRange>) -> Self {
+        let cidr = subnet.base.cidr;
+        let (first, last) = assignable_range(&cidr);
+        let is_v4 = cidr.is_ipv4();
+
+        let mut used_addresses = Vec::new();
+        let mut conflicts = Vec::new();
+        for host in hosts {
+            for interface in host
+                .base
+                .interfaces
+                .iter()
+                .filter(|i| i.base.subnet_id == subnet.id)
+            {
+                let ip_address = interface.base.ip_address;
+                if !cidr.contains(&ip_address) {
+                    conflicts.push(IpamConflict::OutsideSubnet {
+                        ip_address,
+                        host_id: host.id,
+                        interface_id: interface.id,
+                    });
+                    continue;
+                }
+                used_addresses.push(UsedAddress {
+                    ip_address,
+                    host_id: host.id,
+                    interface_id: interface.id,
+                });
+            }
+        }
+        used_addresses.sort_by_key(|u| to_u128(&u.ip_address));
+
+        let mut hosts_by_ip: BTreeMap<u128, Vec<Uuid>> = BTreeMap::new();
+        for used in &used_addresses {
+            let host_ids = hosts_by_ip.entry(to_u128(&used.ip_address)).or_default();
+            if !host_ids.contains(&used.host_id) {
+                host_ids.push(used.host_id);
+            }
+        }
+        for (ip, host_ids) in &hosts_by_ip {
+            if host_ids.len() > 1 {
+                conflicts.push(IpamConflict::DuplicateIp {
+                    ip_address: from_u128(*ip, is_v4),
+                    host_ids: host_ids.clone(),
+                });
+            }
+        }
+
+        for range in ranges
+            .iter()
+            .filter(|r| r.base.range_type == IpRangeType::Reservation)
+        {
+            let Some(reserved_for) = range.base.host_id else {
+                continue;
+            };
+            for used in used_addresses
+                .iter()
+                .filter(|u| u.host_id != reserved_for && range.contains(&u.ip_address))
+            {
+                conflicts.push(IpamConflict::ReservedForOtherHost {
+                    ip_address: used.ip_address,
+                    host_id: used.host_id,
+                    range_id: range.id,
+                });
+            }
+        }
+
+        // Each interval claims addresses for a state, with the highest state winning
+        let mut intervals: Vec<(u128, u128, AddressState)> = hosts_by_ip
+            .keys()
+            .map(|ip| (*ip, *ip, AddressState::Used))
+            .collect();
+        intervals.extend(ranges.iter().map(|r| {
+            let state = match r.base.range_type {
+                IpRangeType::DhcpScope => AddressState::DhcpScope,
+                IpRangeType::Reservation => AddressState::Reserved,
+            };
+            (to_u128(&r.base.start_ip), to_u128(&r.base.end_ip), state)
+        }));
+        intervals.retain(|(start, end, _)| *start <= last && *end >= first);
+
+        let mut boundaries = vec![first, last.saturating_add(1)];
+        for (start, end, _) in &intervals {
+            boundaries.push((*start).max(first));
+            boundaries.push((*end).min(last).saturating_add(1));
+        }
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut blocks: Vec<(u128, u128, AddressState)> = Vec::new();
+        for window in boundaries.windows(2) {
+            let (start, end) = (window[0], window[1] - 1);
+            let state = intervals
+                .iter()
+                .filter(|(s, e, _)| *s <= start && start <= *e)
+                .map(|(_, _, state)| *state)
+                .max()
+                .unwrap_or(AddressState::Free);
+
+            match blocks.last_mut() {
+                Some(last_block) if last_block.2 == state && last_block.1 + 1 == start => {
+                    last_block.1 = end;
+                }
+                _ => blocks.push((start, end, state)),
+            }
+        }
+
+        let count = |state: AddressState| -> u64 {
+            blocks
+                .iter()
+                .filter(|(_, _, s)| *s == state)
+                .map(|(start, end, _)| block_size(*start, *end))
+                .fold(0, u64::saturating_add)
+        };
+        let total = block_size(first, last);
+        let used = count(AddressState::Used);
+
+        Self {
+            subnet_id: subnet.id,
+            cidr,
+            total,
+            used,
+            reserved: count(AddressState::Reserved),
+            dhcp: count(AddressState::DhcpScope),
+            free: count(AddressState::Free),
+            utilisation: if total > 0 {
+                used as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            },
+            next_free_ip: blocks
+                .iter()
+                .find(|(_, _, state)| *state == AddressState::Free)
+                .map(|(start, _, _)| from_u128(*start, is_v4)),
+            used_addresses,
+            ranges,
+            blocks: blocks
+                .into_iter()
+                .map(|(start, end, state)| AddressBlock {
+                    start_ip: from_u128(start, is_v4),
+                    end_ip: from_u128(end, is_v4),
+                    state,
+                    size: block_size(start, end),
+                })
+                .collect(),
+            conflicts,
+        }
+    }
+}
+
+/// First and last addresses hosts can be given, skipping the IPv4 network and broadcast addresses
+/// where the subnet is big enough to have them
+fn assignable_range(cidr: &IpCidr) -> (u128, u128) {
+    let first = to_u128(&cidr.first_address());
+    let last = to_u128(&cidr.last_address());
+
+    if cidr.is_ipv4() && cidr.network_length() <= 30 {
+        (first + 1, last - 1)
+    } else {
+        (first, last)
+    }
+}
+
+fn block_size(start: u128, end: u128) -> u64 {
+    u64::try_from(end - start + 1).unwrap_or(u64::MAX)
+}
+
+pub fn to_u128(ip: &IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u32::from(*ip) as u128,
+        IpAddr::V6(ip) => u128::from(*ip),
+    }
+}
+
+fn from_u128(value: u128, is_v4: bool) -> IpAddr {
+    if is_v4 {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(value))
+    }
+}
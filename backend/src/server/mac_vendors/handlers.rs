@@ -0,0 +1,134 @@
+use crate::server::{
+    config::AppState,
+    daemons::handlers::require_daemon,
+    mac_vendors::types::{MacVendorOverride, MacVendorOverrideBase},
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_override))
+        .route("/", get(get_all_overrides))
+        .route("/daemon", get(get_daemon_overrides))
+        .route("/:id", get(get_override))
+        .route("/:id", put(update_override))
+        .route("/:id", delete(delete_override))
+}
+
+async fn create_override(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<MacVendorOverrideBase>,
+) -> ApiResult<Json<ApiResponse<MacVendorOverride>>> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "MAC vendor override validation failed: {}",
+            validation_errors
+        )));
+    }
+
+    let service = &state.services.mac_vendor_service;
+
+    let created = service
+        .create_override(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(created)))
+}
+
+async fn get_all_overrides(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<MacVendorOverride>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.mac_vendor_service;
+
+    let overrides = service.get_all_overrides(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(overrides)))
+}
+
+/// Overrides of the requesting daemon's network. Only answered for registered daemons.
+async fn get_daemon_overrides(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<MacVendorOverride>>>> {
+    let daemon = require_daemon(&state, &params).await?;
+
+    let service = &state.services.mac_vendor_service;
+
+    let overrides = service.get_all_overrides(&daemon.base.network_id).await?;
+
+    Ok(Json(ApiResponse::success(overrides)))
+}
+
+async fn get_override(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<MacVendorOverride>>> {
+    let service = &state.services.mac_vendor_service;
+
+    match service.get_override(&id).await? {
+        Some(vendor_override) => Ok(Json(ApiResponse::success(vendor_override))),
+        None => Err(ApiError::not_found(&format!(
+            "MAC vendor override '{}'",
+            id
+        ))),
+    }
+}
+
+async fn update_override(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<MacVendorOverrideBase>,
+) -> ApiResult<Json<ApiResponse<MacVendorOverride>>> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "MAC vendor override validation failed: {}",
+            validation_errors
+        )));
+    }
+
+    let service = &state.services.mac_vendor_service;
+
+    let vendor_override = service
+        .get_override(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("MAC vendor override '{}'", id)))?;
+
+    let updated = service
+        .update_override(vendor_override, request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(updated)))
+}
+
+async fn delete_override(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.mac_vendor_service;
+
+    let vendor_override = service
+        .get_override(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("MAC vendor override '{}'", id)))?;
+
+    service.delete_override(&vendor_override).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
@@ -0,0 +1,7 @@
+pub mod handlers;
+pub mod registry;
+pub mod service;
+pub mod storage;
+#[cfg(test)]
+pub mod tests;
+pub mod types;
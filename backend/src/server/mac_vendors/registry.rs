@@ -0,0 +1,69 @@
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+use mac_address::MacAddress;
+use mac_oui::Oui;
+use uuid::Uuid;
+
+use crate::server::mac_vendors::types::{
+    MacVendorOverride, is_locally_administered, virtualization_vendor,
+};
+
+/// Loaded on first use, as vendors are looked up while matching each host
+static OUI_DB: OnceLock<Option<Oui>> = OnceLock::new();
+
+/// Vendor by OUI
+type VendorsByOui = HashMap<[u8; 3], String>;
+
+/// User overrides by network
+static OVERRIDES: RwLock<Option<HashMap<Uuid, VendorsByOui>>> = RwLock::new(None);
+
+pub struct MacVendorRegistry;
+
+impl MacVendorRegistry {
+    /// Replace the registered user overrides
+    pub fn set_overrides(overrides: &[MacVendorOverride]) {
+        let mut by_network: HashMap<Uuid, VendorsByOui> = HashMap::new();
+        for o in overrides {
+            if let Ok(oui) = o.base.oui_bytes() {
+                by_network
+                    .entry(o.base.network_id)
+                    .or_default()
+                    .insert(oui, o.base.vendor.clone());
+            }
+        }
+
+        *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = Some(by_network);
+    }
+
+    /// Vendor of a MAC address on a network from the network's user overrides, then the OUI
+    /// database. Locally administered addresses are only resolved by overrides and known
+    /// virtualization prefixes, as their OUI wasn't assigned to a vendor.
+    pub fn lookup(network_id: &Uuid, mac: &MacAddress) -> Option<String> {
+        let bytes = mac.bytes();
+        let oui = [bytes[0], bytes[1], bytes[2]];
+
+        let overridden = OVERRIDES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .and_then(|overrides| overrides.get(network_id)?.get(&oui).cloned());
+
+        if overridden.is_some() {
+            return overridden;
+        }
+
+        if is_locally_administered(mac) {
+            return virtualization_vendor(mac).map(str::to_string);
+        }
+
+        let oui_db = OUI_DB.get_or_init(|| Oui::default().ok()).as_ref()?;
+
+        match Oui::lookup_by_mac(oui_db, &mac.to_string()) {
+            Ok(Some(entry)) => Some(entry.company_name.clone()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::mac_vendors::{
+    registry::MacVendorRegistry,
+    storage::MacVendorOverrideStorage,
+    types::{MacVendorOverride, MacVendorOverrideBase},
+};
+
+pub struct MacVendorService {
+    storage: Arc<dyn MacVendorOverrideStorage>,
+}
+
+impl MacVendorService {
+    pub fn new(storage: Arc<dyn MacVendorOverrideStorage>) -> Self {
+        Self { storage }
+    }
+
+    /// Load overrides from storage into the vendor registry
+    pub async fn refresh_registry(&self) -> Result<()> {
+        let overrides = self.storage.get_all().await?;
+        MacVendorRegistry::set_overrides(&overrides);
+
+        tracing::debug!("Registered {} MAC vendor overrides", overrides.len());
+        Ok(())
+    }
+
+    pub async fn get_override(&self, id: &Uuid) -> Result<Option<MacVendorOverride>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn get_all_overrides(&self, network_id: &Uuid) -> Result<Vec<MacVendorOverride>> {
+        self.storage.get_for_network(network_id).await
+    }
+
+    pub async fn create_override(
+        &self,
+        mut base: MacVendorOverrideBase,
+    ) -> Result<MacVendorOverride> {
+        base.normalize()?;
+        self.ensure_oui_available(&base, None).await?;
+
+        let vendor_override = MacVendorOverride::new(base);
+        self.storage.create(&vendor_override).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Created MAC vendor override {} -> {}: {}",
+            vendor_override.base.oui,
+            vendor_override.base.vendor,
+            vendor_override.id
+        );
+        Ok(vendor_override)
+    }
+
+    pub async fn update_override(
+        &self,
+        mut vendor_override: MacVendorOverride,
+        mut base: MacVendorOverrideBase,
+    ) -> Result<MacVendorOverride> {
+        base.normalize()?;
+        self.ensure_oui_available(&base, Some(&vendor_override.id))
+            .await?;
+
+        vendor_override.base = base;
+        vendor_override.updated_at = chrono::Utc::now();
+
+        self.storage.update(&vendor_override).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Updated MAC vendor override {} -> {}: {}",
+            vendor_override.base.oui,
+            vendor_override.base.vendor,
+            vendor_override.id
+        );
+        Ok(vendor_override)
+    }
+
+    pub async fn delete_override(&self, vendor_override: &MacVendorOverride) -> Result<()> {
+        self.storage.delete(&vendor_override.id).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Deleted MAC vendor override {}: {}",
+            vendor_override.base.oui,
+            vendor_override.id
+        );
+        Ok(())
+    }
+
+    async fn ensure_oui_available(
+        &self,
+        base: &MacVendorOverrideBase,
+        current_id: Option<&Uuid>,
+    ) -> Result<()> {
+        match self.storage.get_by_oui(&base.network_id, &base.oui).await? {
+            Some(existing) if Some(&existing.id) != current_id => Err(anyhow!(
+                "An override for OUI {} already exists on this network",
+                base.oui
+            )),
+            _ => Ok(()),
+        }
+    }
+}
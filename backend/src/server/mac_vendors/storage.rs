@@ -0,0 +1,127 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::mac_vendors::types::{MacVendorOverride, MacVendorOverrideBase};
+
+#[async_trait]
+pub trait MacVendorOverrideStorage: Send + Sync {
+    async fn create(&self, vendor_override: &MacVendorOverride) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<MacVendorOverride>>;
+    async fn get_by_oui(&self, network_id: &Uuid, oui: &str) -> Result<Option<MacVendorOverride>>;
+    async fn get_all(&self) -> Result<Vec<MacVendorOverride>>;
+    async fn get_for_network(&self, network_id: &Uuid) -> Result<Vec<MacVendorOverride>>;
+    async fn update(&self, vendor_override: &MacVendorOverride) -> Result<()>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresMacVendorOverrideStorage {
+    pool: PgPool,
+}
+
+impl PostgresMacVendorOverrideStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MacVendorOverrideStorage for PostgresMacVendorOverrideStorage {
+    async fn create(&self, vendor_override: &MacVendorOverride) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO mac_vendor_overrides (id, network_id, oui, vendor, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(vendor_override.id)
+        .bind(vendor_override.base.network_id)
+        .bind(&vendor_override.base.oui)
+        .bind(&vendor_override.base.vendor)
+        .bind(vendor_override.created_at)
+        .bind(vendor_override.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<MacVendorOverride>> {
+        let row = sqlx::query("SELECT * FROM mac_vendor_overrides WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(row_to_override).transpose()
+    }
+
+    async fn get_by_oui(&self, network_id: &Uuid, oui: &str) -> Result<Option<MacVendorOverride>> {
+        let row =
+            sqlx::query("SELECT * FROM mac_vendor_overrides WHERE network_id = $1 AND oui = $2")
+                .bind(network_id)
+                .bind(oui)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(row_to_override).transpose()
+    }
+
+    async fn get_all(&self) -> Result<Vec<MacVendorOverride>> {
+        let rows = sqlx::query("SELECT * FROM mac_vendor_overrides ORDER BY network_id, oui")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_override).collect()
+    }
+
+    async fn get_for_network(&self, network_id: &Uuid) -> Result<Vec<MacVendorOverride>> {
+        let rows =
+            sqlx::query("SELECT * FROM mac_vendor_overrides WHERE network_id = $1 ORDER BY oui")
+                .bind(network_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter().map(row_to_override).collect()
+    }
+
+    async fn update(&self, vendor_override: &MacVendorOverride) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE mac_vendor_overrides SET network_id = $2, oui = $3, vendor = $4, updated_at = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(vendor_override.id)
+        .bind(vendor_override.base.network_id)
+        .bind(&vendor_override.base.oui)
+        .bind(&vendor_override.base.vendor)
+        .bind(vendor_override.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM mac_vendor_overrides WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_override(row: sqlx::postgres::PgRow) -> Result<MacVendorOverride, Error> {
+    Ok(MacVendorOverride {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: MacVendorOverrideBase {
+            network_id: row.get("network_id"),
+            oui: row.get("oui"),
+            vendor: row.get("vendor"),
+        },
+    })
+}
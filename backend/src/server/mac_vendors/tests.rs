@@ -0,0 +1,141 @@
+use mac_address::MacAddress;
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::{
+    server::mac_vendors::{
+        registry::MacVendorRegistry,
+        types::{MacVendorOverrideBase, is_randomized},
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_mac_vendor_overrides_and_randomized_macs() {
+    let (storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let other_network = services
+        .network_service
+        .create_network(crate::tests::network(&user.id))
+        .await
+        .unwrap();
+
+    // OUI is normalised, and a second override for the same OUI on the network is rejected
+    let vendor_override = services
+        .mac_vendor_service
+        .create_override(MacVendorOverrideBase {
+            network_id: network.id,
+            oui: "da-3f-10".to_string(),
+            vendor: " Lab Phones ".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(vendor_override.base.oui, "DA:3F:10");
+    assert_eq!(vendor_override.base.vendor, "Lab Phones");
+
+    let duplicate = services
+        .mac_vendor_service
+        .create_override(MacVendorOverrideBase {
+            network_id: network.id,
+            oui: "DA:3F:10".to_string(),
+            vendor: "Other".to_string(),
+        })
+        .await;
+    assert!(duplicate.is_err());
+
+    // Overrides only apply to their own network
+    let random_mac = MacAddress::new([0xda, 0x3f, 0x10, 0x20, 0x30, 0x40]);
+    assert_eq!(
+        MacVendorRegistry::lookup(&network.id, &random_mac),
+        Some("Lab Phones".to_string())
+    );
+    assert_eq!(
+        MacVendorRegistry::lookup(&other_network.id, &random_mac),
+        None
+    );
+    assert!(
+        services
+            .mac_vendor_service
+            .get_all_overrides(&other_network.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Docker and QEMU addresses are locally administered, but not randomized
+    let docker_mac = MacAddress::new([0x02, 0x42, 0xac, 0x11, 0x00, 0x02]);
+    let qemu_mac = MacAddress::new([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+    assert!(is_randomized(&random_mac));
+    assert!(!is_randomized(&docker_mac));
+    assert!(!is_randomized(&qemu_mac));
+    assert_eq!(
+        MacVendorRegistry::lookup(&other_network.id, &docker_mac),
+        Some("Docker".to_string())
+    );
+    assert_eq!(
+        MacVendorRegistry::lookup(&other_network.id, &qemu_mac),
+        Some("QEMU".to_string())
+    );
+
+    // Interfaces get vendor and randomized flag from their MAC address
+    let mut host1 = host(&network.id);
+    host1.base.interfaces[0].base.mac_address = Some(random_mac);
+    let (created1, _) = services
+        .host_service
+        .create_host_with_services(host1, vec![])
+        .await
+        .unwrap();
+    let interface = &created1.base.interfaces[0].base;
+    assert_eq!(interface.mac_vendor, Some("Lab Phones".to_string()));
+    assert!(interface.randomized_mac);
+
+    // A randomized MAC on a host with a different hostname doesn't identify the same host
+    let mut host2 = host(&network.id);
+    host2.base.hostname = Some("phone.local".to_string());
+    host2.base.interfaces[0].base.ip_address = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 101));
+    host2.base.interfaces[0].base.mac_address = Some(random_mac);
+    let (created2, _) = services
+        .host_service
+        .create_host_with_services(host2, vec![])
+        .await
+        .unwrap();
+    assert_ne!(created1.id, created2.id);
+
+    // A container's MAC isn't flagged as randomized
+    let mut container_host = host(&network.id);
+    container_host.base.hostname = Some("container.local".to_string());
+    container_host.base.interfaces[0].base.ip_address = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 102));
+    container_host.base.interfaces[0].base.mac_address = Some(docker_mac);
+    let (created_container, _) = services
+        .host_service
+        .create_host_with_services(container_host, vec![])
+        .await
+        .unwrap();
+    let interface = &created_container.base.interfaces[0].base;
+    assert_eq!(interface.mac_vendor, Some("Docker".to_string()));
+    assert!(!interface.randomized_mac);
+
+    // Deleting the override leaves the randomized MAC without a vendor
+    services
+        .mac_vendor_service
+        .delete_override(&vendor_override)
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .mac_vendor_overrides
+            .get_all()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(MacVendorRegistry::lookup(&network.id, &random_mac), None);
+}
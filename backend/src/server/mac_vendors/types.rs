@@ -0,0 +1,98 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use mac_address::MacAddress;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Vendor name to report for MAC addresses with a given OUI on a network, in place of the OUI
+/// database entry
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq, Eq)]
+pub struct MacVendorOverrideBase {
+    pub network_id: Uuid,
+    /// First three octets of the MAC address, ie "AA:BB:CC". "-" separators or none are accepted.
+    #[validate(length(min = 6, max = 8))]
+    pub oui: String,
+    #[validate(length(min = 1, max = 100))]
+    pub vendor: String,
+}
+
+impl MacVendorOverrideBase {
+    /// OUI as bytes, rejecting anything that isn't three hex octets
+    pub fn oui_bytes(&self) -> Result<[u8; 3]> {
+        parse_oui(&self.oui)
+    }
+
+    /// Canonical "AA:BB:CC" form
+    pub fn normalize(&mut self) -> Result<()> {
+        let [a, b, c] = self.oui_bytes()?;
+        self.oui = format!("{:02X}:{:02X}:{:02X}", a, b, c);
+        self.vendor = self.vendor.trim().to_string();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MacVendorOverride {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: MacVendorOverrideBase,
+}
+
+impl MacVendorOverride {
+    pub fn new(base: MacVendorOverrideBase) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+}
+
+fn parse_oui(oui: &str) -> Result<[u8; 3]> {
+    let hex: String = oui
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.'))
+        .collect();
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "'{}' is not a valid OUI, expected three hex octets ie \"AA:BB:CC\"",
+            oui
+        ));
+    }
+
+    let octet = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+    Ok([octet(0)?, octet(2)?, octet(4)?])
+}
+
+/// Locally administered prefixes assigned by virtualization platforms rather than randomized, with
+/// the platform they identify. Docker derives container MACs from their IP.
+const VIRTUALIZATION_PREFIXES: [(&[u8], &str); 2] =
+    [(&[0x02, 0x42], "Docker"), (&[0x52, 0x54, 0x00], "QEMU")];
+
+/// Whether a MAC address is locally administered rather than assigned by its manufacturer
+pub fn is_locally_administered(mac: &MacAddress) -> bool {
+    mac.bytes()[0] & 0x02 != 0
+}
+
+/// Virtualization platform which assigned a locally administered MAC address, if any
+pub fn virtualization_vendor(mac: &MacAddress) -> Option<&'static str> {
+    VIRTUALIZATION_PREFIXES
+        .iter()
+        .find(|(prefix, _)| mac.bytes().starts_with(prefix))
+        .map(|(_, vendor)| *vendor)
+}
+
+/// Whether a MAC address is randomized. Phones and laptops use locally administered addresses as
+/// randomized, per-network addresses, so their OUI doesn't identify a vendor and they may change
+/// between scans. Addresses assigned by virtualization platforms are locally administered too, but
+/// stay with their container or VM.
+pub fn is_randomized(mac: &MacAddress) -> bool {
+    is_locally_administered(mac) && virtualization_vendor(mac).is_none()
+}
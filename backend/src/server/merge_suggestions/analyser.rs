@@ -0,0 +1,215 @@
+use mac_address::MacAddress;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use crate::server::{
+    hosts::types::base::Host,
+    merge_suggestions::types::{MergeEvidence, MergeSuggestionBase, MergeSuggestionStatus},
+    services::types::{base::Service, definitions::ServiceDefinition},
+};
+
+/// Suggestions scoring below this aren't worth a user's attention
+pub const MIN_MERGE_SCORE: u32 = 50;
+
+/// Services need to overlap this much to count, as most hosts share a few common ones
+const MIN_SHARED_SERVICES: usize = 2;
+
+const INVALID_MACS: [[u8; 6]; 2] = [[0x00; 6], [0xFF; 6]];
+
+/// Finds hosts in a network which are likely the same machine, ie seen through a VPN and a LAN,
+/// or before and after a DHCP lease or MAC address changed
+pub struct HostMergeAnalyser;
+
+impl HostMergeAnalyser {
+    /// Score pairs of hosts which share evidence, returning the pairs worth merging, best first. Hosts which both
+    /// run a daemon are never suggested, as they can't be consolidated.
+    pub fn analyse(
+        hosts: &[Host],
+        services: &[Service],
+        daemon_host_ids: &[Uuid],
+    ) -> Vec<MergeSuggestionBase> {
+        let mut services_by_host: HashMap<Uuid, Vec<&Service>> = HashMap::new();
+        for service in services {
+            services_by_host
+                .entry(service.base.host_id)
+                .or_default()
+                .push(service);
+        }
+
+        // Only hosts sharing at least one identifying key can reach the minimum score, as shared
+        // services alone never do, so those are the only pairs worth scoring
+        let mut hosts_by_key: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, host) in hosts.iter().enumerate() {
+            for key in match_keys(host) {
+                hosts_by_key.entry(key).or_default().push(i);
+            }
+        }
+
+        let mut pairs = BTreeSet::new();
+        for indices in hosts_by_key.values() {
+            for (n, &i) in indices.iter().enumerate() {
+                for &j in &indices[n + 1..] {
+                    pairs.insert((i.min(j), i.max(j)));
+                }
+            }
+        }
+
+        let mut suggestions = Vec::new();
+        for (i, j) in pairs {
+            let (host, other) = (&hosts[i], &hosts[j]);
+            if daemon_host_ids.contains(&host.id) && daemon_host_ids.contains(&other.id) {
+                continue;
+            }
+
+            let evidence = Self::evidence(
+                host,
+                services_by_host.get(&host.id).map_or(&[][..], |s| s),
+                other,
+                services_by_host.get(&other.id).map_or(&[][..], |s| s),
+            );
+            let score = evidence.iter().map(|e| e.weight()).sum();
+
+            if score >= MIN_MERGE_SCORE {
+                let (host_id, other_host_id) = if host.id < other.id {
+                    (host.id, other.id)
+                } else {
+                    (other.id, host.id)
+                };
+
+                suggestions.push(MergeSuggestionBase {
+                    network_id: host.base.network_id,
+                    host_id,
+                    other_host_id,
+                    score,
+                    evidence,
+                    status: MergeSuggestionStatus::Pending,
+                });
+            }
+        }
+
+        suggestions.sort_by(|a, b| b.score.cmp(&a.score));
+        suggestions
+    }
+
+    /// What two hosts have in common
+    pub fn evidence(
+        host: &Host,
+        host_services: &[&Service],
+        other: &Host,
+        other_services: &[&Service],
+    ) -> Vec<MergeEvidence> {
+        let (identity, other_identity) = (&host.base.identity, &other.base.identity);
+        let mut evidence = Vec::new();
+
+        if let Some(fingerprint) = identity
+            .ssh_host_keys
+            .iter()
+            .find(|k| other_identity.ssh_host_keys.contains(k))
+        {
+            evidence.push(MergeEvidence::SshHostKey {
+                fingerprint: fingerprint.clone(),
+            });
+        }
+
+        if let Some(fingerprint) = identity
+            .tls_certificates
+            .iter()
+            .find(|c| other_identity.tls_certificates.contains(c))
+        {
+            evidence.push(MergeEvidence::TlsCertificate {
+                fingerprint: fingerprint.clone(),
+            });
+        }
+
+        if let (Some(name), Some(other_name)) =
+            (&identity.snmp_sys_name, &other_identity.snmp_sys_name)
+            && name.eq_ignore_ascii_case(other_name)
+        {
+            evidence.push(MergeEvidence::SnmpSysName { name: name.clone() });
+        }
+
+        if let (Some(hostname), Some(other_hostname)) = (
+            short_hostname(&host.base.hostname),
+            short_hostname(&other.base.hostname),
+        ) && hostname == other_hostname
+        {
+            evidence.push(MergeEvidence::Hostname { hostname });
+        }
+
+        let other_macs = stable_macs(other);
+        if let Some(mac_address) = stable_macs(host)
+            .into_iter()
+            .find(|m| other_macs.contains(m))
+        {
+            evidence.push(MergeEvidence::MacAddress { mac_address });
+        }
+
+        let mut shared_services: Vec<String> = host_services
+            .iter()
+            .filter(|s| !s.base.service_definition.is_generic())
+            .map(|s| s.base.service_definition.name().to_string())
+            .filter(|name| {
+                other_services
+                    .iter()
+                    .any(|o| o.base.service_definition.name() == name)
+            })
+            .collect();
+        shared_services.sort();
+        shared_services.dedup();
+        if shared_services.len() >= MIN_SHARED_SERVICES {
+            evidence.push(MergeEvidence::SharedServices {
+                services: shared_services,
+            });
+        }
+
+        evidence
+    }
+}
+
+/// Keys which any evidence worth a suggestion has in common, each prefixed by its kind so that
+/// different kinds of evidence never collide
+fn match_keys(host: &Host) -> Vec<String> {
+    let identity = &host.base.identity;
+    let mut keys: Vec<String> = identity
+        .ssh_host_keys
+        .iter()
+        .map(|k| format!("ssh:{}", k))
+        .chain(
+            identity
+                .tls_certificates
+                .iter()
+                .map(|c| format!("tls:{}", c)),
+        )
+        .chain(
+            identity
+                .snmp_sys_name
+                .iter()
+                .map(|n| format!("snmp:{}", n.to_ascii_lowercase())),
+        )
+        .chain(short_hostname(&host.base.hostname).map(|h| format!("hostname:{}", h)))
+        .chain(stable_macs(host).into_iter().map(|m| format!("mac:{}", m)))
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Lowercase hostname without its domain, so "nas" and "nas.lan" match
+fn short_hostname(hostname: &Option<String>) -> Option<String> {
+    hostname
+        .as_deref()
+        .and_then(|h| h.split('.').next())
+        .map(|h| h.trim().to_lowercase())
+        .filter(|h| !h.is_empty() && h != "localhost")
+}
+
+/// MAC addresses which identify the hardware, rather than being randomized per network
+fn stable_macs(host: &Host) -> Vec<MacAddress> {
+    host.base
+        .interfaces
+        .iter()
+        .filter(|i| !i.base.randomized_mac)
+        .filter_map(|i| i.base.mac_address)
+        .filter(|mac| !INVALID_MACS.contains(&mac.bytes()))
+        .collect()
+}
@@ -0,0 +1,85 @@
+use crate::server::{
+    config::AppState,
+    merge_suggestions::types::{
+        AcceptMergeSuggestionsRequest, MergeSuggestion, MergeSuggestionResult,
+    },
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{get, post},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(get_all_suggestions))
+        .route("/analyse", post(analyse_network))
+        .route("/accept", post(accept_suggestions))
+        .route("/:id/dismiss", post(dismiss_suggestion))
+}
+
+fn network_id_param(params: &HashMap<String, String>) -> Result<Uuid, ApiError> {
+    params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))
+}
+
+async fn get_all_suggestions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<MergeSuggestion>>>> {
+    let network_id = network_id_param(&params)?;
+
+    let service = &state.services.merge_suggestion_service;
+
+    let suggestions = service.get_all_suggestions(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(suggestions)))
+}
+
+async fn analyse_network(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<MergeSuggestion>>>> {
+    let network_id = network_id_param(&params)?;
+
+    let service = &state.services.merge_suggestion_service;
+
+    let suggestions = service.analyse_network(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(suggestions)))
+}
+
+async fn accept_suggestions(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AcceptMergeSuggestionsRequest>,
+) -> ApiResult<Json<ApiResponse<Vec<MergeSuggestionResult>>>> {
+    let service = &state.services.merge_suggestion_service;
+
+    let results = service.accept_suggestions(&request.suggestion_ids).await;
+
+    Ok(Json(ApiResponse::success(results)))
+}
+
+async fn dismiss_suggestion(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<MergeSuggestion>>> {
+    let service = &state.services.merge_suggestion_service;
+
+    if service.get_suggestion(&id).await?.is_none() {
+        return Err(ApiError::not_found(&format!(
+            "Merge suggestion '{}' not found",
+            &id
+        )));
+    }
+
+    let suggestion = service.dismiss_suggestion(&id).await?;
+
+    Ok(Json(ApiResponse::success(suggestion)))
+}
@@ -0,0 +1,7 @@
+pub mod analyser;
+pub mod handlers;
+pub mod service;
+pub mod storage;
+#[cfg(test)]
+pub mod tests;
+pub mod types;
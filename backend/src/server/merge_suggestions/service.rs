@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    daemons::service::DaemonService,
+    hosts::{service::HostService, types::base::Host},
+    merge_suggestions::{
+        analyser::HostMergeAnalyser,
+        storage::MergeSuggestionStorage,
+        types::{MergeOutcome, MergeSuggestion, MergeSuggestionResult, MergeSuggestionStatus},
+    },
+    services::service::ServiceService,
+};
+
+pub struct MergeSuggestionService {
+    storage: Arc<dyn MergeSuggestionStorage>,
+    host_service: Arc<HostService>,
+    service_service: Arc<ServiceService>,
+    daemon_service: Arc<DaemonService>,
+}
+
+impl MergeSuggestionService {
+    pub fn new(
+        storage: Arc<dyn MergeSuggestionStorage>,
+        host_service: Arc<HostService>,
+        service_service: Arc<ServiceService>,
+        daemon_service: Arc<DaemonService>,
+    ) -> Self {
+        Self {
+            storage,
+            host_service,
+            service_service,
+            daemon_service,
+        }
+    }
+
+    /// Re-score every pair of hosts in a network. Dismissed suggestions stay dismissed unless their
+    /// evidence changed, and pending suggestions whose evidence went away are removed.
+    pub async fn analyse_network(&self, network_id: &Uuid) -> Result<Vec<MergeSuggestion>> {
+        let hosts = self.host_service.get_all_hosts(network_id).await?;
+        let services = self.service_service.get_all_services(network_id).await?;
+        let daemon_host_ids: Vec<Uuid> = self
+            .daemon_service
+            .get_all_daemons(network_id)
+            .await?
+            .iter()
+            .map(|d| d.base.host_id)
+            .collect();
+
+        let found = HostMergeAnalyser::analyse(&hosts, &services, &daemon_host_ids);
+        let existing = self.storage.get_all(network_id).await?;
+
+        for base in &found {
+            match existing.iter().find(|s| {
+                s.base.host_id == base.host_id && s.base.other_host_id == base.other_host_id
+            }) {
+                Some(suggestion) if suggestion.base.evidence == base.evidence => {}
+                Some(suggestion) => {
+                    let mut suggestion = suggestion.clone();
+                    suggestion.base.score = base.score;
+                    suggestion.base.evidence = base.evidence.clone();
+                    suggestion.base.status = MergeSuggestionStatus::Pending;
+                    suggestion.updated_at = chrono::Utc::now();
+                    self.storage.update(&suggestion).await?;
+                }
+                None => {
+                    self.storage
+                        .create(&MergeSuggestion::new(base.clone()))
+                        .await?;
+                }
+            }
+        }
+
+        for stale in existing.iter().filter(|s| {
+            s.base.status == MergeSuggestionStatus::Pending
+                && !found
+                    .iter()
+                    .any(|f| f.host_id == s.base.host_id && f.other_host_id == s.base.other_host_id)
+        }) {
+            self.storage.delete(&stale.id).await?;
+        }
+
+        tracing::info!(
+            "Analysed {} hosts in network {} for duplicates, found {} merge suggestions",
+            hosts.len(),
+            network_id,
+            found.len()
+        );
+        self.get_all_suggestions(network_id).await
+    }
+
+    pub async fn get_suggestion(&self, id: &Uuid) -> Result<Option<MergeSuggestion>> {
+        self.storage.get_by_id(id).await
+    }
+
+    /// Suggestions for a network, highest scoring first
+    pub async fn get_all_suggestions(&self, network_id: &Uuid) -> Result<Vec<MergeSuggestion>> {
+        self.storage.get_all(network_id).await
+    }
+
+    pub async fn dismiss_suggestion(&self, id: &Uuid) -> Result<MergeSuggestion> {
+        let mut suggestion = self
+            .get_suggestion(id)
+            .await?
+            .ok_or_else(|| anyhow!("Merge suggestion {} not found", id))?;
+
+        suggestion.base.status = MergeSuggestionStatus::Dismissed;
+        suggestion.updated_at = chrono::Utc::now();
+        self.storage.update(&suggestion).await?;
+
+        Ok(suggestion)
+    }
+
+    /// Consolidate the hosts of each suggestion, in order, reporting what happened to each.
+    /// Consolidation can't be rolled back, so a suggestion that fails doesn't stop the rest of the
+    /// batch.
+    pub async fn accept_suggestions(&self, ids: &[Uuid]) -> Vec<MergeSuggestionResult> {
+        let mut results = Vec::new();
+
+        for id in ids {
+            let outcome = match self.accept_suggestion(id).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    tracing::warn!("Failed to accept merge suggestion {}: {}", id, e);
+                    MergeOutcome::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            };
+
+            results.push(MergeSuggestionResult {
+                suggestion_id: *id,
+                outcome,
+            });
+        }
+
+        results
+    }
+
+    async fn accept_suggestion(&self, id: &Uuid) -> Result<MergeOutcome> {
+        // Suggestions are removed along with their consolidated hosts
+        let Some(suggestion) = self.get_suggestion(id).await? else {
+            return Ok(MergeOutcome::Skipped {
+                reason: "Suggestion no longer exists".to_string(),
+            });
+        };
+
+        let (Some(host), Some(other_host)) = (
+            self.host_service.get_host(&suggestion.base.host_id).await?,
+            self.host_service
+                .get_host(&suggestion.base.other_host_id)
+                .await?,
+        ) else {
+            return Ok(MergeOutcome::Skipped {
+                reason: "One of the hosts no longer exists".to_string(),
+            });
+        };
+
+        let (destination_host, other_host) = self.merge_order(host, other_host).await?;
+        let host = self
+            .host_service
+            .consolidate_hosts(destination_host, other_host)
+            .await?;
+
+        Ok(MergeOutcome::Merged {
+            host: Box::new(host),
+        })
+    }
+
+    /// Hosts with a daemon can't be consolidated into another host, otherwise the older host is
+    /// kept so its id stays stable
+    async fn merge_order(&self, host: Host, other_host: Host) -> Result<(Host, Host)> {
+        let daemon_service = &self.daemon_service;
+        let host_has_daemon = daemon_service.get_host_daemon(&host.id).await?.is_some();
+        let other_has_daemon = daemon_service
+            .get_host_daemon(&other_host.id)
+            .await?
+            .is_some();
+
+        if other_has_daemon || (!host_has_daemon && other_host.created_at < host.created_at) {
+            Ok((other_host, host))
+        } else {
+            Ok((host, other_host))
+        }
+    }
+}
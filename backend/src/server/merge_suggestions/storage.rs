@@ -0,0 +1,134 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::merge_suggestions::types::{
+    MergeEvidence, MergeSuggestion, MergeSuggestionBase, MergeSuggestionStatus,
+};
+
+#[async_trait]
+pub trait MergeSuggestionStorage: Send + Sync {
+    async fn create(&self, suggestion: &MergeSuggestion) -> Result<()>;
+    async fn update(&self, suggestion: &MergeSuggestion) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<MergeSuggestion>>;
+    /// Suggestions for a network, highest scoring first
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<MergeSuggestion>>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresMergeSuggestionStorage {
+    pool: PgPool,
+}
+
+impl PostgresMergeSuggestionStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MergeSuggestionStorage for PostgresMergeSuggestionStorage {
+    async fn create(&self, suggestion: &MergeSuggestion) -> Result<()> {
+        let evidence_json = serde_json::to_value(&suggestion.base.evidence)?;
+        let status_str: &'static str = suggestion.base.status.into();
+
+        sqlx::query(
+            r#"
+            INSERT INTO host_merge_suggestions (
+                id, network_id, host_id, other_host_id, score, evidence, status,
+                created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(suggestion.id)
+        .bind(suggestion.base.network_id)
+        .bind(suggestion.base.host_id)
+        .bind(suggestion.base.other_host_id)
+        .bind(suggestion.base.score as i32)
+        .bind(evidence_json)
+        .bind(status_str)
+        .bind(suggestion.created_at)
+        .bind(suggestion.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, suggestion: &MergeSuggestion) -> Result<()> {
+        let evidence_json = serde_json::to_value(&suggestion.base.evidence)?;
+        let status_str: &'static str = suggestion.base.status.into();
+
+        sqlx::query(
+            r#"
+            UPDATE host_merge_suggestions SET
+                score = $2, evidence = $3, status = $4, updated_at = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(suggestion.id)
+        .bind(suggestion.base.score as i32)
+        .bind(evidence_json)
+        .bind(status_str)
+        .bind(suggestion.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<MergeSuggestion>> {
+        let row = sqlx::query("SELECT * FROM host_merge_suggestions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(row_to_merge_suggestion(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<MergeSuggestion>> {
+        let rows = sqlx::query(
+            "SELECT * FROM host_merge_suggestions WHERE network_id = $1 ORDER BY score DESC, created_at",
+        )
+        .bind(network_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_merge_suggestion).collect()
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM host_merge_suggestions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_merge_suggestion(row: sqlx::postgres::PgRow) -> Result<MergeSuggestion, Error> {
+    let evidence: Vec<MergeEvidence> = serde_json::from_value(row.get::<Value, _>("evidence"))
+        .or(Err(Error::msg("Failed to deserialize evidence")))?;
+    let status: MergeSuggestionStatus = serde_json::from_value(Value::String(row.get("status")))
+        .or(Err(Error::msg("Failed to deserialize status")))?;
+
+    Ok(MergeSuggestion {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: MergeSuggestionBase {
+            network_id: row.get("network_id"),
+            host_id: row.get("host_id"),
+            other_host_id: row.get("other_host_id"),
+            score: row.get::<i32, _>("score") as u32,
+            evidence,
+            status,
+        },
+    })
+}
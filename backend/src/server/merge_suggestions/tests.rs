@@ -0,0 +1,133 @@
+use mac_address::MacAddress;
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::{
+    server::{
+        hosts::types::base::Host,
+        merge_suggestions::types::{MergeEvidence, MergeOutcome, MergeSuggestionStatus},
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_merge_suggestions() {
+    let (_storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let subnet = services
+        .subnet_service
+        .create_subnet(subnet(&network.id))
+        .await
+        .unwrap();
+
+    let host_on = |last_octet: u8, hostname: &str| -> Host {
+        let mut host = host(&network.id);
+        host.base.hostname = Some(hostname.to_string());
+        host.base.interfaces = vec![interface(&subnet.id)];
+        host.base.interfaces[0].base.ip_address =
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, last_octet));
+        host.base.interfaces[0].base.mac_address =
+            Some(MacAddress::new([0x00, 0x11, 0x22, 0x33, 0x44, last_octet]));
+        host
+    };
+
+    // The same machine seen on two addresses, sharing a host key and short hostname
+    let mut nas = host_on(10, "nas.lan");
+    nas.base.identity.ssh_host_keys = vec!["SHA256:nas-key".to_string()];
+    let mut nas_vpn = host_on(11, "NAS");
+    nas_vpn.base.identity.ssh_host_keys = vec!["SHA256:nas-key".to_string()];
+
+    // A shared certificate alone isn't enough
+    let mut web1 = host_on(20, "web1");
+    web1.base.identity.tls_certificates = vec!["aa".to_string()];
+    let mut web2 = host_on(21, "web2");
+    web2.base.identity.tls_certificates = vec!["aa".to_string()];
+
+    let mut created = Vec::new();
+    for host in [nas, nas_vpn, web1, web2] {
+        let (host, _) = services
+            .host_service
+            .create_host_with_services(host, vec![])
+            .await
+            .unwrap();
+        created.push(host);
+    }
+    let (nas, nas_vpn) = (&created[0], &created[1]);
+
+    let suggestions = services
+        .merge_suggestion_service
+        .analyse_network(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(suggestions.len(), 1);
+
+    let suggestion = &suggestions[0];
+    let mut pair = [nas.id, nas_vpn.id];
+    pair.sort();
+    assert_eq!(
+        [suggestion.base.host_id, suggestion.base.other_host_id],
+        pair
+    );
+    assert_eq!(suggestion.base.score, 90);
+    assert!(suggestion.base.evidence.contains(&MergeEvidence::Hostname {
+        hostname: "nas".to_string()
+    }));
+    assert!(
+        suggestion
+            .base
+            .evidence
+            .iter()
+            .any(|e| matches!(e, MergeEvidence::SshHostKey { .. }))
+    );
+
+    // Dismissed suggestions aren't reopened while their evidence is unchanged
+    services
+        .merge_suggestion_service
+        .dismiss_suggestion(&suggestion.id)
+        .await
+        .unwrap();
+    let suggestions = services
+        .merge_suggestion_service
+        .analyse_network(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].base.status, MergeSuggestionStatus::Dismissed);
+
+    // Accepting consolidates into the older host
+    let results = services
+        .merge_suggestion_service
+        .accept_suggestions(&[suggestions[0].id, suggestions[0].id])
+        .await;
+    assert_eq!(results.len(), 2);
+    let MergeOutcome::Merged { host: merged } = &results[0].outcome else {
+        panic!("Expected a merge, got {:?}", results[0].outcome);
+    };
+    assert_eq!(merged.id, nas.id);
+    assert_eq!(merged.base.interfaces.len(), 2);
+
+    // The suggestion went with the consolidated host
+    assert!(matches!(results[1].outcome, MergeOutcome::Skipped { .. }));
+    assert!(
+        services
+            .host_service
+            .get_host(&nas_vpn.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let suggestions = services
+        .merge_suggestion_service
+        .get_all_suggestions(&network.id)
+        .await
+        .unwrap();
+    assert!(suggestions.is_empty());
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use mac_address::MacAddress;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, IntoStaticStr};
+use uuid::Uuid;
+
+use crate::server::hosts::types::base::Host;
+
+/// Something two hosts have in common which suggests they are the same machine
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MergeEvidence {
+    SshHostKey { fingerprint: String },
+    TlsCertificate { fingerprint: String },
+    SnmpSysName { name: String },
+    Hostname { hostname: String },
+    MacAddress { mac_address: MacAddress },
+    SharedServices { services: Vec<String> },
+}
+
+impl MergeEvidence {
+    /// How much the evidence contributes to a suggestion's score
+    pub fn weight(&self) -> u32 {
+        match self {
+            // Host keys are generated per machine, and are rarely copied between them
+            MergeEvidence::SshHostKey { .. } => 60,
+            MergeEvidence::MacAddress { .. } => 50,
+            // Certificates and names are often shared by load balanced or cloned machines
+            MergeEvidence::TlsCertificate { .. } => 40,
+            MergeEvidence::SnmpSysName { .. } => 40,
+            MergeEvidence::Hostname { .. } => 30,
+            MergeEvidence::SharedServices { services } => (10 * services.len() as u32).min(30),
+        }
+    }
+}
+
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+pub enum MergeSuggestionStatus {
+    #[default]
+    Pending,
+    /// Rejected by a user, so not suggested again while the evidence is unchanged
+    Dismissed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSuggestionBase {
+    pub network_id: Uuid,
+    /// The pair is ordered by id, so each pair of hosts has at most one suggestion
+    pub host_id: Uuid,
+    pub other_host_id: Uuid,
+    pub score: u32,
+    pub evidence: Vec<MergeEvidence>,
+    #[serde(default)]
+    pub status: MergeSuggestionStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSuggestion {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: MergeSuggestionBase,
+}
+
+impl MergeSuggestion {
+    pub fn new(base: MergeSuggestionBase) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptMergeSuggestionsRequest {
+    pub suggestion_ids: Vec<Uuid>,
+}
+
+/// What happened to one suggestion of an accepted batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum MergeOutcome {
+    /// The suggestion's hosts were consolidated into the host
+    Merged {
+        host: Box<Host>,
+    },
+    /// The suggestion or one of its hosts was removed, ie by an earlier merge in the batch
+    Skipped {
+        reason: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeSuggestionResult {
+    pub suggestion_id: Uuid,
+    #[serde(flatten)]
+    pub outcome: MergeOutcome,
+}
@@ -1,11 +1,22 @@
 pub mod config;
+pub mod custom_fields;
 pub mod daemons;
 pub mod discovery;
+pub mod entity_changes;
 pub mod groups;
 pub mod hosts;
+pub mod ipam;
+pub mod mac_vendors;
+pub mod merge_suggestions;
 pub mod networks;
+pub mod reconciliation;
+pub mod scan_evidence;
+pub mod service_definitions;
 pub mod services;
 pub mod shared;
+pub mod snapshots;
 pub mod subnets;
 pub mod topology;
 pub mod users;
+pub mod vlans;
+pub mod vulnerabilities;
@@ -0,0 +1,5 @@
+pub mod service;
+pub mod storage;
+#[cfg(test)]
+pub mod tests;
+pub mod types;
@@ -0,0 +1,269 @@
+use anyhow::Result;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use strum::IntoDiscriminant;
+
+use crate::server::{
+    discovery::types::base::{EntitySource, EntitySourceDiscriminants},
+    hosts::{
+        service::HostService,
+        types::{base::Host, interfaces::Interface, ports::Port},
+    },
+    reconciliation::{
+        storage::ObservationStateStorage,
+        types::{HostObservation, ObservationState, ReconciliationPolicy},
+    },
+    services::{service::ServiceService, types::base::Service},
+};
+
+pub struct ReconciliationService {
+    storage: Arc<dyn ObservationStateStorage>,
+    host_service: Arc<HostService>,
+    service_service: Arc<ServiceService>,
+}
+
+impl ReconciliationService {
+    pub fn new(
+        storage: Arc<dyn ObservationStateStorage>,
+        host_service: Arc<HostService>,
+        service_service: Arc<ServiceService>,
+    ) -> Self {
+        Self {
+            storage,
+            host_service,
+            service_service,
+        }
+    }
+
+    /// Apply a policy to discovered interfaces, ports and services within an observation's scope
+    /// that the observing session didn't report. Dropping an interface or closing a port drops the
+    /// bindings to it, and retiring a service removes it from the host. Manually created hosts and
+    /// services are never touched, nor are interfaces and ports they bind to or ones no earlier
+    /// scan observed. Interfaces are only in scope on the scanned subnet, and services only if the
+    /// same type of discovery found them.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn reconcile_host(
+        &self,
+        host: Host,
+        observation: &HostObservation,
+        observed_interfaces: &[Interface],
+        observed_ports: &[Port],
+        observed_services: &[Service],
+        policy: ReconciliationPolicy,
+        missed_scans: u32,
+    ) -> Result<Host> {
+        if policy == ReconciliationPolicy::Keep
+            || host.base.source.discriminant() != EntitySourceDiscriminants::Discovery
+        {
+            return Ok(host);
+        }
+
+        let Some(interface_id) = host
+            .base
+            .interfaces
+            .iter()
+            .find(|i| {
+                i.base.ip_address == observation.ip_address
+                    && i.base.subnet_id == observation.subnet_id
+            })
+            .map(|i| i.id)
+        else {
+            tracing::warn!(
+                "No interface for {} on host {}, skipping reconciliation",
+                observation.ip_address,
+                host.id
+            );
+            return Ok(host);
+        };
+
+        let (discovered_services, manual_services): (Vec<Service>, Vec<Service>) = self
+            .service_service
+            .get_services_for_host(&host.id)
+            .await?
+            .into_iter()
+            .partition(|s| {
+                s.base.source.discriminant() == EntitySourceDiscriminants::DiscoveryWithMatch
+            });
+
+        let mut states: HashMap<String, ObservationState> = self
+            .storage
+            .get_for_host(&host.id)
+            .await?
+            .into_iter()
+            .map(|s| (s.entity_key.clone(), s))
+            .collect();
+
+        // Interfaces and ports carry no source, so the only ones known to be discovered are ones a
+        // scan saw
+        let previously_seen: HashSet<String> = states
+            .values()
+            .filter(|s| s.last_seen_session.is_some())
+            .map(|s| s.entity_key.clone())
+            .collect();
+
+        let mut state_for = |key: String, seen: bool| -> ObservationState {
+            let state = states
+                .entry(key.clone())
+                .or_insert_with(|| ObservationState::new(host.id, key));
+            if seen {
+                state.seen(observation.session_id);
+            } else {
+                state.missed(observation.session_id);
+            }
+            state.clone()
+        };
+
+        let mut updated_host = host.clone();
+        let mut changed = false;
+        let mut updated_states = Vec::new();
+        let mut removed_keys = Vec::new();
+
+        // A scan of a subnet would have found the host's other addresses on it, ie ones it held
+        // before DHCP moved it
+        let interface_in_scope = |interface: &Interface| {
+            interface.base.subnet_id == observation.subnet_id
+                && !manual_services.iter().any(|s| {
+                    s.base
+                        .bindings
+                        .iter()
+                        .any(|b| b.interface_id() == Some(interface.id))
+                })
+        };
+
+        let mut kept_interfaces = Vec::new();
+        for mut interface in updated_host.base.interfaces.iter().cloned() {
+            if !interface_in_scope(&interface) {
+                kept_interfaces.push(interface);
+                continue;
+            }
+
+            let seen =
+                interface.id == interface_id || observed_interfaces.iter().any(|i| i == &interface);
+            let key = ObservationState::interface_key(&interface.base);
+            if !seen && !previously_seen.contains(&key) {
+                kept_interfaces.push(interface);
+                continue;
+            }
+
+            let state = state_for(key, seen);
+            let expired = state.missed_scans >= missed_scans;
+
+            if expired && policy == ReconciliationPolicy::Remove {
+                tracing::info!(
+                    "Dropping interface {} on host {}",
+                    interface.base.ip_address,
+                    host
+                );
+                changed = true;
+                removed_keys.push(state.entity_key);
+                continue;
+            }
+
+            let stale = expired && policy == ReconciliationPolicy::MarkStale;
+            changed |= interface.stale != stale;
+            interface.stale = stale;
+            kept_interfaces.push(interface);
+            updated_states.push(state);
+        }
+        updated_host.base.interfaces = kept_interfaces;
+
+        // Ports are host-wide, so leave ones bound through another interface to scans of it
+        let port_in_scope = |port: &Port| {
+            observation.scanned_ports.contains(&port.base.config())
+                && !manual_services
+                    .iter()
+                    .any(|s| s.base.bindings.iter().any(|b| b.port_id() == Some(port.id)))
+                && !discovered_services.iter().any(|s| {
+                    s.base.bindings.iter().any(|b| {
+                        b.port_id() == Some(port.id)
+                            && b.interface_id().is_some_and(|id| id != interface_id)
+                    })
+                })
+        };
+
+        let mut kept_ports = Vec::new();
+        for mut port in updated_host.base.ports.iter().copied() {
+            if !port_in_scope(&port) {
+                kept_ports.push(port);
+                continue;
+            }
+
+            let seen = observed_ports.iter().any(|p| p.base == port.base);
+            let key = ObservationState::port_key(&port.base);
+            if !seen && !previously_seen.contains(&key) {
+                kept_ports.push(port);
+                continue;
+            }
+
+            let state = state_for(key, seen);
+            let expired = state.missed_scans >= missed_scans;
+
+            if expired && policy == ReconciliationPolicy::Remove {
+                tracing::info!("Closing port {} on host {}", port, host);
+                changed = true;
+                removed_keys.push(state.entity_key);
+                continue;
+            }
+
+            let stale = expired && policy == ReconciliationPolicy::MarkStale;
+            changed |= port.stale != stale;
+            port.stale = stale;
+            kept_ports.push(port);
+            updated_states.push(state);
+        }
+        updated_host.base.ports = kept_ports;
+
+        for mut service in discovered_services {
+            let same_discovery = matches!(
+                &service.base.source,
+                EntitySource::DiscoveryWithMatch { metadata, .. }
+                    if metadata.iter().any(|m| m.discovery_type == observation.discovery_type)
+            );
+            let in_scope = same_discovery
+                && service
+                    .base
+                    .bindings
+                    .iter()
+                    .any(|b| b.interface_id().is_none_or(|id| id == interface_id));
+            if !in_scope {
+                continue;
+            }
+
+            let seen = observed_services.iter().any(|s| s.id == service.id);
+            let state = state_for(ObservationState::service_key(&service.id), seen);
+            let expired = state.missed_scans >= missed_scans;
+
+            if expired && policy == ReconciliationPolicy::Remove {
+                tracing::info!("Retiring service {} on host {}", service, host);
+                changed = true;
+                updated_host.base.services.retain(|id| *id != service.id);
+                removed_keys.push(state.entity_key);
+                continue;
+            }
+
+            let stale = expired && policy == ReconciliationPolicy::MarkStale;
+            if service.base.stale != stale {
+                service.base.stale = stale;
+                self.service_service.update_service(service).await?;
+            }
+            updated_states.push(state);
+        }
+
+        for state in &updated_states {
+            self.storage.upsert(state).await?;
+        }
+        for key in &removed_keys {
+            self.storage.delete(&host.id, key).await?;
+        }
+
+        if !changed {
+            return Ok(host);
+        }
+
+        // Updating the host deletes retired services and drops bindings to dropped interfaces and
+        // closed ports
+        self.host_service.update_host(updated_host).await
+    }
+}
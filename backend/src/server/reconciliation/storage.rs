@@ -0,0 +1,83 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::reconciliation::types::ObservationState;
+
+#[async_trait]
+pub trait ObservationStateStorage: Send + Sync {
+    async fn upsert(&self, state: &ObservationState) -> Result<()>;
+    async fn get_for_host(&self, host_id: &Uuid) -> Result<Vec<ObservationState>>;
+    async fn delete(&self, host_id: &Uuid, entity_key: &str) -> Result<()>;
+}
+
+pub struct PostgresObservationStateStorage {
+    pool: PgPool,
+}
+
+impl PostgresObservationStateStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ObservationStateStorage for PostgresObservationStateStorage {
+    async fn upsert(&self, state: &ObservationState) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO observation_states (
+                host_id, entity_key, last_seen_session, last_missed_session, missed_scans,
+                updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (host_id, entity_key) DO UPDATE SET
+                last_seen_session = EXCLUDED.last_seen_session,
+                last_missed_session = EXCLUDED.last_missed_session,
+                missed_scans = EXCLUDED.missed_scans,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(state.host_id)
+        .bind(&state.entity_key)
+        .bind(state.last_seen_session)
+        .bind(state.last_missed_session)
+        .bind(state.missed_scans as i32)
+        .bind(state.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_for_host(&self, host_id: &Uuid) -> Result<Vec<ObservationState>> {
+        let rows =
+            sqlx::query("SELECT * FROM observation_states WHERE host_id = $1 ORDER BY entity_key")
+                .bind(host_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter().map(row_to_observation_state).collect()
+    }
+
+    async fn delete(&self, host_id: &Uuid, entity_key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM observation_states WHERE host_id = $1 AND entity_key = $2")
+            .bind(host_id)
+            .bind(entity_key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_observation_state(row: sqlx::postgres::PgRow) -> Result<ObservationState, Error> {
+    Ok(ObservationState {
+        host_id: row.get("host_id"),
+        entity_key: row.get("entity_key"),
+        last_seen_session: row.get("last_seen_session"),
+        last_missed_session: row.get("last_missed_session"),
+        missed_scans: row.get::<i32, _>("missed_scans") as u32,
+        updated_at: row.get("updated_at"),
+    })
+}
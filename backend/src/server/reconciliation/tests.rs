@@ -0,0 +1,341 @@
+use cidr::{IpCidr, Ipv4Cidr};
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr};
+use uuid::Uuid;
+
+use crate::{
+    server::{
+        discovery::types::base::{DiscoveryMetadata, DiscoveryType, EntitySource},
+        hosts::types::ports::{Port, PortBase},
+        reconciliation::types::{HostObservation, ReconciliationPolicy},
+        services::types::{bindings::Binding, patterns::MatchDetails},
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_reconcile_missed_ports_and_services() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(subnet_obj.clone())
+        .await
+        .unwrap();
+
+    let mut host_obj = host(&network.id);
+    host_obj.base.source = EntitySource::Discovery {
+        metadata: vec![DiscoveryMetadata::default()],
+    };
+    host_obj.base.interfaces = vec![interface(&subnet_obj.id)];
+    host_obj.base.ports.push(Port::new(PortBase::Https));
+    // Added by hand, so never observed by a scan
+    host_obj.base.ports.push(Port::new(PortBase::new_tcp(8443)));
+
+    // A manually created service protects the port it binds to
+    let mut manual_svc = service(&network.id, &host_obj.id);
+    manual_svc.base.source = EntitySource::Manual;
+    manual_svc.base.bindings = vec![Binding::new_port(
+        host_obj.base.ports[0].id,
+        Some(host_obj.base.interfaces[0].id),
+    )];
+    let mut web_svc = service(&network.id, &host_obj.id);
+    web_svc.base.name = "Web Server".to_string();
+    web_svc.base.source = EntitySource::DiscoveryWithMatch {
+        metadata: vec![DiscoveryMetadata::default()],
+        details: MatchDetails::new_certain("Test"),
+    };
+    web_svc.base.bindings = vec![Binding::new_port(
+        host_obj.base.ports[1].id,
+        Some(host_obj.base.interfaces[0].id),
+    )];
+    // Found by another type of discovery, so network scans don't reconcile it
+    let mut docker_svc = service(&network.id, &host_obj.id);
+    docker_svc.base.name = "Container".to_string();
+    docker_svc.base.source = EntitySource::DiscoveryWithMatch {
+        metadata: vec![DiscoveryMetadata::new(
+            DiscoveryType::Docker {
+                host_id: host_obj.id,
+            },
+            Uuid::new_v4(),
+        )],
+        details: MatchDetails::new_certain("Test"),
+    };
+    docker_svc.base.bindings = vec![Binding::new_interface(host_obj.base.interfaces[0].id)];
+
+    let (host_obj, created_svcs) = services
+        .host_service
+        .create_host_with_services(host_obj, vec![manual_svc, web_svc, docker_svc])
+        .await
+        .unwrap();
+    let web_svc = created_svcs[1].clone();
+
+    let observation = |session_id: Uuid| HostObservation {
+        session_id,
+        subnet_id: subnet_obj.id,
+        ip_address: host_obj.base.interfaces[0].base.ip_address,
+        discovery_type: DiscoveryType::Network,
+        scanned_ports: vec![
+            PortBase::Ssh.config(),
+            PortBase::Https.config(),
+            PortBase::new_tcp(8443).config(),
+        ],
+    };
+    let reconciliation = &services.reconciliation_service;
+
+    // A scan sees the discovered ports and service
+    let reconciled = reconciliation
+        .reconcile_host(
+            host_obj.clone(),
+            &observation(Uuid::new_v4()),
+            &[],
+            &host_obj.base.ports[..2],
+            &created_svcs[1..2],
+            ReconciliationPolicy::MarkStale,
+            2,
+        )
+        .await
+        .unwrap();
+
+    // Nothing is observed open, but the host is scanned twice in the first session
+    let first_session = Uuid::new_v4();
+    let mut reconciled = reconciled;
+    for _ in 0..2 {
+        reconciled = reconciliation
+            .reconcile_host(
+                reconciled,
+                &observation(first_session),
+                &[],
+                &[],
+                &[],
+                ReconciliationPolicy::MarkStale,
+                2,
+            )
+            .await
+            .unwrap();
+    }
+    assert!(reconciled.base.ports.iter().all(|p| !p.stale));
+
+    let reconciled = reconciliation
+        .reconcile_host(
+            reconciled,
+            &observation(Uuid::new_v4()),
+            &[],
+            &[],
+            &[],
+            ReconciliationPolicy::MarkStale,
+            2,
+        )
+        .await
+        .unwrap();
+    assert!(!reconciled.base.ports[0].stale);
+    assert!(reconciled.base.ports[1].stale);
+    assert!(!reconciled.base.ports[2].stale);
+    let stale_svc = services
+        .service_service
+        .get_service(&web_svc.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stale_svc.base.stale);
+
+    // Seeing them again clears the flag
+    let reconciled = reconciliation
+        .reconcile_host(
+            reconciled,
+            &observation(Uuid::new_v4()),
+            &[],
+            &host_obj.base.ports[..2],
+            &created_svcs[1..2],
+            ReconciliationPolicy::MarkStale,
+            2,
+        )
+        .await
+        .unwrap();
+    assert!(!reconciled.base.ports[1].stale);
+
+    let mut reconciled = reconciled;
+    for _ in 0..2 {
+        reconciled = reconciliation
+            .reconcile_host(
+                reconciled,
+                &observation(Uuid::new_v4()),
+                &[],
+                &[],
+                &[],
+                ReconciliationPolicy::Remove,
+                2,
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        reconciled.base.ports,
+        vec![host_obj.base.ports[0], host_obj.base.ports[2]]
+    );
+    assert_eq!(
+        reconciled.base.services,
+        vec![created_svcs[0].id, created_svcs[2].id]
+    );
+    assert!(
+        services
+            .service_service
+            .get_service(&web_svc.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let manual_svc = services
+        .service_service
+        .get_service(&created_svcs[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(manual_svc.base.bindings, created_svcs[0].base.bindings);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reconcile_missed_interfaces() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    let mut other_subnet = subnet(&network.id);
+    other_subnet.base.cidr = IpCidr::V4(Ipv4Cidr::new(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap());
+    for s in [&subnet_obj, &other_subnet] {
+        services
+            .subnet_service
+            .create_subnet(s.clone())
+            .await
+            .unwrap();
+    }
+
+    let interface_at = |subnet_id: &Uuid, ip: [u8; 4]| {
+        let mut iface = interface(subnet_id);
+        iface.base.ip_address = IpAddr::V4(Ipv4Addr::from(ip));
+        iface
+    };
+
+    let mut host_obj = host(&network.id);
+    host_obj.base.source = EntitySource::Discovery {
+        metadata: vec![DiscoveryMetadata::default()],
+    };
+    host_obj.base.interfaces = vec![
+        interface_at(&subnet_obj.id, [192, 168, 1, 101]),
+        // Held before DHCP moved the host
+        interface_at(&subnet_obj.id, [192, 168, 1, 100]),
+        // A manually created service protects the interface it binds to
+        interface_at(&subnet_obj.id, [192, 168, 1, 102]),
+        // Not on the scanned subnet
+        interface_at(&other_subnet.id, [10, 0, 0, 5]),
+    ];
+
+    let mut manual_svc = service(&network.id, &host_obj.id);
+    manual_svc.base.source = EntitySource::Manual;
+    manual_svc.base.bindings = vec![Binding::new_interface(host_obj.base.interfaces[2].id)];
+    let mut web_svc = service(&network.id, &host_obj.id);
+    web_svc.base.name = "Web Server".to_string();
+    web_svc.base.source = EntitySource::DiscoveryWithMatch {
+        metadata: vec![DiscoveryMetadata::default()],
+        details: MatchDetails::new_certain("Test"),
+    };
+    web_svc.base.bindings = vec![
+        Binding::new_interface(host_obj.base.interfaces[0].id),
+        Binding::new_interface(host_obj.base.interfaces[1].id),
+    ];
+
+    let (host_obj, created_svcs) = services
+        .host_service
+        .create_host_with_services(host_obj, vec![manual_svc, web_svc])
+        .await
+        .unwrap();
+    let web_svc = created_svcs[1].clone();
+
+    let observation = |session_id: Uuid| HostObservation {
+        session_id,
+        subnet_id: subnet_obj.id,
+        ip_address: host_obj.base.interfaces[0].base.ip_address,
+        discovery_type: DiscoveryType::Network,
+        scanned_ports: vec![],
+    };
+    let reconciliation = &services.reconciliation_service;
+
+    // A scan sees the host at both addresses
+    let mut reconciled = reconciliation
+        .reconcile_host(
+            host_obj.clone(),
+            &observation(Uuid::new_v4()),
+            &host_obj.base.interfaces[..2],
+            &[],
+            &created_svcs[1..2],
+            ReconciliationPolicy::MarkStale,
+            2,
+        )
+        .await
+        .unwrap();
+
+    // Then only at its current one
+    for _ in 0..2 {
+        reconciled = reconciliation
+            .reconcile_host(
+                reconciled,
+                &observation(Uuid::new_v4()),
+                &host_obj.base.interfaces[..1],
+                &[],
+                &created_svcs[1..2],
+                ReconciliationPolicy::MarkStale,
+                2,
+            )
+            .await
+            .unwrap();
+    }
+    let stale: Vec<bool> = reconciled.base.interfaces.iter().map(|i| i.stale).collect();
+    assert_eq!(stale, vec![false, true, false, false]);
+
+    let reconciled = reconciliation
+        .reconcile_host(
+            reconciled,
+            &observation(Uuid::new_v4()),
+            &host_obj.base.interfaces[..1],
+            &[],
+            &created_svcs[1..2],
+            ReconciliationPolicy::Remove,
+            2,
+        )
+        .await
+        .unwrap();
+    let remaining: Vec<Uuid> = reconciled.base.interfaces.iter().map(|i| i.id).collect();
+    assert_eq!(
+        remaining,
+        vec![
+            host_obj.base.interfaces[0].id,
+            host_obj.base.interfaces[2].id,
+            host_obj.base.interfaces[3].id,
+        ]
+    );
+
+    // Bindings to the dropped address go with it
+    let web_after = services
+        .service_service
+        .get_service(&web_svc.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(web_after.base.bindings, vec![web_svc.base.bindings[0]]);
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use strum_macros::{Display, IntoStaticStr};
+use uuid::Uuid;
+
+use crate::server::{
+    discovery::types::base::DiscoveryType,
+    hosts::types::{
+        interfaces::InterfaceBase,
+        ports::{PortBase, PortConfig},
+    },
+};
+
+/// What happens to discovered interfaces, ports and services which discovery stops observing.
+/// Manually created entities are never reconciled.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationPolicy {
+    /// Leave them in place
+    #[default]
+    Keep,
+    /// Flag them as stale once enough consecutive scans have missed them
+    MarkStale,
+    /// Drop interfaces, close ports and retire services once enough consecutive scans have missed
+    /// them
+    Remove,
+}
+
+/// The scope a discovery session scanned a host in. A host reported with an observation is the
+/// complete state seen within that scope, so anything in scope that wasn't reported was missed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostObservation {
+    pub session_id: Uuid,
+    /// Interface the host was scanned through
+    pub subnet_id: Uuid,
+    pub ip_address: IpAddr,
+    /// Only services found by the same type of discovery are in scope
+    pub discovery_type: DiscoveryType,
+    /// Every port probed, whether or not it was open
+    pub scanned_ports: Vec<PortConfig>,
+}
+
+/// How many consecutive discovery sessions have missed an interface, port or service on a host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObservationState {
+    pub host_id: Uuid,
+    /// Identifies the interface, port or service, see interface_key, port_key and service_key
+    pub entity_key: String,
+    pub last_seen_session: Option<Uuid>,
+    pub last_missed_session: Option<Uuid>,
+    pub missed_scans: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ObservationState {
+    pub fn new(host_id: Uuid, entity_key: String) -> Self {
+        Self {
+            host_id,
+            entity_key,
+            last_seen_session: None,
+            last_missed_session: None,
+            missed_scans: 0,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn interface_key(interface: &InterfaceBase) -> String {
+        format!("interface:{}@{}", interface.ip_address, interface.subnet_id)
+    }
+
+    pub fn port_key(port: &PortBase) -> String {
+        format!("port:{}", port)
+    }
+
+    pub fn service_key(service_id: &Uuid) -> String {
+        format!("service:{}", service_id)
+    }
+
+    pub fn seen(&mut self, session_id: Uuid) {
+        self.last_seen_session = Some(session_id);
+        self.missed_scans = 0;
+        self.updated_at = Utc::now();
+    }
+
+    /// Hosts are scanned once per interface, so a session only counts as a miss if no other scan
+    /// in it saw the entity, and only counts once
+    pub fn missed(&mut self, session_id: Uuid) {
+        if self.last_seen_session == Some(session_id)
+            || self.last_missed_session == Some(session_id)
+        {
+            return;
+        }
+        self.last_missed_session = Some(session_id);
+        self.missed_scans += 1;
+        self.updated_at = Utc::now();
+    }
+}
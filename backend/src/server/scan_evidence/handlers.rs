@@ -0,0 +1,58 @@
+use crate::server::{
+    config::AppState,
+    scan_evidence::types::{Reclassification, ReclassifyRequest, ScanEvidence},
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json,
+    routing::{get, post},
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/:host_id", get(get_host_evidence))
+        .route("/:host_id/reclassify", post(reclassify_host))
+}
+
+async fn get_host_evidence(
+    State(state): State<Arc<AppState>>,
+    Path(host_id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<ScanEvidence>>>> {
+    let service = &state.services.scan_evidence_service;
+
+    let evidence = service.get_evidence_for_host(&host_id).await?;
+
+    Ok(Json(ApiResponse::success(evidence)))
+}
+
+async fn reclassify_host(
+    State(state): State<Arc<AppState>>,
+    Path(host_id): Path<Uuid>,
+    Json(request): Json<ReclassifyRequest>,
+) -> ApiResult<Json<ApiResponse<Reclassification>>> {
+    let service = &state.services.scan_evidence_service;
+
+    if state
+        .services
+        .host_service
+        .get_host(&host_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::not_found(&format!(
+            "Host '{}' not found",
+            host_id
+        )));
+    }
+
+    let reclassification = service
+        .reclassify_host(&host_id, request.apply, state.config.min_match_score)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(reclassification)))
+}
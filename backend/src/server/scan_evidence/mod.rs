@@ -0,0 +1,6 @@
+pub mod handlers;
+pub mod service;
+pub mod storage;
+#[cfg(test)]
+pub mod tests;
+pub mod types;
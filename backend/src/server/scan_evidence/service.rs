@@ -0,0 +1,251 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    discovery::types::base::EntitySource,
+    hosts::{service::HostService, types::base::Host},
+    scan_evidence::{
+        storage::ScanEvidenceStorage,
+        types::{Reclassification, ScanEvidence, ScanEvidenceBase, ServiceChange},
+    },
+    services::{
+        service::ServiceService,
+        types::{base::Service, bindings::Binding, patterns::MatchResult},
+    },
+};
+
+pub struct ScanEvidenceService {
+    storage: Arc<dyn ScanEvidenceStorage>,
+    host_service: Arc<HostService>,
+    service_service: Arc<ServiceService>,
+}
+
+impl ScanEvidenceService {
+    pub fn new(
+        storage: Arc<dyn ScanEvidenceStorage>,
+        host_service: Arc<HostService>,
+        service_service: Arc<ServiceService>,
+    ) -> Self {
+        Self {
+            storage,
+            host_service,
+            service_service,
+        }
+    }
+
+    pub async fn get_evidence_for_host(&self, host_id: &Uuid) -> Result<Vec<ScanEvidence>> {
+        self.storage.get_for_host(host_id).await
+    }
+
+    /// Store evidence reported by a daemon alongside a host. The host may have been merged into an
+    /// existing host, so evidence is re-keyed to the stored host's matching interface.
+    pub async fn record_evidence(
+        &self,
+        created_host: &Host,
+        evidence: Vec<ScanEvidenceBase>,
+    ) -> Result<()> {
+        for mut base in evidence {
+            let Some(interface) = created_host.base.interfaces.iter().find(|i| {
+                i.base.ip_address == base.ip_address && i.base.subnet_id == base.subnet_id
+            }) else {
+                tracing::warn!(
+                    "No interface for {} on host {}, discarding scan evidence",
+                    base.ip_address,
+                    created_host.id
+                );
+                continue;
+            };
+
+            base.host_id = created_host.id;
+            base.interface_id = interface.id;
+
+            self.storage.upsert(&ScanEvidence::new(base)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the current service definitions over a host's recorded evidence and diff the result
+    /// against its discovered services. Manually added services are never changed.
+    pub async fn reclassify_host(
+        &self,
+        host_id: &Uuid,
+        apply: bool,
+        min_score: u32,
+    ) -> Result<Reclassification> {
+        let mut host = self
+            .host_service
+            .get_host(host_id)
+            .await?
+            .ok_or_else(|| anyhow!("Host '{}' not found", host_id))?;
+
+        let evidence = self.storage.get_for_host(host_id).await?;
+
+        if evidence.is_empty() {
+            return Err(anyhow!(
+                "No scan evidence has been recorded for host '{}'",
+                host_id
+            ));
+        }
+
+        // Bind matches to the host's ports up front, so they can be compared with the stored
+        // services' bindings
+        let mut matches: Vec<Service> = Vec::new();
+        for e in &evidence {
+            for (mut service, result) in e.base.match_services(&host, min_score, false).matches {
+                Self::bind_to_host_ports(&mut host, &mut service, &result);
+                if !matches.iter().any(|s| Self::same_match(s, &service)) {
+                    matches.push(service);
+                }
+            }
+        }
+
+        let mut discovered: Vec<Service> = self
+            .service_service
+            .get_services_for_host(host_id)
+            .await?
+            .into_iter()
+            .filter(|s| matches!(s.base.source, EntitySource::DiscoveryWithMatch { .. }))
+            .collect();
+
+        let mut changed = Vec::new();
+        let mut added = Vec::new();
+
+        // Pair matches with the stored service of the same definition and bindings first, then
+        // with any left over of the same definition, whose bindings moved
+        let mut unpaired = Vec::new();
+        for service in matches {
+            match discovered
+                .iter()
+                .position(|e| Self::same_match(e, &service))
+            {
+                Some(index) => {
+                    let existing = discovered.remove(index);
+                    if let Some(change) = Self::service_change(existing, &service) {
+                        changed.push(change);
+                    }
+                }
+                None => unpaired.push(service),
+            }
+        }
+
+        for service in unpaired {
+            match discovered.iter().position(|e| {
+                e.base.service_definition.id() == service.base.service_definition.id()
+            }) {
+                Some(index) => {
+                    let existing = discovered.remove(index);
+                    if let Some(change) = Self::service_change(existing, &service) {
+                        changed.push(change);
+                    }
+                }
+                None => added.push(service),
+            }
+        }
+
+        let removed = discovered;
+
+        if apply {
+            // Store ports the matches bound to first, as updating the host drops bindings to
+            // ports it doesn't have
+            let mut host = self.host_service.update_host(host).await?;
+
+            for change in &changed {
+                self.service_service
+                    .update_service(change.after.clone())
+                    .await?;
+            }
+
+            for service in &mut added {
+                let created = self.service_service.create_service(service.clone()).await?;
+                *service = created;
+                host.base.services.push(service.id);
+            }
+
+            // Updating the host deletes services no longer listed on it
+            host.base
+                .services
+                .retain(|id| !removed.iter().any(|s| s.id == *id));
+            self.host_service.update_host(host).await?;
+
+            tracing::info!(
+                "Reclassified host {}: {} added, {} removed, {} changed",
+                host_id,
+                added.len(),
+                removed.len(),
+                changed.len()
+            );
+        }
+
+        Ok(Reclassification {
+            host_id: *host_id,
+            added,
+            removed,
+            changed,
+            applied: apply,
+        })
+    }
+
+    /// Whether two services are of the same definition with the same bindings
+    fn same_match(a: &Service, b: &Service) -> bool {
+        a.base.service_definition.id() == b.base.service_definition.id()
+            && a.base.bindings.len() == b.base.bindings.len()
+            && a.base.bindings.iter().all(|x| b.base.bindings.contains(x))
+    }
+
+    /// Update a stored service in place with a new match's bindings and details, keeping its id,
+    /// name, history and tags. None if nothing would change.
+    fn service_change(existing: Service, matched: &Service) -> Option<ServiceChange> {
+        let (
+            EntitySource::DiscoveryWithMatch { metadata, details },
+            EntitySource::DiscoveryWithMatch {
+                details: new_details,
+                ..
+            },
+        ) = (&existing.base.source, &matched.base.source)
+        else {
+            return None;
+        };
+
+        if details == new_details && Self::same_match(&existing, matched) {
+            return None;
+        }
+
+        let mut after = existing.clone();
+        after.base.source = EntitySource::DiscoveryWithMatch {
+            metadata: metadata.clone(),
+            details: new_details.clone(),
+        };
+        if !Self::same_match(&existing, matched) {
+            after.base.bindings = matched.base.bindings.clone();
+        }
+
+        Some(ServiceChange {
+            before: existing,
+            after,
+        })
+    }
+
+    /// Point a matched service's port bindings at the host's existing ports, adding any ports the
+    /// host doesn't have yet
+    fn bind_to_host_ports(host: &mut Host, service: &mut Service, result: &MatchResult) {
+        for port in &result.ports {
+            let host_port_id = match host.base.ports.iter().find(|p| p.base == port.base) {
+                Some(existing) => existing.id,
+                None => {
+                    host.base.ports.push(*port);
+                    port.id
+                }
+            };
+
+            for binding in service.base.bindings.iter_mut() {
+                if let Binding::Port { port_id, .. } = binding
+                    && *port_id == port.id
+                {
+                    *port_id = host_port_id;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::scan_evidence::types::{ScanEvidence, ScanEvidenceBase};
+
+#[async_trait]
+pub trait ScanEvidenceStorage: Send + Sync {
+    /// Insert evidence, replacing any previously recorded for the same host interface and container
+    async fn upsert(&self, evidence: &ScanEvidence) -> Result<()>;
+    async fn get_for_host(&self, host_id: &Uuid) -> Result<Vec<ScanEvidence>>;
+}
+
+pub struct PostgresScanEvidenceStorage {
+    pool: PgPool,
+}
+
+impl PostgresScanEvidenceStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ScanEvidenceStorage for PostgresScanEvidenceStorage {
+    async fn upsert(&self, evidence: &ScanEvidence) -> Result<()> {
+        let evidence_json = serde_json::to_value(&evidence.base)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO scan_evidence (
+                id, host_id, interface_id, evidence_key, evidence, recorded_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (host_id, interface_id, evidence_key) DO UPDATE SET
+                evidence = EXCLUDED.evidence, recorded_at = EXCLUDED.recorded_at
+            "#,
+        )
+        .bind(evidence.id)
+        .bind(evidence.base.host_id)
+        .bind(evidence.base.interface_id)
+        .bind(evidence.base.evidence_key())
+        .bind(evidence_json)
+        .bind(evidence.recorded_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_for_host(&self, host_id: &Uuid) -> Result<Vec<ScanEvidence>> {
+        let rows = sqlx::query(
+            "SELECT * FROM scan_evidence WHERE host_id = $1 ORDER BY recorded_at, evidence_key",
+        )
+        .bind(host_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_evidence).collect()
+    }
+}
+
+fn row_to_evidence(row: sqlx::postgres::PgRow) -> Result<ScanEvidence, Error> {
+    let base: ScanEvidenceBase =
+        serde_json::from_value(row.get::<serde_json::Value, _>("evidence"))
+            .or(Err(Error::msg("Failed to deserialize evidence")))?;
+
+    Ok(ScanEvidence {
+        id: row.get("id"),
+        recorded_at: row.get("recorded_at"),
+        base,
+    })
+}
@@ -0,0 +1,169 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    server::{
+        discovery::types::base::{DiscoveryType, EntitySource},
+        hosts::types::ports::PortBase,
+        scan_evidence::types::{RecordedEndpointResponse, ScanEvidenceBase},
+        services::types::{endpoints::ApplicationProtocol, patterns::MatchDetails},
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_reclassify_host_from_evidence() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let subnet_obj = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(subnet_obj.clone())
+        .await
+        .unwrap();
+
+    let mut host_obj = host(&network.id);
+    host_obj.base.interfaces = vec![interface(&subnet_obj.id)];
+
+    let (created_host, _) = services
+        .host_service
+        .create_host_with_services(host_obj.clone(), vec![])
+        .await
+        .unwrap();
+
+    let interface = &host_obj.base.interfaces[0];
+    let evidence = ScanEvidenceBase {
+        host_id: host_obj.id,
+        interface_id: interface.id,
+        ip_address: interface.base.ip_address,
+        mac_address: interface.base.mac_address,
+        subnet_id: subnet_obj.id,
+        subnet_cidr: subnet_obj.base.cidr,
+        subnet_type: subnet_obj.base.subnet_type,
+        open_ports: vec![PortBase::DnsUdp.config(), PortBase::Http.config()],
+        endpoint_responses: vec![RecordedEndpointResponse {
+            protocol: ApplicationProtocol::Http,
+            port: PortBase::Http.config(),
+            path: "/admin".to_string(),
+            headers: vec![],
+            response: "<title>Pi-hole Admin Console</title>".to_string(),
+        }],
+        banners: vec![],
+        virtualization: None,
+        gateway_ips: vec![],
+        daemon_id: Uuid::new_v4(),
+        discovery_type: DiscoveryType::Network,
+        match_trace: vec![],
+    };
+
+    services
+        .scan_evidence_service
+        .record_evidence(&created_host, vec![evidence])
+        .await
+        .unwrap();
+
+    // Evidence is keyed to the stored host's interface
+    let stored = services
+        .scan_evidence_service
+        .get_evidence_for_host(&created_host.id)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(
+        stored[0].base.interface_id,
+        created_host.base.interfaces[0].id
+    );
+
+    // Dry run reports the new match without creating anything
+    let preview = services
+        .scan_evidence_service
+        .reclassify_host(&created_host.id, false, 0)
+        .await
+        .unwrap();
+
+    assert!(!preview.applied);
+    assert!(
+        preview
+            .added
+            .iter()
+            .any(|s| s.base.service_definition.id() == "Pi-Hole")
+    );
+    assert!(
+        services
+            .service_service
+            .get_services_for_host(&created_host.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let applied = services
+        .scan_evidence_service
+        .reclassify_host(&created_host.id, true, 0)
+        .await
+        .unwrap();
+    assert!(applied.applied);
+
+    let host_services = services
+        .service_service
+        .get_services_for_host(&created_host.id)
+        .await
+        .unwrap();
+    assert!(
+        host_services
+            .iter()
+            .any(|s| s.base.service_definition.id() == "Pi-Hole")
+    );
+
+    // Nothing left to change once applied
+    let rerun = services
+        .scan_evidence_service
+        .reclassify_host(&created_host.id, false, 0)
+        .await
+        .unwrap();
+    assert!(rerun.added.is_empty());
+    assert!(rerun.removed.is_empty());
+    assert!(rerun.changed.is_empty());
+
+    // A service whose match details differ is updated in place, keeping its id and tags
+    let mut pihole = host_services
+        .into_iter()
+        .find(|s| s.base.service_definition.id() == "Pi-Hole")
+        .unwrap();
+    pihole.base.tags = vec!["dns".to_string()];
+    if let EntitySource::DiscoveryWithMatch { details, .. } = &mut pihole.base.source {
+        *details = MatchDetails::new_certain("Outdated match");
+    }
+    services
+        .service_service
+        .update_service(pihole.clone())
+        .await
+        .unwrap();
+
+    let applied = services
+        .scan_evidence_service
+        .reclassify_host(&created_host.id, true, 0)
+        .await
+        .unwrap();
+    assert!(applied.added.is_empty());
+    assert!(applied.removed.is_empty());
+    assert_eq!(applied.changed.len(), 1);
+    assert_eq!(applied.changed[0].after.id, pihole.id);
+
+    let updated = services
+        .service_service
+        .get_service(&pihole.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.base.tags, vec!["dns".to_string()]);
+    assert_ne!(updated.base.source, pihole.base.source);
+}
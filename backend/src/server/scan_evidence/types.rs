@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use cidr::IpCidr;
+use mac_address::MacAddress;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::server::{
+    discovery::types::base::{DiscoveryType, EntitySource},
+    hosts::types::{
+        base::Host,
+        interfaces::{Interface, InterfaceBase},
+        ports::{PortBase, PortConfig},
+    },
+    services::types::{
+        base::{Service, ServiceMatchBaselineParams, ServiceMatchOutcome},
+        endpoints::{ApplicationProtocol, Endpoint, EndpointResponse},
+        trace::DefinitionMatchTrace,
+        versions::PortBanner,
+        virtualization::ServiceVirtualization,
+    },
+    subnets::types::base::{Subnet, SubnetBase, SubnetType},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEndpointResponse {
+    pub protocol: ApplicationProtocol,
+    pub port: PortConfig,
+    pub path: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    pub response: String,
+}
+
+impl From<&EndpointResponse> for RecordedEndpointResponse {
+    fn from(response: &EndpointResponse) -> Self {
+        Self {
+            protocol: response.endpoint.protocol,
+            port: response.endpoint.port_base.config(),
+            path: response.endpoint.path.clone(),
+            headers: response.headers.clone(),
+            response: response.response.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedBanner {
+    pub port: PortConfig,
+    pub banner: String,
+}
+
+/// Raw data a daemon gathered for a host interface, as used for service matching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanEvidenceBase {
+    pub host_id: Uuid,
+    pub interface_id: Uuid,
+    pub ip_address: IpAddr,
+    pub mac_address: Option<MacAddress>,
+    pub subnet_id: Uuid,
+    pub subnet_cidr: IpCidr,
+    pub subnet_type: SubnetType,
+    pub open_ports: Vec<PortConfig>,
+    pub endpoint_responses: Vec<RecordedEndpointResponse>,
+    #[serde(default)]
+    pub banners: Vec<RecordedBanner>,
+    pub virtualization: Option<ServiceVirtualization>,
+    /// IPs in the daemon's routing table at scan time
+    pub gateway_ips: Vec<IpAddr>,
+    pub daemon_id: Uuid,
+    pub discovery_type: DiscoveryType,
+    /// Evaluation of each definition when the daemon matched the services, if it recorded
+    /// match traces
+    #[serde(default)]
+    pub match_trace: Vec<DefinitionMatchTrace>,
+}
+
+impl ScanEvidenceBase {
+    pub fn new(
+        host_id: Uuid,
+        baseline_params: &ServiceMatchBaselineParams,
+        gateway_ips: &[IpAddr],
+        daemon_id: Uuid,
+        discovery_type: DiscoveryType,
+    ) -> Self {
+        let ServiceMatchBaselineParams {
+            subnet,
+            interface,
+            all_ports,
+            endpoint_responses,
+            banners,
+            virtualization,
+        } = baseline_params;
+
+        Self {
+            host_id,
+            interface_id: interface.id,
+            ip_address: interface.base.ip_address,
+            mac_address: interface.base.mac_address,
+            subnet_id: subnet.id,
+            subnet_cidr: subnet.base.cidr,
+            subnet_type: subnet.base.subnet_type,
+            open_ports: all_ports.iter().map(|p| p.config()).collect(),
+            endpoint_responses: endpoint_responses
+                .iter()
+                .map(RecordedEndpointResponse::from)
+                .collect(),
+            banners: banners
+                .iter()
+                .map(|b| RecordedBanner {
+                    port: b.port_base.config(),
+                    banner: b.banner.clone(),
+                })
+                .collect(),
+            virtualization: (*virtualization).clone(),
+            gateway_ips: gateway_ips.to_vec(),
+            daemon_id,
+            discovery_type,
+            match_trace: Vec::new(),
+        }
+    }
+
+    /// Distinguishes evidence recorded for several containers sharing a host interface
+    pub fn evidence_key(&self) -> String {
+        match &self.virtualization {
+            Some(ServiceVirtualization::Container(docker)) => {
+                docker.container_id.clone().unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Run the current service definitions over the evidence, tracing each definition's
+    /// evaluation if `with_traces` is set
+    pub fn match_services(
+        &self,
+        host: &Host,
+        min_score: u32,
+        with_traces: bool,
+    ) -> ServiceMatchOutcome {
+        let subnet = Subnet {
+            id: self.subnet_id,
+            ..Subnet::new(SubnetBase {
+                cidr: self.subnet_cidr,
+                network_id: host.base.network_id,
+                name: self.subnet_cidr.to_string(),
+                description: None,
+                subnet_type: self.subnet_type,
+                vlan_id: None,
+                source: EntitySource::System,
+                tags: Vec::new(),
+                custom_fields: BTreeMap::new(),
+            })
+        };
+
+        let interface = Interface {
+            id: self.interface_id,
+            base: InterfaceBase {
+                subnet_id: self.subnet_id,
+                ip_address: self.ip_address,
+                mac_address: self.mac_address,
+                name: None,
+                mac_vendor: None,
+                randomized_mac: false,
+                vlan_tag: None,
+            },
+            stale: false,
+        };
+
+        let all_ports: Vec<PortBase> = self
+            .open_ports
+            .iter()
+            .map(|p| PortBase::from_config(*p))
+            .collect();
+
+        let endpoint_responses: Vec<EndpointResponse> = self
+            .endpoint_responses
+            .iter()
+            .map(|r| EndpointResponse {
+                endpoint: Endpoint {
+                    protocol: r.protocol,
+                    ip: Some(self.ip_address),
+                    port_base: PortBase::from_config(r.port),
+                    path: r.path.clone(),
+                },
+                headers: r.headers.clone(),
+                response: r.response.clone(),
+            })
+            .collect();
+
+        let banners: Vec<PortBanner> = self
+            .banners
+            .iter()
+            .map(|b| PortBanner {
+                port_base: PortBase::from_config(b.port),
+                banner: b.banner.clone(),
+            })
+            .collect();
+
+        let baseline_params = ServiceMatchBaselineParams {
+            subnet: &subnet,
+            interface: &interface,
+            all_ports: &all_ports,
+            endpoint_responses: &endpoint_responses,
+            banners: &banners,
+            virtualization: &self.virtualization,
+        };
+
+        Service::match_definitions(
+            &host.id,
+            &baseline_params,
+            &self.gateway_ips,
+            &self.daemon_id,
+            &host.base.network_id,
+            &self.discovery_type,
+            min_score,
+            with_traces,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanEvidence {
+    pub id: Uuid,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: ScanEvidenceBase,
+}
+
+impl ScanEvidence {
+    pub fn new(base: ScanEvidenceBase) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            recorded_at: Utc::now(),
+            base,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReclassifyRequest {
+    /// Apply the changes rather than only reporting them
+    #[serde(default)]
+    pub apply: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceChange {
+    pub before: Service,
+    pub after: Service,
+}
+
+/// Difference between a host's discovered services and those the current service definitions
+/// match on its recorded evidence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reclassification {
+    pub host_id: Uuid,
+    pub added: Vec<Service>,
+    pub removed: Vec<Service>,
+    pub changed: Vec<ServiceChange>,
+    pub applied: bool,
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::server::{
+    hosts::types::ports::{PortBase, PortConfig, TransportProtocol},
+    service_definitions::types::PatternDefinition,
+    services::types::{
+        definitions::{ServiceDefinition, ServiceDefinitionExt},
+        patterns::MatchConfidence,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverlapKind {
+    /// Both patterns match exactly the same hosts
+    Identical,
+    /// Every host matching the narrower pattern also matches the broader one
+    Subsumed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternOverlap {
+    pub kind: OverlapKind,
+    pub narrower: String,
+    pub broader: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedPort {
+    pub port: PortConfig,
+    pub definitions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointResponsePattern {
+    pub definition: String,
+    pub response: String,
+}
+
+/// An endpoint probed by several definitions, where a response body matching one definition can
+/// also match another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmbiguousEndpoint {
+    pub port: PortConfig,
+    pub path: String,
+    pub definitions: Vec<EndpointResponsePattern>,
+}
+
+struct AnalyzedPattern {
+    name: String,
+    is_generic: bool,
+    pattern: PatternDefinition,
+}
+
+/// Static analysis of service definition patterns, used to catch conflicts between definitions
+/// before they show up as misclassified services
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceDefinitionAnalysis {
+    pub overlapping_patterns: Vec<PatternOverlap>,
+    pub shared_ports: Vec<SharedPort>,
+    pub ambiguous_endpoints: Vec<AmbiguousEndpoint>,
+    /// Non-generic definitions whose pattern can't produce a match with High confidence or above
+    pub below_high_confidence: Vec<String>,
+}
+
+impl ServiceDefinitionAnalysis {
+    pub fn new(definitions: &[Box<dyn ServiceDefinition>]) -> Self {
+        let patterns: Vec<AnalyzedPattern> = definitions
+            .iter()
+            .map(|d| AnalyzedPattern {
+                name: d.name().to_string(),
+                is_generic: ServiceDefinitionExt::is_generic(d),
+                pattern: PatternDefinition::from(&d.discovery_pattern()),
+            })
+            .collect();
+
+        let shared_ports = Self::shared_ports(&patterns);
+
+        let is_unique_port = |port: &PortBase| {
+            !shared_ports
+                .iter()
+                .any(|shared| shared.port == port.config())
+        };
+
+        let below_high_confidence = definitions
+            .iter()
+            .zip(&patterns)
+            .filter(|(_, p)| !p.is_generic && p.pattern != PatternDefinition::None)
+            .filter(|(d, _)| {
+                d.discovery_pattern()
+                    .max_score(&is_unique_port)
+                    .is_none_or(|score| MatchConfidence::from_score(score) < MatchConfidence::High)
+            })
+            .map(|(_, p)| p.name.to_string())
+            .collect();
+
+        Self {
+            overlapping_patterns: Self::overlapping_patterns(&patterns),
+            ambiguous_endpoints: Self::ambiguous_endpoints(&patterns),
+            shared_ports,
+            below_high_confidence,
+        }
+    }
+
+    /// Human readable list of the issues involving a definition. Intended for tests of new
+    /// definitions, ie `assert!(analysis.issues_for("My Service").is_empty())`.
+    pub fn issues_for(&self, name: &str) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        for overlap in &self.overlapping_patterns {
+            if overlap.narrower != name && overlap.broader != name {
+                continue;
+            }
+            issues.push(match overlap.kind {
+                OverlapKind::Identical => format!(
+                    "{} and {} have identical patterns",
+                    overlap.narrower, overlap.broader
+                ),
+                OverlapKind::Subsumed => format!(
+                    "{} matches every host that {} matches",
+                    overlap.broader, overlap.narrower
+                ),
+            });
+        }
+
+        for shared in &self.shared_ports {
+            if shared.definitions.iter().any(|d| d == name) {
+                issues.push(format!(
+                    "Port {} is also used by {}",
+                    PortBase::Custom(shared.port),
+                    shared
+                        .definitions
+                        .iter()
+                        .filter(|d| *d != name)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+
+        for endpoint in &self.ambiguous_endpoints {
+            if endpoint.definitions.iter().any(|d| d.definition == name) {
+                issues.push(format!(
+                    "Responses from {}{} can match several definitions: {}",
+                    PortBase::Custom(endpoint.port),
+                    endpoint.path,
+                    endpoint
+                        .definitions
+                        .iter()
+                        .map(|d| format!("{} (\"{}\")", d.definition, d.response))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+
+        if self.below_high_confidence.iter().any(|d| d == name) {
+            issues.push(format!(
+                "{} can never be matched with High confidence",
+                name
+            ));
+        }
+
+        issues
+    }
+
+    fn overlapping_patterns(patterns: &[AnalyzedPattern]) -> Vec<PatternOverlap> {
+        let alternatives: Vec<_> = patterns
+            .iter()
+            .map(|p| (p, alternatives(&p.pattern)))
+            .filter(|(_, alternatives)| !alternatives.is_empty())
+            .collect();
+
+        let mut overlaps = Vec::new();
+
+        for (i, (definition, definition_alternatives)) in alternatives.iter().enumerate() {
+            for (j, (other, other_alternatives)) in alternatives.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                let is_subsumed = subsumes(other_alternatives, definition_alternatives);
+
+                if !is_subsumed {
+                    continue;
+                }
+
+                if subsumes(definition_alternatives, other_alternatives) {
+                    // Report identical patterns once
+                    if i < j {
+                        overlaps.push(PatternOverlap {
+                            kind: OverlapKind::Identical,
+                            narrower: definition.name.to_string(),
+                            broader: other.name.to_string(),
+                        });
+                    }
+                } else if !other.is_generic {
+                    // Generic definitions are catch-alls by design
+                    overlaps.push(PatternOverlap {
+                        kind: OverlapKind::Subsumed,
+                        narrower: definition.name.to_string(),
+                        broader: other.name.to_string(),
+                    });
+                }
+            }
+        }
+
+        overlaps
+    }
+
+    fn shared_ports(patterns: &[AnalyzedPattern]) -> Vec<SharedPort> {
+        let mut definitions_by_port: BTreeMap<(u16, TransportProtocol), Vec<String>> =
+            BTreeMap::new();
+
+        for AnalyzedPattern { name, pattern, .. } in patterns {
+            let mut ports = Vec::new();
+            collect_ports(pattern, &mut ports);
+
+            for port in ports {
+                let definitions = definitions_by_port
+                    .entry((port.number, port.protocol))
+                    .or_default();
+                if !definitions.iter().any(|d| d == name) {
+                    definitions.push(name.to_string());
+                }
+            }
+        }
+
+        definitions_by_port
+            .into_iter()
+            .filter(|(_, definitions)| definitions.len() > 1)
+            .map(|((number, protocol), definitions)| SharedPort {
+                port: PortConfig { number, protocol },
+                definitions,
+            })
+            .collect()
+    }
+
+    fn ambiguous_endpoints(patterns: &[AnalyzedPattern]) -> Vec<AmbiguousEndpoint> {
+        let mut responses_by_endpoint: BTreeMap<
+            (u16, TransportProtocol, String),
+            Vec<EndpointResponsePattern>,
+        > = BTreeMap::new();
+
+        for AnalyzedPattern { name, pattern, .. } in patterns {
+            let mut endpoints = Vec::new();
+            collect_endpoints(pattern, &mut endpoints);
+
+            for (port, path, response) in endpoints {
+                responses_by_endpoint
+                    .entry((port.number, port.protocol, path.to_string()))
+                    .or_default()
+                    .push(EndpointResponsePattern {
+                        definition: name.to_string(),
+                        response: response.to_string(),
+                    });
+            }
+        }
+
+        responses_by_endpoint
+            .into_iter()
+            .filter_map(|((number, protocol, path), responses)| {
+                // Endpoint patterns match on a case-insensitive substring, so a body containing
+                // the longer of two nested strings also contains the shorter one
+                let ambiguous: Vec<EndpointResponsePattern> = responses
+                    .iter()
+                    .filter(|r| {
+                        responses.iter().any(|other| {
+                            let (a, b) = (r.response.to_lowercase(), other.response.to_lowercase());
+                            other.definition != r.definition && (a.contains(&b) || b.contains(&a))
+                        })
+                    })
+                    .cloned()
+                    .collect();
+
+                (!ambiguous.is_empty()).then_some(AmbiguousEndpoint {
+                    port: PortConfig { number, protocol },
+                    path,
+                    definitions: ambiguous,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Expand a pattern into the alternative sets of leaf patterns that must all match, ie
+/// AllOf(A, AnyOf(B, C)) -> [[A, B], [A, C]]. A pattern that can't match has no alternatives.
+fn alternatives(pattern: &PatternDefinition) -> Vec<Vec<&PatternDefinition>> {
+    match pattern {
+        PatternDefinition::AnyOf(patterns) => patterns.iter().flat_map(alternatives).collect(),
+        PatternDefinition::AllOf(patterns) => patterns.iter().fold(vec![Vec::new()], |acc, p| {
+            let child_alternatives = alternatives(p);
+            acc.iter()
+                .flat_map(|a| {
+                    child_alternatives.iter().map(move |b| {
+                        let mut combined = a.clone();
+                        combined.extend(b.iter().filter(|leaf| !a.contains(leaf)));
+                        combined
+                    })
+                })
+                .collect()
+        }),
+        PatternDefinition::None => vec![],
+        leaf => vec![vec![leaf]],
+    }
+}
+
+/// Whether every host matching `narrower` also matches `broader`: each alternative of the
+/// narrower pattern requires at least the leaves of one of the broader pattern's alternatives
+fn subsumes(broader: &[Vec<&PatternDefinition>], narrower: &[Vec<&PatternDefinition>]) -> bool {
+    narrower.iter().all(|n| {
+        broader
+            .iter()
+            .any(|b| b.iter().all(|leaf| n.contains(leaf)))
+    })
+}
+
+fn collect_ports(pattern: &PatternDefinition, ports: &mut Vec<PortConfig>) {
+    match pattern {
+        PatternDefinition::Port(port) => ports.push(*port),
+        PatternDefinition::AnyOf(patterns) | PatternDefinition::AllOf(patterns) => {
+            patterns.iter().for_each(|p| collect_ports(p, ports))
+        }
+        _ => {}
+    }
+}
+
+fn collect_endpoints<'a>(
+    pattern: &'a PatternDefinition,
+    endpoints: &mut Vec<(PortConfig, &'a str, &'a str)>,
+) {
+    match pattern {
+        PatternDefinition::Endpoint {
+            port,
+            path,
+            response,
+        } => endpoints.push((*port, path, response)),
+        PatternDefinition::AnyOf(patterns) | PatternDefinition::AllOf(patterns) => patterns
+            .iter()
+            .for_each(|p| collect_endpoints(p, endpoints)),
+        _ => {}
+    }
+}
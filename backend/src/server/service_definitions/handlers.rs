@@ -0,0 +1,155 @@
+use crate::server::{
+    config::AppState,
+    daemons::handlers::require_daemon,
+    service_definitions::{
+        analysis::ServiceDefinitionAnalysis,
+        types::{CustomServiceDefinition, CustomServiceDefinitionBase, PublishedServiceDefinition},
+    },
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_definition))
+        .route("/", get(get_all_definitions))
+        .route("/published", get(get_published_definitions))
+        .route("/analysis", get(get_definition_analysis))
+        .route("/:id", get(get_definition))
+        .route("/:id", put(update_definition))
+        .route("/:id", delete(delete_definition))
+}
+
+async fn create_definition(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CustomServiceDefinitionBase>,
+) -> ApiResult<Json<ApiResponse<CustomServiceDefinition>>> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "Service definition validation failed: {}",
+            validation_errors
+        )));
+    }
+
+    let service = &state.services.custom_service_definition_service;
+
+    let created = service
+        .create_definition(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(created)))
+}
+
+async fn get_all_definitions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<CustomServiceDefinition>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.custom_service_definition_service;
+
+    let definitions = service.get_all_definitions(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(definitions)))
+}
+
+/// Definitions for the requesting daemon's network to match against. Only answered for
+/// registered daemons.
+async fn get_published_definitions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<PublishedServiceDefinition>>>> {
+    let daemon = require_daemon(&state, &params).await?;
+
+    let service = &state.services.custom_service_definition_service;
+
+    Ok(Json(ApiResponse::success(
+        service.get_published_definitions(&daemon.base.network_id),
+    )))
+}
+
+async fn get_definition_analysis(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<ServiceDefinitionAnalysis>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.custom_service_definition_service;
+
+    Ok(Json(ApiResponse::success(
+        service.analyze_definitions(&network_id),
+    )))
+}
+
+async fn get_definition(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<CustomServiceDefinition>>> {
+    let service = &state.services.custom_service_definition_service;
+
+    match service.get_definition(&id).await? {
+        Some(definition) => Ok(Json(ApiResponse::success(definition))),
+        None => Err(ApiError::not_found(&format!("Service definition '{}'", id))),
+    }
+}
+
+async fn update_definition(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CustomServiceDefinitionBase>,
+) -> ApiResult<Json<ApiResponse<CustomServiceDefinition>>> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "Service definition validation failed: {}",
+            validation_errors
+        )));
+    }
+
+    let service = &state.services.custom_service_definition_service;
+
+    let definition = service
+        .get_definition(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Service definition '{}'", id)))?;
+
+    let updated = service
+        .update_definition(definition, request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(updated)))
+}
+
+async fn delete_definition(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.custom_service_definition_service;
+
+    let definition = service
+        .get_definition(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Service definition '{}'", id)))?;
+
+    service
+        .delete_definition(&definition)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(())))
+}
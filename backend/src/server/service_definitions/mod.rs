@@ -0,0 +1,7 @@
+pub mod analysis;
+pub mod handlers;
+pub mod service;
+pub mod storage;
+#[cfg(test)]
+pub mod tests;
+pub mod types;
@@ -0,0 +1,167 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    service_definitions::{
+        analysis::ServiceDefinitionAnalysis,
+        storage::CustomServiceDefinitionStorage,
+        types::{CustomServiceDefinition, CustomServiceDefinitionBase, PublishedServiceDefinition},
+    },
+    services::{definitions::ServiceDefinitionRegistry, types::categories::ServiceCategory},
+};
+
+pub struct CustomServiceDefinitionService {
+    storage: Arc<dyn CustomServiceDefinitionStorage>,
+}
+
+impl CustomServiceDefinitionService {
+    pub fn new(storage: Arc<dyn CustomServiceDefinitionStorage>) -> Self {
+        Self { storage }
+    }
+
+    /// Load custom definitions from storage into the service definition registry
+    pub async fn refresh_registry(&self) -> Result<()> {
+        let definitions = self.storage.get_all().await?;
+        ServiceDefinitionRegistry::set_custom_service_definitions(&definitions);
+
+        tracing::debug!(
+            "Registered {} custom service definitions",
+            definitions.len()
+        );
+        Ok(())
+    }
+
+    /// Built-in definitions and the network's custom definitions in their serialisable form, for
+    /// the network's daemons to match against
+    pub fn get_published_definitions(&self, network_id: &Uuid) -> Vec<PublishedServiceDefinition> {
+        ServiceDefinitionRegistry::network_service_definitions(network_id)
+            .iter()
+            .map(PublishedServiceDefinition::from)
+            .collect()
+    }
+
+    /// Conflicts and weaknesses across built-in definitions and the network's custom definitions
+    pub fn analyze_definitions(&self, network_id: &Uuid) -> ServiceDefinitionAnalysis {
+        ServiceDefinitionAnalysis::new(&ServiceDefinitionRegistry::network_service_definitions(
+            network_id,
+        ))
+    }
+
+    pub async fn get_definition(&self, id: &Uuid) -> Result<Option<CustomServiceDefinition>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn get_all_definitions(
+        &self,
+        network_id: &Uuid,
+    ) -> Result<Vec<CustomServiceDefinition>> {
+        self.storage.get_for_network(network_id).await
+    }
+
+    pub async fn create_definition(
+        &self,
+        base: CustomServiceDefinitionBase,
+    ) -> Result<CustomServiceDefinition> {
+        Self::validate_category(&base)?;
+
+        if ServiceDefinitionRegistry::service_exists(&base.network_id, &base.name) {
+            return Err(anyhow!(
+                "A service definition named '{}' already exists",
+                base.name
+            ));
+        }
+
+        let definition = CustomServiceDefinition::new(base);
+        self.storage.create(&definition).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Created custom service definition {}: {}",
+            definition.base.name,
+            definition.id
+        );
+        Ok(definition)
+    }
+
+    pub async fn update_definition(
+        &self,
+        mut definition: CustomServiceDefinition,
+        base: CustomServiceDefinitionBase,
+    ) -> Result<CustomServiceDefinition> {
+        Self::validate_category(&base)?;
+
+        // Services on the network reference the definition
+        if base.network_id != definition.base.network_id {
+            return Err(anyhow!(
+                "Service definitions can't be moved to another network"
+            ));
+        }
+
+        if base.name != definition.base.name {
+            if ServiceDefinitionRegistry::service_exists(&base.network_id, &base.name) {
+                return Err(anyhow!(
+                    "A service definition named '{}' already exists",
+                    base.name
+                ));
+            }
+
+            // Services reference definitions by name
+            if self
+                .storage
+                .is_in_use(&definition.base.network_id, &definition.base.name)
+                .await?
+            {
+                return Err(anyhow!(
+                    "Service definition '{}' is used by existing services and can't be renamed",
+                    definition.base.name
+                ));
+            }
+        }
+
+        definition.base = base;
+        definition.updated_at = chrono::Utc::now();
+
+        self.storage.update(&definition).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Updated custom service definition {}: {}",
+            definition.base.name,
+            definition.id
+        );
+        Ok(definition)
+    }
+
+    pub async fn delete_definition(&self, definition: &CustomServiceDefinition) -> Result<()> {
+        if self
+            .storage
+            .is_in_use(&definition.base.network_id, &definition.base.name)
+            .await?
+        {
+            return Err(anyhow!(
+                "Service definition '{}' is used by existing services and can't be deleted",
+                definition.base.name
+            ));
+        }
+
+        self.storage.delete(&definition.id).await?;
+        self.refresh_registry().await?;
+
+        tracing::info!(
+            "Deleted custom service definition {}: {}",
+            definition.base.name,
+            definition.id
+        );
+        Ok(())
+    }
+
+    fn validate_category(base: &CustomServiceDefinitionBase) -> Result<()> {
+        if base.category == ServiceCategory::Netvisor {
+            return Err(anyhow!(
+                "The Netvisor category is reserved for built-in definitions"
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::{
+    service_definitions::types::{
+        CustomServiceDefinition, CustomServiceDefinitionBase, PatternDefinition,
+        VersionExtractorDefinition,
+    },
+    services::types::categories::ServiceCategory,
+};
+
+#[async_trait]
+pub trait CustomServiceDefinitionStorage: Send + Sync {
+    async fn create(&self, definition: &CustomServiceDefinition) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<CustomServiceDefinition>>;
+    async fn get_all(&self) -> Result<Vec<CustomServiceDefinition>>;
+    async fn get_for_network(&self, network_id: &Uuid) -> Result<Vec<CustomServiceDefinition>>;
+    async fn update(&self, definition: &CustomServiceDefinition) -> Result<()>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+    /// Whether any service on the network references the definition with this name
+    async fn is_in_use(&self, network_id: &Uuid, name: &str) -> Result<bool>;
+}
+
+pub struct PostgresCustomServiceDefinitionStorage {
+    pool: PgPool,
+}
+
+impl PostgresCustomServiceDefinitionStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomServiceDefinitionStorage for PostgresCustomServiceDefinitionStorage {
+    async fn create(&self, definition: &CustomServiceDefinition) -> Result<()> {
+        let category_str = category_to_string(&definition.base.category)?;
+        let pattern_json = serde_json::to_value(&definition.base.discovery_pattern)?;
+        let extractors_json = serde_json::to_value(&definition.base.version_extractors)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO custom_service_definitions (
+                id, name, description, category, dashboard_icons_path, simple_icons_path,
+                vector_logo_zone_icons_path, logo_needs_white_background, discovery_pattern,
+                version_extractors, created_at, updated_at, network_id
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+        )
+        .bind(definition.id)
+        .bind(&definition.base.name)
+        .bind(&definition.base.description)
+        .bind(category_str)
+        .bind(&definition.base.dashboard_icons_path)
+        .bind(&definition.base.simple_icons_path)
+        .bind(&definition.base.vector_logo_zone_icons_path)
+        .bind(definition.base.logo_needs_white_background)
+        .bind(pattern_json)
+        .bind(extractors_json)
+        .bind(definition.created_at)
+        .bind(definition.updated_at)
+        .bind(definition.base.network_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<CustomServiceDefinition>> {
+        let row = sqlx::query("SELECT * FROM custom_service_definitions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(row_to_definition(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_all(&self) -> Result<Vec<CustomServiceDefinition>> {
+        let rows = sqlx::query("SELECT * FROM custom_service_definitions ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_definition).collect()
+    }
+
+    async fn get_for_network(&self, network_id: &Uuid) -> Result<Vec<CustomServiceDefinition>> {
+        let rows = sqlx::query(
+            "SELECT * FROM custom_service_definitions WHERE network_id = $1 ORDER BY name",
+        )
+        .bind(network_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_definition).collect()
+    }
+
+    async fn update(&self, definition: &CustomServiceDefinition) -> Result<()> {
+        let category_str = category_to_string(&definition.base.category)?;
+        let pattern_json = serde_json::to_value(&definition.base.discovery_pattern)?;
+        let extractors_json = serde_json::to_value(&definition.base.version_extractors)?;
+
+        sqlx::query(
+            r#"
+            UPDATE custom_service_definitions SET
+                name = $2, description = $3, category = $4, dashboard_icons_path = $5,
+                simple_icons_path = $6, vector_logo_zone_icons_path = $7,
+                logo_needs_white_background = $8, discovery_pattern = $9,
+                version_extractors = $10, updated_at = $11
+            WHERE id = $1
+            "#,
+        )
+        .bind(definition.id)
+        .bind(&definition.base.name)
+        .bind(&definition.base.description)
+        .bind(category_str)
+        .bind(&definition.base.dashboard_icons_path)
+        .bind(&definition.base.simple_icons_path)
+        .bind(&definition.base.vector_logo_zone_icons_path)
+        .bind(definition.base.logo_needs_white_background)
+        .bind(pattern_json)
+        .bind(extractors_json)
+        .bind(definition.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM custom_service_definitions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_in_use(&self, network_id: &Uuid, name: &str) -> Result<bool> {
+        // Services store their definition id as a JSON string
+        let service_def_str = serde_json::to_string(name)?;
+
+        let row = sqlx::query(
+            "SELECT EXISTS(SELECT 1 FROM services WHERE network_id = $1 AND service_definition = $2) AS in_use",
+        )
+        .bind(network_id)
+        .bind(service_def_str)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("in_use"))
+    }
+}
+
+fn category_to_string(category: &ServiceCategory) -> Result<String, Error> {
+    match serde_json::to_value(category)? {
+        serde_json::Value::String(category) => Ok(category),
+        _ => Err(Error::msg("Failed to serialize category")),
+    }
+}
+
+fn row_to_definition(row: sqlx::postgres::PgRow) -> Result<CustomServiceDefinition, Error> {
+    let category: ServiceCategory =
+        serde_json::from_value(serde_json::Value::String(row.get("category")))
+            .or(Err(Error::msg("Failed to deserialize category")))?;
+    let discovery_pattern: PatternDefinition =
+        serde_json::from_value(row.get::<serde_json::Value, _>("discovery_pattern"))
+            .or(Err(Error::msg("Failed to deserialize discovery_pattern")))?;
+    let version_extractors: Vec<VersionExtractorDefinition> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("version_extractors"))
+            .or(Err(Error::msg("Failed to deserialize version_extractors")))?;
+
+    Ok(CustomServiceDefinition {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: CustomServiceDefinitionBase {
+            network_id: row.get("network_id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            category,
+            dashboard_icons_path: row.get("dashboard_icons_path"),
+            simple_icons_path: row.get("simple_icons_path"),
+            vector_logo_zone_icons_path: row.get("vector_logo_zone_icons_path"),
+            logo_needs_white_background: row.get("logo_needs_white_background"),
+            discovery_pattern,
+            version_extractors,
+        },
+    })
+}
@@ -0,0 +1,263 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    server::{
+        hosts::types::ports::{PortBase, PortConfig, TransportProtocol},
+        service_definitions::{
+            analysis::{OverlapKind, PatternOverlap, ServiceDefinitionAnalysis},
+            types::{
+                CustomServiceDefinitionBase, DynamicServiceDefinition, PatternDefinition,
+                PublishedServiceDefinition, VersionExtractorDefinition,
+            },
+        },
+        services::{
+            definitions::ServiceDefinitionRegistry,
+            types::{categories::ServiceCategory, definitions::ServiceDefinition},
+        },
+    },
+    tests::*,
+};
+
+fn custom_definition_base(network_id: &Uuid, name: &str) -> CustomServiceDefinitionBase {
+    let port = PortConfig {
+        number: 7420,
+        protocol: TransportProtocol::Tcp,
+    };
+
+    CustomServiceDefinitionBase {
+        network_id: *network_id,
+        name: name.to_string(),
+        description: "In-house inventory app".to_string(),
+        category: ServiceCategory::Development,
+        dashboard_icons_path: String::new(),
+        simple_icons_path: String::new(),
+        vector_logo_zone_icons_path: String::new(),
+        logo_needs_white_background: false,
+        discovery_pattern: PatternDefinition::AllOf(vec![
+            PatternDefinition::Port(port),
+            PatternDefinition::Endpoint {
+                port,
+                path: "/health".to_string(),
+                response: "inventory".to_string(),
+            },
+        ]),
+        version_extractors: vec![VersionExtractorDefinition::Body {
+            port,
+            path: "/health".to_string(),
+            regex: r"inventory v([\d.]+)".to_string(),
+        }],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_custom_service_definition_registry() {
+    let (_, services, _container) = test_services().await;
+    let definition_service = &services.custom_service_definition_service;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let created_network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let other_network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let created = definition_service
+        .create_definition(custom_definition_base(&created_network.id, "Inventory"))
+        .await
+        .unwrap();
+
+    // Registered alongside built-in definitions on its own network only
+    let registered =
+        ServiceDefinitionRegistry::find_in_network(&created_network.id, "Inventory").unwrap();
+    assert_eq!(registered.description(), "In-house inventory app");
+    assert_eq!(registered.version_extractors().len(), 1);
+    assert!(ServiceDefinitionRegistry::find_in_network(&other_network.id, "Inventory").is_none());
+
+    // Published to the network's daemons, so its ports are scanned
+    let published = definition_service.get_published_definitions(&created_network.id);
+    let inventory = published.iter().find(|d| d.name == "Inventory").unwrap();
+    assert_eq!(
+        inventory.discovery_pattern.to_pattern().ports(),
+        vec![PortBase::new_tcp(7420)]
+    );
+    assert!(
+        !definition_service
+            .get_published_definitions(&other_network.id)
+            .iter()
+            .any(|d| d.name == "Inventory")
+    );
+
+    // Names can't collide with built-in definitions, but can with other networks' definitions
+    assert!(
+        definition_service
+            .create_definition(custom_definition_base(&created_network.id, "Pi-Hole"))
+            .await
+            .is_err()
+    );
+    let other_created = definition_service
+        .create_definition(custom_definition_base(&other_network.id, "Inventory"))
+        .await
+        .unwrap();
+
+    // Services can only use definitions of their own network
+    let (created_host, _) = services
+        .host_service
+        .create_host_with_services(host(&created_network.id), vec![])
+        .await
+        .unwrap();
+    let (other_host, _) = services
+        .host_service
+        .create_host_with_services(host(&other_network.id), vec![])
+        .await
+        .unwrap();
+
+    let mut svc = service(&created_network.id, &created_host.id);
+    svc.base.service_definition = registered.clone();
+    let svc = services.service_service.create_service(svc).await.unwrap();
+
+    definition_service
+        .delete_definition(&other_created)
+        .await
+        .unwrap();
+    let mut other_svc = service(&other_network.id, &other_host.id);
+    other_svc.base.service_definition = registered;
+    assert!(
+        services
+            .service_service
+            .create_service(other_svc)
+            .await
+            .is_err()
+    );
+
+    // Definitions referenced by services can't be deleted
+    assert!(
+        definition_service
+            .delete_definition(&created)
+            .await
+            .is_err()
+    );
+
+    services
+        .service_service
+        .delete_service(&svc.id)
+        .await
+        .unwrap();
+    definition_service
+        .delete_definition(&created)
+        .await
+        .unwrap();
+    assert!(ServiceDefinitionRegistry::find_in_network(&created_network.id, "Inventory").is_none());
+}
+
+#[test]
+#[serial]
+fn test_published_service_definitions() {
+    let published: Vec<PublishedServiceDefinition> =
+        ServiceDefinitionRegistry::all_service_definitions()
+            .iter()
+            .map(PublishedServiceDefinition::from)
+            .collect();
+
+    // Every built-in pattern survives the trip to a daemon unchanged
+    let json = serde_json::to_string(&published).unwrap();
+    let downloaded: Vec<PublishedServiceDefinition> = serde_json::from_str(&json).unwrap();
+    assert_eq!(published, downloaded);
+
+    for definition in &downloaded {
+        let pattern = definition.discovery_pattern.to_pattern();
+        assert_eq!(
+            PatternDefinition::from(&pattern),
+            definition.discovery_pattern
+        );
+    }
+
+    // Downloaded definitions replace the compiled ones
+    let mut downloaded = downloaded;
+    let pi_hole = downloaded.iter_mut().find(|d| d.name == "Pi-Hole").unwrap();
+    pi_hole.discovery_pattern = PatternDefinition::Port(PortConfig {
+        number: 8053,
+        protocol: TransportProtocol::Tcp,
+    });
+
+    ServiceDefinitionRegistry::set_published_service_definitions(&downloaded);
+
+    let registered = ServiceDefinitionRegistry::find_by_id("Pi-Hole").unwrap();
+    assert_eq!(
+        registered.discovery_pattern().ports(),
+        vec![PortBase::new_tcp(8053)]
+    );
+    assert_eq!(
+        ServiceDefinitionRegistry::all_service_definitions().len(),
+        published.len()
+    );
+
+    ServiceDefinitionRegistry::set_published_service_definitions(&[]);
+}
+
+#[test]
+#[serial]
+fn test_service_definition_analysis() {
+    let port = PortConfig {
+        number: 7420,
+        protocol: TransportProtocol::Tcp,
+    };
+
+    let with_pattern = |name: &str, discovery_pattern: PatternDefinition| {
+        let mut base = custom_definition_base(&Uuid::nil(), name);
+        base.discovery_pattern = discovery_pattern;
+        Box::new(DynamicServiceDefinition::from(&base)) as Box<dyn ServiceDefinition>
+    };
+
+    let definitions = vec![
+        with_pattern(
+            "Inventory",
+            custom_definition_base(&Uuid::nil(), "").discovery_pattern,
+        ),
+        with_pattern("Any Port", PatternDefinition::Port(port)),
+        with_pattern(
+            "Inventory Admin",
+            PatternDefinition::Endpoint {
+                port,
+                path: "/health".to_string(),
+                response: "Inventory Admin".to_string(),
+            },
+        ),
+    ];
+
+    let analysis = ServiceDefinitionAnalysis::new(&definitions);
+
+    assert_eq!(
+        analysis.overlapping_patterns,
+        vec![PatternOverlap {
+            kind: OverlapKind::Subsumed,
+            narrower: "Inventory".to_string(),
+            broader: "Any Port".to_string(),
+        }]
+    );
+    assert_eq!(
+        analysis.shared_ports[0].definitions,
+        vec!["Inventory".to_string(), "Any Port".to_string()]
+    );
+    assert_eq!(analysis.ambiguous_endpoints[0].definitions.len(), 2);
+    assert_eq!(analysis.below_high_confidence, vec!["Any Port".to_string()]);
+
+    assert_eq!(analysis.issues_for("Inventory").len(), 3);
+    assert_eq!(analysis.issues_for("Inventory Admin").len(), 1);
+
+    // Built-in definitions can be analyzed as a whole
+    let analysis =
+        ServiceDefinitionAnalysis::new(&ServiceDefinitionRegistry::all_service_definitions());
+    assert!(
+        analysis
+            .overlapping_patterns
+            .iter()
+            .all(|o| o.kind != OverlapKind::Identical)
+    );
+}
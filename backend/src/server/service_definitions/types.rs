@@ -0,0 +1,329 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::server::{
+    hosts::types::ports::{PortBase, PortConfig},
+    services::types::{
+        categories::ServiceCategory,
+        definitions::{ServiceDefinition, ServiceDefinitionExt},
+        patterns::{MatchPredicate, Pattern},
+        versions::{VersionExtractor, VersionSource},
+    },
+    subnets::types::base::SubnetType,
+};
+
+/// Owned, serialisable form of `Pattern`. Built-in definitions are published in this form so
+/// daemons can match against the server's current definitions, and user-defined definitions are
+/// stored in it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum PatternDefinition {
+    AnyOf(Vec<PatternDefinition>),
+    AllOf(Vec<PatternDefinition>),
+    Not(Box<PatternDefinition>),
+    Port(PortConfig),
+    Endpoint {
+        port: PortConfig,
+        /// ie "/", "/admin"
+        path: String,
+        /// String to match on in response
+        response: String,
+    },
+    SubnetIsType(SubnetType),
+    IsGateway,
+    MacVendor(String),
+    Predicate(MatchPredicate),
+    DockerContainer,
+    DockerImage(String),
+    None,
+}
+
+impl PatternDefinition {
+    pub fn to_pattern(&self) -> Pattern<'_> {
+        match self {
+            PatternDefinition::AnyOf(patterns) => {
+                Pattern::AnyOf(patterns.iter().map(|p| p.to_pattern()).collect())
+            }
+            PatternDefinition::AllOf(patterns) => {
+                Pattern::AllOf(patterns.iter().map(|p| p.to_pattern()).collect())
+            }
+            PatternDefinition::Not(pattern) => Pattern::Not(Box::new(pattern.to_pattern())),
+            PatternDefinition::Port(port) => Pattern::Port(PortBase::from_config(*port)),
+            PatternDefinition::Endpoint {
+                port,
+                path,
+                response,
+            } => Pattern::Endpoint(PortBase::from_config(*port), path, response),
+            PatternDefinition::SubnetIsType(subnet_type) => Pattern::SubnetIsType(*subnet_type),
+            PatternDefinition::IsGateway => Pattern::IsGateway,
+            PatternDefinition::MacVendor(vendor) => Pattern::MacVendor(vendor),
+            PatternDefinition::Predicate(predicate) => Pattern::Predicate(*predicate),
+            PatternDefinition::DockerContainer => Pattern::DockerContainer,
+            PatternDefinition::DockerImage(repository) => Pattern::DockerImage(repository),
+            PatternDefinition::None => Pattern::None,
+        }
+    }
+}
+
+impl From<&Pattern<'_>> for PatternDefinition {
+    fn from(pattern: &Pattern<'_>) -> Self {
+        match pattern {
+            Pattern::AnyOf(patterns) => {
+                PatternDefinition::AnyOf(patterns.iter().map(PatternDefinition::from).collect())
+            }
+            Pattern::AllOf(patterns) => {
+                PatternDefinition::AllOf(patterns.iter().map(PatternDefinition::from).collect())
+            }
+            Pattern::Not(pattern) => {
+                PatternDefinition::Not(Box::new(PatternDefinition::from(pattern.as_ref())))
+            }
+            Pattern::Port(port_base) => PatternDefinition::Port(port_base.config()),
+            Pattern::Endpoint(port_base, path, response) => PatternDefinition::Endpoint {
+                port: port_base.config(),
+                path: path.to_string(),
+                response: response.to_string(),
+            },
+            Pattern::SubnetIsType(subnet_type) => PatternDefinition::SubnetIsType(*subnet_type),
+            Pattern::IsGateway => PatternDefinition::IsGateway,
+            Pattern::MacVendor(vendor) => PatternDefinition::MacVendor(vendor.to_string()),
+            Pattern::Predicate(predicate) => PatternDefinition::Predicate(*predicate),
+            Pattern::DockerContainer => PatternDefinition::DockerContainer,
+            Pattern::DockerImage(repository) => {
+                PatternDefinition::DockerImage(repository.to_string())
+            }
+            Pattern::None => PatternDefinition::None,
+        }
+    }
+}
+
+/// Owned, serialisable form of `VersionExtractor`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum VersionExtractorDefinition {
+    Body {
+        port: PortConfig,
+        path: String,
+        regex: String,
+    },
+    Header {
+        port: PortConfig,
+        path: String,
+        header: String,
+        regex: String,
+    },
+    Banner {
+        port: PortConfig,
+        regex: String,
+    },
+}
+
+impl VersionExtractorDefinition {
+    pub fn to_extractor(&self) -> VersionExtractor<'_> {
+        match self {
+            VersionExtractorDefinition::Body { port, path, regex } => {
+                VersionExtractor::body(PortBase::from_config(*port), path, regex)
+            }
+            VersionExtractorDefinition::Header {
+                port,
+                path,
+                header,
+                regex,
+            } => VersionExtractor::header(PortBase::from_config(*port), path, header, regex),
+            VersionExtractorDefinition::Banner { port, regex } => {
+                VersionExtractor::banner(PortBase::from_config(*port), regex)
+            }
+        }
+    }
+}
+
+impl From<&VersionExtractor<'_>> for VersionExtractorDefinition {
+    fn from(extractor: &VersionExtractor<'_>) -> Self {
+        let regex = extractor.regex.to_string();
+
+        match extractor.source {
+            VersionSource::Body(port_base, path) => VersionExtractorDefinition::Body {
+                port: port_base.config(),
+                path: path.to_string(),
+                regex,
+            },
+            VersionSource::Header(port_base, path, header) => VersionExtractorDefinition::Header {
+                port: port_base.config(),
+                path: path.to_string(),
+                header: header.to_string(),
+                regex,
+            },
+            VersionSource::Banner(port_base) => VersionExtractorDefinition::Banner {
+                port: port_base.config(),
+                regex,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq, Eq)]
+pub struct CustomServiceDefinitionBase {
+    pub network_id: Uuid,
+    /// Also used as the definition's identifier, unique within the network
+    #[validate(length(min = 1, max = 25))]
+    pub name: String,
+    #[validate(length(min = 0, max = 100))]
+    pub description: String,
+    pub category: ServiceCategory,
+    #[serde(default)]
+    pub dashboard_icons_path: String,
+    #[serde(default)]
+    pub simple_icons_path: String,
+    #[serde(default)]
+    pub vector_logo_zone_icons_path: String,
+    #[serde(default)]
+    pub logo_needs_white_background: bool,
+    pub discovery_pattern: PatternDefinition,
+    #[serde(default)]
+    pub version_extractors: Vec<VersionExtractorDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomServiceDefinition {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: CustomServiceDefinitionBase,
+}
+
+impl CustomServiceDefinition {
+    pub fn new(base: CustomServiceDefinitionBase) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+}
+
+/// A built-in or user-defined service definition as published by the server to daemons
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PublishedServiceDefinition {
+    pub name: String,
+    pub description: String,
+    pub category: ServiceCategory,
+    pub is_generic: bool,
+    pub dashboard_icons_path: String,
+    pub simple_icons_path: String,
+    pub vector_logo_zone_icons_path: String,
+    pub logo_needs_white_background: bool,
+    pub discovery_pattern: PatternDefinition,
+    #[serde(default)]
+    pub version_extractors: Vec<VersionExtractorDefinition>,
+}
+
+impl From<&Box<dyn ServiceDefinition>> for PublishedServiceDefinition {
+    fn from(definition: &Box<dyn ServiceDefinition>) -> Self {
+        Self {
+            name: definition.name().to_string(),
+            description: definition.description().to_string(),
+            category: definition.category(),
+            is_generic: ServiceDefinitionExt::is_generic(definition),
+            dashboard_icons_path: definition.dashboard_icons_path().to_string(),
+            simple_icons_path: definition.simple_icons_path().to_string(),
+            vector_logo_zone_icons_path: definition.vector_logo_zone_icons_path().to_string(),
+            logo_needs_white_background: definition.logo_needs_white_background(),
+            discovery_pattern: PatternDefinition::from(&definition.discovery_pattern()),
+            version_extractors: definition
+                .version_extractors()
+                .iter()
+                .map(VersionExtractorDefinition::from)
+                .collect(),
+        }
+    }
+}
+
+/// `ServiceDefinition` implementation backed by a `CustomServiceDefinition` or a
+/// `PublishedServiceDefinition`, registered in `ServiceDefinitionRegistry` at runtime
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynamicServiceDefinition {
+    name: String,
+    description: String,
+    category: ServiceCategory,
+    is_generic: bool,
+    dashboard_icons_path: String,
+    simple_icons_path: String,
+    vector_logo_zone_icons_path: String,
+    logo_needs_white_background: bool,
+    discovery_pattern: PatternDefinition,
+    version_extractors: Vec<VersionExtractorDefinition>,
+}
+
+impl From<&CustomServiceDefinitionBase> for DynamicServiceDefinition {
+    fn from(base: &CustomServiceDefinitionBase) -> Self {
+        Self {
+            name: base.name.clone(),
+            description: base.description.clone(),
+            category: base.category,
+            is_generic: false,
+            dashboard_icons_path: base.dashboard_icons_path.clone(),
+            simple_icons_path: base.simple_icons_path.clone(),
+            vector_logo_zone_icons_path: base.vector_logo_zone_icons_path.clone(),
+            logo_needs_white_background: base.logo_needs_white_background,
+            discovery_pattern: base.discovery_pattern.clone(),
+            version_extractors: base.version_extractors.clone(),
+        }
+    }
+}
+
+impl From<&PublishedServiceDefinition> for DynamicServiceDefinition {
+    fn from(definition: &PublishedServiceDefinition) -> Self {
+        Self {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            category: definition.category,
+            is_generic: definition.is_generic,
+            dashboard_icons_path: definition.dashboard_icons_path.clone(),
+            simple_icons_path: definition.simple_icons_path.clone(),
+            vector_logo_zone_icons_path: definition.vector_logo_zone_icons_path.clone(),
+            logo_needs_white_background: definition.logo_needs_white_background,
+            discovery_pattern: definition.discovery_pattern.clone(),
+            version_extractors: definition.version_extractors.clone(),
+        }
+    }
+}
+
+impl ServiceDefinition for DynamicServiceDefinition {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn category(&self) -> ServiceCategory {
+        self.category
+    }
+    fn discovery_pattern(&self) -> Pattern<'_> {
+        self.discovery_pattern.to_pattern()
+    }
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        self.version_extractors
+            .iter()
+            .map(|e| e.to_extractor())
+            .collect()
+    }
+    fn is_generic(&self) -> bool {
+        self.is_generic
+    }
+    fn dashboard_icons_path(&self) -> &str {
+        &self.dashboard_icons_path
+    }
+    fn simple_icons_path(&self) -> &str {
+        &self.simple_icons_path
+    }
+    fn vector_logo_zone_icons_path(&self) -> &str {
+        &self.vector_logo_zone_icons_path
+    }
+    fn logo_needs_white_background(&self) -> bool {
+        self.logo_needs_white_background
+    }
+}
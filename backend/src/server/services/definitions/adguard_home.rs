@@ -19,12 +19,15 @@ impl ServiceDefinition for AdguardHome {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::AllOf(vec![
+        Pattern::AnyOf(vec![
             Pattern::AllOf(vec![
-                Pattern::Port(PortBase::DnsUdp),
-                Pattern::Port(PortBase::DnsTcp),
+                Pattern::AllOf(vec![
+                    Pattern::Port(PortBase::DnsUdp),
+                    Pattern::Port(PortBase::DnsTcp),
+                ]),
+                Pattern::Endpoint(PortBase::Http, "/", "AdGuard Home"),
             ]),
-            Pattern::Endpoint(PortBase::Http, "/", "AdGuard Home"),
+            Pattern::DockerImage("adguard/adguardhome"),
         ])
     }
 
@@ -19,7 +19,10 @@ impl ServiceDefinition for Cloudflared {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/metrics", "cloudflared")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/metrics", "cloudflared"),
+            Pattern::DockerImage("cloudflare/cloudflared"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
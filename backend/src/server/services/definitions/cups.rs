@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct CUPS;
@@ -25,6 +26,19 @@ impl ServiceDefinition for CUPS {
         ])
     }
 
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::header(
+            PortBase::Http,
+            "/",
+            "Server",
+            r"CUPS/([\d.]+)",
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("openprinting:cups")
+    }
+
     fn dashboard_icons_path(&self) -> &'static str {
         "cups"
     }
@@ -1,9 +1,7 @@
 use crate::server::services::definitions::{ServiceDefinitionFactory, create_service};
-use crate::server::services::types::base::DiscoverySessionServiceMatchParams;
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
-use crate::server::services::types::patterns::{MatchConfidence, Pattern};
-use crate::server::services::types::virtualization::{DockerVirtualization, ServiceVirtualization};
+use crate::server::services::types::patterns::{MatchPredicate, Pattern};
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct DockerContainer;
@@ -22,32 +20,7 @@ impl ServiceDefinition for DockerContainer {
     fn discovery_pattern(&self) -> Pattern<'_> {
         Pattern::AllOf(vec![
             Pattern::DockerContainer,
-            Pattern::Custom(
-                |p: &DiscoverySessionServiceMatchParams| {
-                    // If there's a matched service with the id of the container, the container was already detected as a non-generic service
-                    let c_id = match p.baseline_params.virtualization {
-                        Some(ServiceVirtualization::Docker(DockerVirtualization {
-                            container_id: Some(id),
-                            ..
-                        })) => id,
-                        _ => return false, // No docker container_id -> not a docker container
-                    };
-
-                    p.service_params
-                        .matched_services
-                        .iter()
-                        .all(|s| match &s.base.virtualization {
-                            Some(ServiceVirtualization::Docker(DockerVirtualization {
-                                container_id,
-                                ..
-                            })) if container_id.is_some() => *container_id != Some(c_id.clone()),
-                            _ => true,
-                        })
-                },
-                "No other services with this container's ID have been matched",
-                "A service with this container's ID has already been matched",
-                MatchConfidence::Low,
-            ),
+            Pattern::Predicate(MatchPredicate::NoServiceMatchedInContainer),
         ])
     }
 
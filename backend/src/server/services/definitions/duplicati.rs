@@ -19,7 +19,12 @@ impl ServiceDefinition for Duplicati {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/", "Duplicati")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/", "Duplicati"),
+            Pattern::DockerImage("duplicati/duplicati"),
+            Pattern::DockerImage("linuxserver/duplicati"),
+            Pattern::DockerImage("lscr.io/linuxserver/duplicati"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct Emby;
@@ -19,7 +20,20 @@ impl ServiceDefinition for Emby {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::new_tcp(8096), "/emby/System/Info/Public", "Emby")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::new_tcp(8096), "/emby/System/Info/Public", "Emby"),
+            Pattern::DockerImage("emby/embyserver"),
+            Pattern::DockerImage("linuxserver/emby"),
+            Pattern::DockerImage("lscr.io/linuxserver/emby"),
+        ])
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::body(
+            PortBase::new_tcp(8096),
+            "/emby/System/Info/Public",
+            r#""Version"\s*:\s*"([^"]+)""#,
+        )]
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
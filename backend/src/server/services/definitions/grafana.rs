@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct Grafana;
@@ -19,7 +20,24 @@ impl ServiceDefinition for Grafana {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/", "grafana")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/", "grafana"),
+            Pattern::DockerImage("grafana/grafana"),
+            Pattern::DockerImage("grafana/grafana-oss"),
+            Pattern::DockerImage("grafana/grafana-enterprise"),
+        ])
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::body(
+            PortBase::Http,
+            "/api/health",
+            r#""version"\s*:\s*"([^"]+)""#,
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("grafana:grafana")
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
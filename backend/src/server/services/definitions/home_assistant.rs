@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct HomeAssistant;
@@ -19,7 +20,25 @@ impl ServiceDefinition for HomeAssistant {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::new_tcp(8123), "/auth/authorize", "home assistant")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::new_tcp(8123), "/auth/authorize", "home assistant"),
+            Pattern::DockerImage("ghcr.io/home-assistant/home-assistant"),
+            Pattern::DockerImage("homeassistant/home-assistant"),
+            Pattern::DockerImage("linuxserver/homeassistant"),
+            Pattern::DockerImage("lscr.io/linuxserver/homeassistant"),
+        ])
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::body(
+            PortBase::new_tcp(8123),
+            "/manifest.json",
+            r#""version"\s*:\s*"([^"]+)""#,
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("home-assistant:home-assistant")
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct Jellyfin;
@@ -19,7 +20,24 @@ impl ServiceDefinition for Jellyfin {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/System/Info/Public", "Jellyfin")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/System/Info/Public", "Jellyfin"),
+            Pattern::DockerImage("jellyfin/jellyfin"),
+            Pattern::DockerImage("linuxserver/jellyfin"),
+            Pattern::DockerImage("lscr.io/linuxserver/jellyfin"),
+        ])
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::body(
+            PortBase::Http,
+            "/System/Info/Public",
+            r#""Version"\s*:\s*"([^"]+)""#,
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("jellyfin:jellyfin")
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
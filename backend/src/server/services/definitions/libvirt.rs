@@ -0,0 +1,33 @@
+use crate::server::hosts::types::ports::PortBase;
+use crate::server::services::definitions::{ServiceDefinitionFactory, create_service};
+use crate::server::services::types::categories::ServiceCategory;
+use crate::server::services::types::definitions::ServiceDefinition;
+use crate::server::services::types::patterns::Pattern;
+
+#[derive(Default, Clone, Eq, PartialEq, Hash)]
+pub struct Libvirt;
+
+impl ServiceDefinition for Libvirt {
+    fn name(&self) -> &'static str {
+        "Libvirt"
+    }
+    fn description(&self) -> &'static str {
+        "Virtualization API and daemon for KVM / QEMU hypervisors"
+    }
+    fn category(&self) -> ServiceCategory {
+        ServiceCategory::Virtualization
+    }
+
+    fn discovery_pattern(&self) -> Pattern<'_> {
+        Pattern::AnyOf(vec![
+            Pattern::Port(PortBase::new_tcp(16509)),
+            Pattern::Port(PortBase::new_tcp(16514)),
+        ])
+    }
+
+    fn simple_icons_path(&self) -> &'static str {
+        "qemu"
+    }
+}
+
+inventory::submit!(ServiceDefinitionFactory::new(create_service::<Libvirt>));
@@ -1,6 +1,14 @@
+use crate::server::hosts::types::ports::PortBase;
+use crate::server::service_definitions::types::{
+    CustomServiceDefinition, DynamicServiceDefinition, PublishedServiceDefinition,
+};
 use crate::server::services::types::definitions::ServiceDefinition;
+use crate::server::services::types::virtualization::image_matches_repository;
 use crate::server::shared::types::metadata::HasId;
 use inventory;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ServiceDefinitionFactory(pub fn() -> Box<dyn ServiceDefinition>);
@@ -24,30 +32,247 @@ where
 
 inventory::collect!(ServiceDefinitionFactory);
 
+/// User-defined definitions by network, loaded from the database on the server. Daemons receive
+/// their network's definitions with the published definitions.
+static CUSTOM_SERVICE_DEFINITIONS: RwLock<Option<HashMap<Uuid, Vec<DynamicServiceDefinition>>>> =
+    RwLock::new(None);
+
+/// Definitions downloaded from the server on daemons. These take precedence over the definitions
+/// compiled into the daemon binary, so definition updates don't require redeploying daemons.
+static PUBLISHED_SERVICE_DEFINITIONS: RwLock<Vec<DynamicServiceDefinition>> =
+    RwLock::new(Vec::new());
+
+/// Built registry, cached because matching looks definitions up for every port and endpoint.
+/// Cleared whenever published definitions change, and rebuilt on next use.
+static REGISTRY: RwLock<Option<Arc<RegisteredDefinitions>>> = RwLock::new(None);
+
+pub struct RegisteredDefinitions {
+    pub definitions: Vec<Box<dyn ServiceDefinition>>,
+    /// Ids of the definitions whose discovery pattern includes each port
+    port_users: HashMap<PortBase, Vec<String>>,
+}
+
+impl RegisteredDefinitions {
+    fn build() -> Self {
+        let definitions = ServiceDefinitionRegistry::build_service_definitions();
+
+        let mut port_users: HashMap<PortBase, Vec<String>> = HashMap::new();
+        for definition in &definitions {
+            for port in definition.discovery_pattern().ports() {
+                let users = port_users.entry(port).or_default();
+                if !users.iter().any(|id| id == definition.id()) {
+                    users.push(definition.id().to_string());
+                }
+            }
+        }
+
+        Self {
+            definitions,
+            port_users,
+        }
+    }
+
+    /// Whether no definition other than the given one matches on the port
+    pub fn port_is_unique_to(&self, port: &PortBase, definition_id: &str) -> bool {
+        self.port_users
+            .get(port)
+            .is_none_or(|users| users.iter().all(|id| id == definition_id))
+    }
+}
+
 pub struct ServiceDefinitionRegistry;
 
 impl ServiceDefinitionRegistry {
-    /// Get all registered services as instances
+    /// The current registry, built if it isn't cached
+    pub fn registered() -> Arc<RegisteredDefinitions> {
+        if let Some(registered) = REGISTRY.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return registered.clone();
+        }
+
+        // Built under the write lock so a concurrent change can't be overwritten by a registry
+        // built from the definitions it replaced
+        let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+        registry
+            .get_or_insert_with(|| Arc::new(RegisteredDefinitions::build()))
+            .clone()
+    }
+
+    /// Get all built-in and published definitions as instances. Custom definitions belong to a
+    /// network, see `network_service_definitions`.
     pub fn all_service_definitions() -> Vec<Box<dyn ServiceDefinition>> {
-        inventory::iter::<ServiceDefinitionFactory>()
-            .map(|factory| factory.create())
+        Self::registered().definitions.clone()
+    }
+
+    fn build_service_definitions() -> Vec<Box<dyn ServiceDefinition>> {
+        let published = Self::published_service_definitions();
+
+        let mut definitions: Vec<Box<dyn ServiceDefinition>> =
+            inventory::iter::<ServiceDefinitionFactory>()
+                .map(|factory| {
+                    let compiled = factory.create();
+                    published
+                        .iter()
+                        .find(|d| d.id() == compiled.id())
+                        .cloned()
+                        .unwrap_or(compiled)
+                })
+                .collect();
+
+        // Published definitions this binary doesn't know about, ie user-defined or added in a
+        // newer server release
+        let unknown: Vec<_> = published
+            .into_iter()
+            .filter(|p| !definitions.iter().any(|d| d.id() == p.id()))
+            .collect();
+
+        definitions.extend(unknown);
+        definitions
+    }
+
+    fn published_service_definitions() -> Vec<Box<dyn ServiceDefinition>> {
+        PUBLISHED_SERVICE_DEFINITIONS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|d| Box::new(d.clone()) as Box<dyn ServiceDefinition>)
+            .collect()
+    }
+
+    /// Replace the definitions published by the server
+    pub fn set_published_service_definitions(definitions: &[PublishedServiceDefinition]) {
+        let definitions = definitions
+            .iter()
+            .map(DynamicServiceDefinition::from)
+            .collect();
+
+        let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+        *PUBLISHED_SERVICE_DEFINITIONS
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = definitions;
+        *registry = None;
+    }
+
+    /// Built-in and published definitions followed by the network's custom definitions
+    pub fn network_service_definitions(network_id: &Uuid) -> Vec<Box<dyn ServiceDefinition>> {
+        let mut definitions = Self::all_service_definitions();
+        definitions.extend(Self::custom_service_definitions(network_id));
+        definitions
+    }
+
+    pub fn custom_service_definitions(network_id: &Uuid) -> Vec<Box<dyn ServiceDefinition>> {
+        CUSTOM_SERVICE_DEFINITIONS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .and_then(|custom| custom.get(network_id))
+            .into_iter()
+            .flatten()
+            .map(|d| Box::new(d.clone()) as Box<dyn ServiceDefinition>)
             .collect()
     }
 
-    pub fn service_exists(id: &str) -> bool {
-        inventory::iter::<ServiceDefinitionFactory>().any(|factory| factory.create().id() == id)
+    /// Replace the registered custom definitions of every network
+    pub fn set_custom_service_definitions(definitions: &[CustomServiceDefinition]) {
+        let mut by_network: HashMap<Uuid, Vec<DynamicServiceDefinition>> = HashMap::new();
+        for d in definitions {
+            by_network
+                .entry(d.base.network_id)
+                .or_default()
+                .push(DynamicServiceDefinition::from(&d.base));
+        }
+
+        *CUSTOM_SERVICE_DEFINITIONS
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(by_network);
+    }
+
+    pub fn service_exists(network_id: &Uuid, id: &str) -> bool {
+        Self::find_in_network(network_id, id).is_some()
+    }
+
+    /// Find a non-generic service definition for a container image reference, ie
+    /// "ghcr.io/home-assistant/home-assistant:stable". Definitions declaring the image's repository
+    /// in a DockerImage pattern take precedence over a match on name or icon.
+    pub fn find_by_image(image: &str) -> Option<Box<dyn ServiceDefinition>> {
+        let registered = Self::registered();
+        let definitions: Vec<_> = registered
+            .definitions
+            .iter()
+            .filter(|d| !d.is_generic())
+            .collect();
+
+        if let Some(position) = definitions.iter().position(|d| {
+            d.discovery_pattern()
+                .docker_images()
+                .iter()
+                .any(|repository| image_matches_repository(image, repository))
+        }) {
+            return Some(definitions[position].clone());
+        }
+
+        let normalize = |s: &str| -> String {
+            s.chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+
+        let without_digest = image.split('@').next().unwrap_or(image);
+        let repository = without_digest
+            .rsplit('/')
+            .next()
+            .unwrap_or(without_digest)
+            .split(':')
+            .next()
+            .unwrap_or_default();
+
+        let repository = normalize(repository);
+
+        if repository.is_empty() {
+            return None;
+        }
+
+        definitions
+            .into_iter()
+            .find(|d| {
+                normalize(d.name()) == repository
+                    || normalize(d.dashboard_icons_path()) == repository
+            })
+            .cloned()
     }
 
+    /// Find a built-in or published definition, falling back to a custom definition of any
+    /// network. Only for deserializing, where the network isn't known; services are resolved
+    /// against their own network with `find_in_network`.
     pub fn find_by_id(id: &str) -> Option<Box<dyn ServiceDefinition>> {
-        inventory::iter::<ServiceDefinitionFactory>().find_map(|factory| {
-            let service_definition = factory.create();
-            if service_definition.id() == id {
-                Some(service_definition)
-            } else {
-                None
-            }
+        Self::find_built_in(id).or_else(|| {
+            CUSTOM_SERVICE_DEFINITIONS
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()?
+                .values()
+                .flatten()
+                .find(|d| d.id() == id)
+                .map(|d| Box::new(d.clone()) as Box<dyn ServiceDefinition>)
+        })
+    }
+
+    /// Find a built-in or published definition, or a custom definition of the network
+    pub fn find_in_network(network_id: &Uuid, id: &str) -> Option<Box<dyn ServiceDefinition>> {
+        Self::find_built_in(id).or_else(|| {
+            Self::custom_service_definitions(network_id)
+                .into_iter()
+                .find(|d| d.id() == id)
         })
     }
+
+    fn find_built_in(id: &str) -> Option<Box<dyn ServiceDefinition>> {
+        Self::registered()
+            .definitions
+            .iter()
+            .find(|d| d.id() == id)
+            .cloned()
+    }
 }
 
 pub mod access_point;
@@ -81,6 +306,7 @@ pub mod home_assistant;
 pub mod hp_printer;
 pub mod jellyfin;
 pub mod kubernetes;
+pub mod libvirt;
 pub mod nas_device;
 pub mod nest_protect;
 pub mod nest_thermostat;
@@ -105,6 +331,7 @@ pub mod restic;
 pub mod ring_doorbell;
 pub mod roku;
 pub mod sonos_speaker;
+pub mod ssh_server;
 pub mod switch;
 pub mod syncthing;
 pub mod synology;
@@ -19,7 +19,12 @@ impl ServiceDefinition for NextCloud {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/", "nextcloud")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/", "nextcloud"),
+            Pattern::DockerImage("nextcloud"),
+            Pattern::DockerImage("linuxserver/nextcloud"),
+            Pattern::DockerImage("lscr.io/linuxserver/nextcloud"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
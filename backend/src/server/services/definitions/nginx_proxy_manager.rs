@@ -19,7 +19,10 @@ impl ServiceDefinition for NginxProxyManager {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "", "nginx proxy manager")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "", "nginx proxy manager"),
+            Pattern::DockerImage("jc21/nginx-proxy-manager"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
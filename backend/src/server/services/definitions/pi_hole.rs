@@ -19,12 +19,15 @@ impl ServiceDefinition for PiHole {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::AllOf(vec![
-            Pattern::AnyOf(vec![
-                Pattern::Port(PortBase::DnsUdp),
-                Pattern::Port(PortBase::DnsTcp),
+        Pattern::AnyOf(vec![
+            Pattern::AllOf(vec![
+                Pattern::AnyOf(vec![
+                    Pattern::Port(PortBase::DnsUdp),
+                    Pattern::Port(PortBase::DnsTcp),
+                ]),
+                Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole"),
             ]),
-            Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole"),
+            Pattern::DockerImage("pihole/pihole"),
         ])
     }
 
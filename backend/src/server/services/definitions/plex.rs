@@ -3,6 +3,7 @@ use crate::server::services::definitions::{ServiceDefinitionFactory, create_serv
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::definitions::ServiceDefinition;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct Plex;
@@ -19,7 +20,24 @@ impl ServiceDefinition for Plex {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/web/index.html", "Plex")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/web/index.html", "Plex"),
+            Pattern::DockerImage("plexinc/pms-docker"),
+            Pattern::DockerImage("linuxserver/plex"),
+            Pattern::DockerImage("lscr.io/linuxserver/plex"),
+        ])
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::body(
+            PortBase::Http,
+            "/identity",
+            r#"version="([^"]+)""#,
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("plex:media_server")
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
@@ -22,6 +22,8 @@ impl ServiceDefinition for Portainer {
         Pattern::AnyOf(vec![
             Pattern::Endpoint(PortBase::new_tcp(9443), "/", "portainer"),
             Pattern::Port(PortBase::new_tcp(9443)),
+            Pattern::DockerImage("portainer/portainer-ce"),
+            Pattern::DockerImage("portainer/portainer-ee"),
         ])
     }
 
@@ -22,6 +22,7 @@ impl ServiceDefinition for Prometheus {
         Pattern::AnyOf(vec![
             Pattern::Endpoint(PortBase::Http, "/metrics", "Prometheus"),
             Pattern::Endpoint(PortBase::Http, "/graph", "Prometheus"),
+            Pattern::DockerImage("prom/prometheus"),
         ])
     }
 
@@ -0,0 +1,42 @@
+use crate::server::hosts::types::ports::PortBase;
+use crate::server::services::definitions::{ServiceDefinitionFactory, create_service};
+use crate::server::services::types::categories::ServiceCategory;
+use crate::server::services::types::definitions::ServiceDefinition;
+use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
+
+#[derive(Default, Clone, Eq, PartialEq, Hash)]
+pub struct SshServer;
+
+impl ServiceDefinition for SshServer {
+    fn name(&self) -> &'static str {
+        "SSH Server"
+    }
+    fn description(&self) -> &'static str {
+        "Generic secure shell remote access"
+    }
+    fn category(&self) -> ServiceCategory {
+        ServiceCategory::NetworkCore
+    }
+
+    fn discovery_pattern(&self) -> Pattern<'_> {
+        Pattern::Port(PortBase::Ssh)
+    }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![VersionExtractor::banner(
+            PortBase::Ssh,
+            r"^SSH-[\d.]+-OpenSSH_([\w.]+)",
+        )]
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        Some("openbsd:openssh")
+    }
+
+    fn is_generic(&self) -> bool {
+        true
+    }
+}
+
+inventory::submit!(ServiceDefinitionFactory::new(create_service::<SshServer>));
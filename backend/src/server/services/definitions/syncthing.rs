@@ -19,7 +19,12 @@ impl ServiceDefinition for Syncthing {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/", "Syncthing")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/", "Syncthing"),
+            Pattern::DockerImage("syncthing/syncthing"),
+            Pattern::DockerImage("linuxserver/syncthing"),
+            Pattern::DockerImage("lscr.io/linuxserver/syncthing"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
@@ -19,7 +19,10 @@ impl ServiceDefinition for Traefik {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/dashboard", "traefik")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/dashboard", "traefik"),
+            Pattern::DockerImage("traefik"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
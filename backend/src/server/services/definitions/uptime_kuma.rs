@@ -19,7 +19,10 @@ impl ServiceDefinition for UptimeKuma {
     }
 
     fn discovery_pattern(&self) -> Pattern<'_> {
-        Pattern::Endpoint(PortBase::Http, "/", "Uptime Kuma")
+        Pattern::AnyOf(vec![
+            Pattern::Endpoint(PortBase::Http, "/", "Uptime Kuma"),
+            Pattern::DockerImage("louislam/uptime-kuma"),
+        ])
     }
 
     fn dashboard_icons_path(&self) -> &'static str {
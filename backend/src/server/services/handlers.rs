@@ -1,11 +1,15 @@
 use crate::server::{
     config::AppState,
-    services::types::base::Service,
-    shared::types::api::{ApiError, ApiResponse, ApiResult},
+    entity_changes::types::{EntityChange, EntityType},
+    services::types::{api::ServiceQuery, base::Service},
+    shared::types::{
+        api::{ApiError, ApiResponse, ApiResult},
+        query::Page,
+    },
 };
 use axum::{
     Router,
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::Json,
     routing::get,
 };
@@ -13,13 +17,15 @@ use std::{collections::HashMap, sync::Arc};
 use uuid::Uuid;
 
 pub fn create_router() -> Router<Arc<AppState>> {
-    Router::new().route("/", get(get_all_services))
+    Router::new()
+        .route("/", get(get_all_services))
+        .route("/:id/history", get(get_service_history))
 }
 
 async fn get_all_services(
     State(state): State<Arc<AppState>>,
     Query(params): Query<HashMap<String, String>>,
-) -> ApiResult<Json<ApiResponse<Vec<Service>>>> {
+) -> ApiResult<Json<ApiResponse<Page<Service>>>> {
     let network_id = params
         .get("network_id")
         .and_then(|id| Uuid::parse_str(id).ok())
@@ -27,7 +33,42 @@ async fn get_all_services(
 
     let service_service = &state.services.service_service;
 
-    let subnets = service_service.get_all_services(&network_id).await?;
+    // Looking up by image returns every service running that image, so it isn't combined with
+    // the other filters or pagination
+    if let Some(image) = params.get("image") {
+        if let Some(other) = params
+            .keys()
+            .find(|key| !matches!(key.as_str(), "network_id" | "image"))
+        {
+            return Err(ApiError::bad_request(&format!(
+                "image can't be combined with {}",
+                other
+            )));
+        }
 
-    Ok(Json(ApiResponse::success(subnets)))
+        let services = service_service
+            .get_services_with_image(&network_id, image)
+            .await?;
+        return Ok(Json(ApiResponse::success(Page::all(services))));
+    }
+
+    let query = ServiceQuery::from_params(network_id, &params)
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    let services = service_service.query_services(&query).await?;
+
+    Ok(Json(ApiResponse::success(services)))
+}
+
+async fn get_service_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<EntityChange>>>> {
+    let history = state
+        .services
+        .entity_change_service
+        .get_history(EntityType::Service, &id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(history)))
 }
@@ -1,18 +1,24 @@
 use crate::server::{
     discovery::types::base::{EntitySource, EntitySourceDiscriminants},
+    entity_changes::{service::EntityChangeService, types::ChangeAction},
     groups::{service::GroupService, types::GroupType},
     hosts::{
         service::HostService,
         types::{base::Host, interfaces::Interface},
     },
     services::{
+        definitions::ServiceDefinitionRegistry,
         storage::ServiceStorage,
         types::{
+            api::ServiceQuery,
             base::Service,
             bindings::Binding,
             patterns::{MatchDetails, MatchReason},
+            versions::ServiceVersion,
+            virtualization::ImageReference,
         },
     },
+    shared::types::{metadata::HasId, query::Page},
 };
 use anyhow::anyhow;
 use anyhow::{Error, Result};
@@ -28,15 +34,21 @@ pub struct ServiceService {
     storage: Arc<dyn ServiceStorage>,
     host_service: OnceLock<Arc<HostService>>,
     group_service: Arc<GroupService>,
+    entity_change_service: Arc<EntityChangeService>,
     group_update_lock: Arc<Mutex<()>>,
     service_locks: Arc<Mutex<HashMap<Uuid, Arc<Mutex<()>>>>>,
 }
 
 impl ServiceService {
-    pub fn new(storage: Arc<dyn ServiceStorage>, group_service: Arc<GroupService>) -> Self {
+    pub fn new(
+        storage: Arc<dyn ServiceStorage>,
+        group_service: Arc<GroupService>,
+        entity_change_service: Arc<EntityChangeService>,
+    ) -> Self {
         Self {
             storage,
             group_service,
+            entity_change_service,
             host_service: OnceLock::new(),
             group_update_lock: Arc::new(Mutex::new(())),
             service_locks: Arc::new(Mutex::new(HashMap::new())),
@@ -55,7 +67,9 @@ impl ServiceService {
         self.host_service.set(host_service)
     }
 
-    pub async fn create_service(&self, service: Service) -> Result<Service> {
+    pub async fn create_service(&self, mut service: Service) -> Result<Service> {
+        Self::resolve_service_definition(&mut service)?;
+
         let lock = self.get_service_lock(&service.id).await;
         let _guard = lock.lock().await;
 
@@ -81,7 +95,12 @@ impl ServiceService {
                 self.upsert_service(existing_service, service).await?
             }
             _ => {
-                self.storage.create(&service).await?;
+                let change = self.entity_change_service.change(
+                    ChangeAction::Create,
+                    None,
+                    Some(&service),
+                )?;
+                self.storage.create(&service, change.as_ref()).await?;
                 tracing::info!(
                     "Created service {} for host {}",
                     service,
@@ -95,6 +114,17 @@ impl ServiceService {
         Ok(service_from_storage)
     }
 
+    /// Services name their definition, and a custom definition with that name has to exist on the
+    /// service's network. Deserializing may have picked up another network's definition.
+    fn resolve_service_definition(service: &mut Service) -> Result<()> {
+        let id = service.base.service_definition.id().to_string();
+
+        service.base.service_definition =
+            ServiceDefinitionRegistry::find_in_network(&service.base.network_id, &id)
+                .ok_or_else(|| anyhow!("Service definition '{}' not found on this network", id))?;
+        Ok(())
+    }
+
     pub async fn upsert_service(
         &self,
         mut existing_service: Service,
@@ -111,6 +141,8 @@ impl ServiceService {
             existing_service
         );
 
+        let service_before_upsert = existing_service.clone();
+
         for new_service_binding in &new_service_data.base.bindings {
             if !existing_service.base.bindings.contains(new_service_binding) {
                 binding_updates += 1;
@@ -122,6 +154,27 @@ impl ServiceService {
             existing_service.base.virtualization = Some(virtualization.clone())
         }
 
+        // Keep the last detected version if the latest discovery couldn't read one
+        let mut version_update = None;
+        if let Some(version) = &new_service_data.base.version
+            && existing_service.base.version.as_ref() != Some(version)
+        {
+            version_update = Some(format!(
+                "version {} -> {}",
+                existing_service
+                    .base
+                    .version
+                    .as_deref()
+                    .unwrap_or("unknown"),
+                version
+            ));
+            existing_service.base.version = Some(version.clone());
+            existing_service
+                .base
+                .version_history
+                .push(ServiceVersion::new(version.clone()));
+        }
+
         existing_service.base.source = match (
             existing_service.base.source,
             new_service_data.base.source.clone(),
@@ -146,6 +199,9 @@ impl ServiceService {
                 let confidence = existing_service_details
                     .confidence
                     .max(new_service_details.confidence);
+                let score = existing_service_details
+                    .score
+                    .max(new_service_details.score);
 
                 let reason_str = format!(
                     "Updated match data on {}",
@@ -172,7 +228,11 @@ impl ServiceService {
 
                 EntitySource::DiscoveryWithMatch {
                     metadata: new_metadata,
-                    details: MatchDetails { confidence, reason },
+                    details: MatchDetails {
+                        confidence,
+                        reason,
+                        score,
+                    },
                 }
             }
 
@@ -192,7 +252,14 @@ impl ServiceService {
             (existing_source, _) => existing_source,
         };
 
-        self.storage.update(&existing_service).await?;
+        let change = self.entity_change_service.change(
+            ChangeAction::Upsert,
+            Some(&service_before_upsert),
+            Some(&existing_service),
+        )?;
+        self.storage
+            .update(&existing_service, change.as_ref())
+            .await?;
 
         let mut data = Vec::new();
 
@@ -200,6 +267,10 @@ impl ServiceService {
             data.push(format!("{} bindings", binding_updates))
         };
 
+        if let Some(version_update) = version_update {
+            data.push(version_update)
+        };
+
         if !data.is_empty() {
             tracing::info!(
                 "Upserted service {} with new data: {}",
@@ -226,11 +297,29 @@ impl ServiceService {
         self.storage.get_all(network_id).await
     }
 
+    pub async fn query_services(&self, query: &ServiceQuery) -> Result<Page<Service>> {
+        self.storage.query(query).await
+    }
+
+    /// Get services running in docker containers created from the given image reference.
+    /// A reference without a tag matches every tag of the image.
+    pub async fn get_services_with_image(
+        &self,
+        network_id: &Uuid,
+        image: &str,
+    ) -> Result<Vec<Service>> {
+        self.storage
+            .get_by_image(network_id, &ImageReference::parse(image))
+            .await
+    }
+
     pub async fn get_services_for_host(&self, host_id: &Uuid) -> Result<Vec<Service>> {
         self.storage.get_services_for_host(host_id).await
     }
 
     pub async fn update_service(&self, mut service: Service) -> Result<Service> {
+        Self::resolve_service_definition(&mut service)?;
+
         let lock = self.get_service_lock(&service.id).await;
         let _guard = lock.lock().await;
 
@@ -246,7 +335,12 @@ impl ServiceService {
 
         service.updated_at = chrono::Utc::now();
 
-        self.storage.update(&service).await?;
+        let change = self.entity_change_service.change(
+            ChangeAction::Update,
+            Some(&current_service),
+            Some(&service),
+        )?;
+        self.storage.update(&service, change.as_ref()).await?;
         tracing::info!(
             "Updated service {} for host {}",
             service,
@@ -415,6 +509,36 @@ impl ServiceService {
         mutable_service
     }
 
+    /// Write services back to an earlier state, recreating any deleted since. Used to roll back
+    /// host operations that fail after moving some of their services.
+    pub async fn restore_services(&self, services: &[Service]) -> Result<()> {
+        for service in services {
+            let lock = self.get_service_lock(&service.id).await;
+            let _guard = lock.lock().await;
+
+            match self.get_service(&service.id).await? {
+                Some(current_service) => {
+                    let change = self.entity_change_service.change(
+                        ChangeAction::Update,
+                        Some(&current_service),
+                        Some(service),
+                    )?;
+                    self.storage.update(service, change.as_ref()).await?;
+                }
+                None => {
+                    let change = self.entity_change_service.change(
+                        ChangeAction::Create,
+                        None,
+                        Some(service),
+                    )?;
+                    self.storage.create(service, change.as_ref()).await?;
+                }
+            }
+            tracing::info!("Restored service {}", service);
+        }
+        Ok(())
+    }
+
     pub async fn delete_service(&self, id: &Uuid) -> Result<()> {
         let lock = self.get_service_lock(id).await;
         let _guard = lock.lock().await;
@@ -426,7 +550,10 @@ impl ServiceService {
 
         self.update_group_service_bindings(&service, None).await?;
 
-        self.storage.delete(id).await?;
+        let change =
+            self.entity_change_service
+                .change(ChangeAction::Delete, Some(&service), None)?;
+        self.storage.delete(id, change.as_ref()).await?;
         tracing::info!(
             "Deleted service {}: {} for host {}",
             service.base.name,
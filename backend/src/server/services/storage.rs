@@ -1,26 +1,42 @@
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use serde_json::json;
+use sqlx::{PgPool, QueryBuilder, Row};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 use crate::server::{
     discovery::types::base::EntitySource,
+    entity_changes::{storage::append_change, types::EntityChange},
+    services::definitions::ServiceDefinitionRegistry,
     services::types::{
+        api::ServiceQuery,
         base::{Service, ServiceBase},
         bindings::Binding,
         definitions::ServiceDefinition,
-        virtualization::ServiceVirtualization,
+        versions::ServiceVersion,
+        virtualization::{ImageReference, ServiceVirtualization},
+    },
+    shared::{
+        storage::query::{
+            like_pattern, push_entity_filter, push_list_options, push_source_filter,
+            service_definition_values,
+        },
+        types::query::Page,
     },
 };
 
 #[async_trait]
 pub trait ServiceStorage: Send + Sync {
-    async fn create(&self, service: &Service) -> Result<()>;
+    async fn create(&self, service: &Service, change: Option<&EntityChange>) -> Result<()>;
     async fn get_by_id(&self, id: &Uuid) -> Result<Option<Service>>;
     async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Service>>;
+    async fn query(&self, query: &ServiceQuery) -> Result<Page<Service>>;
+    async fn get_by_image(&self, network_id: &Uuid, image: &ImageReference)
+    -> Result<Vec<Service>>;
     async fn get_services_for_host(&self, host_id: &Uuid) -> Result<Vec<Service>>;
-    async fn update(&self, service: &Service) -> Result<()>;
-    async fn delete(&self, id: &Uuid) -> Result<()>;
+    async fn update(&self, service: &Service, change: Option<&EntityChange>) -> Result<()>;
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()>;
 }
 
 pub struct PostgresServiceStorage {
@@ -35,18 +51,22 @@ impl PostgresServiceStorage {
 
 #[async_trait]
 impl ServiceStorage for PostgresServiceStorage {
-    async fn create(&self, service: &Service) -> Result<()> {
+    async fn create(&self, service: &Service, change: Option<&EntityChange>) -> Result<()> {
         let service_def_str = serde_json::to_string(&service.base.service_definition)?;
         let bindings_str = serde_json::to_value(&service.base.bindings)?;
         let virtualization_str = serde_json::to_value(&service.base.virtualization)?;
         let source_str = serde_json::to_value(&service.base.source)?;
+        let version_history_str = serde_json::to_value(&service.base.version_history)?;
+        let image = image_reference(service);
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO services (
                 id, name, host_id, service_definition, bindings, virtualization, 
-                source, created_at, updated_at, network_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                source, created_at, updated_at, network_id, version, version_history, stale,
+                tags, custom_fields, image_repository, image_tag
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             "#,
         )
         .bind(service.id)
@@ -59,9 +79,19 @@ impl ServiceStorage for PostgresServiceStorage {
         .bind(service.created_at)
         .bind(service.updated_at)
         .bind(service.base.network_id)
-        .execute(&self.pool)
+        .bind(&service.base.version)
+        .bind(version_history_str)
+        .bind(service.base.stale)
+        .bind(serde_json::to_value(&service.base.tags)?)
+        .bind(serde_json::to_value(&service.base.custom_fields)?)
+        .bind(image.as_ref().map(|i| &i.repository))
+        .bind(image.as_ref().and_then(|i| i.tag.as_ref()))
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -92,6 +122,79 @@ impl ServiceStorage for PostgresServiceStorage {
         Ok(services)
     }
 
+    async fn query(&self, query: &ServiceQuery) -> Result<Page<Service>> {
+        let mut builder = QueryBuilder::new("SELECT * FROM services WHERE network_id = ");
+        builder.push_bind(query.network_id);
+
+        if let Some(search) = &query.search {
+            builder
+                .push(" AND lower(name) LIKE ")
+                .push_bind(like_pattern(search));
+        }
+
+        if let Some(host_id) = query.host_id {
+            builder.push(" AND host_id = ").push_bind(host_id);
+        }
+
+        if let Some(subnet_id) = query.subnet_id {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM hosts h WHERE h.id = services.host_id \
+                     AND h.interfaces @> ",
+                )
+                .push_bind(json!([{ "subnet_id": subnet_id }]))
+                .push(")");
+        }
+
+        for ids in query.definitions.id_sets() {
+            builder
+                .push(" AND service_definition = ANY(")
+                .push_bind(service_definition_values(&ids))
+                .push(")");
+        }
+
+        push_source_filter(&mut builder, &query.source);
+        push_entity_filter(&mut builder, &query.filter);
+        push_list_options(&mut builder, &query.options);
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+
+        let mut services = Vec::new();
+        for row in rows {
+            services.push(row_to_service(row)?);
+        }
+
+        Ok(query.options.page(services))
+    }
+
+    async fn get_by_image(
+        &self,
+        network_id: &Uuid,
+        image: &ImageReference,
+    ) -> Result<Vec<Service>> {
+        // Images without a tag are pulled as latest
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM services
+            WHERE network_id = $1 AND image_repository = $2
+                AND ($3::TEXT IS NULL OR COALESCE(image_tag, 'latest') = $3)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(network_id)
+        .bind(&image.repository)
+        .bind(&image.tag)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut services = Vec::new();
+        for row in rows {
+            services.push(row_to_service(row)?);
+        }
+
+        Ok(services)
+    }
+
     async fn get_services_for_host(&self, host_id: &Uuid) -> Result<Vec<Service>> {
         let rows = sqlx::query("SELECT * FROM services WHERE host_id = $1 ORDER BY created_at")
             .bind(host_id)
@@ -106,17 +209,21 @@ impl ServiceStorage for PostgresServiceStorage {
         Ok(services)
     }
 
-    async fn update(&self, service: &Service) -> Result<()> {
+    async fn update(&self, service: &Service, change: Option<&EntityChange>) -> Result<()> {
         let service_def_str = serde_json::to_string(&service.base.service_definition)?;
         let bindings_str = serde_json::to_value(&service.base.bindings)?;
         let virtualization_str = serde_json::to_value(&service.base.virtualization)?;
         let source_str = serde_json::to_value(&service.base.source)?;
+        let version_history_str = serde_json::to_value(&service.base.version_history)?;
+        let image = image_reference(service);
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             UPDATE services SET 
                 name = $2, host_id = $3, service_definition = $4, bindings = $5, virtualization = $6, source = $7, 
-                updated_at = $8
+                updated_at = $8, version = $9, version_history = $10, stale = $11,
+                tags = $12, custom_fields = $13, image_repository = $14, image_tag = $15
             WHERE id = $1
             "#,
         )
@@ -128,27 +235,61 @@ impl ServiceStorage for PostgresServiceStorage {
         .bind(virtualization_str)
         .bind(source_str)
         .bind(service.updated_at)
-        .execute(&self.pool)
+        .bind(&service.base.version)
+        .bind(version_history_str)
+        .bind(service.base.stale)
+        .bind(serde_json::to_value(&service.base.tags)?)
+        .bind(serde_json::to_value(&service.base.custom_fields)?)
+        .bind(image.as_ref().map(|i| &i.repository))
+        .bind(image.as_ref().and_then(|i| i.tag.as_ref()))
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM services WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 }
 
+/// Normalized image of a docker service's container, stored alongside it for image lookups
+fn image_reference(service: &Service) -> Option<ImageReference> {
+    match &service.base.virtualization {
+        Some(ServiceVirtualization::Container(docker)) => {
+            docker.image.as_deref().map(ImageReference::parse)
+        }
+        _ => None,
+    }
+}
+
 fn row_to_service(row: sqlx::postgres::PgRow) -> Result<Service, Error> {
-    // Parse JSON fields safely
-    let service_definition: Box<dyn ServiceDefinition> =
+    // Parse JSON fields safely. Definitions are resolved against the service's network, which may
+    // have custom definitions of its own.
+    let network_id: Uuid = row.get("network_id");
+    let service_definition_id: String =
         serde_json::from_str(&row.get::<String, _>("service_definition"))
             .or(Err(Error::msg("Failed to deserialize service_definition")))?;
+    let service_definition: Box<dyn ServiceDefinition> =
+        ServiceDefinitionRegistry::find_in_network(&network_id, &service_definition_id)
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "Service definition not found: {}",
+                    service_definition_id
+                ))
+            })?;
     let bindings: Vec<Binding> =
         serde_json::from_value(row.get::<serde_json::Value, _>("bindings"))
             .or(Err(Error::msg("Failed to deserialize bindings")))?;
@@ -157,6 +298,14 @@ fn row_to_service(row: sqlx::postgres::PgRow) -> Result<Service, Error> {
             .or(Err(Error::msg("Failed to deserialize virtualization")))?;
     let source: EntitySource = serde_json::from_value(row.get::<serde_json::Value, _>("source"))
         .or(Err(Error::msg("Failed to deserialize source")))?;
+    let version_history: Vec<ServiceVersion> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("version_history"))
+            .or(Err(Error::msg("Failed to deserialize version_history")))?;
+    let tags: Vec<String> = serde_json::from_value(row.get::<serde_json::Value, _>("tags"))
+        .or(Err(Error::msg("Failed to deserialize tags")))?;
+    let custom_fields: BTreeMap<Uuid, String> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("custom_fields"))
+            .or(Err(Error::msg("Failed to deserialize custom_fields")))?;
 
     Ok(Service {
         id: row.get("id"),
@@ -164,12 +313,17 @@ fn row_to_service(row: sqlx::postgres::PgRow) -> Result<Service, Error> {
         updated_at: row.get("updated_at"),
         base: ServiceBase {
             name: row.get("name"),
-            network_id: row.get("network_id"),
+            network_id,
             host_id: row.get("host_id"),
             service_definition,
             virtualization,
             bindings,
             source,
+            version: row.get("version"),
+            version_history,
+            stale: row.get("stale"),
+            tags,
+            custom_fields,
         },
     })
 }
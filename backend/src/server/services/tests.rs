@@ -1,10 +1,17 @@
 use serial_test::serial;
+use std::collections::HashMap;
 
 use crate::{
     server::{
         discovery::types::base::EntitySource,
         groups::types::GroupType,
-        services::types::{bindings::Binding, patterns::MatchDetails},
+        services::types::{
+            api::ServiceQuery,
+            bindings::Binding,
+            patterns::MatchDetails,
+            versions::ServiceVersion,
+            virtualization::{ContainerRuntime, ContainerVirtualization, ServiceVirtualization},
+        },
     },
     tests::*,
 };
@@ -43,6 +50,8 @@ async fn test_service_deduplication_on_create() {
         metadata: vec![],
         details: MatchDetails::new_certain("Test"),
     };
+    svc1.base.version = Some("1.0".to_string());
+    svc1.base.version_history = vec![ServiceVersion::new("1.0".to_string())];
 
     let (created_host, created1) = services
         .host_service
@@ -62,6 +71,7 @@ async fn test_service_deduplication_on_create() {
         metadata: vec![],
         details: MatchDetails::new_certain("Test"),
     };
+    svc2.base.version = Some("1.1".to_string());
 
     let created2 = services
         .service_service
@@ -72,6 +82,16 @@ async fn test_service_deduplication_on_create() {
     // Should return same service (upserted)
     assert_eq!(created1[0].id, created2.id);
 
+    // Version change is recorded in the history
+    assert_eq!(created2.base.version.as_deref(), Some("1.1"));
+    let history: Vec<&str> = created2
+        .base
+        .version_history
+        .iter()
+        .map(|v| v.version.as_str())
+        .collect();
+    assert_eq!(history, vec!["1.0", "1.1"]);
+
     // Verify only one service in DB
     let all_services = services
         .service_service
@@ -153,3 +173,131 @@ async fn test_service_deletion_cleans_up_relationships() {
         GroupType::RequestPath { service_bindings } => assert!(service_bindings.is_empty()),
     }
 }
+
+#[tokio::test]
+#[serial]
+async fn test_get_services_with_image() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let (created_host, _) = services
+        .host_service
+        .create_host_with_services(host(&network.id), vec![])
+        .await
+        .unwrap();
+
+    let docker_virtualization = |image: &str| {
+        Some(ServiceVirtualization::Container(ContainerVirtualization {
+            container_name: Some(image.replace([':', '/'], "-")),
+            container_id: None,
+            service_id: uuid::Uuid::new_v4(),
+            runtime: ContainerRuntime::Docker,
+            namespace: None,
+            image: Some(image.to_string()),
+            image_digest: None,
+            created: None,
+            restart_policy: None,
+            health: None,
+            published_ports: vec![],
+            volumes: vec![],
+        }))
+    };
+
+    for image in ["postgres:13", "docker.io/library/postgres:16", "redis"] {
+        let mut svc = service(&network.id, &created_host.id);
+        svc.base.name = image.to_string();
+        svc.base.virtualization = docker_virtualization(image);
+        services.service_service.create_service(svc).await.unwrap();
+    }
+
+    let postgres_13 = services
+        .service_service
+        .get_services_with_image(&network.id, "postgres:13")
+        .await
+        .unwrap();
+    assert_eq!(postgres_13.len(), 1);
+    assert_eq!(postgres_13[0].base.name, "postgres:13");
+
+    let postgres = services
+        .service_service
+        .get_services_with_image(&network.id, "postgres")
+        .await
+        .unwrap();
+    assert_eq!(postgres.len(), 2);
+
+    let redis = services
+        .service_service
+        .get_services_with_image(&network.id, "redis:latest")
+        .await
+        .unwrap();
+    assert_eq!(redis.len(), 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_service_query() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let (created_host, _) = services
+        .host_service
+        .create_host_with_services(host(&network.id), vec![])
+        .await
+        .unwrap();
+
+    for name in ["Resolver 1", "Resolver 2", "Resolver 3", "Web"] {
+        let mut svc = service(&network.id, &created_host.id);
+        svc.base.name = name.to_string();
+        services.service_service.create_service(svc).await.unwrap();
+    }
+
+    let query = |params: &[(&str, &str)]| {
+        let mut params: HashMap<String, String> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        params.insert("host_id".to_string(), created_host.id.to_string());
+        ServiceQuery::from_params(network.id, &params).unwrap()
+    };
+
+    // Newest first by default, paging through everything matching the search
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut params = vec![("q", "resolver"), ("limit", "2")];
+        if let Some(cursor) = &cursor {
+            params.push(("cursor", cursor));
+        }
+        let page = services
+            .service_service
+            .query_services(&query(&params))
+            .await
+            .unwrap();
+        names.extend(page.items.into_iter().map(|s| s.base.name));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(names, vec!["Resolver 3", "Resolver 2", "Resolver 1"]);
+
+    let other_host = services
+        .service_service
+        .query_services(&ServiceQuery {
+            host_id: Some(uuid::Uuid::new_v4()),
+            ..query(&[])
+        })
+        .await
+        .unwrap();
+    assert!(other_host.items.is_empty());
+}
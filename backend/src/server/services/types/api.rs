@@ -0,0 +1,102 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::server::{
+    custom_fields::types::EntityFilter,
+    services::{definitions::ServiceDefinitionRegistry, types::categories::ServiceCategory},
+    shared::types::query::{ListOptions, SourceFilter, param_list, parse_enum_param, parse_param},
+};
+
+/// Filter on service definitions, from the `service_definition` query parameter (comma separated
+/// definition ids) and `category` (comma separated service categories). Each matches any of its
+/// values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceDefinitionFilter {
+    pub network_id: Uuid,
+    pub service_definitions: Vec<String>,
+    pub categories: Vec<ServiceCategory>,
+}
+
+impl ServiceDefinitionFilter {
+    /// Parse the filter for a network, whose custom definitions can be filtered on
+    pub fn from_params(network_id: &Uuid, params: &HashMap<String, String>) -> Result<Self> {
+        let service_definitions = param_list(params, "service_definition");
+
+        if let Some(unknown) = service_definitions
+            .iter()
+            .find(|id| !ServiceDefinitionRegistry::service_exists(network_id, id))
+        {
+            return Err(anyhow!("Unknown service definition '{}'", unknown));
+        }
+
+        Ok(Self {
+            network_id: *network_id,
+            service_definitions,
+            categories: param_list(params, "category")
+                .iter()
+                .map(|category| parse_enum_param("category", category))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Sets of definition ids for each part of the filter that was given. A service has to be of
+    /// a definition in every set.
+    pub fn id_sets(&self) -> Vec<Vec<String>> {
+        let mut sets = Vec::new();
+
+        if !self.service_definitions.is_empty() {
+            sets.push(self.service_definitions.clone());
+        }
+
+        if !self.categories.is_empty() {
+            sets.push(
+                ServiceDefinitionRegistry::network_service_definitions(&self.network_id)
+                    .into_iter()
+                    .filter(|d| self.categories.contains(&d.category()))
+                    .map(|d| d.id().to_string())
+                    .collect(),
+            );
+        }
+
+        sets
+    }
+}
+
+/// Query parameters of the service list endpoint. `q` searches service names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceQuery {
+    pub network_id: Uuid,
+    pub search: Option<String>,
+    pub host_id: Option<Uuid>,
+    /// Services on hosts with an interface in the subnet
+    pub subnet_id: Option<Uuid>,
+    pub definitions: ServiceDefinitionFilter,
+    pub source: SourceFilter,
+    pub filter: EntityFilter,
+    pub options: ListOptions,
+}
+
+impl ServiceQuery {
+    pub fn from_params(network_id: Uuid, params: &HashMap<String, String>) -> Result<Self> {
+        Ok(Self {
+            network_id,
+            search: params
+                .get("q")
+                .map(|q| q.trim().to_string())
+                .filter(|q| !q.is_empty()),
+            host_id: params
+                .get("host_id")
+                .map(|id| parse_param("host_id", id))
+                .transpose()?,
+            subnet_id: params
+                .get("subnet_id")
+                .map(|id| parse_param("subnet_id", id))
+                .transpose()?,
+            definitions: ServiceDefinitionFilter::from_params(&network_id, params)?,
+            source: SourceFilter::from_params(params)?,
+            filter: EntityFilter::from_params(params)?,
+            options: ListOptions::from_params(params)?,
+        })
+    }
+}
@@ -2,15 +2,24 @@ use crate::server::discovery::types::base::{DiscoveryMetadata, DiscoveryType, En
 use crate::server::hosts::types::interfaces::Interface;
 use crate::server::hosts::types::ports::PortBase;
 use crate::server::services::definitions::ServiceDefinitionRegistry;
+use crate::server::services::definitions::gateway::Gateway;
 use crate::server::services::types::bindings::Binding;
 use crate::server::services::types::definitions::ServiceDefinitionExt;
 use crate::server::services::types::definitions::{DefaultServiceDefinition, ServiceDefinition};
 use crate::server::services::types::endpoints::{Endpoint, EndpointResponse};
 use crate::server::services::types::patterns::{MatchConfidence, MatchReason, MatchResult};
-use crate::server::services::types::virtualization::{DockerVirtualization, ServiceVirtualization};
+use crate::server::services::types::trace::DefinitionMatchTrace;
+use crate::server::services::types::versions::{PortBanner, ServiceVersion, VersionSource};
+use crate::server::services::types::virtualization::{
+    ContainerVirtualization, ServiceVirtualization,
+};
+use crate::server::shared::types::metadata::HasId;
+use crate::server::shared::types::query::Pageable;
 use crate::server::subnets::types::base::Subnet;
+use anyhow::Error;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::net::IpAddr;
@@ -27,6 +36,21 @@ pub struct ServiceBase {
     pub bindings: Vec<Binding>,
     pub virtualization: Option<ServiceVirtualization>,
     pub source: EntitySource,
+    /// Version detected during the latest discovery, if the definition declares how to read it
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Every distinct version detected, oldest first
+    #[serde(default)]
+    pub version_history: Vec<ServiceVersion>,
+    /// Discovery stopped seeing the service, see ReconciliationPolicy::MarkStale
+    #[serde(default)]
+    pub stale: bool,
+    /// Free-form labels, ie "critical" or "rack-2"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Values of the network's custom fields, keyed by field definition id
+    #[serde(default)]
+    pub custom_fields: BTreeMap<Uuid, String>,
 }
 
 impl Default for ServiceBase {
@@ -39,6 +63,11 @@ impl Default for ServiceBase {
             bindings: Vec::new(),
             virtualization: None,
             source: EntitySource::Unknown,
+            version: None,
+            version_history: Vec::new(),
+            stale: false,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         }
     }
 }
@@ -70,6 +99,7 @@ pub struct ServiceMatchBaselineParams<'a> {
     pub interface: &'a Interface,
     pub all_ports: &'a Vec<PortBase>,
     pub endpoint_responses: &'a Vec<EndpointResponse>,
+    pub banners: &'a [PortBanner],
     pub virtualization: &'a Option<ServiceVirtualization>,
 }
 
@@ -80,6 +110,13 @@ pub struct ServiceMatchServiceParams<'a> {
     pub unbound_ports: &'a Vec<PortBase>,
 }
 
+pub struct ServiceMatchOutcome {
+    pub matches: Vec<(Service, MatchResult)>,
+    /// Scanned ports not bound by any matched service
+    pub unbound_ports: Vec<PortBase>,
+    pub traces: Vec<DefinitionMatchTrace>,
+}
+
 impl PartialEq for Service {
     // Primarily applies to
     fn eq(&self, other: &Self) -> bool {
@@ -114,6 +151,24 @@ impl Hash for Service {
     }
 }
 
+impl Pageable for Service {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.base.name
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
 impl Display for Service {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}: {:?}", self.base.name, self.id)
@@ -152,7 +207,8 @@ impl Service {
     }
 
     pub fn all_discovery_ports() -> Vec<PortBase> {
-        let mut ports: Vec<PortBase> = ServiceDefinitionRegistry::all_service_definitions()
+        let mut ports: Vec<PortBase> = ServiceDefinitionRegistry::registered()
+            .definitions
             .iter()
             .flat_map(|s| s.discovery_pattern().ports())
             .collect();
@@ -163,9 +219,14 @@ impl Service {
     }
 
     pub fn all_discovery_endpoints() -> Vec<Endpoint> {
-        let mut endpoints: Vec<Endpoint> = ServiceDefinitionRegistry::all_service_definitions()
+        let mut endpoints: Vec<Endpoint> = ServiceDefinitionRegistry::registered()
+            .definitions
             .iter()
-            .flat_map(|s| s.discovery_pattern().endpoints())
+            .flat_map(|s| {
+                let mut endpoints = s.discovery_pattern().endpoints();
+                endpoints.extend(s.version_extractors().iter().filter_map(|e| e.endpoint()));
+                endpoints
+            })
             .collect();
 
         endpoints.sort_by_key(|e| (e.protocol.to_string(), e.port_base.number(), e.path.clone()));
@@ -173,9 +234,186 @@ impl Service {
         endpoints
     }
 
-    /// Matches scanned data and returns service, vec of matched ports
+    /// TCP ports whose banner is needed to read a service's version
+    pub fn all_banner_ports() -> Vec<PortBase> {
+        let mut ports: Vec<PortBase> = ServiceDefinitionRegistry::registered()
+            .definitions
+            .iter()
+            .flat_map(|s| {
+                s.version_extractors()
+                    .iter()
+                    .filter_map(|e| match e.source {
+                        VersionSource::Banner(port_base) => Some(port_base),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        ports.sort_by_key(|p| (p.number(), p.protocol()));
+        ports.dedup();
+        ports
+    }
+
+    /// Evaluate every registered definition against a host interface in priority order, tracking
+    /// which ports have been bound by earlier matches. Within a priority tier, the best-scoring
+    /// match is accepted first and the definitions whose patterns depend on what it bound are
+    /// re-evaluated, so ambiguous hosts get the definitions with the strongest evidence.
+    /// Non-generic matches scoring below `min_score` are rejected. Returns services in match
+    /// order, along with a trace of every evaluated definition if `with_traces` is set.
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_definitions(
+        host_id: &Uuid,
+        baseline_params: &ServiceMatchBaselineParams,
+        gateway_ips: &[IpAddr],
+        daemon_id: &Uuid,
+        network_id: &Uuid,
+        discovery_type: &DiscoveryType,
+        min_score: u32,
+        with_traces: bool,
+    ) -> ServiceMatchOutcome {
+        let mut services = Vec::new();
+        let mut results = Vec::new();
+
+        // Need to track which ports are bound vs open for services to bind to
+        let mut unbound_ports = baseline_params.all_ports.to_vec();
+
+        let mut sorted_service_definitions = ServiceDefinitionRegistry::all_service_definitions();
+
+        let priority = |s: &dyn ServiceDefinition| {
+            if !s.is_generic() {
+                0 // Highest priority - non-generic services
+            } else if s.is_generic() && s.id() != Gateway.id() {
+                1 // Generic services that aren't Gateway
+            } else {
+                2 // Generic gateways need to go last, as other services may be classified as gateway first
+            }
+        };
+
+        sorted_service_definitions.sort_by_key(|s| priority(&**s));
+
+        let mut traces: Vec<Option<DefinitionMatchTrace>> =
+            vec![None; sorted_service_definitions.len()];
+
+        for tier in 0..=2 {
+            let mut remaining: Vec<usize> = (0..sorted_service_definitions.len())
+                .filter(|i| priority(&*sorted_service_definitions[*i]) == tier)
+                .collect();
+
+            // Definitions needing evaluation against the current unbound ports and matched
+            // services. Results of the others can't have changed since they were evaluated.
+            let mut stale = remaining.clone();
+            let mut matched: Vec<Option<(Service, MatchResult)>> =
+                vec![None; sorted_service_definitions.len()];
+
+            loop {
+                for i in &stale {
+                    let service_definition = sorted_service_definitions[*i].clone();
+                    let is_generic = ServiceDefinitionExt::is_generic(&service_definition);
+
+                    let params = DiscoverySessionServiceMatchParams {
+                        service_params: ServiceMatchServiceParams {
+                            service_definition,
+                            matched_services: &services,
+                            unbound_ports: &unbound_ports,
+                        },
+                        baseline_params,
+                        daemon_id,
+                        discovery_type,
+                        network_id,
+                        gateway_ips,
+                        host_id,
+                    };
+
+                    let pattern = params.service_params.service_definition.discovery_pattern();
+                    let (result, trace) = if with_traces {
+                        let (result, trace) = pattern.matches_with_trace(&params);
+                        (result, Some(trace))
+                    } else {
+                        (pattern.matches(&params), None)
+                    };
+
+                    if let Some(trace) = trace {
+                        traces[*i] = Some(DefinitionMatchTrace {
+                            service_definition: params
+                                .service_params
+                                .service_definition
+                                .name()
+                                .to_string(),
+                            matched: false,
+                            confidence: None,
+                            score: trace.score,
+                            trace,
+                        });
+                    }
+
+                    matched[*i] = Service::from_match(params, result)
+                        .filter(|(_, r)| is_generic || r.details.score >= min_score);
+                }
+
+                // Ties go to the definition evaluated first
+                let best = remaining
+                    .iter()
+                    .filter_map(|i| matched[*i].as_ref().map(|(_, r)| (*i, r.details.score)))
+                    .fold(None, |best: Option<(usize, u32)>, (i, score)| match best {
+                        Some((_, best_score)) if best_score >= score => best,
+                        _ => Some((i, score)),
+                    });
+
+                let Some((i, _)) = best else {
+                    break;
+                };
+
+                let Some((service, result)) = matched[i].take() else {
+                    break;
+                };
+
+                if let Some(trace) = traces[i].as_mut() {
+                    trace.matched = true;
+                    trace.confidence = Some(result.details.confidence);
+                }
+
+                let bound_port_bases: Vec<PortBase> = result.ports.iter().map(|p| p.base).collect();
+                unbound_ports.retain(|p| !bound_port_bases.contains(p));
+                remaining.retain(|r| *r != i);
+                services.push(service);
+                results.push(result);
+
+                stale = remaining
+                    .iter()
+                    .copied()
+                    .filter(|r| {
+                        sorted_service_definitions[*r]
+                            .discovery_pattern()
+                            .depends_on(&bound_port_bases)
+                    })
+                    .collect();
+            }
+        }
+
+        ServiceMatchOutcome {
+            matches: services.into_iter().zip(results).collect(),
+            unbound_ports,
+            traces: traces.into_iter().flatten().collect(),
+        }
+    }
+
     pub fn from_discovery(
         params: DiscoverySessionServiceMatchParams,
+    ) -> Option<(Self, MatchResult)> {
+        let result = params
+            .service_params
+            .service_definition
+            .discovery_pattern()
+            .matches(&params);
+
+        Self::from_match(params, result)
+    }
+
+    /// Create a service from the result of matching the definition's discovery pattern
+    pub fn from_match(
+        params: DiscoverySessionServiceMatchParams,
+        result: Result<MatchResult, Error>,
     ) -> Option<(Self, MatchResult)> {
         let DiscoverySessionServiceMatchParams {
             host_id,
@@ -189,6 +427,8 @@ impl Service {
 
         let ServiceMatchBaselineParams {
             interface,
+            endpoint_responses,
+            banners,
             virtualization,
             ..
         } = baseline_params;
@@ -199,7 +439,7 @@ impl Service {
             service_definition, ..
         } = service_params;
 
-        if let Ok(mut result) = service_definition.discovery_pattern().matches(&params) {
+        if let Ok(mut result) = result {
             tracing::debug!("Matched service with params {:?}", params);
 
             tracing::info!(
@@ -212,7 +452,7 @@ impl Service {
             let mut name = service_definition.name().to_string();
 
             if ServiceDefinitionExt::is_generic(&service_definition) {
-                if let Some(ServiceVirtualization::Docker(DockerVirtualization {
+                if let Some(ServiceVirtualization::Container(ContainerVirtualization {
                     container_name: Some(c_name),
                     ..
                 })) = virtualization
@@ -240,6 +480,11 @@ impl Service {
                 vec![Binding::new_interface(interface.id)]
             };
 
+            let version = service_definition
+                .version_extractors()
+                .iter()
+                .find_map(|e| e.extract(endpoint_responses, banners));
+
             let service = Service::new(ServiceBase {
                 host_id: *host_id,
                 network_id: *network_id,
@@ -251,6 +496,11 @@ impl Service {
                     metadata: vec![discovery_metadata],
                     details: result.details.clone(),
                 },
+                version_history: version.iter().cloned().map(ServiceVersion::new).collect(),
+                stale: false,
+                version,
+                tags: Vec::new(),
+                custom_fields: BTreeMap::new(),
             });
 
             Some((service, result))
@@ -64,8 +64,10 @@ impl HasId for ServiceCategory {
     }
 }
 
-impl EntityMetadataProvider for ServiceCategory {
-    fn icon(&self) -> &'static str {
+impl ServiceCategory {
+    /// Icon of the category. Unlike `EntityMetadataProvider::icon` this doesn't borrow the
+    /// category, so it can be returned for categories built on the fly.
+    pub fn static_icon(self) -> &'static str {
         match self {
             // Infrastructure (always-on, core network services)
             ServiceCategory::NetworkCore => "Network",
@@ -104,6 +106,12 @@ impl EntityMetadataProvider for ServiceCategory {
             ServiceCategory::Unknown => "CircleQuestionMark",
         }
     }
+}
+
+impl EntityMetadataProvider for ServiceCategory {
+    fn icon(&self) -> &str {
+        self.static_icon()
+    }
 
     fn color(&self) -> &'static str {
         match self {
@@ -1,8 +1,10 @@
 use crate::server::services::definitions::ServiceDefinitionRegistry;
 use crate::server::services::definitions::docker_daemon::Docker;
+use crate::server::services::definitions::libvirt::Libvirt;
 use crate::server::services::definitions::proxmox::Proxmox;
 use crate::server::services::types::categories::ServiceCategory;
 use crate::server::services::types::patterns::Pattern;
+use crate::server::services::types::versions::VersionExtractor;
 use crate::server::shared::types::metadata::TypeMetadataProvider;
 use crate::server::shared::types::metadata::{EntityMetadataProvider, HasId};
 use dyn_clone::DynClone;
@@ -14,10 +16,10 @@ use std::hash::Hash;
 // Main trait used in service definition implementation
 pub trait ServiceDefinition: HasId + DynClone + DynHash + DynEq + Send + Sync {
     /// Service name, will also be used as unique identifier. < 25 characters.
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// Service description. < 100 characters.
-    fn description(&self) -> &'static str;
+    fn description(&self) -> &str;
 
     /// Category from ServiceCategory enum
     fn category(&self) -> ServiceCategory;
@@ -25,23 +27,34 @@ pub trait ServiceDefinition: HasId + DynClone + DynHash + DynEq + Send + Sync {
     /// How service should be identified during port scanning
     fn discovery_pattern(&self) -> Pattern<'_>;
 
+    /// How the version of a matched service can be read from scan data, tried in order
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        vec![]
+    }
+
+    /// CPE vendor and product as "vendor:product", ie "grafana:grafana". Used to match detected
+    /// versions against vulnerability feeds.
+    fn cpe_product(&self) -> Option<&'static str> {
+        None
+    }
+
     /// If service is not associated with a particular brand or vendor
     fn is_generic(&self) -> bool {
         false
     }
 
     /// Path of service on https://dashboardicons.com/. For example, Home Assistant -> https://dashboardicons.com/icons/home-assistant. MUST SUPPORT SVG ICON FORMAT. If SVG is not supported, a fallback icon will be used instead.
-    fn dashboard_icons_path(&self) -> &'static str {
+    fn dashboard_icons_path(&self) -> &str {
         ""
     }
 
     /// Path of service on https://simpleicons.org/. For example, Home Assistant -> https://simpleicons.org/icons/homeassistant.svg. MUST SUPPORT SVG ICON FORMAT. If SVG is not supported, a fallback icon will be used instead.
-    fn simple_icons_path(&self) -> &'static str {
+    fn simple_icons_path(&self) -> &str {
         ""
     }
 
     /// Path of service on https://www.vectorlogo.zone. For example, Akamai -> https://www.vectorlogo.zone/logos/akamai/akamai-icon.svg. MUST SUPPORT SVG ICON FORMAT. If SVG is not supported, a fallback icon will be used instead.
-    fn vector_logo_zone_icons_path(&self) -> &'static str {
+    fn vector_logo_zone_icons_path(&self) -> &str {
         ""
     }
 
@@ -55,29 +68,29 @@ impl<T: ServiceDefinition> HasId for T
 where
     T: ServiceDefinition,
 {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         self.name()
     }
 }
 
 impl ServiceDefinition for Box<dyn ServiceDefinition> {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         ServiceDefinition::name(&**self)
     }
 
-    fn description(&self) -> &'static str {
+    fn description(&self) -> &str {
         ServiceDefinition::description(&**self)
     }
 
-    fn dashboard_icons_path(&self) -> &'static str {
+    fn dashboard_icons_path(&self) -> &str {
         ServiceDefinition::dashboard_icons_path(&**self)
     }
 
-    fn simple_icons_path(&self) -> &'static str {
+    fn simple_icons_path(&self) -> &str {
         ServiceDefinition::simple_icons_path(&**self)
     }
 
-    fn vector_logo_zone_icons_path(&self) -> &'static str {
+    fn vector_logo_zone_icons_path(&self) -> &str {
         ServiceDefinition::vector_logo_zone_icons_path(&**self)
     }
 
@@ -92,6 +105,14 @@ impl ServiceDefinition for Box<dyn ServiceDefinition> {
     fn discovery_pattern(&self) -> Pattern<'_> {
         ServiceDefinition::discovery_pattern(&**self)
     }
+
+    fn version_extractors(&self) -> Vec<VersionExtractor<'_>> {
+        ServiceDefinition::version_extractors(&**self)
+    }
+
+    fn cpe_product(&self) -> Option<&'static str> {
+        ServiceDefinition::cpe_product(&**self)
+    }
 }
 
 // Helper methods to be used in rest of codebase, not overridable by definition implementations
@@ -124,6 +145,7 @@ impl ServiceDefinitionExt for Box<dyn ServiceDefinition> {
         let id = self.id();
         match id {
             _ if id == Proxmox.id() => Some("vms"),
+            _ if id == Libvirt.id() => Some("vms"),
             _ if id == Docker.id() => Some("containers"),
             _ => None,
         }
@@ -134,7 +156,7 @@ impl EntityMetadataProvider for Box<dyn ServiceDefinition> {
     fn color(&self) -> &'static str {
         ServiceDefinition::category(self).color()
     }
-    fn icon(&self) -> &'static str {
+    fn icon(&self) -> &str {
         let dashboard_icon = ServiceDefinition::dashboard_icons_path(self);
         let simple_icon = ServiceDefinition::simple_icons_path(self);
         let vector_zone_icon = ServiceDefinition::vector_logo_zone_icons_path(self);
@@ -145,19 +167,19 @@ impl EntityMetadataProvider for Box<dyn ServiceDefinition> {
         } else if !vector_zone_icon.is_empty() {
             return vector_zone_icon;
         }
-        ServiceDefinition::category(self).icon()
+        ServiceDefinition::category(self).static_icon()
     }
 }
 
 impl TypeMetadataProvider for Box<dyn ServiceDefinition> {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         ServiceDefinition::name(self)
     }
-    fn description(&self) -> &'static str {
+    fn description(&self) -> &str {
         ServiceDefinition::description(self)
     }
     fn category(&self) -> &'static str {
-        ServiceDefinition::category(self).id()
+        ServiceDefinition::category(self).into()
     }
     fn metadata(&self) -> serde_json::Value {
         let can_be_added = self.can_be_manually_added();
@@ -231,10 +253,10 @@ impl<'de> Deserialize<'de> for Box<dyn ServiceDefinition> {
 pub struct DefaultServiceDefinition;
 
 impl ServiceDefinition for DefaultServiceDefinition {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Default Service"
     }
-    fn description(&self) -> &'static str {
+    fn description(&self) -> &str {
         "Default service implementation"
     }
     fn category(&self) -> ServiceCategory {
@@ -44,12 +44,26 @@ pub struct Endpoint {
     pub path: String,
 }
 
+/// HTTP response headers as (name, value) pairs
+pub type ResponseHeaders = Vec<(String, String)>;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct EndpointResponse {
     pub endpoint: Endpoint,
+    /// Response headers, with lowercase names
+    pub headers: ResponseHeaders,
     pub response: String,
 }
 
+impl EndpointResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
 impl Endpoint {
     pub fn is_resolved(&self) -> bool {
         self.ip.is_some()
@@ -1,7 +1,10 @@
+pub mod api;
 pub mod base;
 pub mod bindings;
 pub mod categories;
 pub mod definitions;
 pub mod endpoints;
 pub mod patterns;
+pub mod trace;
+pub mod versions;
 pub mod virtualization;
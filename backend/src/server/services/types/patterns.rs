@@ -8,18 +8,20 @@ use crate::server::{
                 DiscoverySessionServiceMatchParams, ServiceMatchBaselineParams,
                 ServiceMatchServiceParams,
             },
-            virtualization::ServiceVirtualization,
+            definitions::ServiceDefinitionExt,
+            trace::PatternTrace,
+            virtualization::{ContainerVirtualization, ServiceVirtualization},
         },
     },
     shared::types::metadata::TypeMetadataProvider,
 };
 use anyhow::{Error, anyhow};
-use mac_oui::Oui;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumDiscriminants, IntoStaticStr};
 
 use crate::server::{
     hosts::types::ports::{Port, PortBase},
+    mac_vendors::{registry::MacVendorRegistry, types::is_randomized},
     services::types::endpoints::Endpoint,
     subnets::types::base::SubnetType,
 };
@@ -36,15 +38,26 @@ pub struct MatchResult {
 pub struct MatchDetails {
     pub reason: MatchReason,
     pub confidence: MatchConfidence,
+    /// Combined weight of the evidence the match was made on
+    #[serde(default)]
+    pub score: u32,
 }
 
 impl MatchDetails {
-    pub fn new_certain(reason_str: &str) -> Self {
+    pub fn new(reason: MatchReason, score: u32) -> Self {
         Self {
-            reason: MatchReason::Reason(reason_str.to_string()),
-            confidence: MatchConfidence::Certain,
+            reason,
+            confidence: MatchConfidence::from_score(score),
+            score,
         }
     }
+
+    pub fn new_certain(reason_str: &str) -> Self {
+        Self::new(
+            MatchReason::Reason(reason_str.to_string()),
+            EvidenceWeight::CERTAIN,
+        )
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Display, Serialize, Deserialize)]
@@ -75,6 +88,70 @@ impl MatchConfidence {
             MatchConfidence::Certain => "Certain",
         }
     }
+
+    /// Confidence level for a combined evidence score
+    pub fn from_score(score: u32) -> Self {
+        match score {
+            s if s >= Self::Certain.min_score() => Self::Certain,
+            s if s >= Self::High.min_score() => Self::High,
+            s if s >= Self::Medium.min_score() => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+
+    /// Lowest score mapped to the confidence level
+    pub fn min_score(&self) -> u32 {
+        match self {
+            MatchConfidence::NotApplicable | MatchConfidence::Low => 0,
+            MatchConfidence::Medium => 25,
+            MatchConfidence::High => 50,
+            MatchConfidence::Certain => 85,
+        }
+    }
+}
+
+/// Score contributed by each kind of evidence. Scores of sub-patterns are summed for `AllOf`;
+/// `AnyOf` takes the best alternative plus half of each other alternative that also matched, so
+/// corroborating evidence raises the score without any one kind dominating.
+pub struct EvidenceWeight;
+impl EvidenceWeight {
+    /// Port that other service definitions also match on
+    pub const SHARED_PORT: u32 = 10;
+    /// Custom port no other service definition matches on
+    pub const UNIQUE_PORT: u32 = 25;
+    pub const ENDPOINT: u32 = 50;
+    pub const MAC_VENDOR: u32 = 25;
+    pub const GATEWAY: u32 = 50;
+    pub const DOCKER_IMAGE: u32 = 60;
+    /// Subnet type, container and predicate checks, which narrow a match rather than identify it
+    pub const CONTEXT: u32 = 5;
+    /// Self-reported or otherwise authoritative matches
+    pub const CERTAIN: u32 = 100;
+
+    /// Score of an open port, which only identifies a service when it's custom and no other
+    /// service definition matches on it
+    pub fn port(port_base: &PortBase, is_unique_to_service: bool) -> u32 {
+        if port_base.is_custom() && is_unique_to_service {
+            Self::UNIQUE_PORT
+        } else {
+            Self::SHARED_PORT
+        }
+    }
+
+    /// Combine the scores of the matched alternatives of an `AnyOf` pattern
+    pub fn any_of(mut scores: Vec<u32>) -> u32 {
+        scores.sort_unstable_by(|a, b| b.cmp(a));
+        scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| if i == 0 { *s } else { s / 2 })
+            .sum()
+    }
+
+    /// Combine the scores of the sub-patterns of an `AllOf` pattern
+    pub fn all_of(scores: Vec<u32>) -> u32 {
+        scores.iter().sum()
+    }
 }
 
 #[derive(Debug, Clone, EnumDiscriminants)]
@@ -87,7 +164,7 @@ pub enum Pattern<'a> {
     AllOf(Vec<Pattern<'a>>),
 
     /// Inverse of pattern
-    Not(&'a Pattern<'a>),
+    Not(Box<Pattern<'a>>),
 
     /// Whether or not a specific port is open on the host
     Port(PortBase),
@@ -105,27 +182,124 @@ pub enum Pattern<'a> {
     IsGateway,
 
     /// Whether the vendor derived from the mac address (https://gist.github.com/aallan/b4bb86db86079509e6159810ae9bd3e4) matches the provided str
-    MacVendor(&'static str),
-
-    /// Custom evaluation of discovery match params
-    /// fn - constraint function
-    /// &'a str - match reason (describe what it means if function evaluates true)
-    /// &'a str - no match reason (describe what it means if function evaluates false)
-    /// MatchConfdence - confidence level that match uniquely identifies service
-    Custom(
-        fn(&DiscoverySessionServiceMatchParams) -> bool,
-        &'a str,
-        &'a str,
-        MatchConfidence,
-    ),
+    MacVendor(&'a str),
+
+    /// Declarative constraint on the services already matched during the discovery session
+    Predicate(MatchPredicate),
 
     /// Whether the host is a docker container
     DockerContainer,
 
+    /// Whether the service is running in a docker container created from an image in the given
+    /// repository, ie "grafana/grafana" or "lscr.io/linuxserver/plex". The tag is ignored and
+    /// docker hub is assumed when the repository has no registry.
+    DockerImage(&'a str),
+
     /// No match pattern (only added manually or by the system)
     None,
 }
 
+/// Constraints evaluated against the services already matched on a host. Used by generic
+/// definitions so they only apply when nothing more specific has been identified.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchPredicate {
+    /// Every service already matched on the host is a gateway, ie nothing more specific than a
+    /// gateway has been identified
+    #[serde(alias = "NoGatewayServiceMatched")]
+    OnlyGatewayServicesMatched,
+    /// No service running in the same docker container has been matched
+    NoServiceMatchedInContainer,
+}
+
+impl MatchPredicate {
+    fn evaluate(&self, params: &DiscoverySessionServiceMatchParams) -> Result<&'static str, Error> {
+        let matched_services = params.service_params.matched_services;
+
+        match self {
+            MatchPredicate::OnlyGatewayServicesMatched => {
+                if matched_services
+                    .iter()
+                    .any(|s| !s.base.service_definition.is_gateway())
+                {
+                    Err(anyhow!("A non-gateway service has already been matched"))
+                } else {
+                    Ok("No other gateway services matched")
+                }
+            }
+            MatchPredicate::NoServiceMatchedInContainer => {
+                let container_id = match params.baseline_params.virtualization {
+                    Some(ServiceVirtualization::Container(ContainerVirtualization {
+                        container_id: Some(id),
+                        ..
+                    })) => id,
+                    _ => return Err(anyhow!("Service is not running in a docker container")),
+                };
+
+                // A matched service with the container's id means the container was already
+                // detected as a non-generic service
+                let already_matched = matched_services.iter().any(|s| {
+                    matches!(
+                        &s.base.virtualization,
+                        Some(ServiceVirtualization::Container(ContainerVirtualization {
+                            container_id: Some(id),
+                            ..
+                        })) if id == container_id
+                    )
+                });
+
+                if already_matched {
+                    Err(anyhow!(
+                        "A service with this container's ID has already been matched"
+                    ))
+                } else {
+                    Ok("No other services with this container's ID have been matched")
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for MatchPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchPredicate::OnlyGatewayServicesMatched => {
+                write!(f, "Only gateway services are matched on the host")
+            }
+            MatchPredicate::NoServiceMatchedInContainer => {
+                write!(f, "No other service is matched in the container")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::AnyOf(_) => write!(f, "Any of"),
+            Pattern::AllOf(_) => write!(f, "All of"),
+            Pattern::Not(_) => write!(f, "Not"),
+            Pattern::Port(port_base) => write!(f, "Port {} is open", port_base),
+            Pattern::Endpoint(port_base, path, response) => write!(
+                f,
+                "Response from {} contains \"{}\"",
+                Endpoint::for_pattern(*port_base, path),
+                response
+            ),
+            Pattern::SubnetIsType(subnet_type) => {
+                write!(f, "Subnet is type {}", subnet_type.name())
+            }
+            Pattern::IsGateway => write!(f, "Host is a gateway"),
+            Pattern::MacVendor(vendor) => write!(f, "Mac address is from vendor {}", vendor),
+            Pattern::Predicate(predicate) => write!(f, "{}", predicate),
+            Pattern::DockerContainer => write!(f, "Service is running in a docker container"),
+            Pattern::DockerImage(repository) => {
+                write!(f, "Container image is from repository {}", repository)
+            }
+            Pattern::None => write!(f, "No match pattern"),
+        }
+    }
+}
+
 // https://gist.github.com/aallan/b4bb86db86079509e6159810ae9bd3e4
 pub struct Vendor;
 impl Vendor {
@@ -146,6 +320,48 @@ impl Pattern<'_> {
     pub fn matches(
         &self,
         params: &DiscoverySessionServiceMatchParams,
+    ) -> Result<MatchResult, Error> {
+        self.evaluate(params, None)
+    }
+
+    /// Match the pattern, also returning the trace of it and each of its sub-patterns. Every
+    /// sub-pattern is evaluated once, with its result used for both.
+    pub fn matches_with_trace(
+        &self,
+        params: &DiscoverySessionServiceMatchParams,
+    ) -> (Result<MatchResult, Error>, PatternTrace) {
+        let mut children = Vec::new();
+        let result = self.evaluate(params, Some(&mut children));
+        let trace = self.trace(params, &result, children);
+        (result, trace)
+    }
+
+    /// Evaluate the pattern and each of its sub-patterns, keeping every result rather than only
+    /// the overall match
+    pub fn explain(&self, params: &DiscoverySessionServiceMatchParams) -> PatternTrace {
+        self.matches_with_trace(params).1
+    }
+
+    /// Evaluate a sub-pattern, adding its trace to `traces` if a trace is being built
+    fn evaluate_child(
+        pattern: &Pattern,
+        params: &DiscoverySessionServiceMatchParams,
+        traces: &mut Option<&mut Vec<PatternTrace>>,
+    ) -> Result<MatchResult, Error> {
+        match traces {
+            Some(traces) => {
+                let (result, trace) = pattern.matches_with_trace(params);
+                traces.push(trace);
+                result
+            }
+            None => pattern.evaluate(params, None),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        params: &DiscoverySessionServiceMatchParams,
+        mut traces: Option<&mut Vec<PatternTrace>>,
     ) -> Result<MatchResult, Error> {
         // Return ports + endpoint that matched, if any
 
@@ -174,45 +390,28 @@ impl Pattern<'_> {
         match self {
             Pattern::Port(port_base) => {
                 if let Some(matched_port) = unbound_ports.iter().find(|p| **p == *port_base) {
-                    let mut all_other_services_ports: Vec<PortBase> =
-                        ServiceDefinitionRegistry::all_service_definitions()
-                            .iter()
-                            .filter(|s| s.id() != service_definition.id())
-                            .flat_map(|s| s.discovery_pattern().ports())
-                            .collect();
-
-                    all_other_services_ports.sort_by_key(|p| (p.number(), p.protocol()));
-                    all_other_services_ports.dedup();
-
-                    let is_unique_to_service =
-                        port_base.is_custom() && !all_other_services_ports.contains(port_base);
-
-                    let (reason, confidence) = if port_base.is_custom() && is_unique_to_service {
-                        (
-                            format!(
-                                "Port {} is open and is not used in other service match patterns",
-                                port_base,
-                            ),
-                            MatchConfidence::Medium,
+                    let is_unique_to_service = port_base.is_custom()
+                        && ServiceDefinitionRegistry::registered()
+                            .port_is_unique_to(port_base, service_definition.id());
+
+                    let reason = if is_unique_to_service {
+                        format!(
+                            "Port {} is open and is not used in other service match patterns",
+                            port_base,
                         )
                     } else {
-                        (
-                            format!(
-                                "Port {} is open but is used in other service match patterns",
-                                port_base
-                            ),
-                            MatchConfidence::Low,
+                        format!(
+                            "Port {} is open but is used in other service match patterns",
+                            port_base
                         )
                     };
+                    let score = EvidenceWeight::port(port_base, is_unique_to_service);
 
                     Ok(MatchResult {
                         ports: vec![Port::new(*matched_port)],
                         endpoint: None,
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Reason(reason),
-                            confidence,
-                        },
+                        details: MatchDetails::new(MatchReason::Reason(reason), score),
                     })
                 } else {
                     Err(anyhow!("Port {} is not open", port_base))
@@ -236,13 +435,13 @@ impl Pattern<'_> {
                         ports: vec![Port::new(actual.endpoint.port_base)],
                         endpoint: Some(actual.endpoint.clone()),
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Reason(format!(
+                        details: MatchDetails::new(
+                            MatchReason::Reason(format!(
                                 "Response from {} contained \"{}\"",
                                 actual.endpoint, expected_response
                             )),
-                            confidence: MatchConfidence::High,
-                        },
+                            EvidenceWeight::ENDPOINT,
+                        ),
                     })
                 } else {
                     Err(anyhow!(
@@ -255,14 +454,17 @@ impl Pattern<'_> {
 
             Pattern::MacVendor(vendor_string) => {
                 if let Some(mac) = interface.base.mac_address {
-                    let Ok(oui_db) = Oui::default() else {
-                        return Err(anyhow!("Could not load Oui database"));
-                    };
-                    let Ok(Some(entry)) = Oui::lookup_by_mac(&oui_db, &mac.to_string()) else {
-                        return Err(anyhow!(
-                            "Could find vendor for mac address {} in Oui database",
-                            mac
-                        ));
+                    let Some(company_name) =
+                        MacVendorRegistry::lookup(&subnet.base.network_id, &mac)
+                    else {
+                        return Err(if is_randomized(&mac) {
+                            anyhow!(
+                                "Mac address {} is randomized and doesn't identify a vendor",
+                                mac
+                            )
+                        } else {
+                            anyhow!("Could not find vendor for mac address {}", mac)
+                        });
                     };
 
                     let normalize = |s: &str| -> String {
@@ -274,20 +476,20 @@ impl Pattern<'_> {
                     };
 
                     let vendor_string = normalize(vendor_string);
-                    let entry_string = normalize(&entry.company_name);
+                    let entry_string = normalize(&company_name);
 
                     if vendor_string == entry_string {
                         Ok(MatchResult {
                             ports: vec![],
                             endpoint: None,
-                            mac_vendor: Some(entry.company_name.clone()),
-                            details: MatchDetails {
-                                reason: MatchReason::Reason(format!(
+                            mac_vendor: Some(company_name.clone()),
+                            details: MatchDetails::new(
+                                MatchReason::Reason(format!(
                                     "Mac address is from vendor {}",
-                                    entry.company_name
+                                    company_name
                                 )),
-                                confidence: MatchConfidence::Medium,
-                            },
+                                EvidenceWeight::MAC_VENDOR,
+                            ),
                         })
                     } else {
                         Err(anyhow!("Mac address is not from vendor {}", vendor_string))
@@ -300,16 +502,16 @@ impl Pattern<'_> {
                 }
             }
 
-            Pattern::Not(pattern) => match pattern.matches(params) {
+            Pattern::Not(pattern) => match Self::evaluate_child(pattern, params, &mut traces) {
                 Ok(result) => Err(anyhow!("{}", result.details.reason)),
                 Err(e) => Ok(MatchResult {
                     ports: vec![],
                     endpoint: None,
                     mac_vendor: None,
-                    details: MatchDetails {
-                        reason: MatchReason::Reason(format!("{}", e)),
-                        confidence: MatchConfidence::Low,
-                    },
+                    details: MatchDetails::new(
+                        MatchReason::Reason(format!("{}", e)),
+                        EvidenceWeight::CONTEXT,
+                    ),
                 }),
             },
 
@@ -318,41 +520,40 @@ impl Pattern<'_> {
                 let mut endpoint = None;
                 let mut mac_vendor = None;
                 let mut any_matched = false;
-                let mut confidence = MatchConfidence::Low;
+                let mut scores = Vec::new();
                 let mut reasons = Vec::new();
                 let mut no_match_errors = String::new();
-                patterns.iter().for_each(|p| match p.matches(params) {
-                    Ok(result) => {
-                        any_matched = true;
-                        ports.extend(result.ports);
-                        reasons.push(result.details.reason);
-
-                        if result.endpoint.is_some() && endpoint.is_none() {
-                            endpoint = result.endpoint;
-                        }
-
-                        if result.mac_vendor.is_some() && mac_vendor.is_none() {
-                            mac_vendor = result.mac_vendor;
+                patterns
+                    .iter()
+                    .for_each(|p| match Self::evaluate_child(p, params, &mut traces) {
+                        Ok(result) => {
+                            any_matched = true;
+                            scores.push(result.details.score);
+                            ports.extend(result.ports);
+                            reasons.push(result.details.reason);
+
+                            if result.endpoint.is_some() && endpoint.is_none() {
+                                endpoint = result.endpoint;
+                            }
+
+                            if result.mac_vendor.is_some() && mac_vendor.is_none() {
+                                mac_vendor = result.mac_vendor;
+                            }
                         }
-
-                        if result.details.confidence > confidence {
-                            confidence = result.details.confidence;
+                        Err(e) => {
+                            no_match_errors = no_match_errors.clone() + ", " + &e.to_string();
                         }
-                    }
-                    Err(e) => {
-                        no_match_errors = no_match_errors.clone() + ", " + &e.to_string();
-                    }
-                });
+                    });
 
                 if any_matched {
                     Ok(MatchResult {
                         ports,
                         endpoint: None,
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Container("Any of".to_string(), reasons),
-                            confidence,
-                        },
+                        details: MatchDetails::new(
+                            MatchReason::Container("Any of".to_string(), reasons),
+                            EvidenceWeight::any_of(scores),
+                        ),
                     })
                 } else {
                     Err(anyhow!(no_match_errors))
@@ -364,58 +565,40 @@ impl Pattern<'_> {
                 let mut ports = Vec::new();
                 let mut endpoint = None;
                 let mut mac_vendor = None;
-                let mut matched_confidences = Vec::new();
+                let mut scores = Vec::new();
                 let mut reasons = Vec::new();
                 let mut no_match_errors = String::new();
-                patterns.iter().for_each(|p| match p.matches(params) {
-                    Ok(result) => {
-                        ports.extend(result.ports);
-                        reasons.push(result.details.reason);
-                        matched_confidences.push(result.details.confidence);
-
-                        if result.endpoint.is_some() && endpoint.is_none() {
-                            endpoint = result.endpoint;
+                patterns
+                    .iter()
+                    .for_each(|p| match Self::evaluate_child(p, params, &mut traces) {
+                        Ok(result) => {
+                            ports.extend(result.ports);
+                            reasons.push(result.details.reason);
+                            scores.push(result.details.score);
+
+                            if result.endpoint.is_some() && endpoint.is_none() {
+                                endpoint = result.endpoint;
+                            }
+
+                            if result.mac_vendor.is_some() && mac_vendor.is_none() {
+                                mac_vendor = result.mac_vendor;
+                            }
                         }
-
-                        if result.mac_vendor.is_some() && mac_vendor.is_none() {
-                            mac_vendor = result.mac_vendor;
+                        Err(e) => {
+                            all_matched = false;
+                            no_match_errors = no_match_errors.clone() + ", " + &e.to_string();
                         }
-                    }
-                    Err(e) => {
-                        all_matched = false;
-                        no_match_errors = no_match_errors.clone() + ", " + &e.to_string();
-                    }
-                });
+                    });
 
                 if all_matched {
-                    matched_confidences.sort();
-
-                    let max_confidence =
-                        matched_confidences.last().unwrap_or(&MatchConfidence::Low);
-
-                    // Boost confidence if multiple lower-confidence patterns are matched
-                    let confidence = if matches!(
-                        max_confidence,
-                        MatchConfidence::Low | MatchConfidence::Medium
-                    ) && matched_confidences.len() > 3
-                    {
-                        match max_confidence {
-                            MatchConfidence::Low => MatchConfidence::Medium,
-                            MatchConfidence::Medium => MatchConfidence::High,
-                            _ => *max_confidence,
-                        }
-                    } else {
-                        *max_confidence
-                    };
-
                     Ok(MatchResult {
                         ports,
                         endpoint: None,
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Container("All of".to_string(), reasons),
-                            confidence,
-                        },
+                        details: MatchDetails::new(
+                            MatchReason::Container("All of".to_string(), reasons),
+                            EvidenceWeight::all_of(scores),
+                        ),
                     })
                 } else {
                     Err(anyhow!(no_match_errors))
@@ -467,10 +650,10 @@ impl Pattern<'_> {
                         ports: vec![],
                         endpoint: None,
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Reason(reason),
-                            confidence: MatchConfidence::High,
-                        },
+                        details: MatchDetails::new(
+                            MatchReason::Reason(reason),
+                            EvidenceWeight::GATEWAY,
+                        ),
                     })
                 } else {
                     Err(anyhow!(
@@ -485,14 +668,14 @@ impl Pattern<'_> {
                         ports: vec![],
                         endpoint: None,
                         mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Reason(format!(
+                        details: MatchDetails::new(
+                            MatchReason::Reason(format!(
                                 "Subnet {} is type {}",
                                 subnet.base.cidr,
                                 subnet_type.name()
                             )),
-                            confidence: MatchConfidence::Low,
-                        },
+                            EvidenceWeight::CONTEXT,
+                        ),
                     })
                 } else {
                     Err(anyhow!(
@@ -503,42 +686,133 @@ impl Pattern<'_> {
                 }
             }
 
-            Pattern::Custom(constraint_function, reason, no_match_reason, confidence) => {
-                if constraint_function(params) {
-                    Ok(MatchResult {
-                        ports: vec![],
-                        endpoint: None,
-                        mac_vendor: None,
-                        details: MatchDetails {
-                            reason: MatchReason::Reason(reason.to_string()),
-                            confidence: *confidence,
-                        },
-                    })
-                } else {
-                    let no_match_reason = no_match_reason.to_string();
-                    Err(anyhow!(no_match_reason))
-                }
+            Pattern::Predicate(predicate) => {
+                let reason = predicate.evaluate(params)?;
+
+                Ok(MatchResult {
+                    ports: vec![],
+                    endpoint: None,
+                    mac_vendor: None,
+                    details: MatchDetails::new(
+                        MatchReason::Reason(reason.to_string()),
+                        EvidenceWeight::CONTEXT,
+                    ),
+                })
             }
 
             Pattern::DockerContainer => match virtualization {
-                Some(ServiceVirtualization::Docker(..)) => Ok(MatchResult {
+                Some(ServiceVirtualization::Container(..)) => Ok(MatchResult {
                     ports: vec![],
                     endpoint: None,
                     mac_vendor: None,
-                    details: MatchDetails {
-                        reason: MatchReason::Reason(
-                            "Service is running in docker container".to_string(),
-                        ),
-                        confidence: MatchConfidence::Low,
-                    },
+                    details: MatchDetails::new(
+                        MatchReason::Reason("Service is running in docker container".to_string()),
+                        EvidenceWeight::CONTEXT,
+                    ),
                 }),
                 _ => Err(anyhow!("Service is not running in a docker container")),
             },
 
+            Pattern::DockerImage(repository) => match virtualization {
+                Some(ServiceVirtualization::Container(docker))
+                    if docker.matches_repository(repository) =>
+                {
+                    Ok(MatchResult {
+                        ports: vec![],
+                        endpoint: None,
+                        mac_vendor: None,
+                        details: MatchDetails::new(
+                            MatchReason::Reason(format!(
+                                "Container image {} is from repository {}",
+                                docker.image.as_deref().unwrap_or_default(),
+                                repository
+                            )),
+                            EvidenceWeight::DOCKER_IMAGE,
+                        ),
+                    })
+                }
+                Some(ServiceVirtualization::Container(..)) => Err(anyhow!(
+                    "Container image is not from repository {}",
+                    repository
+                )),
+                _ => Err(anyhow!("Service is not running in a docker container")),
+            },
+
             Pattern::None => Err(anyhow!("No match pattern provided")),
         }
     }
 
+    fn trace(
+        &self,
+        params: &DiscoverySessionServiceMatchParams,
+        result: &Result<MatchResult, Error>,
+        children: Vec<PatternTrace>,
+    ) -> PatternTrace {
+        let response = match self {
+            Pattern::Endpoint(port_base, path, _) => {
+                let endpoint = Endpoint::for_pattern(*port_base, path);
+                params
+                    .baseline_params
+                    .endpoint_responses
+                    .iter()
+                    .find(|actual| {
+                        actual.endpoint.protocol == endpoint.protocol
+                            && actual.endpoint.port_base.number() == endpoint.port_base.number()
+                            && actual.endpoint.path == endpoint.path
+                    })
+            }
+            _ => None,
+        };
+
+        PatternTrace::new(self.to_string(), result, response, children)
+    }
+
+    /// Highest score a match of the pattern can produce, given which ports are unique to the
+    /// service. None if the pattern can never match.
+    pub fn max_score(&self, is_unique_port: &dyn Fn(&PortBase) -> bool) -> Option<u32> {
+        match self {
+            Pattern::Port(port_base) => {
+                Some(EvidenceWeight::port(port_base, is_unique_port(port_base)))
+            }
+            Pattern::Endpoint(..) => Some(EvidenceWeight::ENDPOINT),
+            Pattern::MacVendor(_) => Some(EvidenceWeight::MAC_VENDOR),
+            Pattern::IsGateway => Some(EvidenceWeight::GATEWAY),
+            Pattern::DockerImage(_) => Some(EvidenceWeight::DOCKER_IMAGE),
+            Pattern::Not(_)
+            | Pattern::SubnetIsType(_)
+            | Pattern::Predicate(_)
+            | Pattern::DockerContainer => Some(EvidenceWeight::CONTEXT),
+            Pattern::AnyOf(patterns) => {
+                let scores: Vec<u32> = patterns
+                    .iter()
+                    .filter_map(|p| p.max_score(is_unique_port))
+                    .collect();
+
+                (!scores.is_empty()).then(|| EvidenceWeight::any_of(scores))
+            }
+            Pattern::AllOf(patterns) => patterns
+                .iter()
+                .map(|p| p.max_score(is_unique_port))
+                .collect::<Option<Vec<u32>>>()
+                .map(EvidenceWeight::all_of),
+            Pattern::None => None,
+        }
+    }
+
+    /// Whether binding any of the ports, or matching another service, could change the result
+    /// of the pattern
+    pub fn depends_on(&self, bound_ports: &[PortBase]) -> bool {
+        match self {
+            Pattern::Port(port) => bound_ports.contains(port),
+            Pattern::Predicate(_) => true,
+            Pattern::AnyOf(patterns) | Pattern::AllOf(patterns) => {
+                patterns.iter().any(|p| p.depends_on(bound_ports))
+            }
+            Pattern::Not(pattern) => pattern.depends_on(bound_ports),
+            _ => false,
+        }
+    }
+
     /// Get all ports which need to be scanned for a given service's match pattern
     pub fn ports(&self) -> Vec<PortBase> {
         match self {
@@ -562,6 +836,17 @@ impl Pattern<'_> {
         }
     }
 
+    /// Get all docker image repositories declared in a service's match pattern
+    pub fn docker_images(&self) -> Vec<&str> {
+        match self {
+            Pattern::DockerImage(repository) => vec![*repository],
+            Pattern::AnyOf(patterns) | Pattern::AllOf(patterns) => {
+                patterns.iter().flat_map(|p| p.docker_images()).collect()
+            }
+            _ => vec![],
+        }
+    }
+
     /// Whether service uses IsGateway as a positive match signal -> service is_gateway = trues
     pub fn contains_gateway_ip_pattern(&self) -> bool {
         match self {
@@ -580,7 +865,13 @@ mod tests {
 
     use crate::server::discovery::types::base::DiscoveryType;
     use crate::server::services::types::base::Service;
-    use crate::server::services::types::virtualization::ServiceVirtualization;
+    use crate::server::services::types::definitions::ServiceDefinitionExt;
+    use crate::server::services::types::patterns::{
+        EvidenceWeight, MatchConfidence, MatchPredicate,
+    };
+    use crate::server::services::types::virtualization::{
+        ContainerRuntime, ContainerVirtualization, ServiceVirtualization, image_matches_repository,
+    };
     use crate::tests::{network, user};
     use serial_test::serial;
     use uuid::Uuid;
@@ -602,7 +893,7 @@ mod tests {
             },
             subnets::types::base::Subnet,
         },
-        tests::{interface, subnet},
+        tests::{interface, service, subnet},
     };
 
     struct TestContext {
@@ -630,6 +921,7 @@ mod tests {
 
             let endpoint_responses = vec![EndpointResponse {
                 endpoint: Endpoint::http(Some(interface.base.ip_address), "/admin"),
+                headers: vec![],
                 response: "Pi-hole".to_string(),
             }];
 
@@ -677,6 +969,7 @@ mod tests {
                 interface: &self.interface,
                 all_ports,
                 endpoint_responses: &self.endpoint_responses,
+                banners: &[],
                 virtualization: &self.virtualization,
             }
         }
@@ -751,6 +1044,140 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_pattern_explain() {
+        let ctx = TestContext::new();
+
+        // Endpoint responds, but DNS isn't open
+        let ports = vec![PortBase::Http];
+        let baseline = ctx.create_baseline_params(&ports);
+        let params = ctx.create_params_with_ports(&baseline, &ports);
+        let trace = ctx.pi.discovery_pattern().explain(&params);
+
+        assert!(!trace.matched);
+        assert_eq!(trace.children.len(), 2);
+
+        let all_of = &trace.children[0];
+        assert!(!all_of.matched);
+
+        let dns = &all_of.children[0];
+        assert!(!dns.matched);
+        assert!(
+            dns.children
+                .iter()
+                .all(|c| !c.matched && c.confidence.is_none())
+        );
+
+        let endpoint = &all_of.children[1];
+        assert!(endpoint.matched);
+        assert_eq!(endpoint.confidence, Some(MatchConfidence::High));
+        assert_eq!(
+            endpoint.evidence.response_snippet.as_deref(),
+            Some("Pi-hole")
+        );
+        assert!(endpoint.evidence.endpoint.is_some());
+    }
+
+    #[test]
+    #[serial]
+    fn test_pattern_evidence_scoring() {
+        let ctx = TestContext::new();
+
+        let ports = vec![PortBase::Http, PortBase::DnsUdp];
+        let baseline = ctx.create_baseline_params(&ports);
+        let params = ctx.create_params_with_ports(&baseline, &ports);
+
+        let endpoint = Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole")
+            .matches(&params)
+            .unwrap();
+        assert_eq!(endpoint.details.score, EvidenceWeight::ENDPOINT);
+        assert_eq!(endpoint.details.confidence, MatchConfidence::High);
+
+        // Corroborating evidence adds to the score
+        let all_of = Pattern::AllOf(vec![
+            Pattern::Port(PortBase::DnsUdp),
+            Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole"),
+        ])
+        .matches(&params)
+        .unwrap();
+        assert!(all_of.details.score > endpoint.details.score);
+
+        // Alternatives count for less than required evidence
+        let any_of = Pattern::AnyOf(vec![
+            Pattern::Port(PortBase::DnsUdp),
+            Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole"),
+        ])
+        .matches(&params)
+        .unwrap();
+        assert!(any_of.details.score > endpoint.details.score);
+        assert!(any_of.details.score < all_of.details.score);
+
+        // The highest possible score agrees with the score of a full match
+        let shared = |_: &PortBase| false;
+        assert_eq!(
+            Pattern::AllOf(vec![
+                Pattern::Port(PortBase::DnsUdp),
+                Pattern::Endpoint(PortBase::Http, "/admin", "pi-hole"),
+            ])
+            .max_score(&shared),
+            Some(all_of.details.score)
+        );
+        assert_eq!(Pattern::None.max_score(&shared), None);
+
+        assert_eq!(MatchConfidence::from_score(0), MatchConfidence::Low);
+        assert_eq!(
+            MatchConfidence::from_score(EvidenceWeight::CERTAIN),
+            MatchConfidence::Certain
+        );
+
+        // Matches scoring below the threshold are rejected
+        let accepted = |min_score| {
+            Service::match_definitions(
+                &ctx.host_id,
+                &baseline,
+                &ctx.gateway_ips,
+                &ctx.daemon_id,
+                &ctx.network_id,
+                &ctx.discovery_type,
+                min_score,
+                false,
+            )
+            .matches
+            .iter()
+            .any(|(s, _)| s.base.service_definition.id() == "Pi-Hole")
+        };
+        assert!(accepted(0));
+        assert!(!accepted(EvidenceWeight::CERTAIN));
+
+        // Traces are only built when requested, and agree with the matches
+        let match_with_traces = |with_traces| {
+            Service::match_definitions(
+                &ctx.host_id,
+                &baseline,
+                &ctx.gateway_ips,
+                &ctx.daemon_id,
+                &ctx.network_id,
+                &ctx.discovery_type,
+                0,
+                with_traces,
+            )
+        };
+        assert!(match_with_traces(false).traces.is_empty());
+
+        let outcome = match_with_traces(true);
+        let pi_hole = outcome
+            .traces
+            .iter()
+            .find(|t| t.service_definition == "Pi-Hole")
+            .unwrap();
+        assert!(pi_hole.matched);
+        assert_eq!(
+            outcome.traces.iter().filter(|t| t.matched).count(),
+            outcome.matches.len()
+        );
+    }
+
     #[test]
     #[serial]
     fn test_pattern_or_logic() {
@@ -792,4 +1219,102 @@ mod tests {
             "OR pattern should not match when no conditions met"
         );
     }
+
+    #[test]
+    #[serial]
+    fn test_pattern_docker_image_matching() {
+        let mut ctx = TestContext::new();
+        ctx.virtualization = Some(ServiceVirtualization::Container(ContainerVirtualization {
+            container_name: Some("pihole".to_string()),
+            container_id: Some("abc123".to_string()),
+            service_id: Uuid::new_v4(),
+            runtime: ContainerRuntime::Docker,
+            namespace: None,
+            image: Some("docker.io/pihole/pihole:2024.07.0".to_string()),
+            image_digest: None,
+            created: None,
+            restart_policy: None,
+            health: None,
+            published_ports: vec![],
+            volumes: vec![],
+        }));
+
+        // Image alone is enough when endpoints can't be scanned
+        let ports = vec![];
+        let baseline = ctx.create_baseline_params(&ports);
+        let params = ctx.create_params_with_ports(&baseline, &ports);
+        let result = Pattern::DockerImage("pihole/pihole").matches(&params);
+        assert!(
+            result.is_ok(),
+            "Image pattern should ignore registry and tag"
+        );
+
+        let result = Pattern::DockerImage("pihole/ftl").matches(&params);
+        assert!(
+            result.is_err(),
+            "Image pattern should not match other repos"
+        );
+
+        // Repositories are compared in full, with docker hub and library as defaults
+        assert!(image_matches_repository(
+            "postgres:13",
+            "docker.io/library/postgres"
+        ));
+        assert!(image_matches_repository(
+            "index.docker.io/library/traefik@sha256:abc",
+            "traefik"
+        ));
+        assert!(image_matches_repository(
+            "registry.local:5000/tools/grafana:1.0",
+            "registry.local:5000/tools/grafana"
+        ));
+        assert!(!image_matches_repository(
+            "lscr.io/linuxserver/plex:latest",
+            "linuxserver/plex"
+        ));
+        assert!(!image_matches_repository(
+            "ghcr.io/someone/pihole/pihole",
+            "pihole/pihole"
+        ));
+
+        // Image + endpoint evidence is boosted above either alone
+        let ports = vec![PortBase::DnsUdp];
+        let baseline = ctx.create_baseline_params(&ports);
+        let params = ctx.create_params_with_ports(&baseline, &ports);
+        let result = ctx.pi.discovery_pattern().matches(&params).unwrap();
+        assert_eq!(result.details.confidence, MatchConfidence::Certain);
+    }
+
+    #[test]
+    #[serial]
+    fn test_gateway_predicate() {
+        let mut ctx = TestContext::new();
+        let predicate = Pattern::Predicate(MatchPredicate::OnlyGatewayServicesMatched);
+
+        let gateway = ServiceDefinitionRegistry::all_service_definitions()
+            .into_iter()
+            .find(|d| d.is_gateway())
+            .unwrap();
+        let mut gateway_service = service(&ctx.network_id, &ctx.host_id);
+        gateway_service.base.service_definition = gateway;
+        let mut other_service = service(&ctx.network_id, &ctx.host_id);
+        other_service.base.service_definition = ctx.pi.clone();
+
+        for (matched, expected) in [
+            (vec![], true),
+            (vec![gateway_service.clone()], true),
+            (vec![gateway_service, other_service], false),
+        ] {
+            ctx.matched_services = matched;
+            let ports = vec![];
+            let baseline = ctx.create_baseline_params(&ports);
+            let params = ctx.create_params_with_ports(&baseline, &ports);
+            assert_eq!(predicate.matches(&params).is_ok(), expected);
+        }
+
+        assert_eq!(
+            predicate.to_string(),
+            "Only gateway services are matched on the host"
+        );
+    }
 }
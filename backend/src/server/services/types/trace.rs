@@ -0,0 +1,110 @@
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::server::services::types::{
+    endpoints::EndpointResponse,
+    patterns::{MatchConfidence, MatchResult},
+};
+
+/// Evidence a pattern matched on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchEvidence {
+    pub ports: Vec<String>,
+    pub endpoint: Option<String>,
+    /// Excerpt of the response body received from the pattern's endpoint, matched or not
+    pub response_snippet: Option<String>,
+    pub mac_vendor: Option<String>,
+}
+
+/// Result of evaluating a single (sub-)pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternTrace {
+    pub pattern: String,
+    pub matched: bool,
+    /// Match reason if the pattern matched, no-match reason otherwise
+    pub reason: String,
+    pub confidence: Option<MatchConfidence>,
+    pub score: Option<u32>,
+    pub evidence: MatchEvidence,
+    pub children: Vec<PatternTrace>,
+}
+
+impl PatternTrace {
+    pub fn new(
+        pattern: String,
+        result: &anyhow::Result<MatchResult>,
+        response: Option<&EndpointResponse>,
+        children: Vec<PatternTrace>,
+    ) -> Self {
+        let response_snippet = response.map(|r| snippet(&r.response));
+
+        match result {
+            Ok(result) => Self {
+                pattern,
+                matched: true,
+                reason: result.details.reason.to_string(),
+                confidence: Some(result.details.confidence),
+                score: Some(result.details.score),
+                evidence: MatchEvidence {
+                    ports: result.ports.iter().map(|p| p.base.to_string()).collect(),
+                    endpoint: result.endpoint.as_ref().map(|e| e.to_string()),
+                    response_snippet,
+                    mac_vendor: result.mac_vendor.clone(),
+                },
+                children,
+            },
+            Err(e) => Self {
+                pattern,
+                matched: false,
+                reason: e.to_string(),
+                confidence: None,
+                score: None,
+                evidence: MatchEvidence {
+                    response_snippet,
+                    ..Default::default()
+                },
+                children,
+            },
+        }
+    }
+}
+
+/// Evaluation of one service definition against a host interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DefinitionMatchTrace {
+    pub service_definition: String,
+    /// Whether a service was created from the definition
+    pub matched: bool,
+    /// Confidence of the created service, after adjustments for generic definitions
+    pub confidence: Option<MatchConfidence>,
+    /// Score of the definition's pattern, if it matched. Matches scoring below the minimum match
+    /// score, or losing their ports to a better-scoring definition, don't create a service.
+    pub score: Option<u32>,
+    pub trace: PatternTrace,
+}
+
+/// Every definition evaluated for a host interface, in evaluation order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostMatchTrace {
+    pub host_id: Uuid,
+    pub hostname: Option<String>,
+    pub ip_address: IpAddr,
+    pub definitions: Vec<DefinitionMatchTrace>,
+}
+
+const SNIPPET_LENGTH: usize = 200;
+
+fn snippet(response: &str) -> String {
+    let response = response.trim();
+
+    if response.chars().count() <= SNIPPET_LENGTH {
+        return response.to_string();
+    }
+
+    format!(
+        "{}...",
+        response.chars().take(SNIPPET_LENGTH).collect::<String>()
+    )
+}
@@ -0,0 +1,247 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+use crate::server::{
+    hosts::types::ports::PortBase,
+    services::types::endpoints::{Endpoint, EndpointResponse},
+};
+
+/// Compiled extractor regexes, as extraction runs for every matched service in every scan. Invalid
+/// regexes are cached as None so they're only reported once.
+static COMPILED_REGEXES: LazyLock<RwLock<HashMap<String, Option<Regex>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Where a service's version can be read from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VersionSource<'a> {
+    /// Body of the response from an endpoint
+    /// PortBase
+    /// path: &str - ie "/", "/api/version"
+    Body(PortBase, &'a str),
+
+    /// Header of the response from an endpoint
+    /// PortBase
+    /// path: &str - ie "/", "/api/version"
+    /// header: &str - header name, case insensitive
+    Header(PortBase, &'a str, &'a str),
+
+    /// Banner sent by the service when connecting to a TCP port, ie "SSH-2.0-OpenSSH_9.6"
+    Banner(PortBase),
+}
+
+/// Reads a version from scan data. The regex's first capture group is the version, or the whole
+/// match if it has no groups.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionExtractor<'a> {
+    pub source: VersionSource<'a>,
+    pub regex: &'a str,
+}
+
+impl<'a> VersionExtractor<'a> {
+    pub fn body(port_base: PortBase, path: &'a str, regex: &'a str) -> Self {
+        Self {
+            source: VersionSource::Body(port_base, path),
+            regex,
+        }
+    }
+
+    pub fn header(port_base: PortBase, path: &'a str, header: &'a str, regex: &'a str) -> Self {
+        Self {
+            source: VersionSource::Header(port_base, path, header),
+            regex,
+        }
+    }
+
+    pub fn banner(port_base: PortBase, regex: &'a str) -> Self {
+        Self {
+            source: VersionSource::Banner(port_base),
+            regex,
+        }
+    }
+
+    /// Endpoint which needs to be requested for the extractor, if any
+    pub fn endpoint(&self) -> Option<Endpoint> {
+        match self.source {
+            VersionSource::Body(port_base, path) | VersionSource::Header(port_base, path, _) => {
+                Some(Endpoint::for_pattern(port_base, path))
+            }
+            VersionSource::Banner(_) => None,
+        }
+    }
+
+    fn compiled_regex(&self) -> Option<Regex> {
+        if let Ok(compiled) = COMPILED_REGEXES.read()
+            && let Some(re) = compiled.get(self.regex)
+        {
+            return re.clone();
+        }
+
+        let re = Regex::new(self.regex)
+            .inspect_err(|_| tracing::warn!("Invalid version extractor regex {}", self.regex))
+            .ok();
+
+        if let Ok(mut compiled) = COMPILED_REGEXES.write() {
+            compiled.insert(self.regex.to_string(), re.clone());
+        }
+
+        re
+    }
+
+    pub fn extract(
+        &self,
+        endpoint_responses: &[EndpointResponse],
+        banners: &[PortBanner],
+    ) -> Option<String> {
+        let re = self.compiled_regex()?;
+
+        let response_for = |port_base: PortBase, path: &str| {
+            let endpoint = Endpoint::for_pattern(port_base, path);
+            endpoint_responses.iter().find(|actual| {
+                actual.endpoint.protocol == endpoint.protocol
+                    && actual.endpoint.port_base.number() == endpoint.port_base.number()
+                    && actual.endpoint.path == endpoint.path
+            })
+        };
+
+        let text = match self.source {
+            VersionSource::Body(port_base, path) => {
+                response_for(port_base, path).map(|r| r.response.as_str())
+            }
+            VersionSource::Header(port_base, path, header) => {
+                response_for(port_base, path).and_then(|r| r.header(header))
+            }
+            VersionSource::Banner(port_base) => banners
+                .iter()
+                .find(|b| b.port_base == port_base)
+                .map(|b| b.banner.as_str()),
+        }?;
+
+        let captures = re.captures(text)?;
+        let version = captures.get(1).or_else(|| captures.get(0))?.as_str().trim();
+
+        (!version.is_empty()).then(|| version.to_string())
+    }
+}
+
+/// Data a service sent on connecting to one of its TCP ports, before any request was made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortBanner {
+    pub port_base: PortBase,
+    pub banner: String,
+}
+
+/// A version of a service seen during discovery
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ServiceVersion {
+    pub version: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl ServiceVersion {
+    pub fn new(version: String) -> Self {
+        Self {
+            version,
+            detected_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::{
+        hosts::types::ports::PortBase,
+        services::{
+            definitions::{home_assistant::HomeAssistant, ssh_server::SshServer},
+            types::{
+                definitions::ServiceDefinition,
+                endpoints::{Endpoint, EndpointResponse},
+                versions::{COMPILED_REGEXES, PortBanner, VersionExtractor},
+            },
+        },
+    };
+
+    #[test]
+    fn test_version_extraction() {
+        let responses = vec![EndpointResponse {
+            endpoint: Endpoint::http(None, "/api/health"),
+            headers: vec![("server".to_string(), "CUPS/2.4 IPP/2.1".to_string())],
+            response: r#"{"database":"ok","version":"10.2.3"}"#.to_string(),
+        }];
+        let banners = vec![PortBanner {
+            port_base: PortBase::Ssh,
+            banner: "SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13".to_string(),
+        }];
+
+        let body = VersionExtractor::body(
+            PortBase::Http,
+            "/api/health",
+            r#""version"\s*:\s*"([^"]+)""#,
+        );
+        assert_eq!(
+            body.extract(&responses, &banners).as_deref(),
+            Some("10.2.3")
+        );
+
+        let header =
+            VersionExtractor::header(PortBase::Http, "/api/health", "Server", r"CUPS/([\d.]+)");
+        assert_eq!(header.extract(&responses, &banners).as_deref(), Some("2.4"));
+
+        let banner = VersionExtractor::banner(PortBase::Ssh, r"OpenSSH_([\w.]+)");
+        assert_eq!(
+            banner.extract(&responses, &banners).as_deref(),
+            Some("9.6p1")
+        );
+
+        // Endpoint wasn't scanned
+        let missing = VersionExtractor::body(PortBase::Http, "/api/version", r"(.+)");
+        assert_eq!(missing.extract(&responses, &banners), None);
+
+        // Regexes are compiled once and reused
+        assert_eq!(
+            body.extract(&responses, &banners).as_deref(),
+            Some("10.2.3")
+        );
+        assert!(
+            COMPILED_REGEXES
+                .read()
+                .unwrap()
+                .contains_key(r#""version"\s*:\s*"([^"]+)""#)
+        );
+
+        // Invalid regexes extract nothing
+        let invalid = VersionExtractor::banner(PortBase::Ssh, r"OpenSSH_(");
+        assert_eq!(invalid.extract(&responses, &banners), None);
+    }
+
+    #[test]
+    fn test_definition_version_extractors() {
+        let banners = vec![PortBanner {
+            port_base: PortBase::Ssh,
+            banner: "SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13".to_string(),
+        }];
+        let ssh = SshServer.version_extractors();
+        assert_eq!(
+            ssh.iter().find_map(|e| e.extract(&[], &banners)).as_deref(),
+            Some("9.6p1")
+        );
+
+        let responses = vec![EndpointResponse {
+            endpoint: Endpoint::for_pattern(PortBase::new_tcp(8123), "/manifest.json"),
+            headers: vec![],
+            response: r#"{"name":"Home Assistant","version":"2025.11.1"}"#.to_string(),
+        }];
+        let home_assistant = HomeAssistant.version_extractors();
+        assert_eq!(
+            home_assistant
+                .iter()
+                .find_map(|e| e.extract(&responses, &[]))
+                .as_deref(),
+            Some("2025.11.1")
+        );
+    }
+}
@@ -1,6 +1,7 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::hash::Hash;
-use strum_macros::{EnumDiscriminants, IntoStaticStr};
+use strum_macros::{Display, EnumDiscriminants, IntoStaticStr};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -14,14 +15,148 @@ use crate::server::shared::{
 )]
 #[serde(tag = "type", content = "details")]
 pub enum ServiceVirtualization {
-    Docker(DockerVirtualization),
+    /// A container managed by any supported runtime. Services stored before containerd and
+    /// podman were supported were tagged "Docker".
+    #[serde(alias = "Docker")]
+    Container(ContainerVirtualization),
+    Kubernetes(KubernetesVirtualization),
 }
 
 #[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
-pub struct DockerVirtualization {
+pub struct ContainerVirtualization {
     pub container_name: Option<String>,
     pub container_id: Option<String>,
     pub service_id: Uuid,
+    /// Runtime managing the container
+    #[serde(default)]
+    pub runtime: ContainerRuntime,
+    /// Namespace the container belongs to, for runtimes which have them, ie the Kubernetes pod
+    /// namespace of a container managed by containerd
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Image reference the container was created from, ie "postgres:13"
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Content digest of the image, ie "sha256:..."
+    #[serde(default)]
+    pub image_digest: Option<String>,
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub restart_policy: Option<String>,
+    #[serde(default)]
+    pub health: Option<String>,
+    #[serde(default)]
+    pub published_ports: Vec<ContainerPublishedPort>,
+    #[serde(default)]
+    pub volumes: Vec<ContainerVolume>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Display)]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    Podman,
+    #[strum(serialize = "containerd")]
+    Containerd,
+}
+
+impl ContainerVirtualization {
+    /// Whether the container's image matches the given reference. A reference without a tag
+    /// matches any tag of that repository; "latest" is assumed when the image has no tag.
+    pub fn matches_image(&self, reference: &str) -> bool {
+        let Some(image) = &self.image else {
+            return false;
+        };
+
+        let image = ImageReference::parse(image);
+        let reference = ImageReference::parse(reference);
+
+        image.repository == reference.repository
+            && reference
+                .tag
+                .is_none_or(|t| image.tag.as_deref().unwrap_or("latest") == t)
+    }
+
+    /// Whether the container's image comes from the given repository, regardless of tag
+    pub fn matches_repository(&self, repository: &str) -> bool {
+        self.image
+            .as_deref()
+            .is_some_and(|image| image_matches_repository(image, repository))
+    }
+}
+
+/// Whether an image reference comes from the given repository, regardless of tag. Both are
+/// normalized first, so "postgres" matches "docker.io/library/postgres:13" but "linuxserver/plex"
+/// doesn't match "lscr.io/linuxserver/plex".
+pub fn image_matches_repository(image: &str, repository: &str) -> bool {
+    ImageReference::parse(image).repository == ImageReference::parse(repository).repository
+}
+
+/// An image reference split into its repository, normalized to the full registry/namespace/name,
+/// and tag. Any digest is dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    pub repository: String,
+    pub tag: Option<String>,
+}
+
+impl ImageReference {
+    /// Parse a reference the way docker resolves it: without a registry it's on docker hub, and
+    /// docker hub images without a namespace are in "library", so "postgres:13" has repository
+    /// "docker.io/library/postgres"
+    pub fn parse(reference: &str) -> Self {
+        let reference = reference.split('@').next().unwrap_or(reference);
+        let (repository, tag) = match reference.rsplit_once(':') {
+            // A colon before the last slash belongs to a registry port, not a tag
+            Some((repo, tag)) if !tag.contains('/') => (repo, Some(tag.to_string())),
+            _ => (reference, None),
+        };
+        let repository = repository.to_lowercase();
+
+        let repository = match repository.split_once('/') {
+            Some(("index.docker.io", path)) => format!("docker.io/{}", path),
+            Some((registry, _)) if registry.contains(['.', ':']) || registry == "localhost" => {
+                repository
+            }
+            _ => format!("docker.io/{}", repository),
+        };
+
+        let repository = match repository.strip_prefix("docker.io/") {
+            Some(name) if !name.contains('/') => format!("docker.io/library/{}", name),
+            _ => repository,
+        };
+
+        Self { repository, tag }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
+pub struct ContainerPublishedPort {
+    pub host_ip: Option<String>,
+    pub host_port: u16,
+    pub container_port: u16,
+    pub protocol: String,
+}
+
+#[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
+pub struct ContainerVolume {
+    /// Host path or volume name, absent for anonymous tmpfs mounts
+    pub source: Option<String>,
+    pub destination: String,
+    /// Mount type, ie "bind", "volume" or "tmpfs"
+    pub mount_type: Option<String>,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Validate, Deserialize, PartialEq, Eq, Hash)]
+pub struct KubernetesVirtualization {
+    pub namespace: String,
+    /// Kind of the Kubernetes object the service was discovered from, ie "Service" or "Ingress"
+    pub kind: String,
+    pub name: String,
+    /// Kubernetes control plane service of the cluster the object belongs to
+    pub service_id: Uuid,
 }
 
 impl HasId for ServiceVirtualization {
@@ -41,10 +176,16 @@ impl EntityMetadataProvider for ServiceVirtualization {
 
 impl TypeMetadataProvider for ServiceVirtualization {
     fn name(&self) -> &'static str {
-        "Docker"
+        match self {
+            ServiceVirtualization::Container(..) => "Container",
+            ServiceVirtualization::Kubernetes(..) => "Kubernetes",
+        }
     }
 
     fn description(&self) -> &'static str {
-        "A service running in a docker container"
+        match self {
+            ServiceVirtualization::Container(..) => "A service running in a container",
+            ServiceVirtualization::Kubernetes(..) => "A service running in a Kubernetes cluster",
+        }
     }
 }
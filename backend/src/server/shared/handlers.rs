@@ -6,22 +6,32 @@ use crate::server::shared::types::metadata::{MetadataProvider, MetadataRegistry}
 use crate::server::topology::types::edges::EdgeType;
 use crate::server::{
     config::AppState,
+    custom_fields::handlers as custom_field_handlers,
     daemons::handlers as daemon_handlers,
     discovery::handlers as discovery_handlers,
+    entity_changes::handlers::change_context,
     groups::handlers as group_handlers,
     hosts::handlers as host_handlers,
+    ipam::handlers as ipam_handlers,
+    mac_vendors::handlers as mac_vendor_handlers,
+    merge_suggestions::handlers as merge_suggestion_handlers,
     networks::handlers as network_handlers,
+    scan_evidence::handlers as scan_evidence_handlers,
+    service_definitions::handlers as service_definition_handlers,
     services::handlers as service_handlers,
     shared::types::api::ApiResponse,
+    snapshots::handlers as snapshot_handlers,
     subnets::{handlers as subnet_handlers, types::base::SubnetType},
     topology::handlers as topology_handlers,
     users::handlers as user_handlers,
+    vlans::handlers as vlan_handlers,
+    vulnerabilities::handlers as vulnerability_handlers,
 };
-use axum::{Json, Router, routing::get};
-use std::sync::Arc;
+use axum::{Json, Router, extract::Query, middleware, routing::get};
+use std::{collections::HashMap, sync::Arc};
 use strum::{IntoDiscriminant, IntoEnumIterator};
 
-pub fn create_router() -> Router<Arc<AppState>> {
+pub fn create_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .nest("/api/hosts", host_handlers::create_router())
         .route("/api/metadata", get(get_metadata_registry))
@@ -29,16 +39,47 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .nest("/api/daemons", daemon_handlers::create_router())
         .nest("/api/discovery", discovery_handlers::create_router())
         .nest("/api/subnets", subnet_handlers::create_router())
+        .nest("/api/ipam", ipam_handlers::create_router())
+        .nest("/api/vlans", vlan_handlers::create_router())
+        .nest("/api/custom-fields", custom_field_handlers::create_router())
         .nest("/api/topology", topology_handlers::create_router())
         .nest("/api/services", service_handlers::create_router())
+        .nest(
+            "/api/services/vulnerabilities",
+            vulnerability_handlers::create_router(),
+        )
+        .nest(
+            "/api/service-definitions",
+            service_definition_handlers::create_router(),
+        )
+        .nest(
+            "/api/scan-evidence",
+            scan_evidence_handlers::create_router(),
+        )
+        .nest("/api/mac-vendors", mac_vendor_handlers::create_router())
+        .nest(
+            "/api/merge-suggestions",
+            merge_suggestion_handlers::create_router(),
+        )
         .nest("/api/networks", network_handlers::create_router())
+        .nest("/api/snapshots", snapshot_handlers::create_router())
         .nest("/api/users", user_handlers::create_router())
         .route("/api/health", get(get_health))
+        .layer(middleware::from_fn_with_state(state, change_context))
 }
 
-async fn get_metadata_registry() -> Json<ApiResponse<MetadataRegistry>> {
+/// Metadata of all types. Service definitions include the custom definitions of the network given
+/// by the optional `network_id` query parameter.
+async fn get_metadata_registry(
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResponse<MetadataRegistry>> {
+    let service_definitions = match params.get("network_id").and_then(|id| id.parse().ok()) {
+        Some(network_id) => ServiceDefinitionRegistry::network_service_definitions(&network_id),
+        None => ServiceDefinitionRegistry::all_service_definitions(),
+    };
+
     let registry = MetadataRegistry {
-        service_definitions: ServiceDefinitionRegistry::all_service_definitions()
+        service_definitions: service_definitions
             .iter()
             .map(|t| t.to_metadata())
             .collect(),
@@ -1,8 +1,15 @@
 use crate::server::{
-    daemons::service::DaemonService, groups::service::GroupService, hosts::service::HostService,
-    networks::service::NetworkService, services::service::ServiceService,
-    shared::types::storage::StorageFactory, subnets::service::SubnetService,
+    custom_fields::service::CustomFieldService, daemons::service::DaemonService,
+    entity_changes::service::EntityChangeService, groups::service::GroupService,
+    hosts::service::HostService, ipam::service::IpamService,
+    mac_vendors::service::MacVendorService, merge_suggestions::service::MergeSuggestionService,
+    networks::service::NetworkService, reconciliation::service::ReconciliationService,
+    scan_evidence::service::ScanEvidenceService,
+    service_definitions::service::CustomServiceDefinitionService,
+    services::service::ServiceService, shared::types::storage::StorageFactory,
+    snapshots::service::SnapshotService, subnets::service::SubnetService,
     topology::service::main::TopologyService, users::service::UserService,
+    vlans::service::VlanService, vulnerabilities::service::VulnerabilityService,
 };
 use anyhow::Result;
 use std::sync::Arc;
@@ -16,6 +23,17 @@ pub struct ServiceFactory {
     pub daemon_service: Arc<DaemonService>,
     pub topology_service: Arc<TopologyService>,
     pub service_service: Arc<ServiceService>,
+    pub custom_service_definition_service: Arc<CustomServiceDefinitionService>,
+    pub scan_evidence_service: Arc<ScanEvidenceService>,
+    pub vulnerability_service: Arc<VulnerabilityService>,
+    pub mac_vendor_service: Arc<MacVendorService>,
+    pub entity_change_service: Arc<EntityChangeService>,
+    pub snapshot_service: Arc<SnapshotService>,
+    pub merge_suggestion_service: Arc<MergeSuggestionService>,
+    pub reconciliation_service: Arc<ReconciliationService>,
+    pub ipam_service: Arc<IpamService>,
+    pub vlan_service: Arc<VlanService>,
+    pub custom_field_service: Arc<CustomFieldService>,
 }
 
 impl ServiceFactory {
@@ -23,32 +41,105 @@ impl ServiceFactory {
         storage: &StorageFactory,
         integrated_daemon_url: Option<String>,
     ) -> Result<Self> {
+        // Register custom service definitions before anything deserializes stored services
+        let custom_service_definition_service = Arc::new(CustomServiceDefinitionService::new(
+            storage.custom_service_definitions.clone(),
+        ));
+        custom_service_definition_service.refresh_registry().await?;
+
+        let mac_vendor_service =
+            Arc::new(MacVendorService::new(storage.mac_vendor_overrides.clone()));
+        mac_vendor_service.refresh_registry().await?;
+
+        let entity_change_service =
+            Arc::new(EntityChangeService::new(storage.entity_changes.clone()));
+
+        let custom_field_service = Arc::new(CustomFieldService::new(
+            storage.custom_field_definitions.clone(),
+        ));
+
         let daemon_service = Arc::new(DaemonService::new(storage.daemons.clone()));
-        let group_service = Arc::new(GroupService::new(storage.host_groups.clone()));
+        let group_service = Arc::new(GroupService::new(
+            storage.host_groups.clone(),
+            entity_change_service.clone(),
+        ));
 
         let service_service = Arc::new(ServiceService::new(
             storage.services.clone(),
             group_service.clone(),
+            entity_change_service.clone(),
         ));
 
         let host_service = Arc::new(HostService::new(
             storage.hosts.clone(),
             service_service.clone(),
             daemon_service.clone(),
+            entity_change_service.clone(),
         ));
 
         let subnet_service = Arc::new(SubnetService::new(
             storage.subnets.clone(),
             host_service.clone(),
+            service_service.clone(),
+            entity_change_service.clone(),
         ));
 
         let _ = service_service.set_host_service(host_service.clone());
 
+        let ipam_service = Arc::new(IpamService::new(
+            storage.ip_ranges.clone(),
+            subnet_service.clone(),
+            host_service.clone(),
+        ));
+
+        let vlan_service = Arc::new(VlanService::new(
+            storage.vlans.clone(),
+            subnet_service.clone(),
+            host_service.clone(),
+        ));
+
+        let _ = subnet_service.set_vlan_service(vlan_service.clone());
+        let _ = host_service.set_vlan_service(vlan_service.clone());
+
+        let scan_evidence_service = Arc::new(ScanEvidenceService::new(
+            storage.scan_evidence.clone(),
+            host_service.clone(),
+            service_service.clone(),
+        ));
+
+        let reconciliation_service = Arc::new(ReconciliationService::new(
+            storage.observation_states.clone(),
+            host_service.clone(),
+            service_service.clone(),
+        ));
+
+        let vulnerability_service = Arc::new(VulnerabilityService::new(
+            storage.vulnerabilities.clone(),
+            service_service.clone(),
+        ));
+
+        let snapshot_service = Arc::new(SnapshotService::new(
+            storage.snapshots.clone(),
+            host_service.clone(),
+            service_service.clone(),
+            subnet_service.clone(),
+            group_service.clone(),
+        ));
+
+        let merge_suggestion_service = Arc::new(MergeSuggestionService::new(
+            storage.merge_suggestions.clone(),
+            host_service.clone(),
+            service_service.clone(),
+            daemon_service.clone(),
+        ));
+
         let topology_service = Arc::new(TopologyService::new(
             host_service.clone(),
             subnet_service.clone(),
             group_service.clone(),
             service_service.clone(),
+            snapshot_service.clone(),
+            vlan_service.clone(),
         ));
 
         let network_service = Arc::new(NetworkService::new(
@@ -71,6 +162,17 @@ impl ServiceFactory {
             daemon_service,
             topology_service,
             service_service,
+            custom_service_definition_service,
+            scan_evidence_service,
+            vulnerability_service,
+            mac_vendor_service,
+            entity_change_service,
+            snapshot_service,
+            merge_suggestion_service,
+            reconciliation_service,
+            ipam_service,
+            vlan_service,
+            custom_field_service,
         })
     }
 }
@@ -1,3 +1,4 @@
 pub mod migrations;
+pub mod query;
 pub mod seed_data;
 pub use migrations::*;
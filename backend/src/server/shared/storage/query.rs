@@ -0,0 +1,87 @@
+use serde_json::json;
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::server::{
+    custom_fields::types::EntityFilter,
+    shared::types::query::{ListOptions, SortOrder, SourceFilter},
+};
+
+/// Pattern for a case insensitive substring match with LIKE against lower-cased text
+pub fn like_pattern(search: &str) -> String {
+    let escaped = search
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Stored service_definition values, which are the JSON serialized definition ids
+pub fn service_definition_values(ids: &[String]) -> Vec<String> {
+    ids.iter().map(|id| json!(id).to_string()).collect()
+}
+
+/// Append conditions on the source column. Containment queries use the GIN index on source.
+pub fn push_source_filter(builder: &mut QueryBuilder<'_, Postgres>, filter: &SourceFilter) {
+    if !filter.sources.is_empty() {
+        let sources: Vec<String> = filter
+            .sources
+            .iter()
+            .filter_map(|s| json!(s).as_str().map(str::to_string))
+            .collect();
+        builder
+            .push(" AND source->>'type' = ANY(")
+            .push_bind(sources)
+            .push(")");
+    }
+
+    if let Some(daemon_id) = filter.daemon_id {
+        builder
+            .push(" AND source @> ")
+            .push_bind(json!({ "metadata": [{ "daemon_id": daemon_id }] }));
+    }
+}
+
+/// Append conditions on the tags and custom_fields columns
+pub fn push_entity_filter(builder: &mut QueryBuilder<'_, Postgres>, filter: &EntityFilter) {
+    if !filter.tags.is_empty() {
+        builder.push(" AND tags @> ").push_bind(json!(filter.tags));
+    }
+
+    if !filter.custom_fields.is_empty() {
+        let values: serde_json::Map<String, serde_json::Value> = filter
+            .custom_fields
+            .iter()
+            .map(|(id, value)| (id.to_string(), json!(value)))
+            .collect();
+        builder
+            .push(" AND custom_fields @> ")
+            .push_bind(serde_json::Value::Object(values));
+    }
+}
+
+/// Append the keyset condition for the cursor, then ORDER BY and LIMIT. Fetches one row more
+/// than the limit so ListOptions::page can tell whether there is a next page.
+pub fn push_list_options(builder: &mut QueryBuilder<'_, Postgres>, options: &ListOptions) {
+    let column = options.sort.column();
+    let (comparison, direction) = match options.order {
+        SortOrder::Asc => (">", "ASC"),
+        SortOrder::Desc => ("<", "DESC"),
+    };
+
+    if let Some(cursor) = &options.cursor {
+        builder
+            .push(format!(" AND ({}, id) {} (CAST(", column, comparison))
+            .push_bind(cursor.value.clone())
+            .push(format!(" AS {}), ", options.sort.sql_type()))
+            .push_bind(cursor.id)
+            .push(")");
+    }
+
+    builder.push(format!(
+        " ORDER BY {} {}, id {}",
+        column, direction, direction
+    ));
+
+    builder.push(" LIMIT ").push_bind(options.limit as i64 + 1);
+}
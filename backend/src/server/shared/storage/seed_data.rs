@@ -1,4 +1,5 @@
 use cidr::Ipv4Cidr;
+use std::collections::BTreeMap;
 use std::net::{IpAddr, Ipv4Addr};
 use uuid::Uuid;
 
@@ -6,6 +7,8 @@ use crate::server::{
     discovery::types::base::EntitySource,
     hosts::types::{
         base::{Host, HostBase},
+        devices::DeviceFingerprint,
+        identity::HostIdentity,
         interfaces::{Interface, InterfaceBase},
         ports::{Port, PortBase},
         targets::HostTarget,
@@ -45,7 +48,10 @@ pub fn create_wan_subnet(network_id: Uuid) -> Subnet {
                 .to_string(),
         ),
         subnet_type: SubnetType::Internet,
+        vlan_id: None,
         source: EntitySource::System,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     };
 
     Subnet::new(base)
@@ -65,7 +71,10 @@ pub fn create_remote_subnet(network_id: Uuid) -> Subnet {
                 .to_string(),
         ),
         subnet_type: SubnetType::Remote,
+        vlan_id: None,
         source: EntitySource::System,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     };
 
     Subnet::new(base)
@@ -89,6 +98,11 @@ pub fn create_remote_host(remote_subnet: &Subnet, network_id: Uuid) -> (Host, Se
         target: HostTarget::None,
         source: EntitySource::System,
         virtualization: None,
+        device_fingerprint: DeviceFingerprint::default(),
+        identity: HostIdentity::default(),
+        device_classification: None,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     };
 
     let mut host = Host::new(base);
@@ -101,6 +115,11 @@ pub fn create_remote_host(remote_subnet: &Subnet, network_id: Uuid) -> (Host, Se
         bindings: vec![binding],
         virtualization: None,
         source: EntitySource::System,
+        version: None,
+        version_history: Vec::new(),
+        stale: false,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     });
 
     host.base.target = HostTarget::ServiceBinding(binding_id);
@@ -130,6 +149,11 @@ pub fn create_internet_connectivity_host(
         target: HostTarget::Hostname,
         source: EntitySource::System,
         virtualization: None,
+        device_fingerprint: DeviceFingerprint::default(),
+        identity: HostIdentity::default(),
+        device_classification: None,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     };
 
     let mut host = Host::new(base);
@@ -142,6 +166,11 @@ pub fn create_internet_connectivity_host(
         bindings: vec![binding],
         virtualization: None,
         source: EntitySource::System,
+        version: None,
+        version_history: Vec::new(),
+        stale: false,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     });
 
     host.base.target = HostTarget::ServiceBinding(binding_id);
@@ -169,6 +198,11 @@ pub fn create_public_dns_host(internet_subnet: &Subnet, network_id: Uuid) -> (Ho
         services: Vec::new(),
         source: EntitySource::System,
         virtualization: None,
+        device_fingerprint: DeviceFingerprint::default(),
+        identity: HostIdentity::default(),
+        device_classification: None,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     };
 
     let mut host = Host::new(base);
@@ -181,6 +215,11 @@ pub fn create_public_dns_host(internet_subnet: &Subnet, network_id: Uuid) -> (Ho
         bindings: vec![binding],
         virtualization: None,
         source: EntitySource::System,
+        version: None,
+        version_history: Vec::new(),
+        stale: false,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     });
 
     host.base.target = HostTarget::ServiceBinding(binding_id);
@@ -55,6 +55,10 @@ impl ApiError {
         Self::new(StatusCode::BAD_REQUEST, message.to_string())
     }
 
+    pub fn unauthorized(message: &str) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, message.to_string())
+    }
+
     pub fn not_found(resource: &str) -> Self {
         Self::new(StatusCode::NOT_FOUND, format!("{} not found", resource))
     }
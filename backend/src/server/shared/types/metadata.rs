@@ -12,24 +12,24 @@ pub struct MetadataRegistry {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct TypeMetadata {
-    pub id: &'static str,
-    pub name: Option<&'static str>,
-    pub description: Option<&'static str>,
-    pub category: Option<&'static str>,
-    pub icon: Option<&'static str>,
-    pub color: Option<&'static str>,
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Debug, Clone)]
 pub struct EntityMetadata {
-    pub id: &'static str,
-    pub color: &'static str,
-    pub icon: &'static str,
+    pub id: String,
+    pub color: String,
+    pub icon: String,
 }
 
 pub trait HasId {
-    fn id(&self) -> &'static str;
+    fn id(&self) -> &str;
 }
 
 pub trait MetadataProvider<T>: HasId {
@@ -38,12 +38,12 @@ pub trait MetadataProvider<T>: HasId {
 
 pub trait EntityMetadataProvider: MetadataProvider<EntityMetadata> {
     fn color(&self) -> &'static str;
-    fn icon(&self) -> &'static str;
+    fn icon(&self) -> &str;
 }
 
 pub trait TypeMetadataProvider: EntityMetadataProvider + MetadataProvider<TypeMetadata> {
-    fn name(&self) -> &'static str;
-    fn description(&self) -> &'static str {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str {
         ""
     }
     fn category(&self) -> &'static str {
@@ -60,9 +60,9 @@ where
 {
     fn to_metadata(&self) -> EntityMetadata {
         EntityMetadata {
-            id: self.id(),
-            color: self.color(),
-            icon: self.icon(),
+            id: self.id().to_string(),
+            color: self.color().to_string(),
+            icon: self.icon().to_string(),
         }
     }
 }
@@ -80,13 +80,15 @@ where
         let color = self.color();
         let metadata = self.metadata();
 
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
         TypeMetadata {
-            id,
-            name: (!name.is_empty()).then_some(name),
-            description: (!description.is_empty()).then_some(description),
-            category: (!category.is_empty()).then_some(category),
-            icon: (!icon.is_empty()).then_some(icon),
-            color: (!color.is_empty()).then_some(color),
+            id: id.to_string(),
+            name: non_empty(name),
+            description: non_empty(description),
+            category: non_empty(category),
+            icon: non_empty(icon),
+            color: non_empty(color),
             metadata: (!metadata.as_object().is_some_and(|obj| obj.is_empty())).then_some(metadata),
         }
     }
@@ -1,3 +1,4 @@
 pub mod api;
 pub mod metadata;
+pub mod query;
 pub mod storage;
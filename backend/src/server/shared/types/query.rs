@@ -0,0 +1,228 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{collections::HashMap, str::FromStr};
+use uuid::Uuid;
+
+use crate::server::discovery::types::base::EntitySourceDiscriminants;
+
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// One page of a list endpoint. Pass next_cursor back as the `cursor` query parameter to get the
+/// page after it; it's None on the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Everything in a single page
+    pub fn all(items: Vec<T>) -> Self {
+        Self {
+            items,
+            next_cursor: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Name,
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl SortField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            SortField::Name => "name",
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+        }
+    }
+
+    /// Postgres type the cursor value is cast to when comparing against the column
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            SortField::Name => "TEXT",
+            SortField::CreatedAt | SortField::UpdatedAt => "TIMESTAMPTZ",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Position after the last item of a page: its sort column value and id, which breaks ties.
+/// Handed to clients base64 encoded so they treat it as opaque.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageCursor {
+    pub value: String,
+    pub id: Uuid,
+}
+
+impl PageCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| anyhow!("Invalid cursor"))
+    }
+}
+
+/// Columns every paged entity has, used to build the cursor for the next page
+pub trait Pageable {
+    fn id(&self) -> Uuid;
+    fn name(&self) -> &str;
+    fn created_at(&self) -> DateTime<Utc>;
+    fn updated_at(&self) -> DateTime<Utc>;
+}
+
+/// Sorting and pagination of a list endpoint, from the `sort` (name, created_at or updated_at),
+/// `order` (asc or desc), `limit` and `cursor` query parameters. Without a limit a page holds
+/// DEFAULT_PAGE_SIZE items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListOptions {
+    pub sort: SortField,
+    pub order: SortOrder,
+    pub limit: usize,
+    pub cursor: Option<PageCursor>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            sort: SortField::default(),
+            order: SortOrder::default(),
+            limit: DEFAULT_PAGE_SIZE,
+            cursor: None,
+        }
+    }
+}
+
+impl ListOptions {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self> {
+        let limit = params
+            .get("limit")
+            .map(|limit| parse_param::<usize>("limit", limit))
+            .transpose()?
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(anyhow!("limit must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+
+        Ok(Self {
+            sort: params
+                .get("sort")
+                .map(|sort| parse_enum_param("sort", sort))
+                .transpose()?
+                .unwrap_or_default(),
+            order: params
+                .get("order")
+                .map(|order| parse_enum_param("order", order))
+                .transpose()?
+                .unwrap_or_default(),
+            limit,
+            cursor: params
+                .get("cursor")
+                .map(|cursor| PageCursor::decode(cursor))
+                .transpose()?,
+        })
+    }
+
+    /// Turn rows fetched with a limit one higher than requested into a page, using the extra
+    /// row only to tell whether there is a next page.
+    pub fn page<T: Pageable>(&self, mut items: Vec<T>) -> Page<T> {
+        let next_cursor = if items.len() > self.limit {
+            items.truncate(self.limit);
+            items.last().map(|last| self.cursor_for(last).encode())
+        } else {
+            None
+        };
+
+        Page { items, next_cursor }
+    }
+
+    fn cursor_for<T: Pageable>(&self, item: &T) -> PageCursor {
+        let value = match self.sort {
+            SortField::Name => item.name().to_string(),
+            SortField::CreatedAt => item
+                .created_at()
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            SortField::UpdatedAt => item
+                .updated_at()
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        };
+
+        PageCursor {
+            value,
+            id: item.id(),
+        }
+    }
+}
+
+/// Filters on where an entity came from, from the `source` query parameter (a comma separated
+/// list of source types such as Manual or Discovery) and `daemon_id`, the daemon that
+/// discovered it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceFilter {
+    pub sources: Vec<EntitySourceDiscriminants>,
+    pub daemon_id: Option<Uuid>,
+}
+
+impl SourceFilter {
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self> {
+        Ok(Self {
+            sources: param_list(params, "source")
+                .iter()
+                .map(|source| parse_enum_param("source", source))
+                .collect::<Result<_>>()?,
+            daemon_id: params
+                .get("daemon_id")
+                .map(|id| parse_param("daemon_id", id))
+                .transpose()?,
+        })
+    }
+}
+
+/// Comma separated values of a query parameter, empty when it's missing
+pub fn param_list(params: &HashMap<String, String>, key: &str) -> Vec<String> {
+    params
+        .get(key)
+        .map(|values| {
+            values
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_param<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid {} '{}'", key, value))
+}
+
+/// Parse a query parameter into an enum by its serialized name
+pub fn parse_enum_param<T: DeserializeOwned>(key: &str, value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| anyhow!("Invalid {} '{}'", key, value))
+}
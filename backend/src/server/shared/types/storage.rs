@@ -3,14 +3,27 @@ use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::server::{
+    custom_fields::storage::{CustomFieldDefinitionStorage, PostgresCustomFieldDefinitionStorage},
     daemons::storage::{DaemonStorage, PostgresDaemonStorage},
+    entity_changes::storage::{EntityChangeStorage, PostgresEntityChangeStorage},
     groups::storage::{GroupStorage, PostgresGroupStorage},
     hosts::storage::{HostStorage, PostgresHostStorage},
+    ipam::storage::{IpRangeStorage, PostgresIpRangeStorage},
+    mac_vendors::storage::{MacVendorOverrideStorage, PostgresMacVendorOverrideStorage},
+    merge_suggestions::storage::{MergeSuggestionStorage, PostgresMergeSuggestionStorage},
     networks::storage::{NetworkStorage, PostgresNetworkStorage},
+    reconciliation::storage::{ObservationStateStorage, PostgresObservationStateStorage},
+    scan_evidence::storage::{PostgresScanEvidenceStorage, ScanEvidenceStorage},
+    service_definitions::storage::{
+        CustomServiceDefinitionStorage, PostgresCustomServiceDefinitionStorage,
+    },
     services::storage::{PostgresServiceStorage, ServiceStorage},
     shared::storage::DatabaseMigrations,
+    snapshots::storage::{PostgresSnapshotStorage, SnapshotStorage},
     subnets::storage::{PostgresSubnetStorage, SubnetStorage},
     users::storage::{PostgresUserStorage, UserStorage},
+    vlans::storage::{PostgresVlanStorage, VlanStorage},
+    vulnerabilities::storage::{PostgresVulnerabilityStorage, VulnerabilityStorage},
 };
 
 pub struct StorageFactory {
@@ -21,6 +34,17 @@ pub struct StorageFactory {
     pub daemons: Arc<dyn DaemonStorage>,
     pub subnets: Arc<dyn SubnetStorage>,
     pub services: Arc<dyn ServiceStorage>,
+    pub custom_service_definitions: Arc<dyn CustomServiceDefinitionStorage>,
+    pub scan_evidence: Arc<dyn ScanEvidenceStorage>,
+    pub vulnerabilities: Arc<dyn VulnerabilityStorage>,
+    pub mac_vendor_overrides: Arc<dyn MacVendorOverrideStorage>,
+    pub entity_changes: Arc<dyn EntityChangeStorage>,
+    pub snapshots: Arc<dyn SnapshotStorage>,
+    pub merge_suggestions: Arc<dyn MergeSuggestionStorage>,
+    pub observation_states: Arc<dyn ObservationStateStorage>,
+    pub ip_ranges: Arc<dyn IpRangeStorage>,
+    pub vlans: Arc<dyn VlanStorage>,
+    pub custom_field_definitions: Arc<dyn CustomFieldDefinitionStorage>,
 }
 
 impl StorageFactory {
@@ -38,6 +62,21 @@ impl StorageFactory {
             daemons: Arc::new(PostgresDaemonStorage::new(pool.clone())),
             subnets: Arc::new(PostgresSubnetStorage::new(pool.clone())),
             services: Arc::new(PostgresServiceStorage::new(pool.clone())),
+            custom_service_definitions: Arc::new(PostgresCustomServiceDefinitionStorage::new(
+                pool.clone(),
+            )),
+            scan_evidence: Arc::new(PostgresScanEvidenceStorage::new(pool.clone())),
+            vulnerabilities: Arc::new(PostgresVulnerabilityStorage::new(pool.clone())),
+            mac_vendor_overrides: Arc::new(PostgresMacVendorOverrideStorage::new(pool.clone())),
+            entity_changes: Arc::new(PostgresEntityChangeStorage::new(pool.clone())),
+            snapshots: Arc::new(PostgresSnapshotStorage::new(pool.clone())),
+            merge_suggestions: Arc::new(PostgresMergeSuggestionStorage::new(pool.clone())),
+            observation_states: Arc::new(PostgresObservationStateStorage::new(pool.clone())),
+            ip_ranges: Arc::new(PostgresIpRangeStorage::new(pool.clone())),
+            vlans: Arc::new(PostgresVlanStorage::new(pool.clone())),
+            custom_field_definitions: Arc::new(PostgresCustomFieldDefinitionStorage::new(
+                pool.clone(),
+            )),
         })
     }
 }
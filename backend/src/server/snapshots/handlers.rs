@@ -0,0 +1,105 @@
+use crate::server::{
+    config::AppState,
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+    snapshots::types::{
+        NetworkSnapshot, NetworkSnapshotBase, NetworkSnapshotSummary, SnapshotDiff,
+    },
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_snapshot))
+        .route("/", get(get_all_snapshots))
+        .route("/:id", get(get_snapshot))
+        .route("/:id", delete(delete_snapshot))
+        .route("/:from_snapshot/diff/:to_snapshot", get(diff_snapshots))
+}
+
+async fn create_snapshot(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<NetworkSnapshotBase>,
+) -> ApiResult<Json<ApiResponse<NetworkSnapshot>>> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "Snapshot validation failed: {}",
+            validation_errors
+        )));
+    }
+
+    let service = &state.services.snapshot_service;
+
+    let snapshot = service.create_snapshot(request).await?;
+
+    Ok(Json(ApiResponse::success(snapshot)))
+}
+
+async fn get_all_snapshots(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<NetworkSnapshotSummary>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.snapshot_service;
+
+    let snapshots = service.get_all_snapshots(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(snapshots)))
+}
+
+async fn get_snapshot(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<NetworkSnapshot>>> {
+    let service = &state.services.snapshot_service;
+
+    let snapshot = service
+        .get_snapshot(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Snapshot '{}' not found", &id)))?;
+
+    Ok(Json(ApiResponse::success(snapshot)))
+}
+
+async fn delete_snapshot(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.snapshot_service;
+
+    if service.get_snapshot(&id).await?.is_none() {
+        return Err(ApiError::not_found(&format!(
+            "Snapshot '{}' not found",
+            &id
+        )));
+    }
+
+    service.delete_snapshot(&id).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn diff_snapshots(
+    State(state): State<Arc<AppState>>,
+    Path((from_snapshot, to_snapshot)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<ApiResponse<SnapshotDiff>>> {
+    let service = &state.services.snapshot_service;
+
+    let diff = service
+        .diff_snapshots(&from_snapshot, &to_snapshot)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(diff)))
+}
@@ -0,0 +1,107 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    groups::service::GroupService,
+    hosts::service::HostService,
+    services::service::ServiceService,
+    snapshots::{
+        storage::SnapshotStorage,
+        types::{
+            CapturedService, NetworkSnapshot, NetworkSnapshotBase, NetworkSnapshotSummary,
+            SnapshotContents, SnapshotDiff,
+        },
+    },
+    subnets::service::SubnetService,
+};
+
+pub struct SnapshotService {
+    storage: Arc<dyn SnapshotStorage>,
+    host_service: Arc<HostService>,
+    service_service: Arc<ServiceService>,
+    subnet_service: Arc<SubnetService>,
+    group_service: Arc<GroupService>,
+}
+
+impl SnapshotService {
+    pub fn new(
+        storage: Arc<dyn SnapshotStorage>,
+        host_service: Arc<HostService>,
+        service_service: Arc<ServiceService>,
+        subnet_service: Arc<SubnetService>,
+        group_service: Arc<GroupService>,
+    ) -> Self {
+        Self {
+            storage,
+            host_service,
+            service_service,
+            subnet_service,
+            group_service,
+        }
+    }
+
+    /// Capture the current state of a network
+    pub async fn create_snapshot(&self, base: NetworkSnapshotBase) -> Result<NetworkSnapshot> {
+        let network_id = base.network_id;
+        let contents = SnapshotContents {
+            hosts: self.host_service.get_all_hosts(&network_id).await?,
+            services: self
+                .service_service
+                .get_all_services(&network_id)
+                .await?
+                .into_iter()
+                .map(CapturedService::from)
+                .collect(),
+            subnets: self.subnet_service.get_all_subnets(&network_id).await?,
+            groups: self.group_service.get_all_groups(&network_id).await?,
+        };
+
+        let snapshot = NetworkSnapshot::new(base, contents);
+        self.storage.create(&snapshot).await?;
+
+        tracing::info!(
+            "Created snapshot {}: {} of network {} with {} hosts",
+            snapshot.base.name,
+            snapshot.id,
+            network_id,
+            snapshot.contents.hosts.len()
+        );
+        Ok(snapshot)
+    }
+
+    pub async fn get_snapshot(&self, id: &Uuid) -> Result<Option<NetworkSnapshot>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn get_all_snapshots(
+        &self,
+        network_id: &Uuid,
+    ) -> Result<Vec<NetworkSnapshotSummary>> {
+        self.storage.get_all(network_id).await
+    }
+
+    pub async fn delete_snapshot(&self, id: &Uuid) -> Result<()> {
+        self.storage.delete(id).await?;
+        tracing::info!("Deleted snapshot {}", id);
+        Ok(())
+    }
+
+    /// Changes from one snapshot to another of the same network
+    pub async fn diff_snapshots(&self, from_id: &Uuid, to_id: &Uuid) -> Result<SnapshotDiff> {
+        let from = self
+            .get_snapshot(from_id)
+            .await?
+            .ok_or_else(|| anyhow!("Snapshot {} not found", from_id))?;
+        let to = self
+            .get_snapshot(to_id)
+            .await?
+            .ok_or_else(|| anyhow!("Snapshot {} not found", to_id))?;
+
+        if from.base.network_id != to.base.network_id {
+            return Err(anyhow!("Can't diff snapshots of different networks"));
+        }
+
+        Ok(SnapshotDiff::between(&from, &to))
+    }
+}
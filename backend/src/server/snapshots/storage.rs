@@ -0,0 +1,131 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::snapshots::types::{
+    NetworkSnapshot, NetworkSnapshotBase, NetworkSnapshotSummary, SnapshotContents, SnapshotTrigger,
+};
+
+#[async_trait]
+pub trait SnapshotStorage: Send + Sync {
+    async fn create(&self, snapshot: &NetworkSnapshot) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<NetworkSnapshot>>;
+    /// Snapshots of a network without their contents, newest first
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<NetworkSnapshotSummary>>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresSnapshotStorage {
+    pool: PgPool,
+}
+
+impl PostgresSnapshotStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SnapshotStorage for PostgresSnapshotStorage {
+    async fn create(&self, snapshot: &NetworkSnapshot) -> Result<()> {
+        let trigger_json = serde_json::to_value(&snapshot.base.trigger)?;
+        let contents_json = serde_json::to_value(&snapshot.contents)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO network_snapshots (id, network_id, name, trigger, contents, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(snapshot.id)
+        .bind(snapshot.base.network_id)
+        .bind(&snapshot.base.name)
+        .bind(trigger_json)
+        .bind(contents_json)
+        .bind(snapshot.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<NetworkSnapshot>> {
+        let row = sqlx::query("SELECT * FROM network_snapshots WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(row_to_snapshot(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<NetworkSnapshotSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, network_id, name, trigger, created_at,
+                jsonb_array_length(contents->'hosts') AS host_count,
+                jsonb_array_length(contents->'services') AS service_count,
+                jsonb_array_length(contents->'subnets') AS subnet_count,
+                jsonb_array_length(contents->'groups') AS group_count
+            FROM network_snapshots
+            WHERE network_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(network_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(row_to_snapshot_summary).collect()
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM network_snapshots WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_snapshot_base(row: &sqlx::postgres::PgRow) -> Result<NetworkSnapshotBase, Error> {
+    let trigger: SnapshotTrigger = serde_json::from_value(row.get::<Value, _>("trigger"))
+        .or(Err(Error::msg("Failed to deserialize trigger")))?;
+
+    Ok(NetworkSnapshotBase {
+        network_id: row.get("network_id"),
+        name: row.get("name"),
+        trigger,
+    })
+}
+
+fn row_to_snapshot(row: sqlx::postgres::PgRow) -> Result<NetworkSnapshot, Error> {
+    let contents: SnapshotContents = serde_json::from_value(row.get::<Value, _>("contents"))
+        .map_err(|e| Error::msg(format!("Failed to deserialize snapshot contents: {}", e)))?;
+
+    Ok(NetworkSnapshot {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        base: row_to_snapshot_base(&row)?,
+        contents,
+    })
+}
+
+fn row_to_snapshot_summary(row: sqlx::postgres::PgRow) -> Result<NetworkSnapshotSummary, Error> {
+    let count = |column: &str| row.get::<Option<i32>, _>(column).unwrap_or(0) as usize;
+
+    Ok(NetworkSnapshotSummary {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        base: row_to_snapshot_base(&row)?,
+        host_count: count("host_count"),
+        service_count: count("service_count"),
+        subnet_count: count("subnet_count"),
+        group_count: count("group_count"),
+    })
+}
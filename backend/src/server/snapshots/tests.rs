@@ -0,0 +1,327 @@
+use mac_address::MacAddress;
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::{
+    server::{
+        hosts::types::{
+            interfaces::Interface,
+            ports::{Port, PortBase, PortConfig, TransportProtocol},
+        },
+        service_definitions::types::{CustomServiceDefinitionBase, PatternDefinition},
+        services::{definitions::ServiceDefinitionRegistry, types::categories::ServiceCategory},
+        snapshots::types::{NetworkSnapshotBase, SnapshotTrigger},
+        topology::types::api::TopologyRequestOptions,
+    },
+    tests::*,
+};
+
+fn snapshot(network_id: &uuid::Uuid, name: &str) -> NetworkSnapshotBase {
+    NetworkSnapshotBase {
+        network_id: *network_id,
+        name: name.to_string(),
+        trigger: SnapshotTrigger::Manual,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_snapshots_and_diffs() {
+    let (_storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let subnet = services
+        .subnet_service
+        .create_subnet(subnet(&network.id))
+        .await
+        .unwrap();
+
+    let mut host1 = host(&network.id);
+    host1.base.interfaces = vec![interface(&subnet.id)];
+    let (host1, _) = services
+        .host_service
+        .create_host_with_services(host1, vec![])
+        .await
+        .unwrap();
+
+    let first = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "Before"))
+        .await
+        .unwrap();
+    assert!(first.contents.hosts.iter().any(|h| h.id == host1.id));
+
+    // Open a port on the existing host, and add a host with a service
+    let mut updated_host1 = host1.clone();
+    updated_host1
+        .base
+        .ports
+        .push(Port::new(PortBase::new_tcp(3389)));
+    services
+        .host_service
+        .update_host(updated_host1)
+        .await
+        .unwrap();
+
+    let mut host2 = host(&network.id);
+    host2.base.hostname = Some("other.local".to_string());
+    host2.base.interfaces = vec![interface(&subnet.id)];
+    host2.base.interfaces[0].base.ip_address = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 101));
+    host2.base.interfaces[0].base.mac_address = Some(MacAddress::new([1, 2, 3, 4, 5, 7]));
+    let host2_service = service(&network.id, &host2.id);
+    let (host2, _) = services
+        .host_service
+        .create_host_with_services(host2, vec![host2_service])
+        .await
+        .unwrap();
+
+    let second = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "After"))
+        .await
+        .unwrap();
+
+    let diff = services
+        .snapshot_service
+        .diff_snapshots(&first.id, &second.id)
+        .await
+        .unwrap();
+    assert_eq!(diff.added_hosts.len(), 1);
+    assert_eq!(diff.added_hosts[0].id, host2.id);
+    assert!(diff.removed_hosts.is_empty());
+    assert_eq!(diff.opened_ports.len(), 1);
+    assert_eq!(diff.opened_ports[0].host_id, host1.id);
+    assert_eq!(diff.opened_ports[0].port.base.number(), 3389);
+    assert_eq!(diff.added_services.len(), 1);
+
+    // Consolidating moves the other host's services
+    services
+        .host_service
+        .consolidate_hosts(host1.clone(), host2.clone())
+        .await
+        .unwrap();
+    let third = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "Consolidated"))
+        .await
+        .unwrap();
+
+    let diff = services
+        .snapshot_service
+        .diff_snapshots(&second.id, &third.id)
+        .await
+        .unwrap();
+    assert_eq!(diff.removed_hosts.len(), 1);
+    assert_eq!(diff.moved_services.len(), 1);
+    assert_eq!(diff.moved_services[0].from_host_id, host2.id);
+    assert_eq!(diff.moved_services[0].to_host_id, host1.id);
+
+    // Listing is newest first and doesn't include contents
+    let summaries = services
+        .snapshot_service
+        .get_all_snapshots(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(summaries.len(), 3);
+    assert_eq!(summaries[0].id, third.id);
+    assert_eq!(summaries[2].host_count, first.contents.hosts.len());
+
+    // Topology can be rendered from a snapshot
+    let graph = services
+        .topology_service
+        .build_graph(TopologyRequestOptions {
+            network_ids: vec![network.id],
+            snapshot_id: Some(second.id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(graph.node_count() > 0);
+
+    // Snapshots are of one network
+    let other_network = services
+        .network_service
+        .create_network(crate::tests::network(&user.id))
+        .await
+        .unwrap();
+    let other = services
+        .snapshot_service
+        .create_snapshot(snapshot(&other_network.id, "Other"))
+        .await
+        .unwrap();
+    assert!(
+        services
+            .snapshot_service
+            .diff_snapshots(&first.id, &other.id)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_snapshots_outlive_service_definitions() {
+    let (_storage, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let subnet = services
+        .subnet_service
+        .create_subnet(subnet(&network.id))
+        .await
+        .unwrap();
+
+    let definition = services
+        .custom_service_definition_service
+        .create_definition(CustomServiceDefinitionBase {
+            network_id: network.id,
+            name: "Inventory".to_string(),
+            description: "In-house inventory app".to_string(),
+            category: ServiceCategory::Development,
+            dashboard_icons_path: String::new(),
+            simple_icons_path: String::new(),
+            vector_logo_zone_icons_path: String::new(),
+            logo_needs_white_background: false,
+            discovery_pattern: PatternDefinition::Port(PortConfig {
+                number: 7420,
+                protocol: TransportProtocol::Tcp,
+            }),
+            version_extractors: vec![],
+        })
+        .await
+        .unwrap();
+
+    let mut inventory_host = host(&network.id);
+    inventory_host.base.interfaces = vec![interface(&subnet.id)];
+    let mut inventory = service(&network.id, &inventory_host.id);
+    inventory.base.service_definition = ServiceDefinitionRegistry::find_by_id("Inventory").unwrap();
+    let (inventory_host, _) = services
+        .host_service
+        .create_host_with_services(inventory_host, vec![inventory])
+        .await
+        .unwrap();
+
+    let before = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "With inventory"))
+        .await
+        .unwrap();
+
+    // Once no service uses it, the definition can be deleted
+    services
+        .host_service
+        .delete_host(&inventory_host.id, true)
+        .await
+        .unwrap();
+    services
+        .custom_service_definition_service
+        .delete_definition(&definition)
+        .await
+        .unwrap();
+    assert!(ServiceDefinitionRegistry::find_by_id("Inventory").is_none());
+
+    // Add a group, and give another host a second interface
+    let mut other_host = host(&network.id);
+    other_host.base.hostname = Some("other.local".to_string());
+    other_host.base.interfaces = vec![interface(&subnet.id)];
+    let (other_host, _) = services
+        .host_service
+        .create_host_with_services(other_host, vec![])
+        .await
+        .unwrap();
+    let group = services
+        .group_service
+        .create_group(group(&network.id))
+        .await
+        .unwrap();
+
+    let between = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "Between"))
+        .await
+        .unwrap();
+
+    let mut updated_other_host = other_host.clone();
+    let mut second_interface = Interface::new(other_host.base.interfaces[0].base.clone());
+    second_interface.base.ip_address = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 150));
+    updated_other_host
+        .base
+        .interfaces
+        .push(second_interface.clone());
+    services
+        .host_service
+        .update_host(updated_other_host)
+        .await
+        .unwrap();
+    services
+        .group_service
+        .delete_group(&group.id)
+        .await
+        .unwrap();
+
+    let after = services
+        .snapshot_service
+        .create_snapshot(snapshot(&network.id, "After"))
+        .await
+        .unwrap();
+
+    // The older snapshot still reads, diffs and renders
+    let read = services
+        .snapshot_service
+        .get_snapshot(&before.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        read.contents
+            .services
+            .iter()
+            .any(|s| s.service_definition == "Inventory")
+    );
+
+    let diff = services
+        .snapshot_service
+        .diff_snapshots(&before.id, &between.id)
+        .await
+        .unwrap();
+    assert_eq!(diff.removed_services.len(), 1);
+    assert_eq!(diff.removed_services[0].service_definition, "Inventory");
+    assert_eq!(diff.added_groups.len(), 1);
+    assert_eq!(diff.added_groups[0].id, group.id);
+
+    let graph = services
+        .topology_service
+        .build_graph(TopologyRequestOptions {
+            network_ids: vec![network.id],
+            snapshot_id: Some(before.id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(graph.node_count() > 0);
+
+    // Interfaces are diffed on hosts in both snapshots, and groups by id
+    let diff = services
+        .snapshot_service
+        .diff_snapshots(&between.id, &after.id)
+        .await
+        .unwrap();
+    assert_eq!(diff.added_interfaces.len(), 1);
+    assert_eq!(diff.added_interfaces[0].host_id, other_host.id);
+    assert_eq!(
+        diff.added_interfaces[0].interface.base.ip_address,
+        second_interface.base.ip_address
+    );
+    assert!(diff.removed_interfaces.is_empty());
+    assert_eq!(diff.removed_groups.len(), 1);
+    assert!(diff.added_groups.is_empty());
+}
@@ -0,0 +1,377 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::server::{
+    discovery::types::base::EntitySource,
+    groups::types::Group,
+    hosts::types::{base::Host, interfaces::Interface, ports::Port},
+    services::{
+        definitions::ServiceDefinitionRegistry,
+        types::{
+            base::{Service, ServiceBase},
+            bindings::Binding,
+            definitions::DefaultServiceDefinition,
+            versions::ServiceVersion,
+            virtualization::ServiceVirtualization,
+        },
+    },
+    subnets::types::base::Subnet,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SnapshotTrigger {
+    #[default]
+    Manual,
+    /// Taken automatically once a discovery session completed
+    Discovery { session_id: Uuid },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq, Eq)]
+pub struct NetworkSnapshotBase {
+    pub network_id: Uuid,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[serde(default)]
+    pub trigger: SnapshotTrigger,
+}
+
+/// Full state of a network when a snapshot was taken. Interfaces and ports are captured as part
+/// of their hosts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotContents {
+    pub hosts: Vec<Host>,
+    pub services: Vec<CapturedService>,
+    pub subnets: Vec<Subnet>,
+    pub groups: Vec<Group>,
+}
+
+impl SnapshotContents {
+    /// Contents with the services' definitions looked up, for rendering
+    pub fn resolve(self) -> NetworkContents {
+        NetworkContents {
+            hosts: self.hosts,
+            services: self
+                .services
+                .into_iter()
+                .map(CapturedService::resolve)
+                .collect(),
+            subnets: self.subnets,
+            groups: self.groups,
+        }
+    }
+}
+
+/// Hosts, services, subnets and groups of a network, either current or from a snapshot
+#[derive(Debug, Clone, Default)]
+pub struct NetworkContents {
+    pub hosts: Vec<Host>,
+    pub services: Vec<Service>,
+    pub subnets: Vec<Subnet>,
+    pub groups: Vec<Group>,
+}
+
+/// A service as captured in a snapshot, serialized the same way as a Service. The definition is
+/// kept as its id rather than looked up in the registry, as custom definitions can be deleted and
+/// built-in ones removed after the snapshot was taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedService {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub host_id: Uuid,
+    pub network_id: Uuid,
+    pub service_definition: String,
+    pub name: String,
+    pub bindings: Vec<Binding>,
+    pub virtualization: Option<ServiceVirtualization>,
+    pub source: EntitySource,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub version_history: Vec<ServiceVersion>,
+    #[serde(default)]
+    pub stale: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<Uuid, String>,
+}
+
+impl CapturedService {
+    /// The service with its definition looked up. Definitions which no longer exist are replaced
+    /// by the default one.
+    pub fn resolve(self) -> Service {
+        let service_definition = ServiceDefinitionRegistry::find_by_id(&self.service_definition)
+            .unwrap_or_else(|| Box::new(DefaultServiceDefinition));
+
+        Service {
+            id: self.id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            base: ServiceBase {
+                host_id: self.host_id,
+                network_id: self.network_id,
+                service_definition,
+                name: self.name,
+                bindings: self.bindings,
+                virtualization: self.virtualization,
+                source: self.source,
+                version: self.version,
+                version_history: self.version_history,
+                stale: self.stale,
+                tags: self.tags,
+                custom_fields: self.custom_fields,
+            },
+        }
+    }
+}
+
+impl From<Service> for CapturedService {
+    fn from(service: Service) -> Self {
+        let ServiceBase {
+            host_id,
+            network_id,
+            service_definition,
+            name,
+            bindings,
+            virtualization,
+            source,
+            version,
+            version_history,
+            stale,
+            tags,
+            custom_fields,
+        } = service.base;
+
+        Self {
+            id: service.id,
+            created_at: service.created_at,
+            updated_at: service.updated_at,
+            host_id,
+            network_id,
+            service_definition: service_definition.id().to_string(),
+            name,
+            bindings,
+            virtualization,
+            source,
+            version,
+            version_history,
+            stale,
+            tags,
+            custom_fields,
+        }
+    }
+}
+
+/// Snapshots are immutable once taken, so have no updated_at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: NetworkSnapshotBase,
+    pub contents: SnapshotContents,
+}
+
+impl NetworkSnapshot {
+    pub fn new(base: NetworkSnapshotBase, contents: SnapshotContents) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            created_at: chrono::Utc::now(),
+            base,
+            contents,
+        }
+    }
+
+    pub fn summary(&self) -> NetworkSnapshotSummary {
+        NetworkSnapshotSummary {
+            id: self.id,
+            created_at: self.created_at,
+            base: self.base.clone(),
+            host_count: self.contents.hosts.len(),
+            service_count: self.contents.services.len(),
+            subnet_count: self.contents.subnets.len(),
+            group_count: self.contents.groups.len(),
+        }
+    }
+}
+
+/// Snapshot without its contents, for listing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetworkSnapshotSummary {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: NetworkSnapshotBase,
+    pub host_count: usize,
+    pub service_count: usize,
+    pub subnet_count: usize,
+    pub group_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HostPortChange {
+    pub host_id: Uuid,
+    pub port: Port,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostInterfaceChange {
+    pub host_id: Uuid,
+    pub interface: Interface,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServiceMove {
+    pub service_id: Uuid,
+    pub from_host_id: Uuid,
+    pub to_host_id: Uuid,
+}
+
+/// Differences between two snapshots of the same network. Entities are matched by id, ports by
+/// number and protocol, and interfaces by id or address. Ports and interfaces of added or removed
+/// hosts are only reported with their host.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from_snapshot_id: Uuid,
+    pub to_snapshot_id: Uuid,
+    pub added_hosts: Vec<Host>,
+    pub removed_hosts: Vec<Host>,
+    pub opened_ports: Vec<HostPortChange>,
+    pub closed_ports: Vec<HostPortChange>,
+    pub added_interfaces: Vec<HostInterfaceChange>,
+    pub removed_interfaces: Vec<HostInterfaceChange>,
+    pub added_services: Vec<CapturedService>,
+    pub removed_services: Vec<CapturedService>,
+    pub moved_services: Vec<ServiceMove>,
+    pub added_subnets: Vec<Subnet>,
+    pub removed_subnets: Vec<Subnet>,
+    pub added_groups: Vec<Group>,
+    pub removed_groups: Vec<Group>,
+}
+
+impl SnapshotDiff {
+    pub fn between(from: &NetworkSnapshot, to: &NetworkSnapshot) -> Self {
+        let (from_contents, to_contents) = (&from.contents, &to.contents);
+
+        let from_hosts: HashMap<Uuid, &Host> =
+            from_contents.hosts.iter().map(|h| (h.id, h)).collect();
+        let to_hosts: HashMap<Uuid, &Host> = to_contents.hosts.iter().map(|h| (h.id, h)).collect();
+
+        let mut diff = SnapshotDiff {
+            from_snapshot_id: from.id,
+            to_snapshot_id: to.id,
+            ..Default::default()
+        };
+
+        for host in &to_contents.hosts {
+            match from_hosts.get(&host.id) {
+                None => diff.added_hosts.push(host.clone()),
+                Some(previous) => {
+                    diff.opened_ports.extend(
+                        host.base
+                            .ports
+                            .iter()
+                            .filter(|p| !previous.base.ports.contains(p))
+                            .map(|port| HostPortChange {
+                                host_id: host.id,
+                                port: *port,
+                            }),
+                    );
+                    diff.closed_ports.extend(
+                        previous
+                            .base
+                            .ports
+                            .iter()
+                            .filter(|p| !host.base.ports.contains(p))
+                            .map(|port| HostPortChange {
+                                host_id: host.id,
+                                port: *port,
+                            }),
+                    );
+                    diff.added_interfaces.extend(
+                        host.base
+                            .interfaces
+                            .iter()
+                            .filter(|i| !previous.base.interfaces.contains(i))
+                            .map(|interface| HostInterfaceChange {
+                                host_id: host.id,
+                                interface: interface.clone(),
+                            }),
+                    );
+                    diff.removed_interfaces.extend(
+                        previous
+                            .base
+                            .interfaces
+                            .iter()
+                            .filter(|i| !host.base.interfaces.contains(i))
+                            .map(|interface| HostInterfaceChange {
+                                host_id: host.id,
+                                interface: interface.clone(),
+                            }),
+                    );
+                }
+            }
+        }
+        diff.removed_hosts = from_contents
+            .hosts
+            .iter()
+            .filter(|h| !to_hosts.contains_key(&h.id))
+            .cloned()
+            .collect();
+
+        let from_services: HashMap<Uuid, &CapturedService> =
+            from_contents.services.iter().map(|s| (s.id, s)).collect();
+        for service in &to_contents.services {
+            match from_services.get(&service.id) {
+                None => diff.added_services.push(service.clone()),
+                Some(previous) if previous.host_id != service.host_id => {
+                    diff.moved_services.push(ServiceMove {
+                        service_id: service.id,
+                        from_host_id: previous.host_id,
+                        to_host_id: service.host_id,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed_services = from_contents
+            .services
+            .iter()
+            .filter(|s| !to_contents.services.iter().any(|t| t.id == s.id))
+            .cloned()
+            .collect();
+
+        diff.added_subnets = to_contents
+            .subnets
+            .iter()
+            .filter(|s| !from_contents.subnets.iter().any(|f| f.id == s.id))
+            .cloned()
+            .collect();
+        diff.removed_subnets = from_contents
+            .subnets
+            .iter()
+            .filter(|s| !to_contents.subnets.iter().any(|t| t.id == s.id))
+            .cloned()
+            .collect();
+
+        diff.added_groups = to_contents
+            .groups
+            .iter()
+            .filter(|g| !from_contents.groups.iter().any(|f| f.id == g.id))
+            .cloned()
+            .collect();
+        diff.removed_groups = from_contents
+            .groups
+            .iter()
+            .filter(|g| !to_contents.groups.iter().any(|t| t.id == g.id))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
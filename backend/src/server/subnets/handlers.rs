@@ -1,7 +1,9 @@
 use crate::server::{
     config::AppState,
+    custom_fields::types::EntityFilter,
+    entity_changes::types::{EntityChange, EntityType},
     shared::types::api::{ApiError, ApiResponse, ApiResult},
-    subnets::types::base::Subnet,
+    subnets::types::{base::Subnet, tree::SubnetTreeNode},
 };
 use axum::{
     Router,
@@ -16,18 +18,36 @@ pub fn create_router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", post(create_subnet))
         .route("/", get(get_all_subnets))
+        .route("/tree", get(get_subnet_tree))
+        .route("/:id/tree", get(get_subnet_subtree))
         .route("/:id", put(update_subnet))
         .route("/:id", delete(delete_subnet))
+        .route("/:id/history", get(get_subnet_history))
 }
 
 async fn create_subnet(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<Subnet>,
+    Json(mut request): Json<Subnet>,
 ) -> ApiResult<Json<ApiResponse<Subnet>>> {
     tracing::info!("Received subnet creation request: {:?}", request);
 
+    state
+        .services
+        .custom_field_service
+        .validate_entity_fields(
+            &request.base.network_id,
+            EntityType::Subnet,
+            &mut request.base.tags,
+            &request.base.custom_fields,
+        )
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
     let service = &state.services.subnet_service;
-    let created_subnet = service.create_subnet(request).await?;
+    let created_subnet = service
+        .create_subnet(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
 
     Ok(Json(ApiResponse::success(created_subnet)))
 }
@@ -41,17 +61,50 @@ async fn get_all_subnets(
         .and_then(|id| Uuid::parse_str(id).ok())
         .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
 
+    let filter =
+        EntityFilter::from_params(&params).map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
     let service = &state.services.subnet_service;
 
-    let subnets = service.get_all_subnets(&network_id).await?;
+    let subnets = service.get_filtered_subnets(&network_id, &filter).await?;
 
     Ok(Json(ApiResponse::success(subnets)))
 }
 
+async fn get_subnet_tree(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<SubnetTreeNode>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.subnet_service;
+
+    let tree = service.get_subnet_tree(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(tree)))
+}
+
+async fn get_subnet_subtree(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<SubnetTreeNode>>> {
+    let service = &state.services.subnet_service;
+
+    let subtree = service
+        .get_subnet_subtree(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("Subnet '{}' not found", &id)))?;
+
+    Ok(Json(ApiResponse::success(subtree)))
+}
+
 async fn update_subnet(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-    Json(request): Json<Subnet>,
+    Json(mut request): Json<Subnet>,
 ) -> ApiResult<Json<ApiResponse<Subnet>>> {
     let service = &state.services.subnet_service;
 
@@ -60,9 +113,24 @@ async fn update_subnet(
         .await?
         .ok_or_else(|| ApiError::not_found(&format!("Subnet '{}' not found", &id)))?;
 
+    state
+        .services
+        .custom_field_service
+        .validate_entity_fields(
+            &subnet.base.network_id,
+            EntityType::Subnet,
+            &mut request.base.tags,
+            &request.base.custom_fields,
+        )
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
     subnet.base = request.base;
 
-    let updated_subnet = service.update_subnet(subnet).await?;
+    let updated_subnet = service
+        .update_subnet(subnet)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
 
     Ok(Json(ApiResponse::success(updated_subnet)))
 }
@@ -82,3 +150,16 @@ async fn delete_subnet(
 
     Ok(Json(ApiResponse::success(())))
 }
+
+async fn get_subnet_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<Vec<EntityChange>>>> {
+    let history = state
+        .services
+        .entity_change_service
+        .get_history(EntityType::Subnet, &id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(history)))
+}
@@ -1,26 +1,48 @@
 use crate::server::{
+    custom_fields::types::EntityFilter,
     discovery::types::base::{DiscoveryType, EntitySource},
+    entity_changes::{service::EntityChangeService, types::ChangeAction},
     hosts::service::HostService,
-    subnets::{storage::SubnetStorage, types::base::Subnet},
+    services::service::ServiceService,
+    subnets::{
+        storage::SubnetStorage,
+        types::{base::Subnet, tree::SubnetTreeNode},
+    },
+    vlans::service::VlanService,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use futures::future::try_join_all;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use uuid::Uuid;
 
 pub struct SubnetService {
     storage: Arc<dyn SubnetStorage>,
     host_service: Arc<HostService>,
+    service_service: Arc<ServiceService>,
+    vlan_service: OnceLock<Arc<VlanService>>,
+    entity_change_service: Arc<EntityChangeService>,
 }
 
 impl SubnetService {
-    pub fn new(storage: Arc<dyn SubnetStorage>, host_service: Arc<HostService>) -> Self {
+    pub fn new(
+        storage: Arc<dyn SubnetStorage>,
+        host_service: Arc<HostService>,
+        service_service: Arc<ServiceService>,
+        entity_change_service: Arc<EntityChangeService>,
+    ) -> Self {
         Self {
             storage,
             host_service,
+            service_service,
+            vlan_service: OnceLock::new(),
+            entity_change_service,
         }
     }
 
+    pub fn set_vlan_service(&self, vlan_service: Arc<VlanService>) -> Result<(), Arc<VlanService>> {
+        self.vlan_service.set(vlan_service)
+    }
+
     /// Create a new subnet
     pub async fn create_subnet(&self, subnet: Subnet) -> Result<Subnet> {
         let all_subnets = self.storage.get_all(&subnet.base.network_id).await?;
@@ -86,12 +108,18 @@ impl SubnetService {
             }
             // If there's no existing subnet, create a new one
             _ => {
-                self.storage.create(&subnet).await?;
+                Self::validate_no_overlapping_sibling(&subnet, &all_subnets)?;
+                self.validate_vlan(&subnet).await?;
+                let change =
+                    self.entity_change_service
+                        .change(ChangeAction::Create, None, Some(&subnet))?;
+                self.storage.create(&subnet, change.as_ref()).await?;
                 tracing::info!("Created subnet {}: {}", subnet.base.name, subnet.id);
                 subnet
             }
         };
-        Ok(subnet_from_storage)
+
+        self.link_tagged_vlan(subnet_from_storage).await
     }
 
     pub async fn get_subnet(&self, id: &Uuid) -> Result<Option<Subnet>> {
@@ -106,13 +134,91 @@ impl SubnetService {
         self.storage.get_all(network_id).await
     }
 
+    /// Subnets of a network with the filter's tags and custom field values
+    pub async fn get_filtered_subnets(
+        &self,
+        network_id: &Uuid,
+        filter: &EntityFilter,
+    ) -> Result<Vec<Subnet>> {
+        self.storage.get_filtered(network_id, filter).await
+    }
+
+    /// Subnets of a network nested by CIDR containment, with host and service counts rolled up
+    /// into the subnets containing them
+    pub async fn get_subnet_tree(&self, network_id: &Uuid) -> Result<Vec<SubnetTreeNode>> {
+        let subnets = self.storage.get_all(network_id).await?;
+        let hosts = self.host_service.get_all_hosts(network_id).await?;
+        let services = self.service_service.get_all_services(network_id).await?;
+
+        Ok(SubnetTreeNode::build_tree(&subnets, &hosts, &services))
+    }
+
+    /// A subnet and the subnets nested inside it
+    pub async fn get_subnet_subtree(&self, id: &Uuid) -> Result<Option<SubnetTreeNode>> {
+        let Some(subnet) = self.get_subnet(id).await? else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .get_subnet_tree(&subnet.base.network_id)
+            .await?
+            .into_iter()
+            .find_map(|root| root.find(id)))
+    }
+
     pub async fn update_subnet(&self, mut subnet: Subnet) -> Result<Subnet> {
+        let current_subnet = self.get_subnet(&subnet.id).await?;
+        let all_subnets = self.storage.get_all(&subnet.base.network_id).await?;
+        Self::validate_no_overlapping_sibling(&subnet, &all_subnets)?;
+        self.validate_vlan(&subnet).await?;
+
         subnet.updated_at = chrono::Utc::now();
-        self.storage.update(&subnet).await?;
+        let change = self.entity_change_service.change(
+            ChangeAction::Update,
+            current_subnet.as_ref(),
+            Some(&subnet),
+        )?;
+        self.storage.update(&subnet, change.as_ref()).await?;
         tracing::info!("Updated subnet {}: {}", subnet.base.name, subnet.id);
         Ok(subnet)
     }
 
+    /// Link a subnet which isn't on a VLAN yet to the VLAN its hosts' interfaces are tagged with
+    async fn link_tagged_vlan(&self, subnet: Subnet) -> Result<Subnet> {
+        let Some(vlan_service) = self.vlan_service.get() else {
+            return Ok(subnet);
+        };
+
+        match vlan_service.tagged_vlan_for_subnet(&subnet).await? {
+            Some(vlan_id) => {
+                let mut subnet = subnet;
+                subnet.base.vlan_id = Some(vlan_id);
+                self.update_subnet(subnet).await
+            }
+            None => Ok(subnet),
+        }
+    }
+
+    async fn validate_vlan(&self, subnet: &Subnet) -> Result<()> {
+        match self.vlan_service.get() {
+            Some(vlan_service) => vlan_service.validate_subnet_vlan(subnet).await,
+            None => Ok(()),
+        }
+    }
+
+    /// CIDRs nest into a hierarchy or are disjoint, so the only subnets which can't be placed
+    /// in the tree are ones covering the same range
+    fn validate_no_overlapping_sibling(subnet: &Subnet, all_subnets: &[Subnet]) -> Result<()> {
+        if let Some(existing) = all_subnets.iter().find(|s| subnet.overlaps_sibling(s)) {
+            return Err(anyhow!(
+                "Subnet {} overlaps existing subnet '{}'",
+                subnet.base.cidr,
+                existing.base.name
+            ));
+        }
+        Ok(())
+    }
+
     pub async fn delete_subnet(&self, id: &Uuid) -> Result<()> {
         let subnet = self
             .get_subnet(id)
@@ -140,7 +246,10 @@ impl SubnetService {
 
         try_join_all(update_futures).await?;
 
-        self.storage.delete(id).await?;
+        let change =
+            self.entity_change_service
+                .change(ChangeAction::Delete, Some(&subnet), None)?;
+        self.storage.delete(id, change.as_ref()).await?;
         tracing::info!("Deleted subnet {}: {}", subnet.base.name, subnet.id);
         Ok(())
     }
@@ -1,21 +1,26 @@
 use crate::server::{
+    custom_fields::types::EntityFilter,
     discovery::types::base::EntitySource,
+    entity_changes::{storage::append_change, types::EntityChange},
+    shared::storage::query::push_entity_filter,
     subnets::types::base::{Subnet, SubnetBase, SubnetType},
 };
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use cidr::IpCidr;
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, QueryBuilder, Row};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait SubnetStorage: Send + Sync {
-    async fn create(&self, subnet: &Subnet) -> Result<()>;
+    async fn create(&self, subnet: &Subnet, change: Option<&EntityChange>) -> Result<()>;
     async fn get_by_id(&self, id: &Uuid) -> Result<Option<Subnet>>;
     async fn get_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Subnet>>;
     async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Subnet>>;
-    async fn update(&self, subnet: &Subnet) -> Result<()>;
-    async fn delete(&self, id: &Uuid) -> Result<()>;
+    async fn get_filtered(&self, network_id: &Uuid, filter: &EntityFilter) -> Result<Vec<Subnet>>;
+    async fn update(&self, subnet: &Subnet, change: Option<&EntityChange>) -> Result<()>;
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()>;
 }
 
 pub struct PostgresSubnetStorage {
@@ -30,17 +35,19 @@ impl PostgresSubnetStorage {
 
 #[async_trait]
 impl SubnetStorage for PostgresSubnetStorage {
-    async fn create(&self, subnet: &Subnet) -> Result<()> {
+    async fn create(&self, subnet: &Subnet, change: Option<&EntityChange>) -> Result<()> {
         let cidr_str = serde_json::to_string(&subnet.base.cidr)?;
         let subnet_type_str = serde_json::to_string(&subnet.base.subnet_type)?;
         let subnet_source_str = serde_json::to_value(&subnet.base.source)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO subnets (
                 id, name, description, cidr, 
-                subnet_type, source, created_at, updated_at, network_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                subnet_type, source, created_at, updated_at, network_id, vlan_id,
+                tags, custom_fields
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             "#,
         )
         .bind(subnet.id)
@@ -52,9 +59,15 @@ impl SubnetStorage for PostgresSubnetStorage {
         .bind(subnet.created_at)
         .bind(subnet.updated_at)
         .bind(subnet.base.network_id)
-        .execute(&self.pool)
+        .bind(subnet.base.vlan_id)
+        .bind(serde_json::to_value(&subnet.base.tags)?)
+        .bind(serde_json::to_value(&subnet.base.custom_fields)?)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -110,16 +123,31 @@ impl SubnetStorage for PostgresSubnetStorage {
         Ok(subnets)
     }
 
-    async fn update(&self, subnet: &Subnet) -> Result<()> {
+    async fn get_filtered(&self, network_id: &Uuid, filter: &EntityFilter) -> Result<Vec<Subnet>> {
+        let mut builder = QueryBuilder::new("SELECT * FROM subnets WHERE network_id = ");
+        builder.push_bind(network_id);
+        push_entity_filter(&mut builder, filter);
+        builder.push(" ORDER BY created_at DESC");
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+
+        rows.into_iter()
+            .map(row_to_subnet)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    async fn update(&self, subnet: &Subnet, change: Option<&EntityChange>) -> Result<()> {
         let cidr_str = serde_json::to_string(&subnet.base.cidr)?;
         let subnet_type_str = serde_json::to_string(&subnet.base.subnet_type)?;
         let subnet_source_str = serde_json::to_value(&subnet.base.source)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             UPDATE subnets SET 
                 name = $2, description = $3, cidr = $4,
-                subnet_type = $5, source = $6, updated_at = $7
+                subnet_type = $5, source = $6, updated_at = $7, vlan_id = $8,
+                tags = $9, custom_fields = $10
             WHERE id = $1
             "#,
         )
@@ -130,18 +158,28 @@ impl SubnetStorage for PostgresSubnetStorage {
         .bind(subnet_type_str)
         .bind(subnet_source_str)
         .bind(subnet.updated_at)
-        .execute(&self.pool)
+        .bind(subnet.base.vlan_id)
+        .bind(serde_json::to_value(&subnet.base.tags)?)
+        .bind(serde_json::to_value(&subnet.base.custom_fields)?)
+        .execute(&mut *tx)
         .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
+    async fn delete(&self, id: &Uuid, change: Option<&EntityChange>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query("DELETE FROM subnets WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
+        append_change(&mut tx, change).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 }
@@ -154,6 +192,11 @@ fn row_to_subnet(row: sqlx::postgres::PgRow) -> Result<Subnet, Error> {
         .or(Err(Error::msg("Failed to deserialize subnet_type")))?;
     let source: EntitySource = serde_json::from_value(row.get::<serde_json::Value, _>("source"))
         .or(Err(Error::msg("Failed to deserialize source")))?;
+    let tags: Vec<String> = serde_json::from_value(row.get::<serde_json::Value, _>("tags"))
+        .or(Err(Error::msg("Failed to deserialize tags")))?;
+    let custom_fields: BTreeMap<Uuid, String> =
+        serde_json::from_value(row.get::<serde_json::Value, _>("custom_fields"))
+            .or(Err(Error::msg("Failed to deserialize custom_fields")))?;
 
     Ok(Subnet {
         id: row.get("id"),
@@ -166,6 +209,9 @@ fn row_to_subnet(row: sqlx::postgres::PgRow) -> Result<Subnet, Error> {
             source,
             cidr,
             subnet_type,
+            vlan_id: row.get("vlan_id"),
+            tags,
+            custom_fields,
         },
     })
 }
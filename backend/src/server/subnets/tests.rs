@@ -0,0 +1,142 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    server::{
+        discovery::types::base::{DiscoveryMetadata, DiscoveryType, EntitySource},
+        services::types::bindings::Binding,
+        subnets::types::base::SubnetType,
+    },
+    tests::*,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_subnet_tree() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let create_subnet = |cidr: &str| {
+        let mut subnet_obj = subnet(&network.id);
+        subnet_obj.base.name = cidr.to_string();
+        subnet_obj.base.cidr = cidr.parse().unwrap();
+        subnet_obj.base.source = EntitySource::Manual;
+        services.subnet_service.create_subnet(subnet_obj)
+    };
+    let site = create_subnet("192.168.0.0/16").await.unwrap();
+    let lan = create_subnet("192.168.1.0/24").await.unwrap();
+    let servers = create_subnet("192.168.1.0/25").await.unwrap();
+    let wifi = create_subnet("192.168.2.0/24").await.unwrap();
+
+    let mut server = host(&network.id);
+    server.base.interfaces = vec![interface(&servers.id)];
+    let mut svc = service(&network.id, &server.id);
+    svc.base.bindings = vec![Binding::new_port(
+        server.base.ports[0].id,
+        Some(server.base.interfaces[0].id),
+    )];
+    services
+        .host_service
+        .create_host_with_services(server, vec![svc])
+        .await
+        .unwrap();
+
+    let mut laptop = host(&network.id);
+    laptop.base.interfaces = vec![interface(&wifi.id)];
+    laptop.base.interfaces[0].base.ip_address = "192.168.2.10".parse().unwrap();
+    laptop.base.interfaces[0].base.mac_address = None;
+    services
+        .host_service
+        .create_host_with_services(laptop, vec![])
+        .await
+        .unwrap();
+
+    let site_tree = services
+        .subnet_service
+        .get_subnet_subtree(&site.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(site_tree.parent_id, None);
+    assert_eq!(site_tree.host_count, 0);
+    assert_eq!(site_tree.total_host_count, 2);
+    assert_eq!(site_tree.total_service_count, 1);
+    assert_eq!(
+        site_tree
+            .children
+            .iter()
+            .map(|c| c.subnet.id)
+            .collect::<Vec<_>>(),
+        vec![lan.id, wifi.id]
+    );
+
+    let lan_tree = &site_tree.children[0];
+    assert_eq!(lan_tree.children[0].subnet.id, servers.id);
+    assert_eq!(lan_tree.children[0].parent_id, Some(lan.id));
+    assert_eq!(lan_tree.children[0].service_count, 1);
+    assert_eq!(lan_tree.total_host_count, 1);
+
+    // Organizational subnets stay at the top level
+    let roots = services
+        .subnet_service
+        .get_subnet_tree(&network.id)
+        .await
+        .unwrap();
+    assert!(roots.iter().any(|r| r.subnet.id == site.id));
+    assert!(roots.iter().all(|r| r.subnet.id == site.id
+        || (r.subnet.is_organizational_subnet() && r.children.is_empty())));
+
+    // Siblings can't cover the same range
+    let mut moved_wifi = wifi.clone();
+    moved_wifi.base.cidr = lan.base.cidr;
+    assert!(
+        services
+            .subnet_service
+            .update_subnet(moved_wifi)
+            .await
+            .is_err()
+    );
+    // Creating the same range again returns the existing subnet
+    assert_eq!(create_subnet("192.168.2.0/24").await.unwrap().id, wifi.id);
+
+    // Except for Docker bridges on different hosts, as Docker reuses ranges across hosts
+    let docker_bridge = |host_id: Uuid| {
+        let mut bridge = subnet(&network.id);
+        bridge.base.cidr = "172.17.0.0/16".parse().unwrap();
+        bridge.base.subnet_type = SubnetType::DockerBridge;
+        bridge.base.source = EntitySource::Discovery {
+            metadata: vec![DiscoveryMetadata::new(
+                DiscoveryType::Docker { host_id },
+                Uuid::new_v4(),
+            )],
+        };
+        bridge
+    };
+    let bridge = services
+        .subnet_service
+        .create_subnet(docker_bridge(Uuid::new_v4()))
+        .await
+        .unwrap();
+    let other_bridge = services
+        .subnet_service
+        .create_subnet(docker_bridge(Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_ne!(bridge.id, other_bridge.id);
+
+    let mut same_host_bridge = other_bridge.clone();
+    same_host_bridge.base.source = bridge.base.source.clone();
+    assert!(
+        services
+            .subnet_service
+            .update_subnet(same_host_bridge)
+            .await
+            .is_err()
+    );
+}
@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 use cidr::{IpCidr, Ipv4Cidr};
 use pnet::ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::hash::Hash;
 use strum_macros::{Display, EnumDiscriminants, EnumIter, IntoStaticStr};
 use uuid::Uuid;
@@ -31,6 +32,15 @@ pub struct SubnetBase {
     pub description: Option<String>,
     pub subnet_type: SubnetType,
     pub source: EntitySource,
+    /// VLAN carrying the subnet. Several subnets can share one.
+    #[serde(default)]
+    pub vlan_id: Option<Uuid>,
+    /// Free-form labels, ie "critical" or "rack-2"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Values of the network's custom fields, keyed by field definition id
+    #[serde(default)]
+    pub custom_fields: BTreeMap<Uuid, String>,
 }
 
 impl Default for SubnetBase {
@@ -42,6 +52,9 @@ impl Default for SubnetBase {
             description: None,
             subnet_type: SubnetType::Unknown,
             source: EntitySource::Manual,
+            vlan_id: None,
+            tags: Vec::new(),
+            custom_fields: BTreeMap::new(),
         }
     }
 }
@@ -83,13 +96,9 @@ impl Subnet {
             IpNetwork::V6(_) => None,
             IpNetwork::V4(ipv4_network) => {
                 let (network_addr, prefix_len) = match (&subnet_type, ipv4_network.prefix()) {
-                    // VPN tunnels with /32 -> expand to /24
-                    (SubnetType::VpnTunnel, 32) => {
-                        let ip_octets = ipv4_network.ip().octets();
-                        let network_addr =
-                            std::net::Ipv4Addr::new(ip_octets[0], ip_octets[1], ip_octets[2], 0);
-                        (network_addr, 24)
-                    }
+                    // VPN tunnels are often point-to-point, so keep their single address. Scans
+                    // still cover the peers around it, see scan_range
+                    (SubnetType::VpnTunnel, 32) => (ipv4_network.network(), 32),
                     // Skip other /32 single IPs
                     (_, 32) => return None,
                     // Normal case - use the network's actual network address and prefix
@@ -104,14 +113,34 @@ impl Subnet {
                     description: None,
                     name: cidr.to_string(),
                     subnet_type,
+                    vlan_id: None,
                     source: EntitySource::Discovery {
                         metadata: vec![DiscoveryMetadata::new(*discovery_type, daemon_id)],
                     },
+                    tags: Vec::new(),
+                    custom_fields: BTreeMap::new(),
                 }))
             }
         }
     }
 
+    /// Addresses a network scan of the subnet probes. A VPN tunnel's /32 only holds the daemon's
+    /// own address, so its peers are looked for in the surrounding /24.
+    pub fn scan_range(&self) -> IpCidr {
+        match self.base.cidr {
+            IpCidr::V4(cidr)
+                if self.base.subnet_type == SubnetType::VpnTunnel
+                    && cidr.network_length() == 32 =>
+            {
+                let [a, b, c, _] = cidr.first_address().octets();
+                Ipv4Cidr::new(Ipv4Addr::new(a, b, c, 0), 24)
+                    .map(IpCidr::V4)
+                    .unwrap_or(self.base.cidr)
+            }
+            cidr => cidr,
+        }
+    }
+
     pub fn has_interface_with_service(&self, host: &Host, service: &Service) -> bool {
         service.base.bindings.iter().any(|binding| {
             host.base.interfaces.iter().any(|interface| {
@@ -129,6 +158,55 @@ impl Subnet {
         let organizational_cidr = IpCidr::V4(Ipv4Cidr::new(Ipv4Addr::new(0, 0, 0, 0), 0).unwrap());
         self.base.cidr == organizational_cidr
     }
+
+    /// Whether the other subnet's CIDR is nested inside this one's. Organizational subnets
+    /// aren't part of the hierarchy, so they neither contain nor are contained by anything.
+    pub fn contains_subnet(&self, other: &Subnet) -> bool {
+        !self.is_organizational_subnet()
+            && !other.is_organizational_subnet()
+            && self.base.cidr.is_ipv4() == other.base.cidr.is_ipv4()
+            && self.base.cidr.network_length() < other.base.cidr.network_length()
+            && self.base.cidr.contains(&other.base.cidr.first_address())
+    }
+
+    /// Whether the subnets cover the same addresses in a network, so neither can be placed
+    /// under the other. Docker bridges on different hosts are exempt as Docker reuses the same
+    /// ranges on every host, as are system subnets, which are never merged with other subnets.
+    pub fn overlaps_sibling(&self, other: &Subnet) -> bool {
+        self.id != other.id
+            && self.base.network_id == other.base.network_id
+            && self.base.cidr == other.base.cidr
+            && !self.is_organizational_subnet()
+            && !matches!(self.base.source, EntitySource::System)
+            && !matches!(other.base.source, EntitySource::System)
+            && !self.is_docker_bridge_on_other_host(other)
+    }
+
+    /// Hosts whose Docker discovery found the subnet
+    fn docker_host_ids(&self) -> Vec<Uuid> {
+        match &self.base.source {
+            EntitySource::Discovery { metadata } => metadata
+                .iter()
+                .filter_map(|m| match m.discovery_type {
+                    DiscoveryType::Docker { host_id } => Some(host_id),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether both subnets are Docker bridges, found on different hosts
+    fn is_docker_bridge_on_other_host(&self, other: &Subnet) -> bool {
+        let host_ids = self.docker_host_ids();
+        let other_host_ids = other.docker_host_ids();
+
+        self.is_docker_bridge_subnet()
+            && other.is_docker_bridge_subnet()
+            && !host_ids.is_empty()
+            && !other_host_ids.is_empty()
+            && !host_ids.iter().any(|id| other_host_ids.contains(id))
+    }
 }
 
 impl PartialEq for Subnet {
@@ -175,6 +253,7 @@ pub enum SubnetType {
     Guest,
 
     DockerBridge,
+    Kubernetes,
     Management,
     Storage,
 
@@ -284,6 +363,7 @@ impl EntityMetadataProvider for SubnetType {
 
             SubnetType::Management => "gray",
             SubnetType::DockerBridge => Entity::Virtualization.color(),
+            SubnetType::Kubernetes => Entity::Virtualization.color(),
             SubnetType::Storage => Entity::Storage.color(),
 
             SubnetType::Unknown => "gray",
@@ -306,6 +386,7 @@ impl EntityMetadataProvider for SubnetType {
 
             SubnetType::Management => "ServerCog",
             SubnetType::DockerBridge => "Box",
+            SubnetType::Kubernetes => "Boxes",
             SubnetType::Storage => Entity::Storage.icon(),
 
             SubnetType::Unknown => Entity::Subnet.icon(),
@@ -331,6 +412,7 @@ impl TypeMetadataProvider for SubnetType {
 
             SubnetType::Management => "Management",
             SubnetType::DockerBridge => "Docker Bridge",
+            SubnetType::Kubernetes => "Kubernetes",
             SubnetType::Storage => "Storage",
 
             SubnetType::Unknown => "Unknown",
@@ -354,6 +436,7 @@ impl TypeMetadataProvider for SubnetType {
 
             SubnetType::Management => "Management network",
             SubnetType::DockerBridge => "Docker bridge network",
+            SubnetType::Kubernetes => "Kubernetes pod or service network",
             SubnetType::Storage => "Storage network",
 
             SubnetType::Unknown => "Unknown network type",
@@ -0,0 +1,129 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::server::{
+    hosts::types::base::Host, ipam::types::to_u128, services::types::base::Service,
+    subnets::types::base::Subnet,
+};
+
+/// A subnet with the subnets nested inside its CIDR, and the hosts and services on them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubnetTreeNode {
+    pub subnet: Subnet,
+    pub parent_id: Option<Uuid>,
+    /// Hosts and services with an interface on the subnet itself
+    pub host_count: usize,
+    pub service_count: usize,
+    /// Hosts and services on the subnet or any nested in it, counting each once
+    pub total_host_count: usize,
+    pub total_service_count: usize,
+    pub children: Vec<SubnetTreeNode>,
+}
+
+impl SubnetTreeNode {
+    /// Nest subnets under the smallest subnet containing their CIDR, returning the top level
+    pub fn build_tree(subnets: &[Subnet], hosts: &[Host], services: &[Service]) -> Vec<Self> {
+        let parents: HashMap<Uuid, Uuid> = subnets
+            .iter()
+            .filter_map(|subnet| {
+                subnets
+                    .iter()
+                    .filter(|candidate| candidate.contains_subnet(subnet))
+                    .max_by_key(|candidate| candidate.base.cidr.network_length())
+                    .map(|parent| (subnet.id, parent.id))
+            })
+            .collect();
+
+        let hosts_by_id: HashMap<Uuid, &Host> = hosts.iter().map(|h| (h.id, h)).collect();
+        let members: HashMap<Uuid, (HashSet<Uuid>, HashSet<Uuid>)> = subnets
+            .iter()
+            .map(|subnet| {
+                let host_ids = hosts
+                    .iter()
+                    .filter(|h| {
+                        h.base
+                            .interfaces
+                            .iter()
+                            .any(|i| i.base.subnet_id == subnet.id)
+                    })
+                    .map(|h| h.id)
+                    .collect();
+                let service_ids = services
+                    .iter()
+                    .filter(|s| {
+                        hosts_by_id
+                            .get(&s.base.host_id)
+                            .is_some_and(|h| subnet.has_interface_with_service(h, s))
+                    })
+                    .map(|s| s.id)
+                    .collect();
+                (subnet.id, (host_ids, service_ids))
+            })
+            .collect();
+
+        subnets
+            .iter()
+            .filter(|s| !parents.contains_key(&s.id))
+            .sorted_by_key(|s| tree_order(s))
+            .map(|root| Self::build_node(root, subnets, &parents, &members).0)
+            .collect()
+    }
+
+    /// Find a subnet anywhere below this node, including the node itself
+    pub fn find(self, subnet_id: &Uuid) -> Option<Self> {
+        if &self.subnet.id == subnet_id {
+            return Some(self);
+        }
+        self.children
+            .into_iter()
+            .find_map(|child| child.find(subnet_id))
+    }
+
+    fn build_node(
+        subnet: &Subnet,
+        subnets: &[Subnet],
+        parents: &HashMap<Uuid, Uuid>,
+        members: &HashMap<Uuid, (HashSet<Uuid>, HashSet<Uuid>)>,
+    ) -> (Self, HashSet<Uuid>, HashSet<Uuid>) {
+        let (host_ids, service_ids) = members.get(&subnet.id).cloned().unwrap_or_default();
+        let (host_count, service_count) = (host_ids.len(), service_ids.len());
+        let (mut all_host_ids, mut all_service_ids) = (host_ids, service_ids);
+
+        let mut children = Vec::new();
+        for child in subnets
+            .iter()
+            .filter(|s| parents.get(&s.id) == Some(&subnet.id))
+            .sorted_by_key(|s| tree_order(s))
+        {
+            let (node, child_host_ids, child_service_ids) =
+                Self::build_node(child, subnets, parents, members);
+            all_host_ids.extend(child_host_ids);
+            all_service_ids.extend(child_service_ids);
+            children.push(node);
+        }
+
+        let node = Self {
+            subnet: subnet.clone(),
+            parent_id: parents.get(&subnet.id).copied(),
+            host_count,
+            service_count,
+            total_host_count: all_host_ids.len(),
+            total_service_count: all_service_ids.len(),
+            children,
+        };
+        (node, all_host_ids, all_service_ids)
+    }
+}
+
+/// Organizational subnets first, then by address
+fn tree_order(subnet: &Subnet) -> (bool, bool, u128, u8, String) {
+    (
+        !subnet.is_organizational_subnet(),
+        !subnet.base.cidr.is_ipv4(),
+        to_u128(&subnet.base.cidr.first_address()),
+        subnet.base.cidr.network_length(),
+        subnet.base.name.clone(),
+    )
+}
@@ -4,7 +4,7 @@ use uuid::Uuid;
 
 use crate::server::{
     groups::types::Group,
-    hosts::types::{base::Host, interfaces::Interface, virtualization::HostVirtualization},
+    hosts::types::{base::Host, interfaces::Interface},
     services::types::{
         base::Service, definitions::ServiceDefinitionExt, virtualization::ServiceVirtualization,
     },
@@ -18,6 +18,7 @@ use crate::server::{
             nodes::{Node, NodeType},
         },
     },
+    vlans::types::Vlan,
 };
 
 /// Composite quality score for graph layout
@@ -64,6 +65,7 @@ pub struct TopologyContext<'a> {
     pub subnets: &'a [Subnet],
     pub services: &'a [Service],
     pub groups: &'a [Group],
+    pub vlans: &'a [Vlan],
     pub options: &'a TopologyRequestOptions,
     utils: OptimizerUtils,
 }
@@ -74,6 +76,7 @@ impl<'a> TopologyContext<'a> {
         subnets: &'a [Subnet],
         services: &'a [Service],
         groups: &'a [Group],
+        vlans: &'a [Vlan],
         options: &'a TopologyRequestOptions,
     ) -> Self {
         Self {
@@ -81,6 +84,7 @@ impl<'a> TopologyContext<'a> {
             subnets,
             services,
             groups,
+            vlans,
             options,
             utils: OptimizerUtils::new(),
         }
@@ -90,6 +94,28 @@ impl<'a> TopologyContext<'a> {
         self.subnets.iter().find(|s| s.id == subnet_id)
     }
 
+    /// VLAN a subnet is linked to, if it still exists
+    pub fn get_subnet_vlan(&self, subnet: &Subnet) -> Option<&'a Vlan> {
+        subnet
+            .base
+            .vlan_id
+            .and_then(|vlan_id| self.vlans.iter().find(|v| v.id == vlan_id))
+    }
+
+    /// Colour of the first tag colour option matching the tags of a node's host or subnet
+    pub fn node_color(&self, node: &Node) -> Option<String> {
+        let tags = match node.node_type {
+            NodeType::SubnetNode { .. } => &self.get_subnet_by_id(node.id)?.base.tags,
+            NodeType::InterfaceNode { host_id, .. } => &self.get_host_by_id(host_id)?.base.tags,
+        };
+
+        self.options
+            .tag_colors
+            .iter()
+            .find(|tc| tags.contains(&tc.tag))
+            .map(|tc| tc.color.clone())
+    }
+
     pub fn get_host_by_id(&self, host_id: Uuid) -> Option<&'a Host> {
         self.hosts.iter().find(|h| h.id == host_id)
     }
@@ -182,20 +208,19 @@ impl<'a> TopologyContext<'a> {
 
     pub fn get_host_is_virtualized_by(&self, host_id: &Uuid) -> Option<&Service> {
         if let Some(host) = self.get_host_by_id(*host_id)
-            && let Some(HostVirtualization::Proxmox(proxmox_virtualization)) =
-                &host.base.virtualization
+            && let Some(virtualization) = &host.base.virtualization
         {
             return self
                 .services
                 .iter()
-                .find(|s| s.id == proxmox_virtualization.service_id);
+                .find(|s| s.id == virtualization.service_id());
         }
         None
     }
 
     pub fn get_service_is_containerized_by(&self, service_id: &Uuid) -> Option<&Service> {
         if let Some(service) = self.get_service_by_id(*service_id)
-            && let Some(ServiceVirtualization::Docker(docker_virtualization)) =
+            && let Some(ServiceVirtualization::Container(docker_virtualization)) =
                 &service.base.virtualization
         {
             return self
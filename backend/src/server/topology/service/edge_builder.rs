@@ -6,7 +6,6 @@ use uuid::Uuid;
 
 use crate::server::{
     groups::types::GroupType,
-    hosts::types::virtualization::HostVirtualization,
     services::types::virtualization::ServiceVirtualization,
     subnets::types::base::{SubnetType, SubnetTypeDiscriminants},
     topology::{
@@ -107,7 +106,7 @@ impl EdgeBuilder {
             HashMap::new();
 
         ctx.services.iter().for_each(|s| {
-            if let Some(ServiceVirtualization::Docker(docker_virtualization)) =
+            if let Some(ServiceVirtualization::Container(docker_virtualization)) =
                 &s.base.virtualization
             {
                 let entry = docker_service_to_containerized_service_ids
@@ -245,74 +244,73 @@ impl EdgeBuilder {
         (edges, docker_bridge_host_subnet_id_to_group_on)
     }
 
-    // Create edges to connect a host that virtualizes other hosts as VMs
+    // Create edges to connect a host that virtualizes other hosts as VMs (Proxmox, libvirt)
     pub fn create_vm_host_edges(ctx: &TopologyContext) -> Vec<Edge> {
-        // Proxmox service interface binding that is present for a given subnet.
+        // Hypervisor service interface binding that is present for a given subnet.
         // There could be multiple host interfaces with a given subnet, we arbitrarily choose the first one so there's
         // one clustering hub rather than multiple hubs
-        // (subnet_id, proxmox_service_id) : (interface_id)
-        let mut subnet_to_promxox_host_interface_id: HashMap<(Uuid, Uuid), Uuid> = HashMap::new();
+        // (subnet_id, hypervisor_service_id) : (interface_id)
+        let mut subnet_to_hypervisor_host_interface_id: HashMap<(Uuid, Uuid), Uuid> =
+            HashMap::new();
 
-        // Hosts VMs managed by a given proxmox service
-        let mut vm_host_id_to_proxmox_service: HashMap<Uuid, Uuid> = HashMap::new();
+        // Hosts VMs managed by a given hypervisor service
+        let mut vm_host_id_to_hypervisor_service: HashMap<Uuid, Uuid> = HashMap::new();
 
         ctx.hosts.iter().for_each(|h| {
-            if let Some(HostVirtualization::Proxmox(proxmox_virtualization)) =
-                &h.base.virtualization
-            {
-                // Create mapping between subnet and proxmox interface(s) on that subnet
-                if let Some(promxox_service) =
-                    ctx.get_service_by_id(proxmox_virtualization.service_id)
-                {
-                    promxox_service
+            if let Some(virtualization) = &h.base.virtualization {
+                let hypervisor_service_id = virtualization.service_id();
+
+                // Create mapping between subnet and hypervisor interface(s) on that subnet
+                if let Some(hypervisor_service) = ctx.get_service_by_id(hypervisor_service_id) {
+                    hypervisor_service
                         .base
                         .bindings
                         .iter()
                         .filter_map(|b| b.interface_id())
                         .for_each(|i| {
                             if let Some(subnet) = ctx.get_subnet_from_interface_id(i)
-                                && !subnet_to_promxox_host_interface_id
-                                    .contains_key(&(subnet.id, promxox_service.id))
+                                && !subnet_to_hypervisor_host_interface_id
+                                    .contains_key(&(subnet.id, hypervisor_service.id))
                             {
-                                subnet_to_promxox_host_interface_id
-                                    .entry((subnet.id, promxox_service.id))
+                                subnet_to_hypervisor_host_interface_id
+                                    .entry((subnet.id, hypervisor_service.id))
                                     .insert_entry(i);
                             }
                         });
                 }
 
-                vm_host_id_to_proxmox_service.insert(h.id, proxmox_virtualization.service_id);
+                vm_host_id_to_hypervisor_service.insert(h.id, hypervisor_service_id);
             }
         });
 
-        // Creates edges between interface that proxmox service has on a given subnet with interfaces that the virtualized host has on the subnet
+        // Creates edges between interface that hypervisor service has on a given subnet with interfaces that the virtualized host has on the subnet
         ctx.hosts
             .iter()
             .flat_map(|h| {
-                if let Some(proxmox_service_id) = vm_host_id_to_proxmox_service.get(&h.id) {
+                if let Some(hypervisor_service_id) = vm_host_id_to_hypervisor_service.get(&h.id) {
                     return h
                         .base
                         .interfaces
                         .iter()
                         .filter_map(|i| {
-                            if let Some(proxmox_service_interface_id) =
-                                subnet_to_promxox_host_interface_id
-                                    .get(&(i.base.subnet_id, *proxmox_service_id))
-                                && ctx.interface_will_have_node(proxmox_service_interface_id)
+                            if let Some(hypervisor_service_interface_id) =
+                                subnet_to_hypervisor_host_interface_id
+                                    .get(&(i.base.subnet_id, *hypervisor_service_id))
+                                && ctx.interface_will_have_node(hypervisor_service_interface_id)
                             {
                                 let is_multi_hop =
-                                    ctx.edge_is_multi_hop(proxmox_service_interface_id, &i.id);
+                                    ctx.edge_is_multi_hop(hypervisor_service_interface_id, &i.id);
 
                                 let (source_handle, target_handle) =
                                     EdgeBuilder::determine_interface_handles(
                                         ctx,
-                                        proxmox_service_interface_id,
+                                        hypervisor_service_interface_id,
                                         &i.id,
                                         is_multi_hop,
                                     )?;
 
                                 return Some(Edge {
-                                    source: *proxmox_service_interface_id,
+                                    source: *hypervisor_service_interface_id,
                                     target: i.id,
                                     edge_type: EdgeType::HostVirtualization,
                                     label: None,
@@ -6,9 +6,10 @@ use uuid::Uuid;
 
 use crate::server::{
     groups::service::GroupService,
-    hosts::service::HostService,
+    hosts::{service::HostService, types::base::Host},
     services::{service::ServiceService, types::base::Service},
-    subnets::service::SubnetService,
+    snapshots::{service::SnapshotService, types::NetworkContents},
+    subnets::{service::SubnetService, types::base::Subnet},
     topology::{
         service::{
             context::TopologyContext, edge_builder::EdgeBuilder,
@@ -17,6 +18,7 @@ use crate::server::{
         },
         types::{api::TopologyRequestOptions, edges::Edge, nodes::Node},
     },
+    vlans::service::VlanService,
 };
 
 pub struct TopologyService {
@@ -24,6 +26,8 @@ pub struct TopologyService {
     subnet_service: Arc<SubnetService>,
     group_service: Arc<GroupService>,
     service_service: Arc<ServiceService>,
+    snapshot_service: Arc<SnapshotService>,
+    vlan_service: Arc<VlanService>,
 }
 
 impl TopologyService {
@@ -32,12 +36,16 @@ impl TopologyService {
         subnet_service: Arc<SubnetService>,
         group_service: Arc<GroupService>,
         service_service: Arc<ServiceService>,
+        snapshot_service: Arc<SnapshotService>,
+        vlan_service: Arc<VlanService>,
     ) -> Self {
         Self {
             host_service,
             subnet_service,
             group_service,
             service_service,
+            snapshot_service,
+            vlan_service,
         }
     }
 
@@ -49,14 +57,17 @@ impl TopologyService {
             .network_ids
             .first()
             .ok_or_else(|| anyhow::anyhow!("No network ID in request"))?;
-        // Fetch all data
-        let hosts = self.host_service.get_all_hosts(network_id).await?;
-        let subnets = self.subnet_service.get_all_subnets(network_id).await?;
-        let groups = self.group_service.get_all_groups(network_id).await?;
-        let services: Vec<Service> = self
-            .service_service
-            .get_all_services(network_id)
-            .await?
+        let NetworkContents {
+            hosts,
+            services,
+            subnets,
+            groups,
+        } = Self::hide_tagged(
+            self.get_contents(network_id, &options).await?,
+            &options.hide_tags,
+        );
+
+        let services: Vec<Service> = services
             .into_iter()
             .filter(|s| {
                 !options
@@ -65,8 +76,11 @@ impl TopologyService {
             })
             .collect();
 
+        // VLANs aren't part of snapshots, so the current ones are used for both
+        let vlans = self.vlan_service.get_all_vlans(network_id).await?;
+
         // Create context to avoid parameter passing
-        let ctx = TopologyContext::new(&hosts, &subnets, &services, &groups, &options);
+        let ctx = TopologyContext::new(&hosts, &subnets, &services, &groups, &vlans, &options);
 
         // Create all edges (needed for anchor analysis)
         let mut all_edges = Vec::new();
@@ -100,6 +114,10 @@ impl TopologyService {
 
         let optimized_edges = optimizer.optimize_graph(&mut all_nodes, &all_edges);
 
+        for node in all_nodes.iter_mut() {
+            node.color = ctx.node_color(node);
+        }
+
         // Build graph
         let mut graph: Graph<Node, Edge> = Graph::new();
         let node_indices: HashMap<Uuid, NodeIndex> = all_nodes
@@ -116,4 +134,73 @@ impl TopologyService {
 
         Ok(graph)
     }
+
+    /// Drop hosts, services and subnets with a hidden tag, along with services on hidden hosts
+    /// and interfaces on hidden subnets
+    fn hide_tagged(contents: NetworkContents, hide_tags: &[String]) -> NetworkContents {
+        if hide_tags.is_empty() {
+            return contents;
+        }
+        let hidden = |tags: &[String]| tags.iter().any(|t| hide_tags.contains(t));
+
+        let subnets: Vec<Subnet> = contents
+            .subnets
+            .into_iter()
+            .filter(|s| !hidden(&s.base.tags))
+            .collect();
+        let hosts: Vec<Host> = contents
+            .hosts
+            .into_iter()
+            .filter(|h| !hidden(&h.base.tags))
+            .map(|mut h| {
+                h.base
+                    .interfaces
+                    .retain(|i| subnets.iter().any(|s| s.id == i.base.subnet_id));
+                h
+            })
+            .collect();
+        let services = contents
+            .services
+            .into_iter()
+            .filter(|s| !hidden(&s.base.tags) && hosts.iter().any(|h| h.id == s.base.host_id))
+            .collect();
+
+        NetworkContents {
+            hosts,
+            services,
+            subnets,
+            groups: contents.groups,
+        }
+    }
+
+    /// Entities to render, either from the requested snapshot or the network's current state
+    async fn get_contents(
+        &self,
+        network_id: &Uuid,
+        options: &TopologyRequestOptions,
+    ) -> Result<NetworkContents, Error> {
+        if let Some(snapshot_id) = options.snapshot_id {
+            let snapshot = self
+                .snapshot_service
+                .get_snapshot(&snapshot_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Snapshot {} not found", snapshot_id))?;
+
+            if &snapshot.base.network_id != network_id {
+                return Err(anyhow::anyhow!(
+                    "Snapshot {} is not of network {}",
+                    snapshot_id,
+                    network_id
+                ));
+            }
+            return Ok(snapshot.contents.resolve());
+        }
+
+        Ok(NetworkContents {
+            hosts: self.host_service.get_all_hosts(network_id).await?,
+            services: self.service_service.get_all_services(network_id).await?,
+            subnets: self.subnet_service.get_all_subnets(network_id).await?,
+            groups: self.group_service.get_all_groups(network_id).await?,
+        })
+    }
 }
@@ -5,7 +5,7 @@ use uuid::Uuid;
 use crate::server::{
     hosts::types::{base::Host, interfaces::Interface},
     services::types::base::Service,
-    subnets::types::base::SubnetType,
+    subnets::types::base::{Subnet, SubnetType},
     topology::{
         service::{
             context::TopologyContext,
@@ -389,6 +389,7 @@ impl SubnetLayoutPlanner {
                     position: layout.position,
                     size: child.size,
                     header: child.header.clone(),
+                    color: None,
                 });
             }
         }
@@ -417,6 +418,7 @@ impl SubnetLayoutPlanner {
                     position: node_position,
                     size: child.size,
                     header: child.header.clone(),
+                    color: None,
                 });
             }
         }
@@ -463,6 +465,7 @@ impl SubnetLayoutPlanner {
                             position: *position,
                             size: layout.size,
                             header: Some(header),
+                            color: None,
                         });
                     }
 
@@ -474,6 +477,7 @@ impl SubnetLayoutPlanner {
                         position: *position,
                         size: layout.size,
                         header: None,
+                        color: None,
                     });
                 }
                 None
@@ -487,12 +491,31 @@ impl SubnetLayoutPlanner {
         ctx: &TopologyContext,
         layouts: &HashMap<Uuid, SubnetLayout>,
     ) -> Vec<Vec<(Uuid, NodeLayout)>> {
+        let vlan_of = |subnet: &Subnet| {
+            ctx.get_subnet_vlan(subnet)
+                .filter(|_| ctx.options.group_subnets_by_vlan)
+        };
+
+        // Subnets sharing a VLAN all go in the layer of the topmost one
+        let vlan_layers: HashMap<Uuid, usize> = ctx
+            .subnets
+            .iter()
+            .filter_map(|s| vlan_of(s).map(|v| (v.id, s.base.subnet_type.vertical_order())))
+            .into_grouping_map()
+            .min();
+        let layer_of = |subnet: &Subnet| {
+            vlan_of(subnet)
+                .and_then(|v| vlan_layers.get(&v.id).copied())
+                .unwrap_or(subnet.base.subnet_type.vertical_order())
+        };
+
         let sorted: Vec<_> = ctx
             .subnets
             .iter()
             .sorted_by_key(|s| {
                 (
-                    s.base.subnet_type.vertical_order(),
+                    layer_of(s),
+                    vlan_of(s).map_or(u32::MAX, |v| v.base.tag as u32),
                     s.base.subnet_type.horizontal_order(),
                     s.base.name.clone(),
                 )
@@ -503,7 +526,7 @@ impl SubnetLayoutPlanner {
         let mut subnets_by_layer: BTreeMap<usize, Vec<(&Uuid, &SubnetLayout)>> = BTreeMap::new();
         for (subnet, layout) in sorted {
             subnets_by_layer
-                .entry(subnet.base.subnet_type.vertical_order())
+                .entry(layer_of(subnet))
                 .or_default()
                 .push((&subnet.id, layout));
         }
@@ -3,6 +3,12 @@ use uuid::Uuid;
 
 use crate::server::services::types::categories::ServiceCategory;
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TagColor {
+    pub tag: String,
+    pub color: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TopologyRequestOptions {
     pub network_ids: Vec<Uuid>,
@@ -11,4 +17,16 @@ pub struct TopologyRequestOptions {
     pub left_zone_service_categories: Vec<ServiceCategory>,
     pub hide_service_categories: Vec<ServiceCategory>,
     pub show_gateway_in_left_zone: bool,
+    /// Place subnets sharing a VLAN next to each other, in the row of the VLAN's topmost subnet
+    #[serde(default)]
+    pub group_subnets_by_vlan: bool,
+    /// Leave out hosts, services and subnets with any of these tags
+    #[serde(default)]
+    pub hide_tags: Vec<String>,
+    /// Colour host and subnet nodes by their tags. Earlier entries win when several match.
+    #[serde(default)]
+    pub tag_colors: Vec<TagColor>,
+    /// Render a snapshot of the network instead of its current state
+    #[serde(default)]
+    pub snapshot_id: Option<Uuid>,
 }
@@ -269,7 +269,7 @@ impl EntityMetadataProvider for EdgeType {
         }
     }
 
-    fn icon(&self) -> &'static str {
+    fn icon(&self) -> &str {
         match self {
             EdgeType::Group(group_type) => group_type.icon(),
             EdgeType::Interface => Entity::Host.icon(),
@@ -280,7 +280,7 @@ impl EntityMetadataProvider for EdgeType {
 }
 
 impl TypeMetadataProvider for EdgeType {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         match self {
             EdgeType::Group(group_type) => group_type.name(),
             EdgeType::Interface => "Host Interface",
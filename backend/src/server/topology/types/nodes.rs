@@ -15,6 +15,8 @@ pub struct Node {
     pub position: Ixy,
     pub size: Uxy,
     pub header: Option<String>,
+    /// From the tag colours in the request options
+    pub color: Option<String>,
 }
 
 #[derive(
@@ -64,6 +66,7 @@ impl SubnetType {
 
             // Layer 3: Infrastructure
             SubnetType::DockerBridge => 3,
+            SubnetType::Kubernetes => 3,
             SubnetType::Management => 3,
             SubnetType::Storage => 3,
 
@@ -94,6 +97,7 @@ impl SubnetType {
             SubnetType::Storage => 0,
             SubnetType::Management => 1,
             SubnetType::DockerBridge => 2,
+            SubnetType::Kubernetes => 3,
 
             // Special
             SubnetType::Unknown => 999,
@@ -0,0 +1,106 @@
+use crate::server::{
+    config::AppState,
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+    vlans::types::Vlan,
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_vlan))
+        .route("/", get(get_all_vlans))
+        .route("/:id", put(update_vlan))
+        .route("/:id", delete(delete_vlan))
+}
+
+async fn create_vlan(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<Vlan>,
+) -> ApiResult<Json<ApiResponse<Vlan>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "VLAN validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.vlan_service;
+
+    let created_vlan = service
+        .create_vlan(request)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(created_vlan)))
+}
+
+async fn get_all_vlans(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<Vlan>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let service = &state.services.vlan_service;
+
+    let vlans = service.get_all_vlans(&network_id).await?;
+
+    Ok(Json(ApiResponse::success(vlans)))
+}
+
+async fn update_vlan(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<Vlan>,
+) -> ApiResult<Json<ApiResponse<Vlan>>> {
+    if let Err(e) = request.base.validate() {
+        return Err(ApiError::bad_request(&format!(
+            "VLAN validation failed: {}",
+            e
+        )));
+    }
+
+    let service = &state.services.vlan_service;
+
+    let mut vlan = service
+        .get_vlan(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(&format!("VLAN '{}' not found", &id)))?;
+
+    // VLANs can't move between networks
+    let network_id = vlan.base.network_id;
+    vlan.base = request.base;
+    vlan.base.network_id = network_id;
+
+    let updated_vlan = service
+        .update_vlan(vlan)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(updated_vlan)))
+}
+
+async fn delete_vlan(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ApiResponse<()>>> {
+    let service = &state.services.vlan_service;
+
+    if service.get_vlan(&id).await?.is_none() {
+        return Err(ApiError::not_found(&format!("VLAN '{}' not found", &id)));
+    }
+
+    service.delete_vlan(&id).await?;
+
+    Ok(Json(ApiResponse::success(())))
+}
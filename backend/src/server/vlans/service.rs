@@ -0,0 +1,214 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::server::{
+    hosts::{service::HostService, types::base::Host},
+    subnets::{service::SubnetService, types::base::Subnet},
+    vlans::{storage::VlanStorage, types::Vlan},
+};
+
+pub struct VlanService {
+    storage: Arc<dyn VlanStorage>,
+    subnet_service: Arc<SubnetService>,
+    host_service: Arc<HostService>,
+}
+
+impl VlanService {
+    pub fn new(
+        storage: Arc<dyn VlanStorage>,
+        subnet_service: Arc<SubnetService>,
+        host_service: Arc<HostService>,
+    ) -> Self {
+        Self {
+            storage,
+            subnet_service,
+            host_service,
+        }
+    }
+
+    /// Create a VLAN, linking it to unassigned subnets with interfaces tagged for it
+    pub async fn create_vlan(&self, vlan: Vlan) -> Result<Vlan> {
+        let vlan = if vlan.id == Uuid::nil() {
+            Vlan::new(vlan.base)
+        } else {
+            vlan
+        };
+
+        self.validate_unique_tag(&vlan).await?;
+        self.storage.create(&vlan).await?;
+        tracing::info!("Created {}: {}", vlan, vlan.id);
+
+        self.link_tagged_subnets(&vlan).await?;
+
+        Ok(vlan)
+    }
+
+    pub async fn get_vlan(&self, id: &Uuid) -> Result<Option<Vlan>> {
+        self.storage.get_by_id(id).await
+    }
+
+    pub async fn get_all_vlans(&self, network_id: &Uuid) -> Result<Vec<Vlan>> {
+        self.storage.get_all(network_id).await
+    }
+
+    pub async fn update_vlan(&self, mut vlan: Vlan) -> Result<Vlan> {
+        self.validate_unique_tag(&vlan).await?;
+
+        vlan.updated_at = chrono::Utc::now();
+        self.storage.update(&vlan).await?;
+        tracing::info!("Updated {}: {}", vlan, vlan.id);
+
+        Ok(vlan)
+    }
+
+    /// Delete a VLAN, unlinking its subnets first so the change shows in their history
+    pub async fn delete_vlan(&self, id: &Uuid) -> Result<()> {
+        let vlan = self
+            .get_vlan(id)
+            .await?
+            .ok_or_else(|| anyhow!("VLAN not found"))?;
+
+        for mut subnet in self
+            .subnet_service
+            .get_all_subnets(&vlan.base.network_id)
+            .await?
+            .into_iter()
+            .filter(|s| s.base.vlan_id == Some(vlan.id))
+        {
+            subnet.base.vlan_id = None;
+            self.subnet_service.update_subnet(subnet).await?;
+        }
+
+        self.storage.delete(id).await?;
+        tracing::info!("Deleted {}: {}", vlan, vlan.id);
+        Ok(())
+    }
+
+    async fn validate_unique_tag(&self, vlan: &Vlan) -> Result<()> {
+        if let Some(existing) = self
+            .storage
+            .get_all(&vlan.base.network_id)
+            .await?
+            .into_iter()
+            .find(|v| v.id != vlan.id && v.base.tag == vlan.base.tag)
+        {
+            return Err(anyhow!(
+                "VLAN {} already exists in this network as '{}'",
+                vlan.base.tag,
+                existing.base.name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Link subnets which aren't on a VLAN yet if a host has an interface on them tagged with
+    /// the VLAN's ID, ie a daemon with a subnet on eth0.20
+    async fn link_tagged_subnets(&self, vlan: &Vlan) -> Result<()> {
+        let hosts = self
+            .host_service
+            .get_all_hosts(&vlan.base.network_id)
+            .await?;
+
+        for mut subnet in self
+            .subnet_service
+            .get_all_subnets(&vlan.base.network_id)
+            .await?
+            .into_iter()
+            .filter(|s| s.base.vlan_id.is_none())
+        {
+            let tagged = hosts
+                .iter()
+                .flat_map(|h| &h.base.interfaces)
+                .any(|i| i.base.subnet_id == subnet.id && i.base.vlan_tag == Some(vlan.base.tag));
+
+            if tagged {
+                tracing::info!("Linking subnet {} to {}", subnet.base.name, vlan);
+                subnet.base.vlan_id = Some(vlan.id);
+                self.subnet_service.update_subnet(subnet).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Link the subnets of a host's tagged interfaces which aren't on a VLAN yet to the VLAN
+    /// with the interface's tag
+    pub async fn link_host_subnets(&self, host: &Host) -> Result<()> {
+        let tagged: Vec<_> = host
+            .base
+            .interfaces
+            .iter()
+            .filter_map(|i| Some((i.base.subnet_id, i.base.vlan_tag?)))
+            .collect();
+
+        if tagged.is_empty() {
+            return Ok(());
+        }
+
+        let vlans = self.storage.get_all(&host.base.network_id).await?;
+
+        for (subnet_id, tag) in tagged {
+            let Some(vlan) = vlans.iter().find(|v| v.base.tag == tag) else {
+                continue;
+            };
+
+            if let Some(mut subnet) = self.subnet_service.get_subnet(&subnet_id).await?
+                && subnet.base.vlan_id.is_none()
+            {
+                tracing::info!("Linking subnet {} to {}", subnet.base.name, vlan);
+                subnet.base.vlan_id = Some(vlan.id);
+                self.subnet_service.update_subnet(subnet).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// VLAN a subnet which isn't on one yet should be linked to, if a host has an interface on
+    /// it tagged with the ID of a VLAN in the network
+    pub async fn tagged_vlan_for_subnet(&self, subnet: &Subnet) -> Result<Option<Uuid>> {
+        if subnet.base.vlan_id.is_some() {
+            return Ok(None);
+        }
+
+        let hosts = self
+            .host_service
+            .get_all_hosts(&subnet.base.network_id)
+            .await?;
+
+        let Some(tag) = hosts
+            .iter()
+            .flat_map(|h| &h.base.interfaces)
+            .filter(|i| i.base.subnet_id == subnet.id)
+            .find_map(|i| i.base.vlan_tag)
+        else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .storage
+            .get_all(&subnet.base.network_id)
+            .await?
+            .into_iter()
+            .find(|v| v.base.tag == tag)
+            .map(|v| v.id))
+    }
+
+    /// Subnets can only be linked to a VLAN in their own network
+    pub async fn validate_subnet_vlan(&self, subnet: &Subnet) -> Result<()> {
+        let Some(vlan_id) = subnet.base.vlan_id else {
+            return Ok(());
+        };
+
+        match self.get_vlan(&vlan_id).await? {
+            Some(vlan) if vlan.base.network_id == subnet.base.network_id => Ok(()),
+            Some(_) => Err(anyhow!(
+                "VLAN {} belongs to a different network than subnet {}",
+                vlan_id,
+                subnet.base.cidr
+            )),
+            None => Err(anyhow!("VLAN {} not found", vlan_id)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::{
+    subnets::types::base::SubnetType,
+    vlans::types::{Vlan, VlanBase},
+};
+
+#[async_trait]
+pub trait VlanStorage: Send + Sync {
+    async fn create(&self, vlan: &Vlan) -> Result<()>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<Vlan>>;
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Vlan>>;
+    async fn update(&self, vlan: &Vlan) -> Result<()>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
+
+pub struct PostgresVlanStorage {
+    pool: PgPool,
+}
+
+impl PostgresVlanStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl VlanStorage for PostgresVlanStorage {
+    async fn create(&self, vlan: &Vlan) -> Result<()> {
+        let purpose_str = serde_json::to_string(&vlan.base.purpose)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO vlans (
+                id, network_id, tag, name, purpose, description, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(vlan.id)
+        .bind(vlan.base.network_id)
+        .bind(vlan.base.tag as i32)
+        .bind(&vlan.base.name)
+        .bind(purpose_str)
+        .bind(&vlan.base.description)
+        .bind(vlan.created_at)
+        .bind(vlan.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<Option<Vlan>> {
+        let row = sqlx::query("SELECT * FROM vlans WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(row_to_vlan).transpose()
+    }
+
+    async fn get_all(&self, network_id: &Uuid) -> Result<Vec<Vlan>> {
+        let rows = sqlx::query("SELECT * FROM vlans WHERE network_id = $1 ORDER BY tag")
+            .bind(network_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_vlan).collect()
+    }
+
+    async fn update(&self, vlan: &Vlan) -> Result<()> {
+        let purpose_str = serde_json::to_string(&vlan.base.purpose)?;
+
+        sqlx::query(
+            r#"
+            UPDATE vlans SET
+                tag = $2, name = $3, purpose = $4, description = $5, updated_at = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(vlan.id)
+        .bind(vlan.base.tag as i32)
+        .bind(&vlan.base.name)
+        .bind(purpose_str)
+        .bind(&vlan.base.description)
+        .bind(vlan.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM vlans WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn row_to_vlan(row: sqlx::postgres::PgRow) -> Result<Vlan, Error> {
+    let purpose: SubnetType = serde_json::from_str(&row.get::<String, _>("purpose"))
+        .or(Err(Error::msg("Failed to deserialize purpose")))?;
+    let tag =
+        u16::try_from(row.get::<i32, _>("tag")).or(Err(Error::msg("Failed to deserialize tag")))?;
+
+    Ok(Vlan {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        base: VlanBase {
+            network_id: row.get("network_id"),
+            tag,
+            name: row.get("name"),
+            purpose,
+            description: row.get("description"),
+        },
+    })
+}
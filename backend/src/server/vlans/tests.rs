@@ -0,0 +1,165 @@
+use serial_test::serial;
+use validator::Validate;
+
+use crate::{
+    server::{
+        hosts::types::interfaces::InterfaceBase,
+        subnets::types::base::SubnetType,
+        topology::types::api::TopologyRequestOptions,
+        vlans::types::{Vlan, VlanBase},
+    },
+    tests::*,
+};
+
+#[test]
+fn test_vlan_tag_from_name() {
+    assert_eq!(InterfaceBase::vlan_tag_from_name("eth0.20"), Some(20));
+    assert_eq!(
+        InterfaceBase::vlan_tag_from_name("enp3s0.100@enp3s0"),
+        Some(100)
+    );
+    assert_eq!(InterfaceBase::vlan_tag_from_name("vlan30"), Some(30));
+    assert_eq!(InterfaceBase::vlan_tag_from_name("eth0"), None);
+    assert_eq!(InterfaceBase::vlan_tag_from_name("eth0.5000"), None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_vlan_links_tagged_subnets() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+
+    let tagged_subnet = subnet(&network.id);
+    services
+        .subnet_service
+        .create_subnet(tagged_subnet.clone())
+        .await
+        .unwrap();
+    let mut other_subnet = subnet(&network.id);
+    other_subnet.base.cidr = "10.0.0.0/24".parse().unwrap();
+    services
+        .subnet_service
+        .create_subnet(other_subnet.clone())
+        .await
+        .unwrap();
+
+    let mut daemon_host = host(&network.id);
+    let mut tagged_interface = interface(&tagged_subnet.id);
+    tagged_interface.base.name = Some("eth0.20".to_string());
+    tagged_interface.base.vlan_tag = InterfaceBase::vlan_tag_from_name("eth0.20");
+    daemon_host.base.interfaces = vec![tagged_interface];
+    let (daemon_host, _) = services
+        .host_service
+        .create_host_with_services(daemon_host, vec![])
+        .await
+        .unwrap();
+
+    let vlan = |tag: u16, name: &str| {
+        Vlan::new(VlanBase {
+            network_id: network.id,
+            tag,
+            name: name.to_string(),
+            purpose: SubnetType::IoT,
+            description: None,
+        })
+    };
+
+    let vlan_service = &services.vlan_service;
+    let iot = vlan_service.create_vlan(vlan(20, "IoT")).await.unwrap();
+
+    let linked = services
+        .subnet_service
+        .get_subnet(&tagged_subnet.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(linked.base.vlan_id, Some(iot.id));
+    let unlinked = services
+        .subnet_service
+        .get_subnet(&other_subnet.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unlinked.base.vlan_id, None);
+
+    // Tags are unique within a network and VLANs need a name
+    assert!(vlan_service.create_vlan(vlan(20, "Cameras")).await.is_err());
+    assert!(vlan(30, "").base.validate().is_err());
+
+    // Tagging an interface later links its subnet when the host is saved
+    let guests = vlan_service.create_vlan(vlan(30, "Guests")).await.unwrap();
+    let mut updated_host = daemon_host.clone();
+    let mut guest_interface = interface(&other_subnet.id);
+    guest_interface.base.ip_address = "10.0.0.5".parse().unwrap();
+    guest_interface.base.vlan_tag = Some(30);
+    updated_host.base.interfaces.push(guest_interface);
+    services
+        .host_service
+        .update_host(updated_host)
+        .await
+        .unwrap();
+    let linked = services
+        .subnet_service
+        .get_subnet(&other_subnet.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(linked.base.vlan_id, Some(guests.id));
+
+    // Subnets can't link to a VLAN of another network
+    let other_network = services
+        .network_service
+        .create_network(crate::tests::network(&user.id))
+        .await
+        .unwrap();
+    let foreign = vlan_service
+        .create_vlan(Vlan::new(VlanBase {
+            network_id: other_network.id,
+            ..vlan(40, "Lab").base
+        }))
+        .await
+        .unwrap();
+    let mut cross_network = linked.clone();
+    cross_network.base.vlan_id = Some(foreign.id);
+    assert!(
+        services
+            .subnet_service
+            .update_subnet(cross_network)
+            .await
+            .is_err()
+    );
+
+    let graph = services
+        .topology_service
+        .build_graph(TopologyRequestOptions {
+            network_ids: vec![network.id],
+            group_subnets_by_vlan: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(graph.node_count() > 0);
+
+    vlan_service.delete_vlan(&iot.id).await.unwrap();
+    let unlinked = services
+        .subnet_service
+        .get_subnet(&tagged_subnet.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unlinked.base.vlan_id, None);
+    let remaining: Vec<_> = vlan_service
+        .get_all_vlans(&network.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    assert_eq!(remaining, vec![guests.id]);
+}
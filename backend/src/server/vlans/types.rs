@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::server::{
+    shared::types::api::deserialize_empty_string_as_none, subnets::types::base::SubnetType,
+};
+
+#[derive(Debug, Clone, Validate, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VlanBase {
+    pub network_id: Uuid,
+    /// 802.1Q VLAN ID
+    #[validate(range(min = 1, max = 4094))]
+    pub tag: u16,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    /// What the VLAN is for, using the same roles as subnets
+    #[serde(default)]
+    pub purpose: SubnetType,
+    #[serde(default, deserialize_with = "deserialize_empty_string_as_none")]
+    #[validate(length(min = 0, max = 500))]
+    pub description: Option<String>,
+}
+
+/// A layer-2 broadcast domain. Subnets link to the VLAN they're carried on, and several subnets
+/// can share one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vlan {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub base: VlanBase,
+}
+
+impl Vlan {
+    pub fn new(base: VlanBase) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            base,
+        }
+    }
+}
+
+impl std::fmt::Display for Vlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VLAN {} ({})", self.base.tag, self.base.name)
+    }
+}
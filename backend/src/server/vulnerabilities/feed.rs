@@ -0,0 +1,279 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+
+use crate::server::vulnerabilities::types::{
+    AffectedProduct, VersionBound, VersionRange, Vulnerability, VulnerabilitySeverity,
+};
+
+/// Parse a vulnerability feed file. Accepts NVD CVE API 2.0 JSON and OSV JSON, either a single
+/// OSV record or an array of them.
+pub fn parse_feed(contents: &str) -> Result<Vec<Vulnerability>> {
+    let json: Value = serde_json::from_str(contents)?;
+
+    match &json {
+        Value::Object(obj) if obj.contains_key("vulnerabilities") => parse_nvd(&json),
+        Value::Object(obj) if obj.contains_key("affected") => {
+            Ok(parse_osv(&json).into_iter().collect())
+        }
+        Value::Array(records) => Ok(records.iter().filter_map(parse_osv).collect()),
+        _ => Err(anyhow!("Unrecognised vulnerability feed format")),
+    }
+}
+
+fn parse_nvd(json: &Value) -> Result<Vec<Vulnerability>> {
+    let entries = json["vulnerabilities"]
+        .as_array()
+        .ok_or_else(|| anyhow!("NVD feed 'vulnerabilities' is not an array"))?;
+
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let cve = &entry["cve"];
+            let id = cve["id"].as_str()?.to_string();
+
+            let summary = cve["descriptions"]
+                .as_array()
+                .and_then(|d| d.iter().find(|d| d["lang"] == "en").or_else(|| d.first()))
+                .and_then(|d| d["value"].as_str())
+                .map(|s| s.to_string());
+
+            let (cvss_score, severity) = nvd_cvss(&cve["metrics"]);
+
+            let affected = cve["configurations"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .flat_map(|config| config["nodes"].as_array().into_iter().flatten())
+                .flat_map(|node| node["cpeMatch"].as_array().into_iter().flatten())
+                .filter(|m| m["vulnerable"].as_bool().unwrap_or(false))
+                .filter_map(nvd_affected_product)
+                .collect();
+
+            Some(Vulnerability {
+                id,
+                aliases: vec![],
+                summary,
+                severity,
+                cvss_score,
+                published: cve["published"].as_str().and_then(parse_timestamp),
+                affected,
+            })
+        })
+        .collect())
+}
+
+/// Highest-version CVSS metric available
+fn nvd_cvss(metrics: &Value) -> (Option<f32>, VulnerabilitySeverity) {
+    for key in [
+        "cvssMetricV40",
+        "cvssMetricV31",
+        "cvssMetricV30",
+        "cvssMetricV2",
+    ] {
+        let Some(metric) = metrics[key].as_array().and_then(|m| m.first()) else {
+            continue;
+        };
+
+        let score = metric["cvssData"]["baseScore"].as_f64().map(|s| s as f32);
+        let severity = metric["cvssData"]["baseSeverity"]
+            .as_str()
+            .or_else(|| metric["baseSeverity"].as_str())
+            .map(VulnerabilitySeverity::from_label)
+            .or_else(|| score.map(VulnerabilitySeverity::from_cvss))
+            .unwrap_or_default();
+
+        return (score, severity);
+    }
+
+    (None, VulnerabilitySeverity::Unknown)
+}
+
+/// cpe:2.3:part:vendor:product:version:...
+fn nvd_affected_product(cpe_match: &Value) -> Option<AffectedProduct> {
+    let criteria = cpe_match["criteria"].as_str()?;
+    let parts: Vec<&str> = criteria.split(':').collect();
+    let (vendor, product, version) = (parts.get(3)?, parts.get(4)?, parts.get(5)?);
+
+    let bound = |key: &str, inclusive: bool| {
+        cpe_match[key].as_str().map(|v| VersionBound {
+            version: v.to_string(),
+            inclusive,
+        })
+    };
+
+    let range = match *version {
+        "*" | "-" | "" => VersionRange {
+            start: bound("versionStartIncluding", true)
+                .or_else(|| bound("versionStartExcluding", false)),
+            end: bound("versionEndIncluding", true).or_else(|| bound("versionEndExcluding", false)),
+        },
+        exact => VersionRange::exact(exact),
+    };
+
+    Some(AffectedProduct {
+        vendor: Some(vendor.to_lowercase()),
+        product: product.to_lowercase(),
+        ranges: vec![range],
+    })
+}
+
+fn parse_osv(record: &Value) -> Option<Vulnerability> {
+    let id = record["id"].as_str()?.to_string();
+
+    let aliases = record["aliases"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.as_str().map(|a| a.to_string()))
+        .collect();
+
+    let summary = record["summary"]
+        .as_str()
+        .or_else(|| record["details"].as_str())
+        .map(|s| s.to_string());
+
+    // OSV only carries CVSS vectors, so rely on the severity label databases commonly attach
+    let severity = record["database_specific"]["severity"]
+        .as_str()
+        .or_else(|| {
+            record["affected"]
+                .as_array()?
+                .iter()
+                .find_map(|a| a["ecosystem_specific"]["severity"].as_str())
+        })
+        .map(VulnerabilitySeverity::from_label)
+        .unwrap_or_default();
+
+    let affected = record["affected"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(osv_affected_product)
+        .collect();
+
+    Some(Vulnerability {
+        id,
+        aliases,
+        summary,
+        severity,
+        cvss_score: None,
+        published: record["published"].as_str().and_then(parse_timestamp),
+        affected,
+    })
+}
+
+fn osv_affected_product(affected: &Value) -> Option<AffectedProduct> {
+    let (vendor, product) = osv_package(
+        affected["package"]["ecosystem"]
+            .as_str()
+            .unwrap_or_default(),
+        affected["package"]["name"].as_str()?,
+    )?;
+
+    let mut ranges: Vec<VersionRange> = affected["versions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(VersionRange::exact))
+        .collect();
+
+    // GIT ranges are commit hashes, which can't be compared with detected versions
+    for range in affected["ranges"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|r| r["type"] != "GIT")
+    {
+        let mut current: Option<VersionRange> = None;
+
+        for event in range["events"].as_array().into_iter().flatten() {
+            if let Some(introduced) = event["introduced"].as_str() {
+                current = Some(VersionRange {
+                    start: (introduced != "0").then(|| VersionBound {
+                        version: introduced.to_string(),
+                        inclusive: true,
+                    }),
+                    end: None,
+                });
+            } else if let Some((end, inclusive)) = event["fixed"]
+                .as_str()
+                .map(|v| (v, false))
+                .or_else(|| event["last_affected"].as_str().map(|v| (v, true)))
+                && let Some(mut open) = current.take()
+            {
+                open.end = Some(VersionBound {
+                    version: end.to_string(),
+                    inclusive,
+                });
+                ranges.push(open);
+            }
+        }
+
+        // Introduced without a fix, every later version is affected
+        ranges.extend(current);
+    }
+
+    // Only unusable ranges, rather than no version constraints at all
+    let constrained = affected["versions"]
+        .as_array()
+        .is_some_and(|v| !v.is_empty())
+        || affected["ranges"].as_array().is_some_and(|r| !r.is_empty());
+    if constrained && ranges.is_empty() {
+        return None;
+    }
+
+    Some(AffectedProduct {
+        vendor,
+        product,
+        ranges,
+    })
+}
+
+/// Vendor and product of an OSV package. The vendor is the owner where the package name includes
+/// one: the repository owner of Go module paths ("github.com/grafana/grafana"), the npm scope
+/// ("@grafana/ui"), the Packagist vendor ("nextcloud/server") or the organisation of a Maven group
+/// ("org.jellyfin:jellyfin-core").
+fn osv_package(ecosystem: &str, name: &str) -> Option<(Option<String>, String)> {
+    let (vendor, product) = match ecosystem {
+        "Maven" => {
+            let (group, artifact) = name.split_once(':')?;
+            let mut domain = group.split('.');
+            let organisation = match (domain.next(), domain.next()) {
+                (_, Some(organisation)) => organisation,
+                (first, None) => first?,
+            };
+            (Some(organisation), artifact)
+        }
+        "npm" => match name.strip_prefix('@').and_then(|n| n.split_once('/')) {
+            Some((scope, package)) => (Some(scope), package),
+            None => (None, name),
+        },
+        "Packagist" => {
+            let (owner, package) = name.split_once('/')?;
+            (Some(owner), package)
+        }
+        _ => {
+            // Go module paths are "host/owner/repository[/subpackage]"
+            let segments: Vec<&str> = name.split('/').collect();
+            match segments.as_slice() {
+                [_host, owner, repository, ..] => (Some(*owner), *repository),
+                _ => (None, name),
+            }
+        }
+    };
+
+    (!product.is_empty()).then(|| (vendor.map(str::to_lowercase), product.to_lowercase()))
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            // NVD timestamps have no offset
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|t| t.and_utc())
+                .ok()
+        })
+}
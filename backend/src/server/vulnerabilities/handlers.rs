@@ -0,0 +1,57 @@
+use crate::server::{
+    config::AppState,
+    shared::types::api::{ApiError, ApiResponse, ApiResult},
+    vulnerabilities::types::{FeedImport, VulnerableService},
+};
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json,
+    routing::{get, post},
+};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+pub fn create_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(get_vulnerable_services))
+        .route("/import", post(import_feed))
+}
+
+async fn get_vulnerable_services(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<ApiResponse<Vec<VulnerableService>>>> {
+    let network_id = params
+        .get("network_id")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| ApiError::bad_request("network_id query parameter required"))?;
+
+    let vulnerable = state
+        .services
+        .vulnerability_service
+        .get_vulnerable_services(&network_id)
+        .await?;
+
+    Ok(Json(ApiResponse::success(vulnerable)))
+}
+
+/// Re-import the vulnerability feed from the configured path
+async fn import_feed(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<ApiResponse<FeedImport>>> {
+    let path = state
+        .config
+        .vulnerability_feed_path
+        .as_ref()
+        .ok_or_else(|| ApiError::bad_request("No vulnerability feed path is configured"))?;
+
+    let import = state
+        .services
+        .vulnerability_service
+        .import_feed(path)
+        .await
+        .map_err(|e| ApiError::bad_request(&e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(import)))
+}
@@ -0,0 +1,204 @@
+use anyhow::Result;
+use std::{collections::HashMap, path::Path, sync::Arc};
+use uuid::Uuid;
+
+use crate::server::{
+    services::service::ServiceService,
+    vulnerabilities::{
+        feed::parse_feed,
+        storage::VulnerabilityStorage,
+        types::{CpeProduct, FeedImport, VulnerabilityMatch, VulnerableService},
+    },
+};
+
+pub struct VulnerabilityService {
+    storage: Arc<dyn VulnerabilityStorage>,
+    service_service: Arc<ServiceService>,
+}
+
+impl VulnerabilityService {
+    pub fn new(
+        storage: Arc<dyn VulnerabilityStorage>,
+        service_service: Arc<ServiceService>,
+    ) -> Self {
+        Self {
+            storage,
+            service_service,
+        }
+    }
+
+    /// Import a vulnerability feed from a file, or from every .json file in a directory, then
+    /// re-match services in every network against it
+    pub async fn import_feed(&self, path: &Path) -> Result<FeedImport> {
+        let files = if tokio::fs::metadata(path).await?.is_dir() {
+            let mut entries = tokio::fs::read_dir(path).await?;
+            let mut files = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                let file = entry.path();
+                if file.extension().is_some_and(|e| e == "json") {
+                    files.push(file);
+                }
+            }
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut import = FeedImport::default();
+
+        for file in files {
+            let parsed = tokio::fs::read_to_string(&file)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|contents| parse_feed(&contents));
+
+            match parsed {
+                Ok(vulnerabilities) => {
+                    self.storage.upsert(&vulnerabilities).await?;
+                    import.files += 1;
+                    import.vulnerabilities += vulnerabilities.len();
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping vulnerability feed file {}: {}", file.display(), e);
+                    import.skipped.push(file.display().to_string());
+                }
+            }
+        }
+
+        tracing::info!(
+            "Imported {} vulnerabilities from {} feed files",
+            import.vulnerabilities,
+            import.files
+        );
+
+        for network_id in self.storage.get_network_ids_with_versions().await? {
+            self.match_network(&network_id).await?;
+        }
+
+        Ok(import)
+    }
+
+    /// Match every versioned service in a network against the imported feed, replacing previous
+    /// matches. Returns the number of vulnerable services.
+    pub async fn match_network(&self, network_id: &Uuid) -> Result<usize> {
+        let services = self.service_service.get_all_services(network_id).await?;
+
+        let candidates: Vec<(Uuid, CpeProduct, String)> = services
+            .into_iter()
+            .filter_map(|s| {
+                let cpe = CpeProduct::parse(s.base.service_definition.cpe_product()?)?;
+                Some((s.id, cpe, s.base.version?))
+            })
+            .collect();
+
+        let mut products: Vec<String> = candidates
+            .iter()
+            .map(|(_, c, _)| c.product.clone())
+            .collect();
+        products.sort();
+        products.dedup();
+
+        let vulnerabilities = if products.is_empty() {
+            vec![]
+        } else {
+            self.storage.get_for_products(&products).await?
+        };
+
+        let matches: Vec<(Uuid, String)> = candidates
+            .iter()
+            .flat_map(|(service_id, cpe, version)| {
+                vulnerabilities
+                    .iter()
+                    .filter(|v| v.affects(cpe, version))
+                    .map(|v| (*service_id, v.id.clone()))
+            })
+            .collect();
+
+        self.storage.replace_matches(network_id, &matches).await?;
+
+        let vulnerable_services = candidates
+            .iter()
+            .filter(|(id, _, _)| matches.iter().any(|(s, _)| s == id))
+            .count();
+
+        tracing::info!(
+            "Matched {} vulnerabilities across {} services in network {}",
+            matches.len(),
+            vulnerable_services,
+            network_id
+        );
+
+        Ok(vulnerable_services)
+    }
+
+    /// Services in a network with known vulnerabilities, most severe first
+    pub async fn get_vulnerable_services(
+        &self,
+        network_id: &Uuid,
+    ) -> Result<Vec<VulnerableService>> {
+        let matches = self.storage.get_matches(network_id).await?;
+
+        if matches.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut ids: Vec<String> = matches.iter().map(|m| m.vulnerability_id.clone()).collect();
+        ids.sort();
+        ids.dedup();
+
+        let vulnerabilities: HashMap<String, _> = self
+            .storage
+            .get_by_ids(&ids)
+            .await?
+            .into_iter()
+            .map(|v| (v.id.clone(), v))
+            .collect();
+
+        let services = self.service_service.get_all_services(network_id).await?;
+
+        let mut vulnerable: Vec<VulnerableService> = services
+            .into_iter()
+            .filter_map(|service| {
+                let mut found: Vec<VulnerabilityMatch> = matches
+                    .iter()
+                    .filter(|m| m.service_id == service.id)
+                    .filter_map(|m| {
+                        let v = vulnerabilities.get(&m.vulnerability_id)?;
+                        Some(VulnerabilityMatch {
+                            id: v.id.clone(),
+                            cve_ids: v.cve_ids(),
+                            severity: v.severity,
+                            cvss_score: v.cvss_score,
+                            summary: v.summary.clone(),
+                            matched_at: m.matched_at,
+                        })
+                    })
+                    .collect();
+
+                if found.is_empty() {
+                    return None;
+                }
+
+                found.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.id.cmp(&b.id)));
+
+                Some(VulnerableService {
+                    service_id: service.id,
+                    host_id: service.base.host_id,
+                    name: service.base.name,
+                    service_definition: service.base.service_definition.id().to_string(),
+                    version: service.base.version,
+                    vulnerabilities: found,
+                })
+            })
+            .collect();
+
+        vulnerable.sort_by(|a, b| {
+            b.max_severity()
+                .cmp(&a.max_severity())
+                .then(a.name.cmp(&b.name))
+        });
+
+        Ok(vulnerable)
+    }
+}
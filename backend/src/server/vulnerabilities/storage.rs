@@ -0,0 +1,175 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::server::vulnerabilities::types::Vulnerability;
+
+/// A stored match between a service and a vulnerability
+#[derive(Debug, Clone)]
+pub struct ServiceVulnerabilityRow {
+    pub service_id: Uuid,
+    pub vulnerability_id: String,
+    pub matched_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait VulnerabilityStorage: Send + Sync {
+    /// Insert vulnerabilities, replacing any previously imported with the same id
+    async fn upsert(&self, vulnerabilities: &[Vulnerability]) -> Result<()>;
+    /// Vulnerabilities affecting any of the given lowercase product names
+    async fn get_for_products(&self, products: &[String]) -> Result<Vec<Vulnerability>>;
+    async fn get_by_ids(&self, ids: &[String]) -> Result<Vec<Vulnerability>>;
+    /// Networks with versioned services, which can be matched against the feed
+    async fn get_network_ids_with_versions(&self) -> Result<Vec<Uuid>>;
+    /// Replace all stored matches for a network
+    async fn replace_matches(&self, network_id: &Uuid, matches: &[(Uuid, String)]) -> Result<()>;
+    async fn get_matches(&self, network_id: &Uuid) -> Result<Vec<ServiceVulnerabilityRow>>;
+}
+
+pub struct PostgresVulnerabilityStorage {
+    pool: PgPool,
+}
+
+impl PostgresVulnerabilityStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl VulnerabilityStorage for PostgresVulnerabilityStorage {
+    async fn upsert(&self, vulnerabilities: &[Vulnerability]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let imported_at = Utc::now();
+
+        for vulnerability in vulnerabilities {
+            let vulnerability_json = serde_json::to_value(vulnerability)?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO vulnerabilities (id, products, vulnerability, imported_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (id) DO UPDATE SET
+                    products = EXCLUDED.products,
+                    vulnerability = EXCLUDED.vulnerability,
+                    imported_at = EXCLUDED.imported_at
+                "#,
+            )
+            .bind(&vulnerability.id)
+            .bind(vulnerability.products())
+            .bind(vulnerability_json)
+            .bind(imported_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_for_products(&self, products: &[String]) -> Result<Vec<Vulnerability>> {
+        let rows = sqlx::query("SELECT * FROM vulnerabilities WHERE products && $1 ORDER BY id")
+            .bind(products)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_vulnerability).collect()
+    }
+
+    async fn get_by_ids(&self, ids: &[String]) -> Result<Vec<Vulnerability>> {
+        let rows = sqlx::query("SELECT * FROM vulnerabilities WHERE id = ANY($1) ORDER BY id")
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_vulnerability).collect()
+    }
+
+    async fn get_network_ids_with_versions(&self) -> Result<Vec<Uuid>> {
+        let rows =
+            sqlx::query("SELECT DISTINCT network_id FROM services WHERE version IS NOT NULL")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|row| row.get("network_id")).collect())
+    }
+
+    async fn replace_matches(&self, network_id: &Uuid, matches: &[(Uuid, String)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let matched_at = Utc::now();
+
+        // Keep the original match time for vulnerabilities which are still present
+        let existing: Vec<(Uuid, String, DateTime<Utc>)> = sqlx::query(
+            "SELECT service_id, vulnerability_id, matched_at FROM service_vulnerabilities WHERE network_id = $1",
+        )
+        .bind(network_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                row.get("service_id"),
+                row.get("vulnerability_id"),
+                row.get("matched_at"),
+            )
+        })
+        .collect();
+
+        sqlx::query("DELETE FROM service_vulnerabilities WHERE network_id = $1")
+            .bind(network_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (service_id, vulnerability_id) in matches {
+            let first_matched_at = existing
+                .iter()
+                .find(|(s, v, _)| s == service_id && v == vulnerability_id)
+                .map(|(_, _, at)| *at)
+                .unwrap_or(matched_at);
+
+            sqlx::query(
+                r#"
+                INSERT INTO service_vulnerabilities (
+                    service_id, vulnerability_id, network_id, matched_at
+                ) VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(service_id)
+            .bind(vulnerability_id)
+            .bind(network_id)
+            .bind(first_matched_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_matches(&self, network_id: &Uuid) -> Result<Vec<ServiceVulnerabilityRow>> {
+        let rows = sqlx::query(
+            "SELECT * FROM service_vulnerabilities WHERE network_id = $1 ORDER BY matched_at",
+        )
+        .bind(network_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ServiceVulnerabilityRow {
+                service_id: row.get("service_id"),
+                vulnerability_id: row.get("vulnerability_id"),
+                matched_at: row.get("matched_at"),
+            })
+            .collect())
+    }
+}
+
+fn row_to_vulnerability(row: sqlx::postgres::PgRow) -> Result<Vulnerability, Error> {
+    serde_json::from_value(row.get::<serde_json::Value, _>("vulnerability"))
+        .or(Err(Error::msg("Failed to deserialize vulnerability")))
+}
@@ -0,0 +1,141 @@
+use serial_test::serial;
+
+use crate::{
+    server::{
+        services::definitions::ServiceDefinitionRegistry,
+        vulnerabilities::types::VulnerabilitySeverity,
+    },
+    tests::*,
+};
+
+const NVD_FEED: &str = r#"{
+    "format": "NVD_CVE",
+    "version": "2.0",
+    "vulnerabilities": [{
+        "cve": {
+            "id": "CVE-2023-0001",
+            "published": "2023-10-01T12:00:00.000",
+            "descriptions": [{ "lang": "en", "value": "Grafana test vulnerability" }],
+            "metrics": {
+                "cvssMetricV31": [{
+                    "cvssData": { "baseScore": 7.5, "baseSeverity": "HIGH" }
+                }]
+            },
+            "configurations": [{
+                "nodes": [{
+                    "cpeMatch": [{
+                        "vulnerable": true,
+                        "criteria": "cpe:2.3:a:grafana:grafana:*:*:*:*:*:*:*:*",
+                        "versionStartIncluding": "10.0.0",
+                        "versionEndExcluding": "10.2.4"
+                    }]
+                }]
+            }]
+        }
+    }]
+}"#;
+
+const OSV_FEED: &str = r#"{
+    "id": "GHSA-test-0000-0000",
+    "aliases": ["CVE-2023-0002"],
+    "summary": "Jellyfin test vulnerability",
+    "database_specific": { "severity": "CRITICAL" },
+    "affected": [{
+        "package": { "ecosystem": "NuGet", "name": "Jellyfin" },
+        "ranges": [{
+            "type": "ECOSYSTEM",
+            "events": [{ "introduced": "0" }, { "fixed": "10.8.13" }]
+        }]
+    }]
+}"#;
+
+#[tokio::test]
+#[serial]
+async fn test_vulnerability_feed_matching() {
+    let (_, services, _container) = test_services().await;
+
+    let user = services.user_service.create_user(user()).await.unwrap();
+    let network = services
+        .network_service
+        .create_network(network(&user.id))
+        .await
+        .unwrap();
+    let (created_host, _) = services
+        .host_service
+        .create_host_with_services(host(&network.id), vec![])
+        .await
+        .unwrap();
+
+    let mut created = Vec::new();
+    for (definition, version) in [("Grafana", "10.2.3"), ("Jellyfin", "10.9.0")] {
+        let mut svc = service(&network.id, &created_host.id);
+        svc.base.name = definition.to_string();
+        svc.base.service_definition = ServiceDefinitionRegistry::find_by_id(definition).unwrap();
+        svc.base.version = Some(version.to_string());
+        created.push(services.service_service.create_service(svc).await.unwrap());
+    }
+
+    let feed_dir = tempfile::tempdir().unwrap();
+    std::fs::write(feed_dir.path().join("nvd.json"), NVD_FEED).unwrap();
+    std::fs::write(feed_dir.path().join("osv.json"), OSV_FEED).unwrap();
+    std::fs::write(feed_dir.path().join("notes.json"), "{}").unwrap();
+
+    // Importing matches existing services against the new feed
+    let import = services
+        .vulnerability_service
+        .import_feed(feed_dir.path())
+        .await
+        .unwrap();
+    assert_eq!(import.files, 2);
+    assert_eq!(import.vulnerabilities, 2);
+    assert_eq!(import.skipped.len(), 1);
+
+    let vulnerable = services
+        .vulnerability_service
+        .get_vulnerable_services(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(vulnerable.len(), 1);
+    assert_eq!(vulnerable[0].service_definition, "Grafana");
+    assert_eq!(
+        vulnerable[0].vulnerabilities[0].cve_ids,
+        vec!["CVE-2023-0001"]
+    );
+    assert_eq!(
+        vulnerable[0].vulnerabilities[0].severity,
+        VulnerabilitySeverity::High
+    );
+
+    // Downgrade Jellyfin into the affected range and upgrade Grafana past the fix
+    for (svc, version) in created.iter_mut().zip(["10.2.4", "10.8.0"]) {
+        svc.base.version = Some(version.to_string());
+        services
+            .service_service
+            .update_service(svc.clone())
+            .await
+            .unwrap();
+    }
+
+    let count = services
+        .vulnerability_service
+        .match_network(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let vulnerable = services
+        .vulnerability_service
+        .get_vulnerable_services(&network.id)
+        .await
+        .unwrap();
+    assert_eq!(vulnerable.len(), 1);
+    assert_eq!(vulnerable[0].service_definition, "Jellyfin");
+    assert_eq!(
+        vulnerable[0].vulnerabilities[0].cve_ids,
+        vec!["CVE-2023-0002"]
+    );
+    assert_eq!(
+        vulnerable[0].max_severity(),
+        VulnerabilitySeverity::Critical
+    );
+}
@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum VulnerabilitySeverity {
+    #[default]
+    Unknown,
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl VulnerabilitySeverity {
+    /// Qualitative rating of a CVSS base score
+    pub fn from_cvss(score: f32) -> Self {
+        match score {
+            s if s >= 9.0 => Self::Critical,
+            s if s >= 7.0 => Self::High,
+            s if s >= 4.0 => Self::Medium,
+            s if s > 0.0 => Self::Low,
+            _ => Self::None,
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        match label.to_ascii_uppercase().as_str() {
+            "CRITICAL" => Self::Critical,
+            "HIGH" => Self::High,
+            "MEDIUM" | "MODERATE" => Self::Medium,
+            "LOW" => Self::Low,
+            "NONE" => Self::None,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// One end of a version range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionBound {
+    pub version: String,
+    pub inclusive: bool,
+}
+
+/// Versions affected by a vulnerability. Missing bounds are open.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    pub start: Option<VersionBound>,
+    pub end: Option<VersionBound>,
+}
+
+impl VersionRange {
+    pub fn exact(version: &str) -> Self {
+        let bound = VersionBound {
+            version: version.to_string(),
+            inclusive: true,
+        };
+        Self {
+            start: Some(bound.clone()),
+            end: Some(bound),
+        }
+    }
+
+    pub fn contains(&self, version: &str) -> bool {
+        let after_start = self.start.as_ref().is_none_or(|start| {
+            match compare_versions(version, &start.version) {
+                Ordering::Greater => true,
+                Ordering::Equal => start.inclusive,
+                Ordering::Less => false,
+            }
+        });
+
+        let before_end =
+            self.end
+                .as_ref()
+                .is_none_or(|end| match compare_versions(version, &end.version) {
+                    Ordering::Less => true,
+                    Ordering::Equal => end.inclusive,
+                    Ordering::Greater => false,
+                });
+
+        after_start && before_end
+    }
+}
+
+/// A product affected by a vulnerability, and which of its versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffectedProduct {
+    /// CPE vendor, or the owner of an OSV package when its name includes one. Without a vendor,
+    /// the product is taken to be published by a vendor of the same name.
+    pub vendor: Option<String>,
+    /// CPE product, or package name for OSV feeds
+    pub product: String,
+    /// An empty list means every version is affected
+    pub ranges: Vec<VersionRange>,
+}
+
+impl AffectedProduct {
+    pub fn matches(&self, cpe: &CpeProduct, version: &str) -> bool {
+        let vendor = self.vendor.as_deref().unwrap_or(&self.product);
+        let vendor_matches = vendor.eq_ignore_ascii_case(&cpe.vendor);
+
+        vendor_matches
+            && self.product.eq_ignore_ascii_case(&cpe.product)
+            && (self.ranges.is_empty() || self.ranges.iter().any(|r| r.contains(version)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vulnerability {
+    /// Feed identifier, ie "CVE-2024-1234" or "GHSA-xxxx-xxxx-xxxx"
+    pub id: String,
+    /// Other identifiers for the same vulnerability
+    pub aliases: Vec<String>,
+    pub summary: Option<String>,
+    pub severity: VulnerabilitySeverity,
+    pub cvss_score: Option<f32>,
+    pub published: Option<DateTime<Utc>>,
+    pub affected: Vec<AffectedProduct>,
+}
+
+impl Vulnerability {
+    pub fn cve_ids(&self) -> Vec<String> {
+        std::iter::once(&self.id)
+            .chain(self.aliases.iter())
+            .filter(|id| id.starts_with("CVE-"))
+            .cloned()
+            .collect()
+    }
+
+    /// Lowercase product names, used to look up vulnerabilities for a service
+    pub fn products(&self) -> Vec<String> {
+        let mut products: Vec<String> = self
+            .affected
+            .iter()
+            .map(|a| a.product.to_lowercase())
+            .collect();
+        products.sort();
+        products.dedup();
+        products
+    }
+
+    pub fn affects(&self, cpe: &CpeProduct, version: &str) -> bool {
+        self.affected.iter().any(|a| a.matches(cpe, version))
+    }
+}
+
+/// CPE vendor and product a service definition corresponds to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CpeProduct {
+    pub vendor: String,
+    pub product: String,
+}
+
+impl CpeProduct {
+    /// Parse "vendor:product"
+    pub fn parse(cpe: &str) -> Option<Self> {
+        let (vendor, product) = cpe.split_once(':')?;
+
+        (!vendor.is_empty() && !product.is_empty()).then(|| Self {
+            vendor: vendor.to_lowercase(),
+            product: product.to_lowercase(),
+        })
+    }
+}
+
+/// Summary of a feed import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedImport {
+    pub files: usize,
+    pub vulnerabilities: usize,
+    /// Files which couldn't be read or weren't a recognised feed format
+    pub skipped: Vec<String>,
+}
+
+/// A known vulnerability affecting a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VulnerabilityMatch {
+    pub id: String,
+    pub cve_ids: Vec<String>,
+    pub severity: VulnerabilitySeverity,
+    pub cvss_score: Option<f32>,
+    pub summary: Option<String>,
+    pub matched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VulnerableService {
+    pub service_id: Uuid,
+    pub host_id: Uuid,
+    pub name: String,
+    pub service_definition: String,
+    pub version: Option<String>,
+    /// Most severe first
+    pub vulnerabilities: Vec<VulnerabilityMatch>,
+}
+
+impl VulnerableService {
+    pub fn max_severity(&self) -> VulnerabilitySeverity {
+        self.vulnerabilities
+            .iter()
+            .map(|v| v.severity)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Compare version strings segment by segment. Numeric segments compare numerically and
+/// alphabetic segments lexically, with a trailing alphabetic segment marking a pre-release,
+/// so 1.10 > 1.9 and 2.0 > 2.0rc1.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a = version_segments(a);
+    let b = version_segments(b);
+
+    for i in 0..a.len().max(b.len()) {
+        // Missing segments count as zero against numbers and as a release against pre-releases
+        let ordering = match (a.get(i).copied(), b.get(i).copied()) {
+            (None, Some(y)) if y.parse::<u64>().is_err() => Ordering::Greater,
+            (Some(x), None) if x.parse::<u64>().is_err() => Ordering::Less,
+            (x, y) => {
+                let (x, y) = (x.unwrap_or("0"), y.unwrap_or("0"));
+                match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Greater,
+                    (Err(_), Ok(_)) => Ordering::Less,
+                    (Err(_), Err(_)) => x.to_lowercase().cmp(&y.to_lowercase()),
+                }
+            }
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+fn version_segments(version: &str) -> Vec<&str> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let mut segments = Vec::new();
+    let mut start = None;
+    let mut numeric = false;
+
+    for (i, c) in version.char_indices() {
+        let is_segment_char = c.is_ascii_alphanumeric();
+        match start {
+            Some(s) if !is_segment_char || c.is_ascii_digit() != numeric => {
+                segments.push(&version[s..i]);
+                start = is_segment_char.then_some(i);
+                numeric = c.is_ascii_digit();
+            }
+            None if is_segment_char => {
+                start = Some(i);
+                numeric = c.is_ascii_digit();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(s) = start {
+        segments.push(&version[s..]);
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::server::vulnerabilities::types::{
+        AffectedProduct, CpeProduct, VersionBound, VersionRange, compare_versions,
+    };
+
+    #[test]
+    fn test_version_ranges() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("2.0", "2.0rc1"), Ordering::Greater);
+        assert_eq!(compare_versions("10.2.3", "10.2.3"), Ordering::Equal);
+
+        let range = VersionRange {
+            start: Some(VersionBound {
+                version: "10.0.0".to_string(),
+                inclusive: true,
+            }),
+            end: Some(VersionBound {
+                version: "10.2.4".to_string(),
+                inclusive: false,
+            }),
+        };
+        assert!(range.contains("10.0.0"));
+        assert!(range.contains("10.2.3"));
+        assert!(!range.contains("10.2.4"));
+        assert!(!range.contains("9.5.1"));
+
+        let affected = AffectedProduct {
+            vendor: Some("grafana".to_string()),
+            product: "grafana".to_string(),
+            ranges: vec![range, VersionRange::exact("8.3.0")],
+        };
+        let grafana = CpeProduct::parse("grafana:grafana").unwrap();
+        assert!(affected.matches(&grafana, "8.3.0"));
+        assert!(affected.matches(&grafana, "10.1.0"));
+        assert!(!affected.matches(&grafana, "8.3.1"));
+        assert!(!affected.matches(&CpeProduct::parse("jellyfin:jellyfin").unwrap(), "10.1.0"));
+
+        // Packages without an owner only match the vendor of the same name
+        let package = AffectedProduct {
+            vendor: None,
+            product: "jellyfin".to_string(),
+            ranges: vec![],
+        };
+        assert!(package.matches(&CpeProduct::parse("jellyfin:jellyfin").unwrap(), "10.8.0"));
+        assert!(!package.matches(&CpeProduct::parse("other:jellyfin").unwrap(), "10.8.0"));
+    }
+}
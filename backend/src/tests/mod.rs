@@ -5,6 +5,8 @@ use crate::server::{
     groups::types::{Group, GroupBase, GroupType},
     hosts::types::{
         base::{Host, HostBase},
+        devices::DeviceFingerprint,
+        identity::HostIdentity,
         interfaces::{Interface, InterfaceBase},
         ports::{Port, PortBase},
         targets::HostTarget,
@@ -23,6 +25,7 @@ use cidr::IpCidr;
 use cidr::Ipv4Cidr;
 use mac_address::MacAddress;
 use sqlx::PgPool;
+use std::collections::BTreeMap;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::sync::Arc;
@@ -34,6 +37,8 @@ pub mod database;
 
 pub const DAEMON_CONFIG_FIXTURE: &str = "src/tests/daemon_config.json";
 pub const SERVER_DB_FIXTURE: &str = "src/tests/netvisor.sql";
+pub const KUBERNETES_API_FIXTURE: &str = "src/tests/kubernetes_api.json";
+pub const DHCP_DISCOVER_FIXTURE: &str = "src/tests/dhcp_discover_android.bin";
 
 pub async fn setup_test_db() -> (PgPool, String, ContainerAsync<GenericImage>) {
     let postgres_image = GenericImage::new("postgres", "17-alpine")
@@ -85,6 +90,11 @@ pub fn host(network_id: &Uuid) -> Host {
         ports: vec![Port::new(PortBase::new_tcp(22))],
         source: EntitySource::System,
         virtualization: None,
+        device_fingerprint: DeviceFingerprint::default(),
+        identity: HostIdentity::default(),
+        device_classification: None,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     })
 }
 
@@ -94,6 +104,9 @@ pub fn interface(subnet_id: &Uuid) -> Interface {
         ip_address: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)),
         mac_address: Some(MacAddress::new([1, 2, 3, 4, 5, 6])),
         name: Some("eth0".to_string()),
+        mac_vendor: None,
+        randomized_mac: false,
+        vlan_tag: None,
     })
 }
 
@@ -104,7 +117,10 @@ pub fn subnet(network_id: &Uuid) -> Subnet {
         network_id: *network_id,
         cidr: IpCidr::V4(Ipv4Cidr::new(Ipv4Addr::new(192, 168, 1, 0), 24).unwrap()),
         subnet_type: SubnetType::Lan,
+        vlan_id: None,
         source: EntitySource::System,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     })
 }
 
@@ -120,6 +136,11 @@ pub fn service(network_id: &Uuid, host_id: &Uuid) -> Service {
         service_definition: service_def,
         virtualization: None,
         source: EntitySource::System,
+        version: None,
+        version_history: Vec::new(),
+        stale: false,
+        tags: Vec::new(),
+        custom_fields: BTreeMap::new(),
     })
 }
 
@@ -159,5 +180,5 @@ pub async fn setup_test_app() -> Router<Arc<AppState>> {
 
     let state = AppState::new(config, discovery_manager).await.unwrap();
 
-    crate::server::shared::handlers::create_router().with_state(state)
+    crate::server::shared::handlers::create_router(state.clone()).with_state(state)
 }
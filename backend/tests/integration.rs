@@ -8,6 +8,7 @@ use netvisor::server::services::definitions::home_assistant::HomeAssistant;
 use netvisor::server::services::types::base::Service;
 use netvisor::server::shared::types::api::ApiResponse;
 use netvisor::server::shared::types::metadata::HasId;
+use netvisor::server::shared::types::query::Page;
 use uuid::Uuid;
 
 struct ContainerManager {
@@ -248,7 +249,10 @@ async fn run_discovery_and_wait(client: &reqwest::Client, daemon_id: Uuid) -> Re
     println!("\n=== Starting Discovery ===");
     let response = client
         .post("http://localhost:60072/api/discovery/initiate")
-        .json(&InitiateDiscoveryRequest { daemon_id })
+        .json(&InitiateDiscoveryRequest {
+            daemon_id,
+            discovery_type: None,
+        })
         .send()
         .await
         .map_err(|e| format!("Failed to initiate discovery: {}", e))?;
@@ -388,7 +392,7 @@ async fn check_for_home_assistant_service(
             }
 
             let api_response = response
-                .json::<ApiResponse<Vec<Service>>>()
+                .json::<ApiResponse<Page<Service>>>()
                 .await
                 .map_err(|e| format!("Failed to parse response: {}", e))?;
 
@@ -401,7 +405,8 @@ async fn check_for_home_assistant_service(
 
             let service_list = api_response
                 .data
-                .ok_or_else(|| "No data in response".to_string())?;
+                .ok_or_else(|| "No data in response".to_string())?
+                .items;
 
             if service_list.is_empty() {
                 return Err("No services found yet".to_string());
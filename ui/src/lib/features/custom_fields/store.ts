@@ -0,0 +1,70 @@
+import { get, writable } from 'svelte/store';
+import { api } from '../../shared/utils/api';
+import type { CustomFieldDefinition } from './types/base';
+import { utcTimeZoneSentinel, uuidv4Sentinel } from '$lib/shared/utils/formatting';
+import { currentNetwork } from '../networks/store';
+import { getHosts } from '../hosts/store';
+import { getServices } from '../services/store';
+import { getSubnets } from '../subnets/store';
+
+export const customFields = writable<CustomFieldDefinition[]>([]);
+
+export async function getCustomFields() {
+	return await api.request<CustomFieldDefinition[]>(
+		`/custom-fields?network_id=${get(currentNetwork).id}`,
+		customFields,
+		(definitions) => definitions,
+		{ method: 'GET' }
+	);
+}
+
+export async function createCustomField(definition: CustomFieldDefinition) {
+	return await api.request<CustomFieldDefinition, CustomFieldDefinition[]>(
+		'/custom-fields',
+		customFields,
+		(response, current) => [...current, response],
+		{
+			method: 'POST',
+			body: JSON.stringify(definition)
+		}
+	);
+}
+
+export async function updateCustomField(definition: CustomFieldDefinition) {
+	return await api.request<CustomFieldDefinition, CustomFieldDefinition[]>(
+		`/custom-fields/${definition.id}`,
+		customFields,
+		(response, current) => current.map((d) => (d.id === definition.id ? response : d)),
+		{
+			method: 'PUT',
+			body: JSON.stringify(definition)
+		}
+	);
+}
+
+export async function deleteCustomField(definitionId: string) {
+	const result = await api.request<void, CustomFieldDefinition[]>(
+		`/custom-fields/${definitionId}`,
+		customFields,
+		(_, current) => current.filter((d) => d.id !== definitionId),
+		{ method: 'DELETE' }
+	);
+	// Values for the field are removed from every entity
+	await Promise.all([getHosts(), getServices(), getSubnets()]);
+
+	return result;
+}
+
+export function createEmptyCustomFieldFormData(): CustomFieldDefinition {
+	return {
+		id: uuidv4Sentinel,
+		created_at: utcTimeZoneSentinel,
+		updated_at: utcTimeZoneSentinel,
+		network_id: get(currentNetwork).id,
+		name: '',
+		field_type: 'Text',
+		options: [],
+		entity_types: ['Host'],
+		description: ''
+	};
+}
@@ -0,0 +1,18 @@
+export type CustomFieldType = 'Text' | 'Number' | 'Date' | 'Enum' | 'Url';
+
+export type CustomFieldEntityType = 'Host' | 'Service' | 'Subnet';
+
+export interface CustomFieldDefinition {
+	id: string;
+	created_at: string;
+	updated_at: string;
+	network_id: string;
+	name: string;
+	field_type: CustomFieldType;
+	options: string[]; // Allowed values for Enum fields
+	entity_types: CustomFieldEntityType[];
+	description?: string;
+}
+
+// Values keyed by custom field definition ID
+export type CustomFieldValues = Record<string, string>;
@@ -1,5 +1,8 @@
+import type { DiscoveryType } from '$lib/shared/types';
+
 export interface InitiateDiscoveryRequest {
 	daemon_id: string;
+	discovery_type?: DiscoveryType;
 }
 
 export interface DiscoverySessionRequest {
@@ -1,5 +1,13 @@
 import { derived, get, writable, type Readable } from 'svelte/store';
-import type { AllInterfaces, Host, HostWithServicesRequest, Interface, Port } from './types/base';
+import type {
+	AllInterfaces,
+	Host,
+	HostWithServicesRequest,
+	Interface,
+	Port,
+	SplitHostRequest,
+	SplitHostResponse
+} from './types/base';
 import { api } from '../../shared/utils/api';
 import { pushSuccess } from '$lib/shared/stores/feedback';
 import { utcTimeZoneSentinel, uuidv4Sentinel } from '$lib/shared/utils/formatting';
@@ -11,12 +19,7 @@ export const hosts = writable<Host[]>([]);
 export const polling = writable(false);
 
 export async function getHosts() {
-	return await api.request<Host[]>(
-		`/hosts?network_id=${get(currentNetwork).id}`,
-		hosts,
-		(hosts) => hosts,
-		{ method: 'GET' }
-	);
+	return await api.requestAllPages<Host>(`/hosts?network_id=${get(currentNetwork).id}`, hosts);
 }
 
 export async function createHost(data: HostWithServicesRequest) {
@@ -65,6 +68,23 @@ export async function consolidateHosts(destination_host_id: string, other_host_i
 	);
 }
 
+export async function splitHost(host_id: string, data: SplitHostRequest) {
+	return await api.request<SplitHostResponse, Host[]>(
+		`/hosts/${host_id}/split`,
+		hosts,
+		({ host, destination_host }, current) => {
+			pushSuccess(`Split "${destination_host.name}" off host "${host.name}"`);
+			return [
+				...current
+					.filter((h) => h.id !== destination_host.id)
+					.map((h) => (h.id === host.id ? host : h)),
+				destination_host
+			];
+		},
+		{ method: 'POST', body: JSON.stringify(data) }
+	);
+}
+
 export function createEmptyHostFormData(): Host {
 	return {
 		id: uuidv4Sentinel,
@@ -83,7 +103,21 @@ export function createEmptyHostFormData(): Host {
 			type: 'Manual'
 		},
 		virtualization: null,
-		network_id: get(currentNetwork).id
+		device_fingerprint: {
+			dhcp_parameter_list: [],
+			mdns_services: [],
+			ssdp_server: null,
+			ssdp_types: []
+		},
+		device_classification: null,
+		identity: {
+			ssh_host_keys: [],
+			tls_certificates: [],
+			snmp_sys_name: null
+		},
+		network_id: get(currentNetwork).id,
+		tags: [],
+		custom_fields: {}
 	};
 }
 
@@ -1,12 +1,28 @@
 import type { Service } from '$lib/features/services/types/base';
 import type { EntitySource } from '$lib/shared/types';
+import type { CustomFieldValues } from '$lib/features/custom_fields/types/base';
 
 export interface HostWithServicesRequest {
 	host: Host;
 	services: Service[];
 }
 
-export type HostVirtualization = { type: 'Proxmox'; details: ProxmoxVirtualization };
+export interface SplitHostRequest {
+	interface_ids: string[];
+	port_ids: string[];
+	service_ids: string[];
+	destination_host_id: string | null;
+	name: string | null;
+}
+
+export interface SplitHostResponse {
+	host: Host;
+	destination_host: Host;
+}
+
+export type HostVirtualization =
+	| { type: 'Proxmox'; details: ProxmoxVirtualization }
+	| { type: 'Libvirt'; details: LibvirtVirtualization };
 
 export interface Host {
 	id: string;
@@ -20,8 +36,46 @@ export interface Host {
 	ports: Port[];
 	interfaces: Interface[];
 	virtualization: HostVirtualization | null;
+	device_fingerprint: DeviceFingerprint;
+	device_classification: DeviceClassification | null;
+	identity: HostIdentity;
 	source: EntitySource;
 	network_id: string;
+	tags: string[];
+	custom_fields: CustomFieldValues;
+}
+
+export type DeviceType =
+	| 'Phone'
+	| 'Tablet'
+	| 'Computer'
+	| 'Wearable'
+	| 'Tv'
+	| 'MediaPlayer'
+	| 'SmartSpeaker'
+	| 'Camera'
+	| 'SmartPlug'
+	| 'GameConsole'
+	| 'IotDevice';
+
+export interface DeviceFingerprint {
+	dhcp_parameter_list: number[];
+	mdns_services: string[];
+	ssdp_server: string | null;
+	ssdp_types: string[];
+}
+
+export interface HostIdentity {
+	ssh_host_keys: string[];
+	tls_certificates: string[];
+	snmp_sys_name: string | null;
+}
+
+export interface DeviceClassification {
+	device_type: DeviceType;
+	score: number;
+	reasons: string[];
+	manual: boolean;
 }
 
 export interface ProxmoxVirtualization {
@@ -30,6 +84,12 @@ export interface ProxmoxVirtualization {
 	service_id: string;
 }
 
+export interface LibvirtVirtualization {
+	vm_id: string | null;
+	vm_name: string | null;
+	service_id: string;
+}
+
 export interface AllInterfaces {
 	id: null;
 	name: string;
@@ -46,6 +106,10 @@ export interface Interface {
 	name: string;
 	ip_address?: string;
 	mac_address?: string;
+	mac_vendor?: string;
+	randomized_mac?: boolean;
+	vlan_tag?: number | null;
+	stale?: boolean;
 }
 
 export type HostTarget =
@@ -66,4 +130,5 @@ export interface Port {
 	protocol: string;
 	id: string;
 	type: string;
+	stale: boolean;
 }
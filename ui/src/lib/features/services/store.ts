@@ -12,13 +12,9 @@ export const services = writable<Service[]>([]);
 
 // Get all services
 export async function getServices() {
-	return await api.request<Service[]>(
+	return await api.requestAllPages<Service>(
 		`/services?network_id=${get(currentNetwork).id}`,
-		services,
-		(services) => services,
-		{
-			method: 'GET'
-		}
+		services
 	);
 }
 
@@ -41,7 +37,12 @@ export function createDefaultService(
 		virtualization: null,
 		source: {
 			type: 'Manual'
-		}
+		},
+		version: null,
+		version_history: [],
+		stale: false,
+		tags: [],
+		custom_fields: {}
 	};
 }
 
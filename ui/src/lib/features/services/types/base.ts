@@ -1,6 +1,9 @@
 import type { EntitySource } from '$lib/shared/types';
+import type { CustomFieldValues } from '$lib/features/custom_fields/types/base';
 
-export type ServiceVirtualization = { type: 'Docker'; details: DockerVirtualization };
+export type ServiceVirtualization =
+	| { type: 'Container'; details: ContainerVirtualization }
+	| { type: 'Kubernetes'; details: KubernetesVirtualization };
 
 export interface Service {
 	id: string;
@@ -14,16 +17,58 @@ export interface Service {
 	virtualization: ServiceVirtualization | null;
 	source: EntitySource;
 	network_id: string;
+	version: string | null;
+	version_history: ServiceVersion[];
+	stale: boolean;
+	tags: string[];
+	custom_fields: CustomFieldValues;
+}
+
+export interface ServiceVersion {
+	version: string;
+	detected_at: string;
 }
 
 export type ServiceWithVMs = Omit<Service, 'vms'> & {
 	vms: string[];
 };
 
-export interface DockerVirtualization {
+export interface ContainerVirtualization {
 	container_id: string | null;
 	container_name: string | null;
 	service_id: string;
+	runtime?: ContainerRuntime;
+	namespace?: string | null;
+	image?: string | null;
+	image_digest?: string | null;
+	created?: string | null;
+	restart_policy?: string | null;
+	health?: string | null;
+	published_ports?: ContainerPublishedPort[];
+	volumes?: ContainerVolume[];
+}
+
+export type ContainerRuntime = 'Docker' | 'Podman' | 'Containerd';
+
+export interface ContainerPublishedPort {
+	host_ip: string | null;
+	host_port: number;
+	container_port: number;
+	protocol: string;
+}
+
+export interface ContainerVolume {
+	source: string | null;
+	destination: string;
+	mount_type: string | null;
+	read_only: boolean;
+}
+
+export interface KubernetesVirtualization {
+	namespace: string;
+	kind: string;
+	name: string;
+	service_id: string;
 }
 
 export type Binding =
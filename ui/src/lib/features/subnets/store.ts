@@ -1,7 +1,8 @@
 import { derived, get, writable, type Readable } from 'svelte/store';
 import { api } from '../../shared/utils/api';
 import { utcTimeZoneSentinel, uuidv4Sentinel } from '$lib/shared/utils/formatting';
-import type { Subnet } from './types/base';
+import type { Subnet, SubnetTreeNode, Vlan } from './types/base';
+import type { IpRange, SubnetIpam } from './types/ipam';
 import { currentNetwork } from '../networks/store';
 
 export const subnets = writable<Subnet[]>([]);
@@ -15,6 +16,17 @@ export async function getSubnets() {
 	);
 }
 
+export const subnetTree = writable<SubnetTreeNode[]>([]);
+
+export async function getSubnetTree() {
+	return await api.request<SubnetTreeNode[]>(
+		`/subnets/tree?network_id=${get(currentNetwork).id}`,
+		subnetTree,
+		(tree) => tree,
+		{ method: 'GET' }
+	);
+}
+
 export async function createSubnet(subnet: Subnet) {
 	const result = await api.request<Subnet, Subnet[]>(
 		'/subnets',
@@ -54,6 +66,124 @@ export async function deleteSubnet(subnetId: string) {
 	return result;
 }
 
+export const vlans = writable<Vlan[]>([]);
+
+export async function getVlans() {
+	return await api.request<Vlan[]>(
+		`/vlans?network_id=${get(currentNetwork).id}`,
+		vlans,
+		(vlans) => vlans,
+		{ method: 'GET' }
+	);
+}
+
+export async function createVlan(vlan: Vlan) {
+	const result = await api.request<Vlan, Vlan[]>(
+		'/vlans',
+		vlans,
+		(response, currentVlans) => [...currentVlans, response],
+		{
+			method: 'POST',
+			body: JSON.stringify(vlan)
+		}
+	);
+	// Subnets with interfaces tagged for the VLAN are linked to it
+	await getSubnets();
+
+	return result;
+}
+
+export async function updateVlan(vlan: Vlan) {
+	return await api.request<Vlan, Vlan[]>(
+		`/vlans/${vlan.id}`,
+		vlans,
+		(response, currentVlans) => currentVlans.map((v) => (v.id === vlan.id ? response : v)),
+		{
+			method: 'PUT',
+			body: JSON.stringify(vlan)
+		}
+	);
+}
+
+export async function deleteVlan(vlanId: string) {
+	const result = await api.request<void, Vlan[]>(
+		`/vlans/${vlanId}`,
+		vlans,
+		(_, currentVlans) => currentVlans.filter((v) => v.id !== vlanId),
+		{ method: 'DELETE' }
+	);
+	await getSubnets();
+
+	return result;
+}
+
+export function createEmptyVlanFormData(): Vlan {
+	return {
+		id: uuidv4Sentinel,
+		created_at: utcTimeZoneSentinel,
+		updated_at: utcTimeZoneSentinel,
+		network_id: get(currentNetwork).id,
+		tag: 1,
+		name: '',
+		purpose: 'Unknown',
+		description: ''
+	};
+}
+
+export const subnetIpam = writable<SubnetIpam[]>([]);
+
+export async function getSubnetIpam() {
+	return await api.request<SubnetIpam[]>(
+		`/ipam?network_id=${get(currentNetwork).id}`,
+		subnetIpam,
+		(plans) => plans,
+		{ method: 'GET' }
+	);
+}
+
+export async function createIpRange(range: IpRange) {
+	const result = await api.request<IpRange>('/ipam/ranges', null, null, {
+		method: 'POST',
+		body: JSON.stringify(range)
+	});
+	await getSubnetIpam();
+
+	return result;
+}
+
+export async function updateIpRange(range: IpRange) {
+	const result = await api.request<IpRange>(`/ipam/ranges/${range.id}`, null, null, {
+		method: 'PUT',
+		body: JSON.stringify(range)
+	});
+	await getSubnetIpam();
+
+	return result;
+}
+
+export async function deleteIpRange(rangeId: string) {
+	const result = await api.request<void>(`/ipam/ranges/${rangeId}`, null, null, {
+		method: 'DELETE'
+	});
+	await getSubnetIpam();
+
+	return result;
+}
+
+export function createEmptyIpRangeFormData(subnetId: string): IpRange {
+	return {
+		id: uuidv4Sentinel,
+		created_at: utcTimeZoneSentinel,
+		updated_at: utcTimeZoneSentinel,
+		subnet_id: subnetId,
+		range_type: 'Reservation',
+		start_ip: '',
+		end_ip: '',
+		host_id: null,
+		note: null
+	};
+}
+
 export function createEmptySubnetFormData(): Subnet {
 	return {
 		id: uuidv4Sentinel,
@@ -64,9 +194,12 @@ export function createEmptySubnetFormData(): Subnet {
 		cidr: '',
 		description: '',
 		subnet_type: 'Unknown',
+		vlan_id: null,
 		source: {
 			type: 'Manual'
-		}
+		},
+		tags: [],
+		custom_fields: {}
 	};
 }
 
@@ -1,4 +1,5 @@
 import type { EntitySource } from '$lib/shared/types';
+import type { CustomFieldValues } from '$lib/features/custom_fields/types/base';
 
 export interface Subnet {
 	id: string;
@@ -10,4 +11,28 @@ export interface Subnet {
 	network_id: string;
 	source: EntitySource;
 	subnet_type: string;
+	vlan_id: string | null;
+	tags: string[];
+	custom_fields: CustomFieldValues;
+}
+
+export interface SubnetTreeNode {
+	subnet: Subnet;
+	parent_id: string | null;
+	host_count: number;
+	service_count: number;
+	total_host_count: number;
+	total_service_count: number;
+	children: SubnetTreeNode[];
+}
+
+export interface Vlan {
+	id: string;
+	created_at: string;
+	updated_at: string;
+	network_id: string;
+	tag: number;
+	name: string;
+	purpose: string;
+	description?: string;
 }
@@ -0,0 +1,49 @@
+export type IpRangeType = 'DhcpScope' | 'Reservation';
+
+export interface IpRange {
+	id: string;
+	created_at: string;
+	updated_at: string;
+	subnet_id: string;
+	range_type: IpRangeType;
+	start_ip: string;
+	end_ip: string;
+	host_id: string | null;
+	note: string | null;
+}
+
+export type AddressState = 'Free' | 'DhcpScope' | 'Reserved' | 'Used';
+
+export interface AddressBlock {
+	start_ip: string;
+	end_ip: string;
+	state: AddressState;
+	size: number;
+}
+
+export interface UsedAddress {
+	ip_address: string;
+	host_id: string;
+	interface_id: string;
+}
+
+export type IpamConflict =
+	| { type: 'DuplicateIp'; ip_address: string; host_ids: string[] }
+	| { type: 'OutsideSubnet'; ip_address: string; host_id: string; interface_id: string }
+	| { type: 'ReservedForOtherHost'; ip_address: string; host_id: string; range_id: string };
+
+export interface SubnetIpam {
+	subnet_id: string;
+	cidr: string;
+	total: number;
+	used: number;
+	reserved: number;
+	dhcp: number;
+	free: number;
+	utilisation: number;
+	next_free_ip: string | null;
+	used_addresses: UsedAddress[];
+	ranges: IpRange[];
+	blocks: AddressBlock[];
+	conflicts: IpamConflict[];
+}
@@ -16,9 +16,13 @@ const defaultOptions: TopologyOptions = {
 		group_docker_bridges_by_host: true,
 		hide_vm_title_on_docker_container: false,
 		show_gateway_in_left_zone: true,
+		group_subnets_by_vlan: false,
 		left_zone_service_categories: ['DNS', 'ReverseProxy'],
 		hide_service_categories: [],
-		network_ids: []
+		hide_tags: [],
+		tag_colors: [],
+		network_ids: [],
+		snapshot_id: null
 	}
 };
 
@@ -8,6 +8,7 @@ export interface NodeBase {
 	position: { x: number; y: number };
 	size: { x: number; y: number };
 	header: string | null;
+	color: string | null;
 }
 
 type NodeType =
@@ -62,13 +63,22 @@ export enum EdgeHandle {
 	Left = 'Left'
 }
 
+export interface TagColor {
+	tag: string;
+	color: string;
+}
+
 export interface TopologyRequestOptions {
 	group_docker_bridges_by_host: boolean;
 	hide_vm_title_on_docker_container: boolean;
 	network_ids: string[];
 	show_gateway_in_left_zone: boolean;
+	group_subnets_by_vlan: boolean;
 	left_zone_service_categories: string[];
 	hide_service_categories: string[];
+	hide_tags: string[];
+	tag_colors: TagColor[];
+	snapshot_id: string | null;
 }
 
 export interface TopologyOptions {
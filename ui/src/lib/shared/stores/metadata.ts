@@ -1,5 +1,6 @@
 import { writable, get } from 'svelte/store';
 import { api } from '../utils/api';
+import { currentNetwork } from '$lib/features/networks/store';
 import {
 	createColorHelper,
 	createIconComponent,
@@ -237,8 +238,14 @@ export const groupTypes = createTypeMetadataHelpers('group_types');
 export const entities = createEntityMetadataHelpers('entities');
 export const ports = createTypeMetadataHelpers('ports');
 
+// Service definitions include the current network's custom definitions
 export async function getMetadata() {
-	await api.request<MetadataRegistry>('/metadata', metadata, (metadata) => metadata, {
-		method: 'GET'
-	});
+	await api.request<MetadataRegistry>(
+		`/metadata?network_id=${get(currentNetwork).id}`,
+		metadata,
+		(metadata) => metadata,
+		{
+			method: 'GET'
+		}
+	);
 }
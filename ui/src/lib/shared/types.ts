@@ -2,6 +2,13 @@ export interface GetAllEntitiesRequest {
 	network_id: string;
 }
 
+// List endpoints return one page of items at a time. Pass next_cursor back as the cursor query
+// parameter for the following page, or use api.requestAllPages to fetch all of them.
+export interface Page<T> {
+	items: T[];
+	next_cursor: string | null;
+}
+
 export type EntitySource =
 	| { type: 'Manual' }
 	| { type: 'System' }
@@ -29,6 +36,7 @@ export type MatchReason =
 export interface MatchDetails {
 	reason: MatchReason;
 	confidence: 'NotApplicable' | 'Low' | 'Medium' | 'High' | 'Certain';
+	score: number;
 }
 
 export function matchConfidenceColor(confidence: MatchDetails['confidence']): string {
@@ -57,4 +65,6 @@ export type DiscoveryType =
 	| { type: 'SelfReport' }
 	| { type: 'Network' }
 	| { type: 'Docker'; host_id: string }
-	| { type: 'Proxmox'; host_id: string };
+	| { type: 'Proxmox'; host_id: string }
+	| { type: 'Libvirt'; host_id: string }
+	| { type: 'Kubernetes' };
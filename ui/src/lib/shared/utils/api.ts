@@ -1,6 +1,7 @@
 import type { Writable } from 'svelte/store';
 import { pushError } from '../stores/feedback';
 import { env } from '$env/dynamic/public';
+import type { Page } from '../types';
 
 interface ApiResponse<T> {
 	success: boolean;
@@ -129,6 +130,40 @@ class ApiClient {
 		return requestPromise;
 	}
 
+	/**
+	 * Fetch every page of a list endpoint, following next_cursor until the last page, and set
+	 * dataStore to all of the items once they've been fetched.
+	 * @param endpoint - list endpoint including its query string
+	 * @param dataStore - store that will be set to the items of all pages
+	 * @returns
+	 */
+	async requestAllPages<T>(
+		endpoint: string,
+		dataStore: Writable<T[]> | null
+	): Promise<ApiResponse<T[]> | null> {
+		const items: T[] = [];
+		let cursor: string | null = null;
+
+		do {
+			const pageEndpoint: string = cursor
+				? `${endpoint}&cursor=${encodeURIComponent(cursor)}`
+				: endpoint;
+			const result: ApiResponse<Page<T>> | null = await this.request<Page<T>>(
+				pageEndpoint,
+				null,
+				null,
+				{ method: 'GET' }
+			);
+			if (!result?.success || !result.data) return null;
+
+			items.push(...result.data.items);
+			cursor = result.data.next_cursor;
+		} while (cursor);
+
+		if (dataStore) dataStore.set(items);
+		return { success: true, data: items };
+	}
+
 	private async executeRequest<TResponseData, TStoreData = TResponseData>(
 		url: URL,
 		dataStore: Writable<TStoreData> | null,
@@ -137,9 +172,12 @@ class ApiClient {
 		baseErrorMessage: string
 	): Promise<ApiResponse<TResponseData> | null> {
 		try {
+			// Attributes changes made through the UI to the current user in entity history
+			const userId = localStorage.getItem('user_id');
 			const response = await fetch(url, {
 				headers: {
 					'Content-Type': 'application/json',
+					...(userId ? { 'X-Netvisor-User-Id': userId } : {}),
 					...options.headers
 				},
 				...options